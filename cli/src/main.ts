@@ -215,6 +215,10 @@ async function main() {
     .option("--agg-key <PATH>")
     .option("--agg-pubkey <BASE58>")
     .option("--chain-id <U64>")
+    .option("--min-lock-amount <U64>")
+    .option("--max-slots-per-artifact <U64>")
+    .option("--max-past-skew <SECS>")
+    .option("--max-future-skew <SECS>")
     .action(async (opts) => {
       const web3 = await import("@solana/web3.js");
       const programIdStr = process.env.PROGRAM_ID_VALIDATOR_LOCK || "";
@@ -276,6 +280,10 @@ async function main() {
         Buffer.from(aggPub), // next_aggregator_pubkey
         activationLe, // activation_seq u64 LE
         chainLe, // chain_id u64 LE
+        u64le(BigInt(String(opts.minLockAmount ?? "0"))), // min_lock_amount (0 = one token)
+        u64le(BigInt(String(opts.maxSlotsPerArtifact ?? "0"))), // max_slots_per_artifact (0 = default)
+        u32le(Number(opts.maxPastSkew ?? 0)), // max_past_skew_secs (0 = default)
+        u32le(Number(opts.maxFutureSkew ?? 0)), // max_future_skew_secs (0 = default)
       ]);
 
       const keys = [
@@ -312,6 +320,19 @@ async function main() {
     .option("--next-agg-pubkey <BASE58>")
     .option("--activation <U64>")
     .option("--paused <BOOL>")
+    .option("--max-past-skew <SECS>")
+    .option("--max-future-skew <SECS>")
+    .option("--allow-emergency-unlock <BOOL>")
+    .option("--enforce-uniform-da <BOOL>")
+    .option("--clear-da-scheme")
+    .option("--bind-proof-hash <BOOL>")
+    .option("--min-lock-amount <U64>")
+    .option("--max-proof-age-slots <U64>")
+    .option("--max-slots-per-artifact <U64>")
+    .option("--per-submitter-range <BOOL>")
+    .option("--rotation-grace <U64>")
+    .option("--min-lock-duration <SECS>")
+    .option("--allow-token-2022 <BOOL>")
     .action(async (opts) => {
       const web3 = await import("@solana/web3.js");
       const programIdStr = process.env.PROGRAM_ID_VALIDATOR_LOCK || "";
@@ -376,6 +397,11 @@ async function main() {
         programId
       );
 
+      const [aggregatorStatePda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("aggregator")],
+        programId
+      );
+
       const disc = sha256_8("global:update_config");
       const encOptPub = (present: boolean, pk?: Uint8Array): Buffer => {
         return present && pk
@@ -392,6 +418,13 @@ async function main() {
           ? Buffer.from([1, v ? 1 : 0])
           : Buffer.from([0]);
       };
+      const encOptU32 = (v?: number): Buffer => {
+        if (v === undefined) return Buffer.from([0]);
+        const b = Buffer.alloc(5);
+        b.writeUInt8(1, 0);
+        b.writeUInt32LE(v, 1);
+        return b;
+      };
 
       const activation = opts.activation
         ? BigInt(String(opts.activation))
@@ -401,17 +434,91 @@ async function main() {
           ? /^(true|1)$/i.test(String(opts.paused))
           : undefined;
 
+      const allowEmergencyUnlock =
+        typeof opts.allowEmergencyUnlock === "string"
+          ? /^(true|1)$/i.test(String(opts.allowEmergencyUnlock))
+          : undefined;
+
+      const enforceUniformDa =
+        typeof opts.enforceUniformDa === "string"
+          ? /^(true|1)$/i.test(String(opts.enforceUniformDa))
+          : undefined;
+
+      const bindProofHash =
+        typeof opts.bindProofHash === "string"
+          ? /^(true|1)$/i.test(String(opts.bindProofHash))
+          : undefined;
+
+      const minLockAmount =
+        opts.minLockAmount !== undefined
+          ? BigInt(String(opts.minLockAmount))
+          : undefined;
+
+      const maxProofAgeSlots =
+        opts.maxProofAgeSlots !== undefined
+          ? BigInt(String(opts.maxProofAgeSlots))
+          : undefined;
+
+      const maxSlotsPerArtifact =
+        opts.maxSlotsPerArtifact !== undefined
+          ? BigInt(String(opts.maxSlotsPerArtifact))
+          : undefined;
+
+      const perSubmitterRange =
+        typeof opts.perSubmitterRange === "string"
+          ? /^(true|1)$/i.test(String(opts.perSubmitterRange))
+          : undefined;
+
+      const rotationGrace =
+        opts.rotationGrace !== undefined
+          ? BigInt(String(opts.rotationGrace))
+          : undefined;
+
+      const minLockDuration =
+        opts.minLockDuration !== undefined
+          ? BigInt(String(opts.minLockDuration))
+          : undefined;
+
+      const allowToken2022 =
+        typeof opts.allowToken2022 === "string"
+          ? /^(true|1)$/i.test(String(opts.allowToken2022))
+          : undefined;
+
+      const maxPastSkew =
+        opts.maxPastSkew !== undefined ? Number(opts.maxPastSkew) : undefined;
+      const maxFutureSkew =
+        opts.maxFutureSkew !== undefined
+          ? Number(opts.maxFutureSkew)
+          : undefined;
+
       const payload = Buffer.concat([
         encOptPub(true, aggPub), // aggregator_pubkey = Some
         encOptPub(!!nextAggPub, nextAggPub), // next_aggregator_pubkey
         encOptU64(activation !== undefined, activation),
         encOptBool(paused !== undefined, paused),
+        encOptU32(maxPastSkew), // max_past_skew_secs
+        encOptU32(maxFutureSkew), // max_future_skew_secs
+        encOptBool(allowEmergencyUnlock !== undefined, allowEmergencyUnlock), // allow_emergency_unlock
+        encOptBool(enforceUniformDa !== undefined, enforceUniformDa), // enforce_uniform_da
+        Buffer.from([opts.clearDaScheme ? 1 : 0]), // clear_da_scheme
+        encOptBool(bindProofHash !== undefined, bindProofHash), // bind_proof_hash
+        encOptU64(minLockAmount !== undefined, minLockAmount), // min_lock_amount
+        encOptU64(maxProofAgeSlots !== undefined, maxProofAgeSlots), // max_proof_age_slots (0 = no limit)
+        encOptU64(maxSlotsPerArtifact !== undefined, maxSlotsPerArtifact), // max_slots_per_artifact (0 = default)
+        encOptBool(perSubmitterRange !== undefined, perSubmitterRange), // per_submitter_range
+        encOptU64(rotationGrace !== undefined, rotationGrace), // rotation_grace (0 = hard cutover)
+        encOptU64(minLockDuration !== undefined, minLockDuration), // min_lock_duration_secs i64 (>= 0)
+        encOptBool(allowToken2022 !== undefined, allowToken2022), // allow_token_2022
       ]);
       const data = Buffer.concat([disc, payload]);
 
+      // aggregator_state is optional and read only to clear the DA scheme
       const keys = [
         { pubkey: admin.publicKey, isSigner: true, isWritable: false },
         { pubkey: configPda, isSigner: false, isWritable: true },
+        ...(opts.clearDaScheme
+          ? [{ pubkey: aggregatorStatePda, isSigner: false, isWritable: true }]
+          : []),
       ];
 
       const ix = new web3.TransactionInstruction({ keys, programId, data });
@@ -428,6 +535,349 @@ async function main() {
       console.log(JSON.stringify({ txid: sig }, null, 2));
     });
 
+  program
+    .command("resize-config")
+    .requiredOption("--keypair <PATH>")
+    .action(async (opts) => {
+      const web3 = await import("@solana/web3.js");
+      const programIdStr = process.env.PROGRAM_ID_VALIDATOR_LOCK || "";
+      if (!programIdStr)
+        throw new Error("PROGRAM_ID_VALIDATOR_LOCK is required");
+      const conn = new web3.Connection(
+        process.env.RPC_URL || "http://localhost:8899",
+        { commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized" }
+      );
+      const programId = new web3.PublicKey(programIdStr);
+      const admin = await readKeypair(opts.keypair);
+      const [configPda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("config")],
+        programId
+      );
+      const data = sha256_8("global:resize_config");
+      const keys = [
+        { pubkey: admin.publicKey, isSigner: true, isWritable: true },
+        { pubkey: configPda, isSigner: false, isWritable: true },
+        {
+          pubkey: (web3 as any).SystemProgram.programId,
+          isSigner: false,
+          isWritable: false,
+        },
+      ];
+
+      const ix = new web3.TransactionInstruction({ keys, programId, data });
+      const tx = new web3.Transaction();
+      tx.add(ix);
+      const bh = await conn.getLatestBlockhash();
+      tx.recentBlockhash = bh.blockhash;
+      tx.feePayer = admin.publicKey;
+      tx.sign(admin);
+      const sig = await web3.sendAndConfirmTransaction(conn, tx, [admin], {
+        commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized",
+      });
+      process.stdout.write(JSON.stringify({ txid: sig }, null, 2) + "\n");
+    });
+
+  program
+    .command("propose-admin")
+    .requiredOption("--keypair <PATH>")
+    .requiredOption("--new-admin <PUBKEY>")
+    .action(async (opts) => {
+      const web3 = await import("@solana/web3.js");
+      const programIdStr = process.env.PROGRAM_ID_VALIDATOR_LOCK || "";
+      if (!programIdStr)
+        throw new Error("PROGRAM_ID_VALIDATOR_LOCK is required");
+      const conn = new web3.Connection(
+        process.env.RPC_URL || "http://localhost:8899",
+        { commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized" }
+      );
+      const programId = new web3.PublicKey(programIdStr);
+      const admin = await readKeypair(opts.keypair);
+      const [configPda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("config")],
+        programId
+      );
+      const data = Buffer.concat([
+        sha256_8("global:propose_admin"),
+        new web3.PublicKey(opts.newAdmin).toBuffer(),
+      ]);
+      const keys = [
+        { pubkey: admin.publicKey, isSigner: true, isWritable: false },
+        { pubkey: configPda, isSigner: false, isWritable: true },
+      ];
+
+      const ix = new web3.TransactionInstruction({ keys, programId, data });
+      const tx = new web3.Transaction();
+      tx.add(ix);
+      const bh = await conn.getLatestBlockhash();
+      tx.recentBlockhash = bh.blockhash;
+      tx.feePayer = admin.publicKey;
+      tx.sign(admin);
+      const sig = await web3.sendAndConfirmTransaction(conn, tx, [admin], {
+        commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized",
+      });
+      process.stdout.write(JSON.stringify({ txid: sig }, null, 2) + "\n");
+    });
+
+  program
+    .command("accept-admin")
+    .requiredOption("--keypair <PATH>")
+    .action(async (opts) => {
+      const web3 = await import("@solana/web3.js");
+      const programIdStr = process.env.PROGRAM_ID_VALIDATOR_LOCK || "";
+      if (!programIdStr)
+        throw new Error("PROGRAM_ID_VALIDATOR_LOCK is required");
+      const conn = new web3.Connection(
+        process.env.RPC_URL || "http://localhost:8899",
+        { commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized" }
+      );
+      const programId = new web3.PublicKey(programIdStr);
+      const pendingAdmin = await readKeypair(opts.keypair);
+      const [configPda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("config")],
+        programId
+      );
+      const data = sha256_8("global:accept_admin");
+      const keys = [
+        { pubkey: pendingAdmin.publicKey, isSigner: true, isWritable: false },
+        { pubkey: configPda, isSigner: false, isWritable: true },
+      ];
+
+      const ix = new web3.TransactionInstruction({ keys, programId, data });
+      const tx = new web3.Transaction();
+      tx.add(ix);
+      const bh = await conn.getLatestBlockhash();
+      tx.recentBlockhash = bh.blockhash;
+      tx.feePayer = pendingAdmin.publicKey;
+      tx.sign(pendingAdmin);
+      const sig = await web3.sendAndConfirmTransaction(conn, tx, [pendingAdmin], {
+        commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized",
+      });
+      process.stdout.write(JSON.stringify({ txid: sig }, null, 2) + "\n");
+    });
+
+  program
+    .command("reset-state") // test deployments only; the program must be paused
+    .requiredOption("--keypair <PATH>")
+    .requiredOption("--confirm-last-seq <U64>")
+    .action(async (opts) => {
+      const web3 = await import("@solana/web3.js");
+      const programIdStr = process.env.PROGRAM_ID_VALIDATOR_LOCK || "";
+      if (!programIdStr)
+        throw new Error("PROGRAM_ID_VALIDATOR_LOCK is required");
+      const conn = new web3.Connection(
+        process.env.RPC_URL || "http://localhost:8899",
+        { commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized" }
+      );
+      const programId = new web3.PublicKey(programIdStr);
+      const admin = await readKeypair(opts.keypair);
+      const [configPda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("config")],
+        programId
+      );
+      const [aggregatorPda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("aggregator")],
+        programId
+      );
+      const [rangePda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("range")],
+        programId
+      );
+      const data = Buffer.concat([
+        sha256_8("global:reset_state"),
+        u64le(BigInt(String(opts.confirmLastSeq))), // confirm_last_seq
+      ]);
+      const keys = [
+        { pubkey: admin.publicKey, isSigner: true, isWritable: false },
+        { pubkey: configPda, isSigner: false, isWritable: false },
+        { pubkey: aggregatorPda, isSigner: false, isWritable: true },
+        { pubkey: rangePda, isSigner: false, isWritable: true },
+      ];
+
+      const ix = new web3.TransactionInstruction({ keys, programId, data });
+      const tx = new web3.Transaction();
+      tx.add(ix);
+      const bh = await conn.getLatestBlockhash();
+      tx.recentBlockhash = bh.blockhash;
+      tx.feePayer = admin.publicKey;
+      tx.sign(admin);
+      const sig = await web3.sendAndConfirmTransaction(conn, tx, [admin], {
+        commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized",
+      });
+      process.stdout.write(JSON.stringify({ txid: sig }, null, 2) + "\n");
+    });
+
+  program
+    .command("close-proof-record") // finalized records only; rent goes to the admin
+    .requiredOption("--keypair <PATH>")
+    .requiredOption("--proof-hash <HEX32>")
+    .requiredOption("--seq <U64>")
+    .action(async (opts) => {
+      const web3 = await import("@solana/web3.js");
+      const programIdStr = process.env.PROGRAM_ID_VALIDATOR_LOCK || "";
+      if (!programIdStr)
+        throw new Error("PROGRAM_ID_VALIDATOR_LOCK is required");
+      const conn = new web3.Connection(
+        process.env.RPC_URL || "http://localhost:8899",
+        { commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized" }
+      );
+      const programId = new web3.PublicKey(programIdStr);
+      const admin = await readKeypair(opts.keypair);
+      const proofHash = Buffer.from(String(opts.proofHash), "hex");
+      if (proofHash.length !== 32)
+        throw new Error("proof-hash must be 32-byte hex");
+      const [configPda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("config")],
+        programId
+      );
+      const [proofRecordPda] = await web3.PublicKey.findProgramAddress(
+        [
+          Buffer.from("zksl"),
+          Buffer.from("proof"),
+          proofHash,
+          u64le(BigInt(String(opts.seq))),
+        ],
+        programId
+      );
+      const data = sha256_8("global:close_proof_record");
+      const keys = [
+        { pubkey: admin.publicKey, isSigner: true, isWritable: true },
+        { pubkey: configPda, isSigner: false, isWritable: false },
+        { pubkey: proofRecordPda, isSigner: false, isWritable: true },
+      ];
+
+      const ix = new web3.TransactionInstruction({ keys, programId, data });
+      const tx = new web3.Transaction();
+      tx.add(ix);
+      const bh = await conn.getLatestBlockhash();
+      tx.recentBlockhash = bh.blockhash;
+      tx.feePayer = admin.publicKey;
+      tx.sign(admin);
+      const sig = await web3.sendAndConfirmTransaction(conn, tx, [admin], {
+        commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized",
+      });
+      process.stdout.write(JSON.stringify({ txid: sig }, null, 2) + "\n");
+    });
+
+  program
+    .command("set-commitment-level") // admin or the record's aggregator; levels only advance
+    .requiredOption("--keypair <PATH>")
+    .requiredOption("--proof-hash <HEX32>")
+    .requiredOption("--seq <U64>")
+    .requiredOption("--level <0|1|2>")
+    .action(async (opts) => {
+      const web3 = await import("@solana/web3.js");
+      const programIdStr = process.env.PROGRAM_ID_VALIDATOR_LOCK || "";
+      if (!programIdStr)
+        throw new Error("PROGRAM_ID_VALIDATOR_LOCK is required");
+      const conn = new web3.Connection(
+        process.env.RPC_URL || "http://localhost:8899",
+        { commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized" }
+      );
+      const programId = new web3.PublicKey(programIdStr);
+      const authority = await readKeypair(opts.keypair);
+      const proofHash = Buffer.from(String(opts.proofHash), "hex");
+      if (proofHash.length !== 32)
+        throw new Error("proof-hash must be 32-byte hex");
+      const level = Number(opts.level);
+      if (![0, 1, 2].includes(level)) throw new Error("level must be 0, 1 or 2");
+      const seq = u64le(BigInt(String(opts.seq)));
+      const [configPda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("config")],
+        programId
+      );
+      const [proofRecordPda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("proof"), proofHash, seq],
+        programId
+      );
+      const data = Buffer.concat([
+        sha256_8("global:set_commitment_level"),
+        seq,
+        proofHash,
+        Buffer.from([level]),
+      ]);
+      const keys = [
+        { pubkey: authority.publicKey, isSigner: true, isWritable: false },
+        { pubkey: configPda, isSigner: false, isWritable: false },
+        { pubkey: proofRecordPda, isSigner: false, isWritable: true },
+      ];
+
+      const ix = new web3.TransactionInstruction({ keys, programId, data });
+      const tx = new web3.Transaction();
+      tx.add(ix);
+      const bh = await conn.getLatestBlockhash();
+      tx.recentBlockhash = bh.blockhash;
+      tx.feePayer = authority.publicKey;
+      tx.sign(authority);
+      const sig = await web3.sendAndConfirmTransaction(conn, tx, [authority], {
+        commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized",
+      });
+      process.stdout.write(JSON.stringify({ txid: sig }, null, 2) + "\n");
+    });
+
+  program
+    .command("set-committee") // M-of-N signers for anchor_proof; threshold 1 keeps a single signature
+    .requiredOption("--keypair <PATH>")
+    .requiredOption("--threshold <U8>")
+    .requiredOption("--members <BASE58,...>")
+    .action(async (opts) => {
+      const web3 = await import("@solana/web3.js");
+      const programIdStr = process.env.PROGRAM_ID_VALIDATOR_LOCK || "";
+      if (!programIdStr)
+        throw new Error("PROGRAM_ID_VALIDATOR_LOCK is required");
+      const conn = new web3.Connection(
+        process.env.RPC_URL || "http://localhost:8899",
+        { commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized" }
+      );
+      const programId = new web3.PublicKey(programIdStr);
+      const admin = await readKeypair(opts.keypair);
+      const threshold = Number(opts.threshold);
+      if (!Number.isInteger(threshold) || threshold < 1 || threshold > 255)
+        throw new Error("threshold must be 1..255");
+      const members = String(opts.members)
+        .split(",")
+        .map((m) => m.trim())
+        .filter((m) => m.length > 0)
+        .map((m) => new web3.PublicKey(m));
+      const [configPda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("config")],
+        programId
+      );
+      const [committeePda] = await web3.PublicKey.findProgramAddress(
+        [Buffer.from("zksl"), Buffer.from("committee")],
+        programId
+      );
+      const count = Buffer.alloc(4);
+      count.writeUInt32LE(members.length, 0);
+      const data = Buffer.concat([
+        sha256_8("global:set_committee"),
+        Buffer.from([threshold]),
+        count,
+        ...members.map((m) => m.toBuffer()),
+      ]);
+      const keys = [
+        { pubkey: admin.publicKey, isSigner: true, isWritable: true },
+        { pubkey: configPda, isSigner: false, isWritable: false },
+        { pubkey: committeePda, isSigner: false, isWritable: true },
+        {
+          pubkey: web3.SystemProgram.programId,
+          isSigner: false,
+          isWritable: false,
+        },
+      ];
+
+      const ix = new web3.TransactionInstruction({ keys, programId, data });
+      const tx = new web3.Transaction();
+      tx.add(ix);
+      const bh = await conn.getLatestBlockhash();
+      tx.recentBlockhash = bh.blockhash;
+      tx.feePayer = admin.publicKey;
+      tx.sign(admin);
+      const sig = await web3.sendAndConfirmTransaction(conn, tx, [admin], {
+        commitment: process.env.MIN_FINALITY_COMMITMENT || "finalized",
+      });
+      process.stdout.write(JSON.stringify({ txid: sig }, null, 2) + "\n");
+    });
+
   program
     .command("init-state")
     .requiredOption("--keypair <PATH>")
@@ -497,3 +947,9 @@ function u64le(n: bigint): Buffer {
   b.writeBigUInt64LE(n);
   return b;
 }
+
+function u32le(n: number): Buffer {
+  const b = Buffer.alloc(4);
+  b.writeUInt32LE(n >>> 0);
+  return b;
+}
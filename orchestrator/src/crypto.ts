@@ -8,25 +8,70 @@ export function buildDS(params: {
   startSlot: bigint;
   endSlot: bigint;
   seq: bigint;
+  // AggregatorState.epoch_nonce; appended only when non-zero (mirrors build_ds on-chain)
+  epochNonce?: bigint;
+  // DS layout (anchor_proof's ds_version); v2 also binds artifactLen
+  dsVersion?: 1 | 2;
+  artifactLen?: number;
 }): { ds: Uint8Array; dsHash: Uint8Array } {
   const enc64 = (n: bigint) => {
     const b = Buffer.alloc(8);
     b.writeBigUInt64LE(n);
     return b;
   };
+  const v2 = params.dsVersion === 2;
+  if (v2 && params.artifactLen === undefined)
+    throw new Error("artifactLen is required for DS v2");
   const ds = Buffer.concat([
-    Buffer.from("zKSL/anchor/v1", "utf8"),
+    Buffer.from(v2 ? "zKSL/anchor/v2" : "zKSL/anchor/v1", "utf8"),
     enc64(params.chainId),
     Buffer.from(params.programId),
     Buffer.from(params.proofHash),
     enc64(params.startSlot),
     enc64(params.endSlot),
     enc64(params.seq),
+    ...(v2 ? [u32le(params.artifactLen ?? 0)] : []),
+    ...(params.epochNonce ? [enc64(params.epochNonce)] : []),
   ]);
   const dsHash = blake3hash(ds);
   return { ds: new Uint8Array(ds), dsHash: new Uint8Array(dsHash) };
 }
 
+// Mirrors prover PublicInputs::derive_proof_hash:
+// blake3("zKSL/proof_hash/v1" || c_in || c_out || h_b || before || after || start || end)
+export function deriveProofHash(params: {
+  cIn: Uint8Array;
+  cOut: Uint8Array;
+  hB: Uint8Array;
+  stateRootBefore: Uint8Array;
+  stateRootAfter: Uint8Array;
+  startSlot: bigint;
+  endSlot: bigint;
+}): Uint8Array {
+  const parts = [
+    params.cIn,
+    params.cOut,
+    params.hB,
+    params.stateRootBefore,
+    params.stateRootAfter,
+  ];
+  for (const p of parts) {
+    if (p.byteLength !== 32) throw new Error("expected 32-byte commitment");
+  }
+  const enc64 = (n: bigint) => {
+    const b = Buffer.alloc(8);
+    b.writeBigUInt64LE(n);
+    return b;
+  };
+  const preimage = Buffer.concat([
+    Buffer.from("zKSL/proof_hash/v1", "utf8"),
+    ...parts.map((p) => Buffer.from(p)),
+    enc64(params.startSlot),
+    enc64(params.endSlot),
+  ]);
+  return new Uint8Array(blake3hash(preimage));
+}
+
 export function canonicalize(value: unknown): string {
   return stringifyCanonical(value);
   function stringifyCanonical(v: unknown): string {
@@ -104,6 +149,30 @@ export function u32le(n: number): Buffer {
   return b;
 }
 
+// ProofRecord.commitment_level encoding of the prover's --commitment values
+export const COMMITMENT_LEVELS = {
+  processed: 0,
+  confirmed: 1,
+  finalized: 2,
+} as const;
+
+export function commitmentLevel(name: unknown): number {
+  if (name === undefined || name === null || name === "") {
+    return COMMITMENT_LEVELS.finalized;
+  }
+  const level = (COMMITMENT_LEVELS as Record<string, number>)[String(name)];
+  if (level === undefined) {
+    throw new Error(`unknown commitment: ${String(name)}`);
+  }
+  return level;
+}
+
+function daParams12(daParams?: Uint8Array): Buffer {
+  if (daParams === undefined) return Buffer.alloc(12);
+  if (daParams.length !== 12) throw new Error("daParams must be 12 bytes");
+  return Buffer.from(daParams);
+}
+
 export function encodeAnchorProofArgsBorsh(params: {
   artifactId: Uint8Array;
   proofHash32: Buffer;
@@ -116,9 +185,14 @@ export function encodeAnchorProofArgsBorsh(params: {
   aggregatorPubkey: Uint8Array;
   timestampLe: Buffer;
   dsHash32: Buffer;
+  commitmentLevel?: number;
+  // ProofRecord.da_params (12 bytes, byte 0 = DA scheme tag); zeroed when omitted
+  daParams?: Uint8Array;
+  // DS layout the aggregator signed (1 or 2); v1 when omitted
+  dsVersion?: number;
 }): Buffer {
   const disc = sha256_8("global:anchor_proof");
-  // Match Rust function arg order: artifact_id, proof_hash, seq, start_slot, end_slot, artifact_len, state_root_before, state_root_after, aggregator_pubkey, timestamp, ds_hash
+  // Match Rust function arg order: artifact_id, proof_hash, seq, start_slot, end_slot, artifact_len, state_root_before, state_root_after, aggregator_pubkey, timestamp, ds_hash, commitment_level, da_params, ds_version
   const payload = Buffer.concat([
     Buffer.from(params.artifactId), // arg 0
     params.proofHash32, // arg 1
@@ -131,6 +205,9 @@ export function encodeAnchorProofArgsBorsh(params: {
     Buffer.from(params.aggregatorPubkey), // arg 8
     params.timestampLe, // arg 9
     params.dsHash32, // arg 10
+    Buffer.from([params.commitmentLevel ?? COMMITMENT_LEVELS.finalized]), // arg 11
+    daParams12(params.daParams), // arg 12
+    Buffer.from([params.dsVersion ?? 1]), // arg 13
   ]);
   return Buffer.concat([disc, payload]);
 }
@@ -42,10 +42,10 @@ export async function fetchConfig(
   };
 }
 
-export async function fetchLastSeq(
+export async function fetchAggregatorState(
   programIdStr: string,
   rpcUrl: string
-): Promise<bigint> {
+): Promise<{ lastSeq: bigint; epochNonce: bigint }> {
   const web3 = await import("@solana/web3.js");
   const programId = new web3.PublicKey(programIdStr);
   const connection = new web3.Connection(rpcUrl, {
@@ -60,9 +60,10 @@ export async function fetchLastSeq(
     commitment:
       (process.env.MIN_FINALITY_COMMITMENT as Commitment) || "finalized",
   });
-  if (!acc) return 0n;
+  if (!acc) return { lastSeq: 0n, epochNonce: 0n };
   const data: Buffer = acc.data;
   const off = 8 + 32; // skip discriminator + aggregator_pubkey
   const lastSeq = data.readBigUInt64LE(off);
-  return lastSeq;
+  const epochNonce = data.readBigUInt64LE(off + 8);
+  return { lastSeq, epochNonce };
 }
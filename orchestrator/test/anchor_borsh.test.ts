@@ -7,7 +7,7 @@ import {
 } from "../src/crypto.js";
 
 describe("anchor borsh payload", () => {
-  it("has correct discriminator and total length (220 bytes)", () => {
+  it("has correct discriminator and total length (234 bytes)", () => {
     const zero16 = new Uint8Array(16);
     const zero32 = Buffer.alloc(32, 0);
     const data = encodeAnchorProofArgsBorsh({
@@ -23,10 +23,38 @@ describe("anchor borsh payload", () => {
       seqLe: u64le(1n),
       dsHash32: zero32,
     });
-    expect(data.byteLength).toBe(220);
+    expect(data.byteLength).toBe(234);
     const disc = data.subarray(0, 8);
     expect(Buffer.from(disc).equals(sha256_8("global:anchor_proof"))).toBe(
       true
     );
   });
+
+  it("appends da_params (12 bytes) then ds_version", () => {
+    const zero32 = Buffer.alloc(32, 0);
+    const daParams = new Uint8Array(12);
+    daParams[0] = 2;
+    const base = {
+      artifactId: new Uint8Array(16),
+      startLe: u64le(1n),
+      endLe: u64le(1n),
+      proofHash32: zero32,
+      artifactLen: 0,
+      stateRootBefore: zero32,
+      stateRootAfter: zero32,
+      aggregatorPubkey: zero32,
+      timestampLe: i64le(1n),
+      seqLe: u64le(1n),
+      dsHash32: zero32,
+    };
+    const data = encodeAnchorProofArgsBorsh({ ...base, daParams });
+    expect(
+      Buffer.from(data.subarray(221, 233)).equals(Buffer.from(daParams))
+    ).toBe(true);
+    expect(data[233]).toBe(1);
+    expect(encodeAnchorProofArgsBorsh({ ...base, dsVersion: 2 })[233]).toBe(2);
+    expect(() =>
+      encodeAnchorProofArgsBorsh({ ...base, daParams: new Uint8Array(11) })
+    ).toThrow();
+  });
 });
@@ -18,7 +18,7 @@ describe("/anchor chain id mismatch", () => {
         activation_seq: 1n,
         chain_id: 1n, // on-chain chain id different from env C
       }),
-      fetchLastSeq: async () => 0n,
+      fetchAggregatorState: async () => ({ lastSeq: 0n, epochNonce: 0n }),
     }));
     const mod = await import("../src/server.js");
     const app = (mod as any).app as any;
@@ -42,6 +42,29 @@ describe("canonicalization and DS invariants (additional)", () => {
     expect(dsHash.byteLength).toBe(32);
   });
 
+  it("DS v2 has its own prefix and binds artifact_len (114 bytes)", () => {
+    const zero32 = new Uint8Array(32);
+    const base = {
+      chainId: 1n,
+      programId: zero32,
+      proofHash: zero32,
+      startSlot: 1n,
+      endSlot: 2n,
+      seq: 3n,
+    };
+    const v1 = buildDS(base).ds;
+    const v2 = buildDS({ ...base, dsVersion: 2, artifactLen: 4096 }).ds;
+    expect(v2.byteLength).toBe(114);
+    expect(Buffer.from(v2.subarray(0, 14)).toString("utf8")).toBe(
+      "zKSL/anchor/v2"
+    );
+    expect(Buffer.from(v2.subarray(14, 110)).equals(v1.subarray(14))).toBe(
+      true
+    );
+    expect(Buffer.from(v2.subarray(110)).readUInt32LE(0)).toBe(4096);
+    expect(() => buildDS({ ...base, dsVersion: 2 })).toThrow();
+  });
+
   it("ds_hash changes when any input changes (seq)", () => {
     const zero32 = new Uint8Array(32);
     const a = buildDS({
@@ -21,6 +21,24 @@ describe("crypto primitives", () => {
     expect(dsHash.byteLength).toBe(32);
   });
 
+  it("buildDS appends a non-zero epochNonce so pre-reset signatures differ", () => {
+    const zero32 = new Uint8Array(32);
+    const base = {
+      chainId: 1n,
+      programId: zero32,
+      proofHash: zero32,
+      startSlot: 1n,
+      endSlot: 1n,
+      seq: 1n,
+    };
+    const before = buildDS({ ...base, epochNonce: 0n });
+    const after = buildDS({ ...base, epochNonce: 1n });
+    expect(before.ds).toEqual(buildDS(base).ds);
+    expect(after.ds.byteLength).toBe(118);
+    expect(Buffer.from(after.ds.subarray(0, 110))).toEqual(Buffer.from(before.ds));
+    expect(Buffer.from(after.dsHash)).not.toEqual(Buffer.from(before.dsHash));
+  });
+
   it("canonicalize sorts keys and omits undefined", () => {
     const a = canonicalize({ b: 2, a: 1, x: undefined });
     const b = canonicalize({ a: 1, b: 2 });
@@ -7,7 +7,7 @@ describe("KATs: DS and anchor_proof encoding", () => {
   const proofHash = Buffer.alloc(32, 5);
 
   Array.from({ length: 40 }, (_, i) => i).forEach((i) => {
-    it(`KAT #${i}: DS(110) and anchor_proof len(220)`, () => {
+    it(`KAT #${i}: DS(110) and anchor_proof len(233)`, () => {
       const start = BigInt(1 + i);
       const end = BigInt(1 + i);
       const seq = BigInt(1 + i);
@@ -35,7 +35,7 @@ describe("KATs: DS and anchor_proof encoding", () => {
         seqLe: u64le(seq),
         dsHash32: Buffer.from(dsHash),
       });
-      expect(data.length).toBe(220);
+      expect(data.length).toBe(233);
     });
   });
 });
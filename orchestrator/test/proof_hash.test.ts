@@ -0,0 +1,31 @@
+import { describe, it, expect } from "vitest";
+import { deriveProofHash } from "../src/crypto.js";
+
+describe("proof_hash derivation", () => {
+  const base = {
+    cIn: new Uint8Array(32).fill(0x11),
+    cOut: new Uint8Array(32).fill(0x22),
+    hB: new Uint8Array(32).fill(0x33),
+    stateRootBefore: new Uint8Array(32).fill(0x01),
+    stateRootAfter: new Uint8Array(32).fill(0x02),
+    startSlot: 1n,
+    endSlot: 8n,
+  };
+
+  it("matches the prover known-answer vector", () => {
+    expect(Buffer.from(deriveProofHash(base)).toString("hex")).toBe(
+      "b5524c1723fe7c81e00d1aa5f639f2ac801b95144b18dfa53a86bb8b1a469cf6"
+    );
+  });
+
+  it("changes when any North Star commitment changes", () => {
+    const h = Buffer.from(deriveProofHash(base)).toString("hex");
+    for (const key of ["cIn", "cOut", "hB"] as const) {
+      const other = deriveProofHash({
+        ...base,
+        [key]: new Uint8Array(32).fill(0x44),
+      });
+      expect(Buffer.from(other).toString("hex")).not.toBe(h);
+    }
+  });
+});
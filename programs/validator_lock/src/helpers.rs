@@ -0,0 +1,1706 @@
+//! Shared helpers usable both on-chain and by off-chain clients.
+//!
+//! Everything here is pure (no account loading, no syscalls) so SDKs and tests can link
+//! the exact logic the program enforces instead of re-implementing it.
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use serde::{Deserialize, Serialize};
+
+use crate::{Config, InitializeArgs, ValidatorRecord, ZkError};
+
+/// Ed25519 instruction header size: signature count byte plus one padding byte.
+const ED25519_HEADER_LEN: usize = 2;
+/// Size of a single Ed25519 signature offsets record within the instruction header.
+const ED25519_OFFSETS_LEN: usize = 14;
+/// Ed25519 signature length in bytes.
+const ED25519_SIGNATURE_LEN: usize = 64;
+/// Ed25519 public key length in bytes.
+const ED25519_PUBKEY_LEN: usize = 32;
+
+/// Select the aggregator key authorized for `seq`: `next` at or after `activation_seq`, else `current`.
+#[must_use]
+pub const fn select_aggregator_key(
+    current: Pubkey,
+    next: Pubkey,
+    activation_seq: u64,
+    seq: u64,
+) -> Pubkey {
+    if seq >= activation_seq {
+        next
+    } else {
+        current
+    }
+}
+
+/// Whether the program accepts either aggregator key for `seq` under a rotation `grace`.
+///
+/// That holds for `seq` in `[activation_seq - grace, activation_seq + grace)`, so never for a
+/// zero `grace`.
+#[must_use]
+pub const fn in_rotation_grace(activation_seq: u64, grace: u64, seq: u64) -> bool {
+    seq >= activation_seq.saturating_sub(grace) && seq < activation_seq.saturating_add(grace)
+}
+
+/// Off-chain mirror of the program's aggregator key selection for a planned `seq`.
+///
+/// Takes a [`ConfigView`] ([`ConfigView::from_account_data`]), so configs not yet grown by
+/// `resize_config` work too. Clients should sign the DS with the returned key to avoid
+/// `AggregatorMismatch`.
+#[must_use]
+pub const fn allowed_aggregator_key_for(config: &ConfigView, seq: u64) -> Pubkey {
+    select_aggregator_key(
+        config.aggregator_pubkey,
+        config.next_aggregator_pubkey,
+        config.activation_seq,
+        seq,
+    )
+}
+
+/// A problem with [`InitializeArgs`] found before the init transaction is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitArgsIssue {
+    /// `aggregator_pubkey` is the default key (the program rejects it).
+    DefaultAggregatorKey,
+    /// `next_aggregator_pubkey` is the default key, so no proof could be anchored once it activates.
+    DefaultNextAggregatorKey,
+    /// `activation_seq` is at or below the first `seq` (1) while the next key differs, so
+    /// the rotation is immediate and `aggregator_pubkey` never authorizes a proof.
+    ImmediateRotation {
+        /// The offending `activation_seq`.
+        activation_seq: u64,
+    },
+    /// `chain_id` is 0, which binds no chain into the DS.
+    ZeroChainId,
+    /// `max_slots_per_artifact` is above one mainnet epoch (the program rejects it).
+    SlotWindowTooWide {
+        /// The offending `max_slots_per_artifact`.
+        max_slots_per_artifact: u64,
+    },
+    /// `max_past_skew_secs` is above one hour (the program rejects it).
+    PastSkewTooWide {
+        /// The offending `max_past_skew_secs`.
+        max_past_skew_secs: u32,
+    },
+    /// `max_future_skew_secs` is above one hour (the program rejects it).
+    FutureSkewTooWide {
+        /// The offending `max_future_skew_secs`.
+        max_future_skew_secs: u32,
+    },
+}
+
+/// Check `args` before submitting `initialize`, `initialize_pool` or `bootstrap`.
+///
+/// Stricter than the program, which rejects a default `aggregator_pubkey` and the limits
+/// `update_config` also bounds; the other issues are accepted on-chain but leave a config
+/// that cannot be fixed without `update_config`.
+///
+/// # Errors
+/// The first [`InitArgsIssue`] in field order.
+pub fn validate_initialize_args(args: &InitializeArgs) -> Result<(), InitArgsIssue> {
+    if args.aggregator_pubkey == Pubkey::default() {
+        return Err(InitArgsIssue::DefaultAggregatorKey);
+    }
+    if args.next_aggregator_pubkey == Pubkey::default() {
+        return Err(InitArgsIssue::DefaultNextAggregatorKey);
+    }
+    if args.activation_seq <= 1 && args.next_aggregator_pubkey != args.aggregator_pubkey {
+        return Err(InitArgsIssue::ImmediateRotation {
+            activation_seq: args.activation_seq,
+        });
+    }
+    if args.chain_id == 0 {
+        return Err(InitArgsIssue::ZeroChainId);
+    }
+    if args.max_slots_per_artifact > crate::MAX_SLOTS_PER_ARTIFACT_LIMIT {
+        return Err(InitArgsIssue::SlotWindowTooWide {
+            max_slots_per_artifact: args.max_slots_per_artifact,
+        });
+    }
+    if args.max_past_skew_secs > crate::MAX_CLOCK_SKEW_LIMIT_SECS {
+        return Err(InitArgsIssue::PastSkewTooWide {
+            max_past_skew_secs: args.max_past_skew_secs,
+        });
+    }
+    if args.max_future_skew_secs > crate::MAX_CLOCK_SKEW_LIMIT_SECS {
+        return Err(InitArgsIssue::FutureSkewTooWide {
+            max_future_skew_secs: args.max_future_skew_secs,
+        });
+    }
+    Ok(())
+}
+
+/// Compute-unit ceiling of a single transaction.
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+/// Estimated fixed cost of `anchor_proof`: account checks, `ProofRecord` creation via the
+/// system program, the 110-byte DS BLAKE3 hash and the Ed25519 data parse.
+const ANCHOR_PROOF_BASE_CU: u32 = 60_000;
+/// Estimated cost per transaction instruction of the sysvar-instructions preflight scan
+/// (`load_instruction_at_checked` deserializes each one in full).
+const ANCHOR_PROOF_CU_PER_INSTRUCTION: u32 = 4_000;
+/// Most instructions a transaction carrying `anchor_proof` may hold.
+///
+/// The preflight scan stops one past this and rejects the transaction with `BadEd25519Order`, so its cost
+/// stays bounded whatever the runtime's own per-transaction limit.
+pub const MAX_PREFLIGHT_INSTRUCTIONS: usize = 64;
+
+/// Conservative compute-unit limit for a transaction carrying `anchor_proof`.
+///
+/// `num_instructions_in_tx` counts every instruction, including the `ComputeBudget` and
+/// Ed25519 ones, since the preflight scan visits all of them (at most
+/// [`MAX_PREFLIGHT_INSTRUCTIONS`] are accepted). The base and per-instruction
+/// costs above are estimates with headroom, and the total is padded by 50% and capped at
+/// [`MAX_COMPUTE_UNITS`]; re-derive them from `consumed` in program logs if the handler grows.
+#[must_use]
+pub fn recommend_compute_units(num_instructions_in_tx: usize) -> u32 {
+    let n = u32::try_from(num_instructions_in_tx).unwrap_or(u32::MAX);
+    let estimate = ANCHOR_PROOF_CU_PER_INSTRUCTION
+        .saturating_mul(n)
+        .saturating_add(ANCHOR_PROOF_BASE_CU);
+    estimate.saturating_add(estimate / 2).min(MAX_COMPUTE_UNITS)
+}
+
+/// Byte offset of `ValidatorRecord::status` in account data (after the 8-byte discriminator).
+pub const VALIDATOR_RECORD_STATUS_OFFSET: usize = 8 + 32 + 32 + 8;
+
+/// A `getProgramAccounts` filter, mirroring the RPC `dataSize` / `memcmp` filter shapes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountFilter {
+    /// Match accounts whose data is exactly this many bytes.
+    DataSize(u64),
+    /// Match accounts whose data contains `bytes` at `offset`.
+    Memcmp {
+        /// Byte offset into account data.
+        offset: usize,
+        /// Bytes to compare (base58-encode for the RPC call).
+        bytes: Vec<u8>,
+    },
+}
+
+/// Account discriminator of `ValidatorRecord`.
+#[must_use]
+pub fn validator_record_discriminator() -> [u8; 8] {
+    let mut out = [0u8; 8];
+    for (dst, src) in out.iter_mut().zip(ValidatorRecord::DISCRIMINATOR) {
+        *dst = *src;
+    }
+    out
+}
+
+/// Filters selecting every `ValidatorRecord` of the program, optionally only with `status`.
+#[must_use]
+pub fn validator_record_filters(status: Option<u8>) -> Vec<AccountFilter> {
+    let data_size = u64::try_from(ValidatorRecord::SIZE.saturating_add(8)).unwrap_or(u64::MAX);
+    let mut filters = vec![
+        AccountFilter::DataSize(data_size),
+        AccountFilter::Memcmp {
+            offset: 0,
+            bytes: validator_record_discriminator().to_vec(),
+        },
+    ];
+    if let Some(wanted) = status {
+        filters.push(AccountFilter::Memcmp {
+            offset: VALIDATOR_RECORD_STATUS_OFFSET,
+            bytes: vec![wanted],
+        });
+    }
+    filters
+}
+
+/// Decoded `ValidatorRecord` fields for off-chain consumers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidatorRecordView {
+    /// Validator wallet public key.
+    pub validator_pubkey: Pubkey,
+    /// Escrow token account holding locked zKSL.
+    pub lock_token_account: Pubkey,
+    /// Unix timestamp when the lock was created.
+    pub lock_timestamp: i64,
+    /// Status (0 = Active, 1 = Unlocked).
+    pub status: u8,
+    /// Number of accepts observed for this validator.
+    pub num_accepts: u64,
+    /// zKSL base units locked at registration (0 on records created before the field).
+    pub locked_amount: u64,
+}
+
+/// Decode raw `ValidatorRecord` account data, discriminator included.
+///
+/// # Errors
+/// Anchor's `AccountDiscriminatorMismatch` / `AccountDidNotDeserialize` for data that is not
+/// a `ValidatorRecord`.
+pub fn decode_validator_record(data: &[u8]) -> anchor_lang::Result<ValidatorRecordView> {
+    let mut buf = data;
+    let record = ValidatorRecord::try_deserialize(&mut buf)?;
+    Ok(ValidatorRecordView {
+        validator_pubkey: record.validator_pubkey,
+        lock_token_account: record.lock_token_account,
+        lock_timestamp: record.lock_timestamp,
+        status: record.status,
+        num_accepts: record.num_accepts,
+        locked_amount: record.locked_amount,
+    })
+}
+
+/// Decoded `Config` fields for off-chain tooling (dashboards, the history verifier).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigView {
+    /// Mint for the zKSL token used for escrow.
+    pub zksl_mint: Pubkey,
+    /// Admin authority allowed to update configuration.
+    pub admin: Pubkey,
+    /// Current aggregator public key authorized for DS signatures.
+    pub aggregator_pubkey: Pubkey,
+    /// Next aggregator public key for rotation.
+    pub next_aggregator_pubkey: Pubkey,
+    /// Activation sequence for aggregator rotation.
+    pub activation_seq: u64,
+    /// Chain identifier bound into domain separation.
+    pub chain_id: u64,
+    /// Paused flag (0 = active, 1 = paused).
+    pub paused: u8,
+    /// PDA bump for `config` account.
+    pub bump: u8,
+    /// Maximum seconds a proof timestamp may lag the on-chain clock (0 = default).
+    pub max_past_skew_secs: u32,
+    /// Maximum seconds a proof timestamp may lead the on-chain clock (0 = default).
+    pub max_future_skew_secs: u32,
+    /// Emergency unlock-all flag (0 = off, 1 = on).
+    pub allow_emergency_unlock: u8,
+    /// Total zKSL base units currently escrowed across all validators.
+    pub total_locked: u64,
+    /// Escrow and record PDA seed layout (0 = legacy singleton, 1 = pooled).
+    pub seeds_version: u8,
+    /// Uniform-DA flag (0 = off, 1 = on).
+    pub enforce_uniform_da: u8,
+    /// Proof-hash binding flag (0 = off, 1 = on).
+    pub bind_proof_hash: u8,
+    /// Range-tracking flag (0 = global, 1 = per submitter).
+    pub per_submitter_range: u8,
+    /// Escrow locked per new validator, in zKSL base units (0 = one token).
+    pub min_lock_amount: u64,
+    /// Maximum slots an anchored proof's `end_slot` may trail the clock (0 = no limit).
+    pub max_proof_age_slots: u64,
+    /// Admin awaiting `accept_admin` (`Pubkey::default()` = none).
+    pub pending_admin: Pubkey,
+    /// Widest slot range one proof may cover (0 = the program default).
+    pub max_slots_per_artifact: u64,
+    /// Sequences around `activation_seq` accepting either aggregator key (0 = none).
+    pub rotation_grace: u64,
+    /// Seconds a lock must age before it can be unlocked (0 = no cooldown).
+    pub min_lock_duration_secs: i64,
+    /// Token-2022 flag (1 = zKSL may be a Token-2022 mint).
+    pub allow_token_2022: u8,
+}
+
+impl ConfigView {
+    /// Decode raw `Config` account data, discriminator included.
+    ///
+    /// Configs not yet grown by `resize_config` (`8 + Config::PRIOR_SIZES` bytes) decode with
+    /// the fields they lack zeroed, as the program reads them after the resize.
+    ///
+    /// # Errors
+    /// `AccountDidNotDeserialize` unless `data` is `8 + Config::SIZE` bytes or `8` plus one of
+    /// `Config::PRIOR_SIZES`, and `AccountDiscriminatorMismatch` for data that is not a `Config`.
+    pub fn from_account_data(data: &[u8]) -> anchor_lang::Result<Self> {
+        let mut padded = data.to_vec();
+        match data.len().saturating_sub(Config::DISCRIMINATOR.len()) {
+            Config::SIZE => {}
+            len if Config::PRIOR_SIZES.contains(&len) => {
+                padded.resize(Config::DISCRIMINATOR.len().saturating_add(Config::SIZE), 0);
+            }
+            _ => return Err(ErrorCode::AccountDidNotDeserialize.into()),
+        }
+        let mut buf = padded.as_slice();
+        let cfg = Config::try_deserialize(&mut buf)?;
+        Ok(Self::from(&cfg))
+    }
+}
+
+impl From<&Config> for ConfigView {
+    fn from(cfg: &Config) -> Self {
+        Self {
+            zksl_mint: cfg.zksl_mint,
+            admin: cfg.admin,
+            aggregator_pubkey: cfg.aggregator_pubkey,
+            next_aggregator_pubkey: cfg.next_aggregator_pubkey,
+            activation_seq: cfg.activation_seq,
+            chain_id: cfg.chain_id,
+            paused: cfg.paused,
+            bump: cfg.bump,
+            max_past_skew_secs: cfg.max_past_skew_secs,
+            max_future_skew_secs: cfg.max_future_skew_secs,
+            allow_emergency_unlock: cfg.allow_emergency_unlock,
+            total_locked: cfg.total_locked,
+            seeds_version: cfg.seeds_version,
+            enforce_uniform_da: cfg.enforce_uniform_da,
+            bind_proof_hash: cfg.bind_proof_hash,
+            per_submitter_range: cfg.per_submitter_range,
+            min_lock_amount: cfg.min_lock_amount,
+            max_proof_age_slots: cfg.max_proof_age_slots,
+            pending_admin: cfg.pending_admin,
+            max_slots_per_artifact: cfg.max_slots_per_artifact,
+            rotation_grace: cfg.rotation_grace,
+            min_lock_duration_secs: cfg.min_lock_duration_secs,
+            allow_token_2022: cfg.allow_token_2022,
+        }
+    }
+}
+
+/// One field that differs between two `Config` snapshots, with its old and new value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConfigChange {
+    /// `zksl_mint` changed.
+    ZkslMint {
+        /// Previous value.
+        from: Pubkey,
+        /// New value.
+        to: Pubkey,
+    },
+    /// `admin` changed.
+    Admin {
+        /// Previous value.
+        from: Pubkey,
+        /// New value.
+        to: Pubkey,
+    },
+    /// `aggregator_pubkey` changed.
+    AggregatorPubkey {
+        /// Previous value.
+        from: Pubkey,
+        /// New value.
+        to: Pubkey,
+    },
+    /// `next_aggregator_pubkey` changed.
+    NextAggregatorPubkey {
+        /// Previous value.
+        from: Pubkey,
+        /// New value.
+        to: Pubkey,
+    },
+    /// `activation_seq` changed.
+    ActivationSeq {
+        /// Previous value.
+        from: u64,
+        /// New value.
+        to: u64,
+    },
+    /// `chain_id` changed.
+    ChainId {
+        /// Previous value.
+        from: u64,
+        /// New value.
+        to: u64,
+    },
+    /// `paused` changed.
+    Paused {
+        /// Previous value.
+        from: u8,
+        /// New value.
+        to: u8,
+    },
+    /// `bump` changed.
+    Bump {
+        /// Previous value.
+        from: u8,
+        /// New value.
+        to: u8,
+    },
+    /// `max_past_skew_secs` changed.
+    MaxPastSkewSecs {
+        /// Previous value.
+        from: u32,
+        /// New value.
+        to: u32,
+    },
+    /// `max_future_skew_secs` changed.
+    MaxFutureSkewSecs {
+        /// Previous value.
+        from: u32,
+        /// New value.
+        to: u32,
+    },
+    /// `allow_emergency_unlock` changed.
+    AllowEmergencyUnlock {
+        /// Previous value.
+        from: u8,
+        /// New value.
+        to: u8,
+    },
+    /// `total_locked` changed.
+    TotalLocked {
+        /// Previous value.
+        from: u64,
+        /// New value.
+        to: u64,
+    },
+    /// `seeds_version` changed.
+    SeedsVersion {
+        /// Previous value.
+        from: u8,
+        /// New value.
+        to: u8,
+    },
+    /// `enforce_uniform_da` changed.
+    EnforceUniformDa {
+        /// Previous value.
+        from: u8,
+        /// New value.
+        to: u8,
+    },
+    /// `bind_proof_hash` changed.
+    BindProofHash {
+        /// Previous value.
+        from: u8,
+        /// New value.
+        to: u8,
+    },
+    /// `per_submitter_range` changed.
+    PerSubmitterRange {
+        /// Previous value.
+        from: u8,
+        /// New value.
+        to: u8,
+    },
+    /// `min_lock_amount` changed.
+    MinLockAmount {
+        /// Previous value.
+        from: u64,
+        /// New value.
+        to: u64,
+    },
+    /// `max_proof_age_slots` changed.
+    MaxProofAgeSlots {
+        /// Previous value.
+        from: u64,
+        /// New value.
+        to: u64,
+    },
+    /// `pending_admin` changed.
+    PendingAdmin {
+        /// Previous value.
+        from: Pubkey,
+        /// New value.
+        to: Pubkey,
+    },
+    /// `max_slots_per_artifact` changed.
+    MaxSlotsPerArtifact {
+        /// Previous value.
+        from: u64,
+        /// New value.
+        to: u64,
+    },
+    /// `rotation_grace` changed.
+    RotationGrace {
+        /// Previous value.
+        from: u64,
+        /// New value.
+        to: u64,
+    },
+    /// `min_lock_duration_secs` changed.
+    MinLockDurationSecs {
+        /// Previous value.
+        from: i64,
+        /// New value.
+        to: i64,
+    },
+    /// `allow_token_2022` changed.
+    AllowToken2022 {
+        /// Previous value.
+        from: u8,
+        /// New value.
+        to: u8,
+    },
+}
+
+/// Every field that differs between `before` and `after`, in `Config` field order.
+#[must_use]
+pub fn diff_config(before: &ConfigView, after: &ConfigView) -> Vec<ConfigChange> {
+    macro_rules! changed {
+        ($field:ident => $variant:ident) => {
+            (before.$field != after.$field).then_some(ConfigChange::$variant {
+                from: before.$field,
+                to: after.$field,
+            })
+        };
+    }
+    let candidates = [
+        changed!(zksl_mint => ZkslMint),
+        changed!(admin => Admin),
+        changed!(aggregator_pubkey => AggregatorPubkey),
+        changed!(next_aggregator_pubkey => NextAggregatorPubkey),
+        changed!(activation_seq => ActivationSeq),
+        changed!(chain_id => ChainId),
+        changed!(paused => Paused),
+        changed!(bump => Bump),
+        changed!(max_past_skew_secs => MaxPastSkewSecs),
+        changed!(max_future_skew_secs => MaxFutureSkewSecs),
+        changed!(allow_emergency_unlock => AllowEmergencyUnlock),
+        changed!(total_locked => TotalLocked),
+        changed!(seeds_version => SeedsVersion),
+        changed!(enforce_uniform_da => EnforceUniformDa),
+        changed!(bind_proof_hash => BindProofHash),
+        changed!(per_submitter_range => PerSubmitterRange),
+        changed!(min_lock_amount => MinLockAmount),
+        changed!(max_proof_age_slots => MaxProofAgeSlots),
+        changed!(pending_admin => PendingAdmin),
+        changed!(max_slots_per_artifact => MaxSlotsPerArtifact),
+        changed!(rotation_grace => RotationGrace),
+        changed!(min_lock_duration_secs => MinLockDurationSecs),
+        changed!(allow_token_2022 => AllowToken2022),
+    ];
+    candidates.into_iter().flatten().collect()
+}
+
+/// Escrow authority PDA for `validator`: seeds `["zksl", "escrow", validator]` under `program_id`.
+#[must_use]
+pub fn escrow_authority(validator: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"zksl", b"escrow", validator.as_ref()], program_id).0
+}
+
+/// `proof_record` PDA for `(proof_hash, seq)`: seeds `["zksl", "proof", proof_hash, seq_le]`.
+///
+/// Every seed has a fixed width (4, 5, 32 and 8 bytes), so the seed bytes decode back to a
+/// unique `(proof_hash, seq)`: no `seq` byte can be read as part of a hash or vice versa, and
+/// no separator is needed. Two distinct pairs therefore only share a PDA through a SHA-256
+/// collision in the address derivation, which keeps `init` failing for a replayed pair and
+/// only for it.
+#[must_use]
+pub fn proof_record_address(proof_hash: &[u8; 32], seq: u64, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"zksl", b"proof", proof_hash, &seq.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+/// `artifact_record` PDA for `artifact_id`: seeds `["zksl", "artifact", artifact_id]`.
+///
+/// Keyed on the artifact alone, so it exists once per artifact across every `seq`.
+#[must_use]
+pub fn artifact_record_address(artifact_id: &[u8; 16], program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"zksl", b"artifact", artifact_id], program_id).0
+}
+
+/// Address of `validator_escrow` as `RegisterValidator` creates it: the ATA of the escrow
+/// authority PDA for `mint` under `token_program`.
+///
+/// SDKs should use this rather than deriving the ATA of `validator` itself, which is what
+/// otherwise surfaces as a confusing `init_if_needed` constraint failure.
+#[must_use]
+pub fn escrow_ata(
+    validator: &Pubkey,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    get_associated_token_address_with_program_id(
+        &escrow_authority(validator, program_id),
+        mint,
+        token_program,
+    )
+}
+
+/// Pool config PDA created by `initialize_pool`: seeds `["zksl", "config", pool_id_le]`.
+#[must_use]
+pub fn pool_config_address(pool_id: u64, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"zksl", b"config", &pool_id.to_le_bytes()], program_id).0
+}
+
+/// Escrow authority PDA for `validator` in the pool `config`: seeds
+/// `["zksl", "escrow", config, validator]`.
+///
+/// Only configs with `seeds_version == SEEDS_VERSION_POOLED` use this layout; the legacy
+/// singleton config keeps `escrow_authority`.
+#[must_use]
+pub fn pool_escrow_authority(config: &Pubkey, validator: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"zksl", b"escrow", config.as_ref(), validator.as_ref()],
+        program_id,
+    )
+    .0
+}
+
+/// `validator_record` PDA for `validator` in the pool `config`: seeds
+/// `["zksl", "validator", config, validator]`.
+#[must_use]
+pub fn pool_validator_record_address(
+    config: &Pubkey,
+    validator: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"zksl", b"validator", config.as_ref(), validator.as_ref()],
+        program_id,
+    )
+    .0
+}
+
+/// Address of `validator_escrow` as `register_validator_pooled` creates it; see `escrow_ata`.
+#[must_use]
+pub fn pool_escrow_ata(
+    config: &Pubkey,
+    validator: &Pubkey,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    get_associated_token_address_with_program_id(
+        &pool_escrow_authority(config, validator, program_id),
+        mint,
+        token_program,
+    )
+}
+
+/// Domain separation prefix of the DS message `anchor_proof` checks.
+pub const DS_PREFIX: &[u8] = b"zKSL/anchor/v1";
+
+/// DS message the aggregator signs for `anchor_proof`:
+/// `DS_PREFIX || chain_id || program_id || proof_hash || start_slot || end_slot || seq`,
+/// integers little-endian (110 bytes).
+///
+/// The program builds its DS with this function, so clients signing its output cannot drift
+/// from the on-chain layout. Once the anchoring state has been reset, the program appends the
+/// non-zero `AggregatorState::epoch_nonce` (8 bytes, little-endian) to this message.
+#[must_use]
+pub fn compute_ds_message(
+    chain_id: u64,
+    program_id: &Pubkey,
+    proof_hash: &[u8; 32],
+    start_slot: u64,
+    end_slot: u64,
+    seq: u64,
+) -> Vec<u8> {
+    let mut ds =
+        Vec::with_capacity(crate::ds_len(crate::DS_VERSION_V1).saturating_add(crate::DS_NONCE_LEN));
+    ds.extend_from_slice(DS_PREFIX);
+    ds.extend_from_slice(&chain_id.to_le_bytes());
+    ds.extend_from_slice(program_id.as_ref());
+    ds.extend_from_slice(proof_hash);
+    ds.extend_from_slice(&start_slot.to_le_bytes());
+    ds.extend_from_slice(&end_slot.to_le_bytes());
+    ds.extend_from_slice(&seq.to_le_bytes());
+    ds
+}
+
+/// `blake3` of [`compute_ds_message`]: the `ds_hash` argument `anchor_proof` expects while
+/// `epoch_nonce` is zero.
+#[must_use]
+pub fn compute_ds_hash(
+    chain_id: u64,
+    program_id: &Pubkey,
+    proof_hash: &[u8; 32],
+    start_slot: u64,
+    end_slot: u64,
+    seq: u64,
+) -> [u8; 32] {
+    let ds = compute_ds_message(chain_id, program_id, proof_hash, start_slot, end_slot, seq);
+    *blake3::hash(&ds).as_bytes()
+}
+
+/// DS prefix of the v2 layout, which also binds `artifact_len`.
+pub const DS_PREFIX_V2: &[u8] = b"zKSL/anchor/v2";
+
+/// v2 DS message: [`compute_ds_message`] under [`DS_PREFIX_V2`], followed by `artifact_len`
+/// (4 bytes, little-endian), 114 bytes in all.
+///
+/// Signing it commits the aggregator to the artifact size it reports; the v1 message leaves
+/// `artifact_len` unsigned. The program appends a non-zero `epoch_nonce` as for v1.
+#[must_use]
+pub fn compute_ds_message_v2(
+    chain_id: u64,
+    program_id: &Pubkey,
+    proof_hash: &[u8; 32],
+    start_slot: u64,
+    end_slot: u64,
+    seq: u64,
+    artifact_len: u32,
+) -> Vec<u8> {
+    let mut ds = compute_ds_message(chain_id, program_id, proof_hash, start_slot, end_slot, seq);
+    ds.splice(..DS_PREFIX.len(), DS_PREFIX_V2.iter().copied());
+    ds.extend_from_slice(&artifact_len.to_le_bytes());
+    ds
+}
+
+/// Domain-separation context prefixed to the transition proof-hash preimage.
+pub const TRANSITION_HASH_CONTEXT: &[u8] = b"zKSL/transition/v1";
+
+/// Proof hash required under `Config::bind_proof_hash`:
+/// `blake3(TRANSITION_HASH_CONTEXT || before || after || start_slot_le || end_slot_le)`.
+///
+/// Unlike the prover's artifact hash it omits the North Star commitments, which the program
+/// never sees, so it can be recomputed on-chain from `anchor_proof`'s own arguments.
+#[must_use]
+pub fn transition_proof_hash(
+    state_root_before: &[u8; 32],
+    state_root_after: &[u8; 32],
+    start_slot: u64,
+    end_slot: u64,
+) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(TRANSITION_HASH_CONTEXT);
+    hasher.update(state_root_before);
+    hasher.update(state_root_after);
+    hasher.update(&start_slot.to_le_bytes());
+    hasher.update(&end_slot.to_le_bytes());
+    *hasher.finalize().as_bytes()
+}
+
+/// `validator_metadata` PDA for `validator`: seeds `["zksl", "metadata", validator]`.
+#[must_use]
+pub fn validator_metadata_address(validator: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"zksl", b"metadata", validator.as_ref()], program_id).0
+}
+
+/// Domain-separation context prefixed to the region commitment preimage.
+pub const REGION_COMMITMENT_CONTEXT: &[u8] = b"zKSL/region/v1";
+
+/// Commitment to `region` (e.g. `"eu-west/AS16509"`) under a secret 32-byte `salt`:
+/// `blake3(REGION_COMMITMENT_CONTEXT || salt || region)`.
+///
+/// The salt keeps the small region space from being brute-forced out of the commitment;
+/// it is fixed-width, so the preimage splits back into one `(salt, region)` pair.
+#[must_use]
+pub fn region_commitment(region: &str, salt: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(REGION_COMMITMENT_CONTEXT);
+    hasher.update(salt);
+    hasher.update(region.as_bytes());
+    *hasher.finalize().as_bytes()
+}
+
+/// Whether a revealed `(region, salt)` opens `commitment`.
+#[must_use]
+pub fn verify_region_reveal(commitment: &[u8; 32], region: &str, salt: &[u8; 32]) -> bool {
+    region_commitment(region, salt) == *commitment
+}
+
+/// Signature, public key and message referenced by a single-signature Ed25519 instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ed25519Fields<'a> {
+    /// The 64-byte signature.
+    pub signature: &'a [u8],
+    /// The 32-byte public key.
+    pub pubkey: &'a [u8],
+    /// The signed message.
+    pub message: &'a [u8],
+}
+
+/// Validate the fixed Ed25519 instruction header and return the offset where the payload starts.
+///
+/// The header is a 1-byte signature count, a 1-byte padding (must be zero), then one 14-byte
+/// offsets record per signature. The declared count must equal `expected_count` and all of its
+/// offset records must fit inside `data`.
+///
+/// # Errors
+/// `InvalidSignature` if the count, padding or header length is wrong.
+pub fn validate_ed25519_header(data: &[u8], expected_count: u8) -> Result<usize, ZkError> {
+    let num = *data.first().ok_or(ZkError::InvalidSignature)?;
+    let padding = *data.get(1).ok_or(ZkError::InvalidSignature)?;
+    if num != expected_count || padding != 0 {
+        return Err(ZkError::InvalidSignature);
+    }
+    let header_end = usize::from(num)
+        .checked_mul(ED25519_OFFSETS_LEN)
+        .and_then(|n| n.checked_add(ED25519_HEADER_LEN))
+        .ok_or(ZkError::InvalidSignature)?;
+    if data.len() < header_end {
+        return Err(ZkError::InvalidSignature);
+    }
+    Ok(header_end)
+}
+
+/// Read a little-endian `u16` at `at`, failing with `InvalidSignature` if out of bounds.
+fn read_u16_le(data: &[u8], at: usize) -> Result<u16, ZkError> {
+    let end = at.checked_add(2).ok_or(ZkError::InvalidSignature)?;
+    let bytes: [u8; 2] = data
+        .get(at..end)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(ZkError::InvalidSignature)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+/// Slice `len` bytes at `off` out of `data`, failing with `InvalidSignature` if out of bounds.
+fn slice_at(data: &[u8], off: usize, len: usize) -> Result<&[u8], ZkError> {
+    let end = off.checked_add(len).ok_or(ZkError::InvalidSignature)?;
+    data.get(off..end).ok_or(ZkError::InvalidSignature)
+}
+
+/// Build the data of an `Ed25519Program` instruction carrying one signature over `message`.
+///
+/// The public key, signature and message follow the header in that order and are referenced
+/// from this instruction (index `u16::MAX`), which is the layout [`parse_ed25519_single`]
+/// accepts. Clients place it immediately before `anchor_proof`.
+///
+/// # Errors
+/// `InvalidSignature` if `message` is longer than a `u16` offset can address.
+pub fn ed25519_instruction_data(
+    pubkey: &[u8; ED25519_PUBKEY_LEN],
+    signature: &[u8; ED25519_SIGNATURE_LEN],
+    message: &[u8],
+) -> Result<Vec<u8>, ZkError> {
+    let pk_off = ED25519_HEADER_LEN.saturating_add(ED25519_OFFSETS_LEN);
+    let sig_off = pk_off.saturating_add(ED25519_PUBKEY_LEN);
+    let msg_off = sig_off.saturating_add(ED25519_SIGNATURE_LEN);
+    let offset = |at: usize| u16::try_from(at).map_err(|_| ZkError::InvalidSignature);
+    let msg_len = offset(message.len())?;
+    // Message end must stay addressable as well
+    offset(msg_off.saturating_add(message.len()))?;
+    let mut data = Vec::with_capacity(msg_off.saturating_add(message.len()));
+    data.extend_from_slice(&[1u8, 0u8]);
+    data.extend_from_slice(&offset(sig_off)?.to_le_bytes());
+    data.extend_from_slice(&u16::MAX.to_le_bytes());
+    data.extend_from_slice(&offset(pk_off)?.to_le_bytes());
+    data.extend_from_slice(&u16::MAX.to_le_bytes());
+    data.extend_from_slice(&offset(msg_off)?.to_le_bytes());
+    data.extend_from_slice(&msg_len.to_le_bytes());
+    data.extend_from_slice(&u16::MAX.to_le_bytes());
+    data.extend_from_slice(pubkey);
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+    Ok(data)
+}
+
+/// Check committee signatures for `anchor_proof`: exactly `threshold` of them, all over the
+/// same message, from distinct keys in `members`.
+///
+/// Each entry is one parsed single-signature Ed25519 instruction; the Ed25519 program has
+/// already verified the signatures themselves.
+///
+/// # Errors
+/// `CommitteeMismatch` for a wrong count, differing messages, a repeated key or a key outside
+/// `members`.
+pub fn check_committee_signatures(
+    members: &[Pubkey],
+    threshold: u8,
+    signatures: &[Ed25519Fields<'_>],
+) -> Result<(), ZkError> {
+    if signatures.len() != usize::from(threshold) {
+        return Err(ZkError::CommitteeMismatch);
+    }
+    let message = signatures.first().map(|f| f.message);
+    let mut signers: Vec<&[u8]> = Vec::with_capacity(signatures.len());
+    for fields in signatures {
+        let is_member = members.iter().any(|m| m.as_ref() == fields.pubkey);
+        if Some(fields.message) != message || !is_member || signers.contains(&fields.pubkey) {
+            return Err(ZkError::CommitteeMismatch);
+        }
+        signers.push(fields.pubkey);
+    }
+    Ok(())
+}
+
+/// Parse the data of an `Ed25519Program` instruction carrying exactly one signature.
+///
+/// All three slices must reference this instruction (index `u16::MAX`, else
+/// `BadEd25519Order`), start after the header, and the payload must end exactly where the
+/// last referenced slice ends. This is the parser `anchor_proof` runs on the preceding
+/// instruction, so clients can preflight their transaction with it.
+///
+/// # Errors
+/// `BadEd25519Order` if a slice references another instruction, `InvalidSignature` for any
+/// malformed header, out-of-bounds or overlapping-header offset, or trailing bytes.
+pub fn parse_ed25519_single(data: &[u8]) -> Result<Ed25519Fields<'_>, ZkError> {
+    parse_ed25519_multi(data, 1)?
+        .into_iter()
+        .next()
+        .ok_or(ZkError::InvalidSignature)
+}
+
+/// Parse the data of an `Ed25519Program` instruction carrying exactly `count` signatures,
+/// in offsets-record order.
+///
+/// Each record is held to the rules of [`parse_ed25519_single`], and the payload must end
+/// exactly where the last slice referenced by any record ends. `anchor_proofs_batch` runs
+/// this on its preceding instruction, one record per proof header.
+///
+/// # Errors
+/// As [`parse_ed25519_single`]; also `InvalidSignature` if `count` is 0 or above `u8::MAX`.
+pub fn parse_ed25519_multi(data: &[u8], count: usize) -> Result<Vec<Ed25519Fields<'_>>, ZkError> {
+    let expected = u8::try_from(count).map_err(|_| ZkError::InvalidSignature)?;
+    if expected == 0 {
+        return Err(ZkError::InvalidSignature);
+    }
+    let header_end = validate_ed25519_header(data, expected)?;
+    let mut fields = Vec::with_capacity(count);
+    let mut end = header_end;
+    for i in 0..count {
+        let record = i
+            .checked_mul(ED25519_OFFSETS_LEN)
+            .and_then(|n| n.checked_add(ED25519_HEADER_LEN))
+            .ok_or(ZkError::InvalidSignature)?;
+        let at = |field: usize| read_u16_le(data, record.saturating_add(field));
+        let sig_off = usize::from(at(0)?);
+        let sig_ix = at(2)?;
+        let pk_off = usize::from(at(4)?);
+        let pk_ix = at(6)?;
+        let msg_off = usize::from(at(8)?);
+        let msg_len = usize::from(at(10)?);
+        let msg_ix = at(12)?;
+        if sig_ix != u16::MAX || pk_ix != u16::MAX || msg_ix != u16::MAX {
+            return Err(ZkError::BadEd25519Order);
+        }
+        if sig_off < header_end || pk_off < header_end || msg_off < header_end {
+            return Err(ZkError::InvalidSignature);
+        }
+        fields.push(Ed25519Fields {
+            signature: slice_at(data, sig_off, ED25519_SIGNATURE_LEN)?,
+            pubkey: slice_at(data, pk_off, ED25519_PUBKEY_LEN)?,
+            message: slice_at(data, msg_off, msg_len)?,
+        });
+        end = end
+            .max(sig_off.saturating_add(ED25519_SIGNATURE_LEN))
+            .max(pk_off.saturating_add(ED25519_PUBKEY_LEN))
+            .max(msg_off.saturating_add(msg_len));
+    }
+    // No trailing bytes: the payload ends exactly where the last referenced slice ends
+    if data.len() != end {
+        return Err(ZkError::InvalidSignature);
+    }
+    Ok(fields)
+}
+
+/// Build the data of an `Ed25519Program` instruction carrying one signature per
+/// `(pubkey, signature, message)` entry, in the layout [`parse_ed25519_multi`] accepts.
+///
+/// Each entry's public key, signature and message follow the header in that order, entry
+/// after entry. Clients place it immediately before `anchor_proofs_batch`.
+///
+/// # Errors
+/// `InvalidSignature` for no entries, more than `u8::MAX`, or a payload longer than a `u16`
+/// offset can address.
+pub fn ed25519_instruction_data_multi(
+    entries: &[(
+        &[u8; ED25519_PUBKEY_LEN],
+        &[u8; ED25519_SIGNATURE_LEN],
+        &[u8],
+    )],
+) -> Result<Vec<u8>, ZkError> {
+    let count = u8::try_from(entries.len()).map_err(|_| ZkError::InvalidSignature)?;
+    if count == 0 {
+        return Err(ZkError::InvalidSignature);
+    }
+    let offset = |at: usize| u16::try_from(at).map_err(|_| ZkError::InvalidSignature);
+    let mut header = vec![count, 0u8];
+    let mut payload = Vec::new();
+    let payload_start = entries
+        .len()
+        .saturating_mul(ED25519_OFFSETS_LEN)
+        .saturating_add(ED25519_HEADER_LEN);
+    for (pubkey, signature, message) in entries {
+        let pk_off = payload_start.saturating_add(payload.len());
+        let sig_off = pk_off.saturating_add(ED25519_PUBKEY_LEN);
+        let msg_off = sig_off.saturating_add(ED25519_SIGNATURE_LEN);
+        // Message end must stay addressable as well
+        offset(msg_off.saturating_add(message.len()))?;
+        header.extend_from_slice(&offset(sig_off)?.to_le_bytes());
+        header.extend_from_slice(&u16::MAX.to_le_bytes());
+        header.extend_from_slice(&offset(pk_off)?.to_le_bytes());
+        header.extend_from_slice(&u16::MAX.to_le_bytes());
+        header.extend_from_slice(&offset(msg_off)?.to_le_bytes());
+        header.extend_from_slice(&offset(message.len())?.to_le_bytes());
+        header.extend_from_slice(&u16::MAX.to_le_bytes());
+        payload.extend_from_slice(*pubkey);
+        payload.extend_from_slice(*signature);
+        payload.extend_from_slice(message);
+    }
+    header.extend_from_slice(&payload);
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allowed_aggregator_key;
+
+    fn sample_config(activation_seq: u64) -> Config {
+        Config {
+            zksl_mint: Pubkey::new_unique(),
+            admin: Pubkey::new_unique(),
+            aggregator_pubkey: Pubkey::new_unique(),
+            next_aggregator_pubkey: Pubkey::new_unique(),
+            activation_seq,
+            chain_id: 103,
+            paused: 0,
+            bump: 255,
+            max_past_skew_secs: 0,
+            max_future_skew_secs: 0,
+            allow_emergency_unlock: 0,
+            total_locked: 0,
+            seeds_version: 0,
+            enforce_uniform_da: 0,
+            bind_proof_hash: 0,
+            per_submitter_range: 0,
+            reserved: [0u8; 1],
+            min_lock_amount: 0,
+            max_proof_age_slots: 0,
+            pending_admin: Pubkey::default(),
+            max_slots_per_artifact: 0,
+            rotation_grace: 0,
+            min_lock_duration_secs: 0,
+            allow_token_2022: 0,
+            reserved_tail: [0u8; 63],
+        }
+    }
+
+    #[test]
+    fn test_allowed_key_matches_on_chain_across_activation() {
+        let cfg = sample_config(10);
+        let view = ConfigView::from(&cfg);
+        for seq in [9u64, 10, 11] {
+            assert_eq!(
+                allowed_aggregator_key_for(&view, seq),
+                allowed_aggregator_key(&cfg, seq),
+                "off-chain and on-chain selection diverge at seq {seq}"
+            );
+        }
+        assert_eq!(allowed_aggregator_key_for(&view, 9), cfg.aggregator_pubkey);
+        assert_eq!(
+            allowed_aggregator_key_for(&view, 10),
+            cfg.next_aggregator_pubkey
+        );
+        assert_eq!(
+            allowed_aggregator_key_for(&view, 11),
+            cfg.next_aggregator_pubkey
+        );
+    }
+
+    #[test]
+    fn test_proof_record_addresses_are_unique() {
+        let program_id = crate::ID;
+        let mut seen = std::collections::HashSet::new();
+        let mut pairs = std::collections::HashSet::new();
+        for h in 0u8..32 {
+            // Hashes differing only in the last byte, where a variable-width seq could bleed in
+            let mut proof_hash = [0xABu8; 32];
+            set_byte(&mut proof_hash, 31, h);
+            for seq in [0u64, 1, 2, 255, 256, u64::from(h) << 56, u64::MAX] {
+                pairs.insert((proof_hash, seq));
+                seen.insert(proof_record_address(&proof_hash, seq, &program_id));
+            }
+        }
+        assert_eq!(seen.len(), pairs.len());
+
+        // Matches the `#[account(seeds = ...)]` derivation in `AnchorProof`
+        let proof_hash = [5u8; 32];
+        let (expected, _) = Pubkey::find_program_address(
+            &[
+                b"zksl".as_ref(),
+                b"proof".as_ref(),
+                proof_hash.as_ref(),
+                &7u64.to_le_bytes(),
+            ],
+            &program_id,
+        );
+        assert_eq!(proof_record_address(&proof_hash, 7, &program_id), expected);
+    }
+
+    #[test]
+    fn test_escrow_ata_matches_anchor_resolution() {
+        let validator = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_program = anchor_spl::token::ID;
+        let program_id = crate::ID;
+
+        // What `seeds = [b"zksl", b"escrow", validator]` and
+        // `associated_token::authority = escrow_authority` resolve to in `RegisterValidator`
+        let (authority, _) =
+            Pubkey::find_program_address(&[b"zksl", b"escrow", validator.as_ref()], &program_id);
+        let (expected, _) = Pubkey::find_program_address(
+            &[authority.as_ref(), token_program.as_ref(), mint.as_ref()],
+            &anchor_spl::associated_token::ID,
+        );
+
+        assert_eq!(escrow_authority(&validator, &program_id), authority);
+        assert_eq!(
+            escrow_ata(&validator, &mint, &program_id, &token_program),
+            expected
+        );
+        // Not the validator's own ATA
+        assert_ne!(
+            escrow_ata(&validator, &mint, &program_id, &token_program),
+            get_associated_token_address_with_program_id(&validator, &mint, &token_program)
+        );
+    }
+
+    #[test]
+    fn test_pools_give_distinct_escrows_for_same_validator() {
+        let validator = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_program = anchor_spl::token::ID;
+        let program_id = crate::ID;
+        let pool_a = pool_config_address(1, &program_id);
+        let pool_b = pool_config_address(2, &program_id);
+        assert_ne!(pool_a, pool_b);
+
+        let escrow_a = pool_escrow_ata(&pool_a, &validator, &mint, &program_id, &token_program);
+        let escrow_b = pool_escrow_ata(&pool_b, &validator, &mint, &program_id, &token_program);
+        assert_ne!(escrow_a, escrow_b);
+        assert_ne!(
+            pool_escrow_authority(&pool_a, &validator, &program_id),
+            pool_escrow_authority(&pool_b, &validator, &program_id)
+        );
+        assert_ne!(
+            pool_validator_record_address(&pool_a, &validator, &program_id),
+            pool_validator_record_address(&pool_b, &validator, &program_id)
+        );
+        // Neither pool collides with the legacy singleton layout
+        let legacy = escrow_ata(&validator, &mint, &program_id, &token_program);
+        assert_ne!(escrow_a, legacy);
+        assert_ne!(escrow_b, legacy);
+        // Deterministic
+        assert_eq!(
+            escrow_a,
+            pool_escrow_ata(&pool_a, &validator, &mint, &program_id, &token_program)
+        );
+    }
+
+    /// Build a single-signature Ed25519 instruction payload laid out like `Ed25519Program`.
+    fn ed25519_ix_data(msg: &[u8]) -> Vec<u8> {
+        ed25519_ix_data_with(16, 48, 112, msg)
+    }
+
+    /// Same as [`ed25519_ix_data`] with explicit public key, signature and message offsets.
+    fn ed25519_ix_data_with(pk_off: u16, sig_off: u16, msg_off: u16, msg: &[u8]) -> Vec<u8> {
+        let msg_len = u16::try_from(msg.len()).unwrap_or(u16::MAX);
+        let mut data = vec![1u8, 0u8];
+        data.extend_from_slice(&sig_off.to_le_bytes());
+        data.extend_from_slice(&u16::MAX.to_le_bytes());
+        data.extend_from_slice(&pk_off.to_le_bytes());
+        data.extend_from_slice(&u16::MAX.to_le_bytes());
+        data.extend_from_slice(&msg_off.to_le_bytes());
+        data.extend_from_slice(&msg_len.to_le_bytes());
+        data.extend_from_slice(&u16::MAX.to_le_bytes());
+        data.extend_from_slice(&[7u8; 32]);
+        data.extend_from_slice(&[9u8; 64]);
+        data.extend_from_slice(msg);
+        data
+    }
+
+    /// Overwrite the byte at `at`, leaving `data` untouched if out of range.
+    fn set_byte(data: &mut [u8], at: usize, value: u8) {
+        if let Some(b) = data.get_mut(at) {
+            *b = value;
+        }
+    }
+
+    #[test]
+    fn test_ed25519_instruction_data_round_trips() {
+        let msg = [5u8; 110];
+        let data = ed25519_instruction_data(&[7u8; 32], &[9u8; 64], &msg)
+            .ok()
+            .unwrap_or_default();
+        assert_eq!(data, ed25519_ix_data(&msg));
+        assert_eq!(
+            parse_ed25519_single(&data).ok(),
+            Some(Ed25519Fields {
+                signature: &[9u8; 64],
+                pubkey: &[7u8; 32],
+                message: &msg,
+            })
+        );
+        assert!(matches!(
+            ed25519_instruction_data(&[7u8; 32], &[9u8; 64], &vec![0u8; 65_536]),
+            Err(ZkError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_ed25519_header_well_formed() {
+        let data = ed25519_ix_data(&[1u8; 110]);
+        assert_eq!(validate_ed25519_header(&data, 1).ok(), Some(16));
+    }
+
+    #[test]
+    fn test_ed25519_header_rejects_nonzero_padding() {
+        let mut data = ed25519_ix_data(&[1u8; 110]);
+        set_byte(&mut data, 1, 1);
+        assert!(matches!(
+            validate_ed25519_header(&data, 1),
+            Err(ZkError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_ed25519_header_rejects_count_length_mismatch() {
+        // Declared count of 2 requires 30 header bytes and is not the expected single signature
+        let mut data = ed25519_ix_data(&[1u8; 110]);
+        set_byte(&mut data, 0, 2);
+        assert!(validate_ed25519_header(&data, 1).is_err());
+        assert!(validate_ed25519_header(&data, 2).is_ok());
+        // Count of 1 but data shorter than one offsets record
+        let short = [1u8, 0, 48, 0, 255, 255, 16, 0, 255, 255];
+        assert!(matches!(
+            validate_ed25519_header(&short, 1),
+            Err(ZkError::InvalidSignature)
+        ));
+        assert!(validate_ed25519_header(&[], 1).is_err());
+    }
+
+    #[test]
+    fn test_ed25519_single_well_formed() {
+        let msg = [1u8; 110];
+        let data = ed25519_ix_data(&msg);
+        assert_eq!(
+            parse_ed25519_single(&data).ok(),
+            Some(Ed25519Fields {
+                signature: &[9u8; 64],
+                pubkey: &[7u8; 32],
+                message: &msg,
+            })
+        );
+    }
+
+    #[test]
+    fn test_ed25519_single_rejects_multi_signature() {
+        // Two offsets records followed by both payloads: valid for the runtime, not for us
+        let mut data = vec![2u8, 0u8];
+        for sig_off in [30u16, 30 + 64] {
+            for field in [sig_off, u16::MAX, 158, u16::MAX, 190, 4, u16::MAX] {
+                data.extend_from_slice(&field.to_le_bytes());
+            }
+        }
+        data.extend_from_slice(&[9u8; 128]);
+        data.extend_from_slice(&[7u8; 32]);
+        data.extend_from_slice(&[1u8; 4]);
+        assert!(validate_ed25519_header(&data, 2).is_ok());
+        assert!(matches!(
+            parse_ed25519_single(&data),
+            Err(ZkError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_ed25519_single_rejects_truncated() {
+        let data = ed25519_ix_data(&[1u8; 110]);
+        for len in [0, 1, 2, 10, 15, 16, 48, 111, data.len().saturating_sub(1)] {
+            let truncated = data.get(..len).unwrap_or_default();
+            assert!(
+                matches!(
+                    parse_ed25519_single(truncated),
+                    Err(ZkError::InvalidSignature)
+                ),
+                "accepted {len} of {} bytes",
+                data.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_ed25519_single_rejects_foreign_instruction_index() {
+        let mut data = ed25519_ix_data(&[1u8; 110]);
+        // Public key instruction index pointing at instruction 0 instead of this one
+        set_byte(&mut data, 8, 0);
+        set_byte(&mut data, 9, 0);
+        assert!(matches!(
+            parse_ed25519_single(&data),
+            Err(ZkError::BadEd25519Order)
+        ));
+    }
+
+    #[test]
+    fn test_ed25519_single_rejects_trailing_bytes() {
+        let mut data = ed25519_ix_data(&[1u8; 110]);
+        data.push(0);
+        assert!(matches!(
+            parse_ed25519_single(&data),
+            Err(ZkError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_ed25519_single_rejects_pointer_into_header() {
+        // Public key offset 0 would alias the count/padding/offsets bytes
+        let data = ed25519_ix_data_with(0, 48, 112, &[1u8; 110]);
+        assert!(matches!(
+            parse_ed25519_single(&data),
+            Err(ZkError::InvalidSignature)
+        ));
+        // Message starting inside the offsets record
+        let data = ed25519_ix_data_with(16, 48, 10, &[1u8; 110]);
+        assert!(matches!(
+            parse_ed25519_single(&data),
+            Err(ZkError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_ds_message_layout_and_hash_vector() {
+        let program_id = Pubkey::new_from_array([1u8; 32]);
+        let ds = compute_ds_message(103, &program_id, &[2u8; 32], 10, 20, 1);
+        assert_eq!(ds.len(), 110);
+        assert_eq!(ds.get(..14), Some(DS_PREFIX));
+        assert_eq!(ds.get(14..22), Some(&103u64.to_le_bytes()[..]));
+        assert_eq!(ds.get(102..), Some(&1u64.to_le_bytes()[..]));
+        let hash = compute_ds_hash(103, &program_id, &[2u8; 32], 10, 20, 1);
+        assert_eq!(hash, *blake3::hash(&ds).as_bytes());
+        let expected: [u8; 32] = [
+            0x3f, 0x7b, 0x30, 0x39, 0xc0, 0x7c, 0x57, 0x74, 0x0c, 0x45, 0x97, 0x10, 0x6f, 0x4f,
+            0x0c, 0xd1, 0x87, 0x4d, 0x31, 0xca, 0xf6, 0x7c, 0x58, 0x0f, 0xe2, 0xae, 0x73, 0xd9,
+            0xf2, 0x1a, 0xd9, 0x4b,
+        ];
+        assert_eq!(hash, expected);
+    }
+
+    #[test]
+    fn test_ds_message_v2_binds_artifact_len() {
+        let program_id = Pubkey::new_from_array([1u8; 32]);
+        let v1 = compute_ds_message(103, &program_id, &[2u8; 32], 10, 20, 1);
+        let v2 = compute_ds_message_v2(103, &program_id, &[2u8; 32], 10, 20, 1, 4_096);
+        assert_eq!(v2.len(), 114);
+        assert_eq!(v2.get(..14), Some(DS_PREFIX_V2));
+        // Same fields as v1 after the prefix, then the length
+        assert_eq!(v2.get(14..110), v1.get(14..));
+        assert_eq!(v2.get(110..), Some(&4_096u32.to_le_bytes()[..]));
+        assert_ne!(
+            v2,
+            compute_ds_message_v2(103, &program_id, &[2u8; 32], 10, 20, 1, 4_097)
+        );
+    }
+
+    #[test]
+    fn test_ed25519_multi_round_trips_in_order() {
+        let (pk_a, pk_b) = ([1u8; 32], [2u8; 32]);
+        let (sig_a, sig_b) = ([3u8; 64], [4u8; 64]);
+        let (msg_a, msg_b) = ([5u8; 110], [6u8; 118]);
+        let entries: [(&[u8; 32], &[u8; 64], &[u8]); 2] =
+            [(&pk_a, &sig_a, &msg_a), (&pk_b, &sig_b, &msg_b)];
+        let data = ed25519_instruction_data_multi(&entries)
+            .ok()
+            .unwrap_or_default();
+        assert_eq!(
+            parse_ed25519_multi(&data, 2).ok(),
+            Some(vec![
+                Ed25519Fields {
+                    signature: &sig_a,
+                    pubkey: &pk_a,
+                    message: &msg_a
+                },
+                Ed25519Fields {
+                    signature: &sig_b,
+                    pubkey: &pk_b,
+                    message: &msg_b
+                },
+            ])
+        );
+        // The count must match the batch exactly
+        assert!(matches!(
+            parse_ed25519_multi(&data, 1),
+            Err(ZkError::InvalidSignature)
+        ));
+        assert!(matches!(
+            parse_ed25519_multi(&data, 3),
+            Err(ZkError::InvalidSignature)
+        ));
+        assert!(matches!(
+            parse_ed25519_multi(&data, 0),
+            Err(ZkError::InvalidSignature)
+        ));
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert!(matches!(
+            parse_ed25519_multi(&trailing, 2),
+            Err(ZkError::InvalidSignature)
+        ));
+        // A second record pointing at another instruction
+        let mut foreign = data;
+        set_byte(&mut foreign, 16 + 6, 0);
+        set_byte(&mut foreign, 16 + 7, 0);
+        assert!(matches!(
+            parse_ed25519_multi(&foreign, 2),
+            Err(ZkError::BadEd25519Order)
+        ));
+
+        // A one-entry batch is exactly the single-signature layout
+        let single = ed25519_instruction_data_multi(&entries[..1]).ok();
+        assert_eq!(single, ed25519_instruction_data(&pk_a, &sig_a, &msg_a).ok());
+        assert!(matches!(
+            ed25519_instruction_data_multi(&[]),
+            Err(ZkError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_committee_signatures_need_threshold_distinct_members() {
+        let members: Vec<Pubkey> = (1..=3u8).map(|b| Pubkey::new_from_array([b; 32])).collect();
+        let (pk_a, pk_b, pk_c, outsider) = ([1u8; 32], [2u8; 32], [3u8; 32], [9u8; 32]);
+        let (sig, ds, other_ds) = ([0u8; 64], [7u8; 118], [8u8; 118]);
+        let fields = |pubkey, message| Ed25519Fields {
+            signature: &sig,
+            pubkey,
+            message,
+        };
+        let (a, b, c) = (
+            fields(&pk_a[..], &ds[..]),
+            fields(&pk_b, &ds),
+            fields(&pk_c, &ds),
+        );
+        assert!(check_committee_signatures(&members, 2, &[a, b]).is_ok());
+        assert!(check_committee_signatures(&members, 3, &[c, a, b]).is_ok());
+        // Count must match the threshold exactly
+        assert!(matches!(
+            check_committee_signatures(&members, 3, &[a, b]),
+            Err(ZkError::CommitteeMismatch)
+        ));
+        assert!(matches!(
+            check_committee_signatures(&members, 1, &[a, b]),
+            Err(ZkError::CommitteeMismatch)
+        ));
+        // One key cannot count twice
+        assert!(matches!(
+            check_committee_signatures(&members, 2, &[a, a]),
+            Err(ZkError::CommitteeMismatch)
+        ));
+        // Outsiders and differing messages are rejected
+        let stranger = fields(&outsider, &ds);
+        assert!(matches!(
+            check_committee_signatures(&members, 2, &[a, stranger]),
+            Err(ZkError::CommitteeMismatch)
+        ));
+        let forked = fields(&pk_b, &other_ds);
+        assert!(matches!(
+            check_committee_signatures(&members, 2, &[a, forked]),
+            Err(ZkError::CommitteeMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_validator_record_round_trip_and_filters() {
+        use anchor_lang::AccountSerialize;
+
+        let record = ValidatorRecord {
+            validator_pubkey: Pubkey::new_unique(),
+            lock_token_account: Pubkey::new_unique(),
+            lock_timestamp: 1_700_000_000,
+            status: 1,
+            num_accepts: 42,
+            locked_amount: 5_000_000,
+            reserved: [0u8; 47],
+        };
+        let mut data = Vec::new();
+        record.try_serialize(&mut data).unwrap_or_default();
+
+        let view = decode_validator_record(&data).ok();
+        assert_eq!(
+            view,
+            Some(ValidatorRecordView {
+                validator_pubkey: record.validator_pubkey,
+                lock_token_account: record.lock_token_account,
+                lock_timestamp: 1_700_000_000,
+                status: 1,
+                num_accepts: 42,
+                locked_amount: 5_000_000,
+            })
+        );
+
+        // Every filter matches the serialized record
+        for filter in validator_record_filters(Some(1)) {
+            match filter {
+                AccountFilter::DataSize(size) => {
+                    assert_eq!(u64::try_from(data.len()).ok(), Some(size));
+                }
+                AccountFilter::Memcmp { offset, bytes } => {
+                    let end = offset.saturating_add(bytes.len());
+                    assert_eq!(data.get(offset..end), Some(bytes.as_slice()));
+                }
+            }
+        }
+        assert_eq!(validator_record_filters(None).len(), 2);
+
+        // Wrong discriminator is rejected
+        if let Some(b) = data.first_mut() {
+            *b ^= 0xFF;
+        }
+        assert!(decode_validator_record(&data).is_err());
+    }
+
+    #[test]
+    fn test_config_view_decodes_account_data() {
+        use anchor_lang::AccountSerialize;
+        let mut cfg = sample_config(7);
+        cfg.paused = 1;
+        cfg.max_past_skew_secs = 60;
+        cfg.allow_emergency_unlock = 1;
+        cfg.total_locked = 3_000_000;
+        cfg.min_lock_amount = 2_500_000;
+        cfg.max_proof_age_slots = 150;
+        let mut data = Vec::new();
+        assert!(cfg.try_serialize(&mut data).is_ok());
+        assert_eq!(data.len(), 8 + Config::SIZE);
+
+        let view = ConfigView::from_account_data(&data).ok();
+        assert_eq!(
+            view,
+            Some(ConfigView {
+                zksl_mint: cfg.zksl_mint,
+                admin: cfg.admin,
+                aggregator_pubkey: cfg.aggregator_pubkey,
+                next_aggregator_pubkey: cfg.next_aggregator_pubkey,
+                activation_seq: 7,
+                chain_id: 103,
+                paused: 1,
+                bump: 255,
+                max_past_skew_secs: 60,
+                max_future_skew_secs: 0,
+                allow_emergency_unlock: 1,
+                total_locked: 3_000_000,
+                seeds_version: 0,
+                enforce_uniform_da: 0,
+                bind_proof_hash: 0,
+                per_submitter_range: 0,
+                min_lock_amount: 2_500_000,
+                max_proof_age_slots: 150,
+                pending_admin: Pubkey::default(),
+                max_slots_per_artifact: 0,
+                rotation_grace: 0,
+                min_lock_duration_secs: 0,
+                allow_token_2022: 0,
+            })
+        );
+
+        // A config not yet resized decodes as the legacy one-token lock
+        let legacy = data.get(..8 + Config::LEGACY_SIZE).unwrap_or_default();
+        assert_eq!(
+            ConfigView::from_account_data(legacy)
+                .ok()
+                .map(|v| (v.total_locked, v.min_lock_amount)),
+            Some((3_000_000, 0))
+        );
+        // No layout sits between the legacy one and the current one
+        let partial = data.get(..8 + Config::LEGACY_SIZE + 8).unwrap_or_default();
+        assert!(ConfigView::from_account_data(partial).is_err());
+
+        // Truncated and over-long buffers are rejected before decoding
+        let short = data.get(..data.len().saturating_sub(1)).unwrap_or_default();
+        assert!(ConfigView::from_account_data(short).is_err());
+        let mut long = data.clone();
+        long.push(0);
+        assert!(ConfigView::from_account_data(&long).is_err());
+
+        // Wrong discriminator is rejected
+        if let Some(b) = data.first_mut() {
+            *b ^= 0xFF;
+        }
+        assert!(ConfigView::from_account_data(&data).is_err());
+    }
+
+    #[test]
+    fn test_diff_config_reports_exactly_the_changed_fields() {
+        let cfg = sample_config(7);
+        let before = ConfigView::from(&cfg);
+        assert!(diff_config(&before, &before).is_empty());
+
+        let after = ConfigView {
+            activation_seq: 9,
+            paused: 1,
+            ..before
+        };
+        assert_eq!(
+            diff_config(&before, &after),
+            vec![
+                ConfigChange::ActivationSeq { from: 7, to: 9 },
+                ConfigChange::Paused { from: 0, to: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_initialize_args_reports_each_field() {
+        let valid = InitializeArgs {
+            aggregator_pubkey: Pubkey::new_unique(),
+            next_aggregator_pubkey: Pubkey::new_unique(),
+            activation_seq: 100,
+            chain_id: 103,
+            min_lock_amount: 0,
+            max_slots_per_artifact: 0,
+            max_past_skew_secs: 0,
+            max_future_skew_secs: 0,
+        };
+        assert_eq!(validate_initialize_args(&valid), Ok(()));
+        // No rotation planned: an early activation_seq is harmless
+        let same_key = InitializeArgs {
+            next_aggregator_pubkey: valid.aggregator_pubkey,
+            activation_seq: 0,
+            ..valid
+        };
+        assert_eq!(validate_initialize_args(&same_key), Ok(()));
+        // The widest limits the program accepts
+        let widest = InitializeArgs {
+            max_slots_per_artifact: 432_000,
+            max_past_skew_secs: 3_600,
+            max_future_skew_secs: 3_600,
+            ..valid
+        };
+        assert_eq!(validate_initialize_args(&widest), Ok(()));
+
+        let cases = [
+            (
+                InitializeArgs {
+                    aggregator_pubkey: Pubkey::default(),
+                    ..valid
+                },
+                InitArgsIssue::DefaultAggregatorKey,
+            ),
+            (
+                InitializeArgs {
+                    next_aggregator_pubkey: Pubkey::default(),
+                    ..valid
+                },
+                InitArgsIssue::DefaultNextAggregatorKey,
+            ),
+            (
+                InitializeArgs {
+                    activation_seq: 0,
+                    ..valid
+                },
+                InitArgsIssue::ImmediateRotation { activation_seq: 0 },
+            ),
+            (
+                InitializeArgs {
+                    activation_seq: 1,
+                    ..valid
+                },
+                InitArgsIssue::ImmediateRotation { activation_seq: 1 },
+            ),
+            (
+                InitializeArgs {
+                    chain_id: 0,
+                    ..valid
+                },
+                InitArgsIssue::ZeroChainId,
+            ),
+            (
+                InitializeArgs {
+                    max_slots_per_artifact: 432_001,
+                    ..valid
+                },
+                InitArgsIssue::SlotWindowTooWide {
+                    max_slots_per_artifact: 432_001,
+                },
+            ),
+            (
+                InitializeArgs {
+                    max_past_skew_secs: 3_601,
+                    ..valid
+                },
+                InitArgsIssue::PastSkewTooWide {
+                    max_past_skew_secs: 3_601,
+                },
+            ),
+            (
+                InitializeArgs {
+                    max_future_skew_secs: 3_601,
+                    ..valid
+                },
+                InitArgsIssue::FutureSkewTooWide {
+                    max_future_skew_secs: 3_601,
+                },
+            ),
+        ];
+        for (args, issue) in cases {
+            assert_eq!(validate_initialize_args(&args), Err(issue));
+        }
+    }
+}
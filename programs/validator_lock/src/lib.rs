@@ -54,14 +54,19 @@
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey;
+use anchor_lang::solana_program::sysvar::epoch_schedule::EpochSchedule;
 use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use blake3::Hasher as Blake3Hasher;
 
 // Program ID (declare_id!) injected at build time from env by build.rs
 include!(concat!(env!("OUT_DIR"), "/program_id.rs"));
 
+pub mod helpers;
+use helpers::{parse_ed25519_single, ConfigChange, ConfigView, DS_PREFIX};
+
 /// Program entrypoint module for validator_lock per Master_Blueprint.md
 #[cfg(not(feature = "skip-anchor-program"))]
 #[allow(missing_docs)]
@@ -74,128 +79,419 @@ pub mod validator_lock {
 
     /// Initialize the on-chain configuration for the validator lock program.
     pub fn initialize(ctx: Context<Initialize>, args: InitializeArgs) -> Result<()> {
-        let cfg = &mut ctx.accounts.config;
-        cfg.zksl_mint = ctx.accounts.zksl_mint.key();
-        cfg.admin = ctx.accounts.admin.key();
-        cfg.aggregator_pubkey = args.aggregator_pubkey;
-        cfg.next_aggregator_pubkey = args.next_aggregator_pubkey;
-        cfg.activation_seq = args.activation_seq;
-        cfg.chain_id = args.chain_id;
-        cfg.paused = 0;
+        check_initialize_args(&args)?;
+        apply_initialize(
+            &mut ctx.accounts.config,
+            ctx.accounts.zksl_mint.key(),
+            ctx.accounts.admin.key(),
+            &args,
+        );
         // minimal state touch to avoid unused warnings on constants/helpers
-        let _ = (DS_PREFIX, MAX_SLOTS_PER_ARTIFACT, MAX_CLOCK_SKEW_SECS);
+        let _ = (DS_PREFIX, MAX_SLOTS_PER_ARTIFACT);
         let _ = allowed_aggregator_key;
         Ok(())
     }
 
-    /// Unlock validator: return exactly 1 token and set status to Unlocked
-    /// Unlock a validator by returning exactly 1 token and marking the record unlocked.
-    pub fn unlock_validator(ctx: Context<UnlockValidator>) -> Result<()> {
-        require!(ctx.accounts.config.paused == 0, ZkError::Paused);
-        // Enforce legacy SPL Token program (reject Token-2022)
-        require_keys_eq!(
-            ctx.accounts.token_program.key(),
-            anchor_spl::token::ID,
-            ZkError::InvalidMint
-        );
-        require!(
-            ctx.accounts.validator_record.status == 0,
-            ZkError::StatusNotActive
-        );
-        // Ensure escrow holds exactly 1 token (10^decimals base units)
-        let decimals = ctx.accounts.zksl_mint.decimals;
-        let amount: u64 = 10u64.pow(decimals as u32);
-        require!(
-            ctx.accounts.validator_escrow.amount == amount,
-            ZkError::InvalidLockAmount
+    /// Initialize an additional pool config at `["zksl", "config", pool_id_le]`.
+    ///
+    /// Pool configs use `SEEDS_VERSION_POOLED`: their escrow and record PDAs include the
+    /// config key, so one validator can lock in several pools without the escrows colliding.
+    ///
+    /// # Errors
+    /// Fails if the pool config for `pool_id` already exists.
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        pool_id: u64,
+        args: InitializeArgs,
+    ) -> Result<()> {
+        let _ = pool_id;
+        check_initialize_args(&args)?;
+        let cfg = &mut ctx.accounts.config;
+        apply_initialize(
+            cfg,
+            ctx.accounts.zksl_mint.key(),
+            ctx.accounts.admin.key(),
+            &args,
         );
-        // Transfer back to validator ATA using escrow PDA as signer
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.validator_escrow.to_account_info(),
-            to: ctx.accounts.validator_ata.to_account_info(),
-            authority: ctx.accounts.escrow_authority.to_account_info(),
-        };
+        cfg.seeds_version = SEEDS_VERSION_POOLED;
+        Ok(())
+    }
+
+    /// Unlock a validator by returning exactly the amount it locked and marking the record
+    /// unlocked.
+    pub fn unlock_validator<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnlockValidator<'info>>,
+    ) -> Result<()> {
+        check_seeds_version(&ctx.accounts.config, SEEDS_VERSION_LEGACY)?;
+        let validator_key = ctx.accounts.validator.key();
+        let bump = [ctx.bumps.escrow_authority];
+        let signer_seeds: &[&[u8]] = &[b"zksl", b"escrow", validator_key.as_ref(), &bump];
+        let a = ctx.accounts;
+        release_escrow(
+            &EscrowAccounts {
+                validator: &a.validator,
+                zksl_mint: &a.zksl_mint,
+                escrow_authority: &a.escrow_authority,
+                validator_escrow: &a.validator_escrow,
+                validator_ata: &a.validator_ata,
+                token_program: &a.token_program,
+                hook_accounts: ctx.remaining_accounts,
+            },
+            &mut a.config,
+            &mut a.validator_record,
+            signer_seeds,
+        )
+    }
+
+    /// Unlock a validator from a pool config; see `unlock_validator`.
+    ///
+    /// # Errors
+    /// `SeedsVersionMismatch` for the legacy config, otherwise as `unlock_validator`.
+    pub fn unlock_validator_pooled<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnlockValidatorPooled<'info>>,
+    ) -> Result<()> {
+        check_seeds_version(&ctx.accounts.config, SEEDS_VERSION_POOLED)?;
+        let config_key = ctx.accounts.config.key();
         let validator_key = ctx.accounts.validator.key();
-        let seeds = &[b"zksl".as_ref(), b"escrow".as_ref(), validator_key.as_ref()];
-        let (_pda, bump) = Pubkey::find_program_address(seeds, ctx.program_id);
-        let bump_slice = &[bump];
+        let bump = [ctx.bumps.escrow_authority];
         let signer_seeds: &[&[u8]] = &[
-            b"zksl".as_ref(),
-            b"escrow".as_ref(),
+            b"zksl",
+            b"escrow",
+            config_key.as_ref(),
             validator_key.as_ref(),
-            bump_slice,
+            &bump,
         ];
-        let signers_seeds = &[signer_seeds];
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            cpi_accounts,
-            signers_seeds,
-        );
-        token::transfer(cpi_ctx, amount)?;
-        ctx.accounts.validator_record.status = 1;
-        Ok(())
+        let a = ctx.accounts;
+        release_escrow(
+            &EscrowAccounts {
+                validator: &a.validator,
+                zksl_mint: &a.zksl_mint,
+                escrow_authority: &a.escrow_authority,
+                validator_escrow: &a.validator_escrow,
+                validator_ata: &a.validator_ata,
+                token_program: &a.token_program,
+                hook_accounts: ctx.remaining_accounts,
+            },
+            &mut a.config,
+            &mut a.validator_record,
+            signer_seeds,
+        )
     }
 
-    /// Register a validator by escrow-locking exactly 1 token and creating/updating its record.
-    pub fn register_validator(ctx: Context<RegisterValidator>) -> Result<()> {
-        require!(ctx.accounts.config.paused == 0, ZkError::Paused);
-        // Transfer exactly 1 token of zKSL (10^decimals base units) from validator ATA to escrow
-        let mint = ctx.accounts.zksl_mint.key();
-        require_keys_eq!(mint, ctx.accounts.config.zksl_mint, ZkError::InvalidMint);
-        // Prevent re-registration if a record already exists for this validator
-        let rec_existing = &ctx.accounts.validator_record;
-        if rec_existing.validator_pubkey != Pubkey::default() {
-            return err!(ZkError::AlreadyRegistered);
-        }
-        // Enforce legacy SPL Token program (reject Token-2022)
-        require_keys_eq!(
-            ctx.accounts.token_program.key(),
-            anchor_spl::token::ID,
-            ZkError::InvalidMint
-        );
-        // Transfer
-        let decimals = ctx.accounts.zksl_mint.decimals;
-        let amount: u64 = 10u64.pow(decimals as u32);
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.validator_ata.to_account_info(),
-            to: ctx.accounts.validator_escrow.to_account_info(),
-            authority: ctx.accounts.validator.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
-
-        let rec = &mut ctx.accounts.validator_record;
-        rec.validator_pubkey = ctx.accounts.validator.key();
-        rec.lock_token_account = ctx.accounts.validator_escrow.key();
-        rec.lock_timestamp = Clock::get()?.unix_timestamp;
-        rec.status = 0;
-        rec.num_accepts = 0;
-        Ok(())
+    /// Register a validator by escrow-locking `Config::lock_amount` and creating/updating its
+    /// record.
+    pub fn register_validator<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterValidator<'info>>,
+    ) -> Result<()> {
+        check_seeds_version(&ctx.accounts.config, SEEDS_VERSION_LEGACY)?;
+        let a = ctx.accounts;
+        lock_escrow(
+            &EscrowAccounts {
+                validator: &a.validator,
+                zksl_mint: &a.zksl_mint,
+                escrow_authority: &a.escrow_authority,
+                validator_escrow: &a.validator_escrow,
+                validator_ata: &a.validator_ata,
+                token_program: &a.token_program,
+                hook_accounts: ctx.remaining_accounts,
+            },
+            &mut a.config,
+            &mut a.validator_record,
+        )
+    }
+
+    /// Register a validator in a pool config; see `register_validator`.
+    ///
+    /// # Errors
+    /// `SeedsVersionMismatch` for the legacy config, otherwise as `register_validator`.
+    pub fn register_validator_pooled<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterValidatorPooled<'info>>,
+    ) -> Result<()> {
+        check_seeds_version(&ctx.accounts.config, SEEDS_VERSION_POOLED)?;
+        let a = ctx.accounts;
+        lock_escrow(
+            &EscrowAccounts {
+                validator: &a.validator,
+                zksl_mint: &a.zksl_mint,
+                escrow_authority: &a.escrow_authority,
+                validator_escrow: &a.validator_escrow,
+                validator_ata: &a.validator_ata,
+                token_program: &a.token_program,
+                hook_accounts: ctx.remaining_accounts,
+            },
+            &mut a.config,
+            &mut a.validator_record,
+        )
     }
 
     /// Update the program configuration (admin only).
+    ///
+    /// `aggregator_state` is read only with `clear_da_scheme`, which fails with
+    /// `AccountNotEnoughKeys` without it.
     pub fn update_config(ctx: Context<UpdateConfig>, args: UpdateConfigArgs) -> Result<()> {
         require_keys_eq!(
             ctx.accounts.admin.key(),
             ctx.accounts.config.admin,
             ZkError::Unauthorized
         );
+        check_config_limits(
+            args.max_slots_per_artifact.unwrap_or(0),
+            args.max_past_skew_secs.unwrap_or(0),
+            args.max_future_skew_secs.unwrap_or(0),
+        )?;
+        require!(
+            args.min_lock_duration_secs.unwrap_or(0) >= 0,
+            ZkError::ConfigOutOfRange
+        );
+        if args.clear_da_scheme {
+            check_seeds_version(&ctx.accounts.config, SEEDS_VERSION_LEGACY)?;
+            let aggregator_state = ctx
+                .accounts
+                .aggregator_state
+                .as_mut()
+                .ok_or(anchor_lang::error::ErrorCode::AccountNotEnoughKeys)?;
+            clear_da_scheme(aggregator_state);
+        }
         let cfg = &mut ctx.accounts.config;
-        if let Some(pk) = args.aggregator_pubkey {
-            cfg.aggregator_pubkey = pk;
+        let before = ConfigView::from(&**cfg);
+        apply_config_update(cfg, &args);
+        let after = ConfigView::from(&**cfg);
+        let timestamp = Clock::get()?.unix_timestamp;
+        if before.allow_emergency_unlock != after.allow_emergency_unlock {
+            emit!(EmergencyUnlockEnabled {
+                enabled: after.allow_emergency_unlock != 0,
+                admin: ctx.accounts.admin.key(),
+                timestamp
+            });
         }
-        if let Some(pk) = args.next_aggregator_pubkey {
-            cfg.next_aggregator_pubkey = pk;
+        emit!(config_updated_event(&before, &after, timestamp));
+        Ok(())
+    }
+
+    /// Grow a config created by an earlier program version (`Config::PRIOR_SIZES`) to
+    /// `Config::SIZE` (admin only).
+    ///
+    /// The added bytes are zeroed, so `min_lock_amount` reads as 0 and keeps the one-token
+    /// lock, `max_proof_age_slots` reads as 0 (no limit), `pending_admin` as none,
+    /// `max_slots_per_artifact` as 0 (the default window), `rotation_grace` as 0 (hard
+    /// cutover), `min_lock_duration_secs` as 0 (no cooldown) and `allow_token_2022` as 0
+    /// (legacy SPL Token only). A config already at `Config::SIZE` is left as is.
+    ///
+    /// # Errors
+    /// `Unauthorized` unless `admin` is the config admin; `AccountDidNotDeserialize` for an
+    /// account that is not a `Config` of either size.
+    pub fn resize_config(ctx: Context<ResizeConfig>) -> Result<()> {
+        let config = ctx.accounts.config.to_account_info();
+        let admin = ctx.accounts.admin.key();
+        if !check_config_resize(&config.try_borrow_data()?, &admin)? {
+            return Ok(());
         }
-        if let Some(seq) = args.activation_seq {
-            cfg.activation_seq = seq;
+        let new_len = Config::DISCRIMINATOR.len().saturating_add(Config::SIZE);
+        let shortfall = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(config.lamports());
+        if shortfall > 0 {
+            let accounts = anchor_lang::system_program::Transfer {
+                from: ctx.accounts.admin.to_account_info(),
+                to: config.clone(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), accounts);
+            anchor_lang::system_program::transfer(cpi_ctx, shortfall)?;
         }
-        if let Some(p) = args.paused {
-            cfg.paused = if p { 1 } else { 0 };
+        config.resize(new_len)?;
+        Ok(())
+    }
+
+    /// Propose `new_admin` as the next config admin (admin only).
+    ///
+    /// Nothing changes hands until `new_admin` signs `accept_admin`, so a mistyped key never
+    /// takes over the config. Proposing again replaces the pending admin, and proposing
+    /// `Pubkey::default()` withdraws it.
+    ///
+    /// # Errors
+    /// `Unauthorized` unless `admin` is the config admin.
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        let accounts = ctx.accounts;
+        propose_admin_transfer(&mut accounts.config, accounts.admin.key(), new_admin)
+    }
+
+    /// Complete an admin transfer started by `propose_admin`; the signer becomes the admin.
+    ///
+    /// # Errors
+    /// `NoPendingAdmin` when no transfer is pending; `Unauthorized` unless the signer is the
+    /// pending admin.
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let accounts = ctx.accounts;
+        let new_admin = accounts.pending_admin.key();
+        let previous_admin = accept_admin_transfer(&mut accounts.config, new_admin)?;
+        emit!(AdminTransferred {
+            previous_admin,
+            new_admin,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Reset the aggregator and range state to their initial values (admin only, test
+    /// deployments only).
+    ///
+    /// This breaks the monotonicity guarantees anchoring relies on: `seq` and the slot range
+    /// start over, so proofs over already anchored slots can be anchored again. It exists to
+    /// recover a testnet from corrupted state or to restart one, and must not be used on a
+    /// production deployment. Signatures over the old DS stay unusable because the reset
+    /// advances `epoch_nonce`.
+    ///
+    /// The program must be paused first, and `confirm_last_seq` must repeat the current
+    /// `last_seq`, so a stray or replayed call does not wipe state that moved on since.
+    ///
+    /// # Errors
+    /// `Unauthorized` unless `admin` is the config admin; `NotPaused` unless the program is
+    /// paused; `ResetNotConfirmed` if `confirm_last_seq` is not the current `last_seq`.
+    pub fn reset_state(ctx: Context<ResetState>, confirm_last_seq: u64) -> Result<()> {
+        let accounts = ctx.accounts;
+        check_state_reset(
+            &accounts.config,
+            accounts.admin.key(),
+            &accounts.aggregator_state,
+            confirm_last_seq,
+        )?;
+        let old_last_seq = accounts.aggregator_state.last_seq;
+        let old_last_end_slot = accounts.range_state.last_end_slot;
+        reset_anchor_state(&mut accounts.aggregator_state, &mut accounts.range_state);
+        emit!(StateReset {
+            old_last_seq,
+            old_last_end_slot,
+            epoch_nonce: accounts.aggregator_state.epoch_nonce,
+            admin: accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Close a finalized proof record and return its rent to `admin` (admin only).
+    ///
+    /// Meant for records already archived off-chain. The `seq` cursor keeps the closed
+    /// `(proof_hash, seq)` from being anchored again, and the artifact record stays, so the
+    /// artifact cannot come back under another `seq`.
+    ///
+    /// # Errors
+    /// `Unauthorized` unless `admin` is the config admin; `ProofNotFinalized` unless the
+    /// record's `commitment_level` is finalized.
+    pub fn close_proof_record(ctx: Context<CloseProofRecord>) -> Result<()> {
+        let accounts = ctx.accounts;
+        check_proof_record_close(
+            &accounts.config,
+            accounts.admin.key(),
+            &accounts.proof_record,
+        )?;
+        emit!(ProofRecordClosed {
+            artifact_id: accounts.proof_record.artifact_id,
+            seq: accounts.proof_record.seq,
+            admin: accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Grow a proof record anchored before its epoch tags to `ProofRecord::SIZE`.
+    ///
+    /// Records of `ProofRecord::PRIOR_SIZES` get `start_epoch` and `end_epoch` from their
+    /// slots, and `payer` funds the added rent. Until then such a record does not decode, so `get_proof_record`, `set_commitment_level`
+    /// and `close_proof_record` fail on it. A record already at `ProofRecord::SIZE` is left as
+    /// is. The tags are derived from the record alone, so anyone may pay for the resize.
+    ///
+    /// # Errors
+    /// `AccountDiscriminatorMismatch` for an account that is not a `ProofRecord`, and
+    /// `AccountDidNotDeserialize` for a record of any other size.
+    pub fn resize_proof_record(
+        ctx: Context<ResizeProofRecord>,
+        seq: u64,
+        proof_hash: [u8; 32],
+    ) -> Result<()> {
+        let _ = (seq, proof_hash);
+        let record_info = ctx.accounts.proof_record.to_account_info();
+        let slots_per_epoch = EpochSchedule::get()?.slots_per_epoch;
+        let Some(record) = grow_proof_record(&record_info.try_borrow_data()?, slots_per_epoch)?
+        else {
+            return Ok(());
+        };
+        let new_len = ProofRecord::DISCRIMINATOR
+            .len()
+            .saturating_add(ProofRecord::SIZE);
+        let shortfall = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(record_info.lamports());
+        if shortfall > 0 {
+            let accounts = anchor_lang::system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: record_info.clone(),
+            };
+            let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), accounts);
+            anchor_lang::system_program::transfer(cpi_ctx, shortfall)?;
         }
-        emit!(ConfigUpdated {
-            aggregator_pubkey: args.aggregator_pubkey,
-            paused: args.paused,
+        record_info.resize(new_len)?;
+        let mut data = record_info.try_borrow_mut_data()?;
+        let mut dst: &mut [u8] = &mut data;
+        record.try_serialize(&mut dst)
+    }
+
+    /// Advance the `commitment_level` of the proof record at `(proof_hash, seq)` to `level`.
+    ///
+    /// Signed by the config admin or the aggregator key recorded on the proof. Levels only move
+    /// forward (0=processed, 1=confirmed, 2=finalized), so a finalized record stays finalized
+    /// and can then be closed with `close_proof_record`.
+    ///
+    /// # Errors
+    /// `Unauthorized` for any other signer, `InvalidCommitmentLevel` above finalized and
+    /// `CommitmentRegression` unless `level` is above the recorded one.
+    pub fn set_commitment_level(
+        ctx: Context<SetCommitmentLevel>,
+        seq: u64,
+        proof_hash: [u8; 32],
+        level: u8,
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+        let authority = accounts.authority.key();
+        let record = &mut accounts.proof_record;
+        let previous = advance_commitment_level(&accounts.config, authority, record, level)?;
+        emit!(CommitmentAdvanced {
+            proof_hash,
+            seq,
+            previous_level: previous,
+            level,
+            authority,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Replace the aggregator committee (admin only), creating it on first use.
+    ///
+    /// With a `threshold` above 1, `anchor_proof` requires that many Ed25519 instructions
+    /// signing the same DS, from distinct `members`, and `anchor_proofs_batch` is refused. A
+    /// `threshold` of 1 keeps the single aggregator signature.
+    ///
+    /// # Errors
+    /// `Unauthorized` unless `admin` is the config admin; `InvalidCommittee` unless
+    /// `1 <= threshold <= members.len() <= AggregatorCommittee::MAX_MEMBERS` and the members are
+    /// distinct, non-default keys.
+    pub fn set_committee(
+        ctx: Context<SetCommittee>,
+        threshold: u8,
+        members: Vec<Pubkey>,
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+        require_keys_eq!(
+            accounts.admin.key(),
+            accounts.config.admin,
+            ZkError::Unauthorized
+        );
+        check_committee_update(threshold, &members)?;
+        accounts.committee.threshold = threshold;
+        accounts.committee.members.clone_from(&members);
+        emit!(CommitteeUpdated {
+            threshold,
+            members,
             timestamp: Clock::get()?.unix_timestamp
         });
         Ok(())
@@ -203,6 +499,14 @@ pub mod validator_lock {
 
     #[allow(clippy::too_many_arguments)]
     /// Anchor a proof artifact with strict Ed25519 preflight and domain separation checks.
+    ///
+    /// `ds_version` selects the signed DS layout: `DS_VERSION_V1` (`zKSL/anchor/v1`) or
+    /// `DS_VERSION_V2` (`zKSL/anchor/v2`), which also binds `artifact_len`; see
+    /// [`helpers::compute_ds_message_v2`]. Any other value fails with `UnsupportedDsVersion`.
+    ///
+    /// Under an aggregator committee with a threshold above 1, the Ed25519 instructions
+    /// preceding this one carry one signature each over the same DS, from distinct committee
+    /// members, and the aggregator key allowed for `seq` must be one of them.
     pub fn anchor_proof(
         ctx: Context<AnchorProof>,
         artifact_id: [u8; 16],       // arg 0
@@ -216,180 +520,179 @@ pub mod validator_lock {
         aggregator_pubkey: Pubkey,   // arg 8
         timestamp: i64,              // arg 9
         ds_hash: [u8; 32],           // arg 10
+        commitment_level: u8,        // arg 11
+        da_params: [u8; 12],         // arg 12
+        ds_version: u8,              // arg 13
     ) -> Result<()> {
         require!(ctx.accounts.config.paused == 0, ZkError::Paused);
-        let allowed = allowed_aggregator_key(&ctx.accounts.config, seq);
-        require_keys_eq!(aggregator_pubkey, allowed, ZkError::AggregatorMismatch);
-
-        // Strict Ed25519 preflight checks: ensure previous ix is Ed25519 and only one Ed25519 in tx
-        let ix_acc = ctx.accounts.sysvar_instructions.to_account_info();
-        let mut ed_count: u32 = 0;
-        let mut idx: usize = 0;
-        let mut has_compute_ok = false;
-        loop {
-            match sysvar_instructions::load_instruction_at_checked(idx, &ix_acc) {
-                Ok(ix) => {
-                    if ix.program_id == ED25519_PROGRAM_ID {
-                        ed_count += 1;
-                    } else if ix.program_id == COMPUTE_BUDGET_PROGRAM_ID {
-                        // Require presence of ComputeBudget to force explicit CU/priority-fee planning
-                        has_compute_ok = true;
-                    }
-                    idx += 1;
-                }
-                Err(_) => break,
-            }
-        }
-        require!(ed_count == 1, ZkError::BadEd25519Order);
-        require!(has_compute_ok, ZkError::InsufficientBudget);
-        // Use the current instruction index to safely reference the immediately preceding instruction
-        let cur_idx = sysvar_instructions::load_current_index_checked(&ix_acc)
-            .map_err(|_| error!(ZkError::BadEd25519Order))? as usize;
-        require!(cur_idx >= 1, ZkError::BadEd25519Order);
-        let prev_ix = sysvar_instructions::load_instruction_at_checked(cur_idx - 1, &ix_acc)
-            .map_err(|_| error!(ZkError::BadEd25519Order))?;
-        let prev_is_ed25519 = prev_ix.program_id == ED25519_PROGRAM_ID;
-        require!(prev_is_ed25519, ZkError::BadEd25519Order);
-
-        // seq monotonic (global, across key rotation)
-        if ctx.accounts.aggregator_state.last_seq == 0 {
-            require!(seq == 1, ZkError::NonMonotonicSeq);
-        } else {
-            require!(
-                seq == ctx
-                    .accounts
-                    .aggregator_state
-                    .last_seq
-                    .checked_add(1)
-                    .ok_or(ZkError::MathOverflow)?,
-                ZkError::NonMonotonicSeq
-            );
+        let header = ProofHeader {
+            artifact_id,
+            proof_hash,
+            seq,
+            start_slot,
+            end_slot,
+            artifact_len,
+            state_root_before,
+            state_root_after,
+            aggregator_pubkey,
+            timestamp,
+            ds_hash,
+            commitment_level,
+            da_params,
+        };
+        check_aggregator_key(&ctx.accounts.config, seq, aggregator_pubkey)?;
+
+        // Strict Ed25519 preflight checks: the preceding instructions are the only Ed25519 ones
+        // in the tx, one per required signature (one without a committee)
+        let committee = load_committee(&ctx.accounts.committee, ctx.program_id)?;
+        let threshold = committee.as_ref().map_or(1, |c| c.threshold.max(1));
+        let preflight = load_ed25519_preflight(&ctx.accounts.sysvar_instructions, threshold)?;
+        let signatures = preflight
+            .iter()
+            .map(|ix| parse_ed25519_single(ix.data.as_slice()))
+            .collect::<core::result::Result<Vec<_>, _>>()?;
+        if let Some(c) = committee.as_ref().filter(|c| c.threshold > 1) {
+            helpers::check_committee_signatures(&c.members, c.threshold, &signatures)?;
         }
+        // The allowed aggregator key must be among the signers; anchor_header checks it signed
+        // the exact DS, which every committee signature shares
+        let signed = *signatures
+            .iter()
+            .find(|f| f.pubkey == aggregator_pubkey.as_ref())
+            .ok_or(ZkError::InvalidSignature)?;
 
-        // range monotonic and bounds
-        require!(end_slot >= start_slot, ZkError::MathOverflow);
+        let a = ctx.accounts;
+        let payer = a.submitted_by.to_account_info();
+        let system_program = a.system_program.to_account_info();
+        let range_info = a.range_state.to_account_info();
+        let mut range_state = load_range_state(
+            &a.config,
+            (&payer, &system_program),
+            &range_info,
+            ctx.program_id,
+        )?;
+        let env = AnchorEnv {
+            config: &a.config,
+            program_id: ctx.program_id,
+            submitted_by: a.submitted_by.key(),
+            now: Clock::get()?.unix_timestamp,
+            current_slot: Clock::get()?.slot,
+            slots_per_epoch: EpochSchedule::get()?.slots_per_epoch,
+            ds_version,
+        };
+        anchor_header(
+            &env,
+            &mut a.aggregator_state,
+            &mut range_state,
+            &header,
+            &signed,
+            &mut a.proof_record,
+            &mut a.artifact_record,
+        )?;
+        store_range_state(&range_info, &range_state)
+    }
+
+    /// Anchor up to `MAX_BATCH_PROOFS` proofs in one instruction, each checked exactly as by
+    /// `anchor_proof`.
+    ///
+    /// The preceding Ed25519 instruction carries one signature per header, in header order,
+    /// each over the v1 DS.
+    /// Batches are single-signer only: under a committee threshold above 1 they fail with
+    /// `CommitteeMismatch`.
+    /// `remaining_accounts` holds, per header and in the same order, its writable
+    /// `proof_record` and `artifact_record` PDAs, which are created here. Headers must be
+    /// consecutive: each `seq` follows the previous one's and each range starts right after
+    /// it. One `ProofAnchored` event is emitted per header.
+    ///
+    /// # Errors
+    /// `InvalidBatchSize` for an empty or oversized batch, `BatchAccountMismatch` when
+    /// `remaining_accounts` does not hold the expected PDAs, otherwise as `anchor_proof`;
+    /// any failing header fails the whole batch.
+    pub fn anchor_proofs_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AnchorProofsBatch<'info>>,
+        headers: Vec<ProofHeader>,
+    ) -> Result<()> {
+        require!(ctx.accounts.config.paused == 0, ZkError::Paused);
+        check_batch_size(headers.len(), ctx.remaining_accounts.len())?;
+        let committee = load_committee(&ctx.accounts.committee, ctx.program_id)?;
         require!(
-            (end_slot - start_slot + 1) <= MAX_SLOTS_PER_ARTIFACT,
-            ZkError::MathOverflow
+            committee.map_or(1, |c| c.threshold) <= 1,
+            ZkError::CommitteeMismatch
         );
-        if ctx.accounts.range_state.last_end_slot != 0 {
-            require!(
-                start_slot == ctx.accounts.range_state.last_end_slot + 1,
-                ZkError::RangeOverlap
+        let preflight = load_ed25519_preflight(&ctx.accounts.sysvar_instructions, 1)?;
+        let prev_ix = preflight.first().ok_or(ZkError::BadEd25519Order)?;
+        let signed = helpers::parse_ed25519_multi(prev_ix.data.as_slice(), headers.len())?;
+
+        let a = ctx.accounts;
+        let payer = a.submitted_by.to_account_info();
+        let system_program = a.system_program.to_account_info();
+        let range_info = a.range_state.to_account_info();
+        let mut range_state = load_range_state(
+            &a.config,
+            (&payer, &system_program),
+            &range_info,
+            ctx.program_id,
+        )?;
+        let env = AnchorEnv {
+            config: &a.config,
+            program_id: ctx.program_id,
+            submitted_by: a.submitted_by.key(),
+            now: Clock::get()?.unix_timestamp,
+            current_slot: Clock::get()?.slot,
+            slots_per_epoch: EpochSchedule::get()?.slots_per_epoch,
+            ds_version: DS_VERSION_V1,
+        };
+        let record_accounts = ctx.remaining_accounts.chunks_exact(2);
+        for ((header, fields), pair) in headers.iter().zip(&signed).zip(record_accounts) {
+            let [proof_info, artifact_info] = pair else {
+                return err!(ZkError::BatchAccountMismatch);
+            };
+            check_aggregator_key(env.config, header.seq, header.aggregator_pubkey)?;
+            let seq_le = header.seq.to_le_bytes();
+            let proof_seeds: &[&[u8]] = &[b"zksl", b"proof", &header.proof_hash, &seq_le];
+            let artifact_seeds: &[&[u8]] = &[b"zksl", b"artifact", &header.artifact_id];
+            let accounts = (&payer, &system_program);
+            let proof = (proof_info, proof_seeds, ZkError::ProofAlreadyAnchored);
+            create_record_pda::<ProofRecord>(accounts, proof, ProofRecord::SIZE, env.program_id)?;
+            let artifact = (
+                artifact_info,
+                artifact_seeds,
+                ZkError::ArtifactAlreadyAnchored,
             );
+            create_record_pda::<ArtifactRecord>(
+                accounts,
+                artifact,
+                ArtifactRecord::SIZE,
+                env.program_id,
+            )?;
+            let mut proof_record = Account::<ProofRecord>::try_from(proof_info)?;
+            let mut artifact_record = Account::<ArtifactRecord>::try_from(artifact_info)?;
+            anchor_header(
+                &env,
+                &mut a.aggregator_state,
+                &mut range_state,
+                header,
+                fields,
+                &mut proof_record,
+                &mut artifact_record,
+            )?;
+            proof_record.exit(env.program_id)?;
+            artifact_record.exit(env.program_id)?;
         }
+        store_range_state(&range_info, &range_state)
+    }
 
-        // clock skew
-        let now = Clock::get()?.unix_timestamp;
-        let skew = now.saturating_sub(timestamp).abs();
-        require!(skew <= MAX_CLOCK_SKEW_SECS, ZkError::ClockSkew);
-
-        // Recompute DS and verify ds_hash and Ed25519 message/public key
-        let mut ds = Vec::with_capacity(14 + 8 + 32 + 32 + 8 + 8 + 8);
-        ds.extend_from_slice(DS_PREFIX);
-        ds.extend_from_slice(&ctx.accounts.config.chain_id.to_le_bytes());
-        ds.extend_from_slice(ctx.program_id.as_ref());
-        ds.extend_from_slice(&proof_hash);
-        ds.extend_from_slice(&start_slot.to_le_bytes());
-        ds.extend_from_slice(&end_slot.to_le_bytes());
-        ds.extend_from_slice(&seq.to_le_bytes());
-        let mut hasher = Blake3Hasher::new();
-        hasher.update(&ds);
-        let expected_ds_hash = *hasher.finalize().as_bytes();
-        require!(expected_ds_hash == ds_hash, ZkError::BadDomainSeparation);
-
-        // Parse Ed25519 instruction to ensure it signed the exact DS and with the allowed pubkey
-        let data = prev_ix.data.as_slice();
-        require!(data.len() >= 16, ZkError::InvalidSignature);
-        let num = *data.get(0).ok_or(ZkError::InvalidSignature)?;
-        require!(num == 1, ZkError::InvalidSignature);
-        let sig_off = u16::from_le_bytes([
-            *data.get(2).ok_or(ZkError::InvalidSignature)?,
-            *data.get(3).ok_or(ZkError::InvalidSignature)?,
-        ]) as usize;
-        let sig_ix = u16::from_le_bytes([
-            *data.get(4).ok_or(ZkError::InvalidSignature)?,
-            *data.get(5).ok_or(ZkError::InvalidSignature)?,
-        ]);
-        let pk_off = u16::from_le_bytes([
-            *data.get(6).ok_or(ZkError::InvalidSignature)?,
-            *data.get(7).ok_or(ZkError::InvalidSignature)?,
-        ]) as usize;
-        let pk_ix = u16::from_le_bytes([
-            *data.get(8).ok_or(ZkError::InvalidSignature)?,
-            *data.get(9).ok_or(ZkError::InvalidSignature)?,
-        ]);
-        let msg_off = u16::from_le_bytes([
-            *data.get(10).ok_or(ZkError::InvalidSignature)?,
-            *data.get(11).ok_or(ZkError::InvalidSignature)?,
-        ]) as usize;
-        let msg_len = u16::from_le_bytes([
-            *data.get(12).ok_or(ZkError::InvalidSignature)?,
-            *data.get(13).ok_or(ZkError::InvalidSignature)?,
-        ]) as usize;
-        let msg_ix = u16::from_le_bytes([
-            *data.get(14).ok_or(ZkError::InvalidSignature)?,
-            *data.get(15).ok_or(ZkError::InvalidSignature)?,
-        ]);
-        require!(
-            sig_ix == u16::MAX && pk_ix == u16::MAX && msg_ix == u16::MAX,
-            ZkError::BadEd25519Order
-        );
-        // Consolidated bounds checks for Ed25519 instruction slices
-        let sig_end = sig_off.saturating_add(64);
-        let pk_end = pk_off.saturating_add(32);
-        let msg_end = msg_off.saturating_add(msg_len);
-        require!(data.len() >= sig_end, ZkError::InvalidSignature);
-        require!(data.len() >= pk_end, ZkError::InvalidSignature);
-        require!(data.len() >= msg_end, ZkError::InvalidSignature);
-        let pk = data
-            .get(pk_off..pk_off + 32)
-            .ok_or(ZkError::InvalidSignature)?;
-        require!(pk == aggregator_pubkey.as_ref(), ZkError::InvalidSignature);
-        require!(msg_len == ds.len(), ZkError::BadDomainSeparation);
-        let msg = data
-            .get(msg_off..(msg_off + msg_len))
-            .ok_or(ZkError::InvalidSignature)?;
-        require!(msg == ds.as_slice(), ZkError::BadDomainSeparation);
-
-        // Populate ProofRecord
-        let pr = &mut ctx.accounts.proof_record;
-        require!(pr.seq == 0, ZkError::ProofAlreadyAnchored);
-        pr.artifact_id = artifact_id;
-        pr.start_slot = start_slot;
-        pr.end_slot = end_slot;
-        pr.proof_hash = proof_hash;
-        // Artifact length bounds guard (defense in depth; also enforced off-chain)
-        require!(
-            artifact_len <= MAX_ARTIFACT_SIZE_BYTES,
-            ZkError::MathOverflow
-        );
-        pr.artifact_len = artifact_len;
-        pr.state_root_before = state_root_before;
-        pr.state_root_after = state_root_after;
-        pr.submitted_by = ctx.accounts.submitted_by.key();
-        pr.aggregator_pubkey = aggregator_pubkey;
-        pr.timestamp = timestamp;
-        pr.seq = seq;
-        pr.ds_hash = ds_hash;
-        pr.commitment_level = 0;
-        pr.da_params = [0u8; 12];
-        pr.reserved = [0u8; 5];
-
-        // Update state
-        ctx.accounts.aggregator_state.last_seq = seq;
-        ctx.accounts.range_state.last_end_slot = end_slot;
-
-        emit!(ProofAnchored {
-            artifact_id,
-            proof_hash,
-            start_slot,
-            end_slot,
-            submitted_by: ctx.accounts.submitted_by.key(),
-            timestamp,
-            seq,
-            ds_hash
-        });
+    /// Return the anchored fields of `proof_record` as a Borsh-encoded
+    /// [`ProofRecordSummary`] via `set_return_data`; no state change.
+    ///
+    /// Meant to be simulated, so clients read an anchoring result in one round-trip.
+    ///
+    /// # Errors
+    /// Fails account validation unless `proof_record` is a `ProofRecord` of this program.
+    pub fn get_proof_record(ctx: Context<GetProofRecord>) -> Result<()> {
+        let accounts = ctx.accounts;
+        let data = proof_record_return_data(&accounts.proof_record)?;
+        anchor_lang::solana_program::program::set_return_data(&data);
         Ok(())
     }
 
@@ -401,11 +704,109 @@ pub mod validator_lock {
         Ok(())
     }
 
+    /// Emit a `Heartbeat` event with a snapshot of config and anchoring state; no state change.
+    pub fn heartbeat(ctx: Context<HeartbeatAccounts>) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        emit!(heartbeat_event(
+            &ctx.accounts.config,
+            &ctx.accounts.aggregator_state,
+            &ctx.accounts.range_state,
+            timestamp
+        ));
+        Ok(())
+    }
+
+    /// Commit a Merkle `root` over the `(proof_hash, seq)` leaves of anchored proofs
+    /// `start_seq..=end_seq` (admin only).
+    ///
+    /// Checkpoints tile the anchored sequence: each starts right after the previous one and
+    /// ends at or before the last anchored `seq`, so light clients can check a proof's
+    /// membership in one checkpoint instead of fetching every `ProofRecord`.
+    ///
+    /// # Errors
+    /// `Unauthorized` for a non-admin signer and `CheckpointRange` for a gap, overlap or
+    /// not-yet-anchored `end_seq`.
+    pub fn commit_checkpoint(
+        ctx: Context<CommitCheckpoint>,
+        root: [u8; 32],
+        start_seq: u64,
+        end_seq: u64,
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+        require_keys_eq!(
+            accounts.admin.key(),
+            accounts.config.admin,
+            ZkError::Unauthorized
+        );
+        check_checkpoint_range(
+            &accounts.checkpoint_state,
+            &accounts.aggregator_state,
+            start_seq,
+            end_seq,
+        )?;
+        let checkpoint = &mut accounts.checkpoint_state;
+        record_checkpoint(checkpoint, root, start_seq, end_seq);
+        emit!(CheckpointCommitted {
+            root,
+            start_seq,
+            end_seq,
+            count: checkpoint.count,
+            timestamp: Clock::get()?.unix_timestamp
+        });
+        Ok(())
+    }
+
+    /// Commit the signing validator to a region/ASN without revealing it.
+    ///
+    /// Only the 32-byte `region_commitment` (see `helpers::region_commitment`) is stored in
+    /// the validator's `ValidatorMetadata` PDA; the region and salt are revealed off-chain
+    /// and checked with `helpers::verify_region_reveal`. Calling again replaces the commitment.
+    ///
+    /// # Errors
+    /// Fails account validation when the signer has no `ValidatorRecord`.
+    pub fn set_region_commitment(
+        ctx: Context<SetRegionCommitment>,
+        region_commitment: [u8; 32],
+    ) -> Result<()> {
+        let accounts = ctx.accounts;
+        let timestamp = Clock::get()?.unix_timestamp;
+        let validator = accounts.validator.key();
+        record_region_commitment(
+            &mut accounts.validator_metadata,
+            validator,
+            region_commitment,
+            timestamp,
+        );
+        emit!(MetadataUpdated {
+            validator,
+            region_commitment,
+            timestamp
+        });
+        Ok(())
+    }
+
     /// Initialize aggregator and range state PDAs to zero.
     pub fn init_state(ctx: Context<InitState>) -> Result<()> {
-        // Initialize aggregator_state and range_state to zero
-        ctx.accounts.aggregator_state.last_seq = 0;
-        ctx.accounts.range_state.last_end_slot = 0;
+        reset_anchor_state(
+            &mut ctx.accounts.aggregator_state,
+            &mut ctx.accounts.range_state,
+        );
+        Ok(())
+    }
+
+    /// Create config, aggregator and range state PDAs in one instruction (`initialize` + `init_state`).
+    pub fn bootstrap(ctx: Context<Bootstrap>, args: InitializeArgs) -> Result<()> {
+        check_initialize_args(&args)?;
+        apply_initialize(
+            &mut ctx.accounts.config,
+            ctx.accounts.zksl_mint.key(),
+            ctx.accounts.admin.key(),
+            &args,
+        );
+        reset_anchor_state(
+            &mut ctx.accounts.aggregator_state,
+            &mut ctx.accounts.range_state,
+        );
         Ok(())
     }
 
@@ -422,11 +823,7 @@ pub mod validator_lock {
         let prog_id = ctx.program_id;
         let agg_pda = Pubkey::find_program_address(&[b"zksl", b"aggregator"], prog_id).0;
         let rng_pda = Pubkey::find_program_address(&[b"zksl", b"range"], prog_id).0;
-        let pr_pda = Pubkey::find_program_address(
-            &[b"zksl", b"proof", &proof_hash, &seq.to_le_bytes()],
-            prog_id,
-        )
-        .0;
+        let pr_pda = helpers::proof_record_address(&proof_hash, seq, prog_id);
         msg!("expected_aggregator_state: {}", agg_pda);
         msg!("expected_range_state: {}", rng_pda);
         msg!("expected_proof_record: {}", pr_pda);
@@ -435,6 +832,169 @@ pub mod validator_lock {
     }
 }
 
+/// DA scheme tag carried in byte 0 of `da_params`.
+const fn da_scheme(da_params: &[u8; 12]) -> u8 {
+    let [scheme, ..] = *da_params;
+    scheme
+}
+
+/// Under `bind_proof_hash`, require `proof_hash` to be `helpers::transition_proof_hash` of
+/// the claimed roots and slots instead of a value only the aggregator signature vouches for.
+fn check_proof_hash_binding(
+    config: &Config,
+    proof_hash: &[u8; 32],
+    (state_root_before, state_root_after): (&[u8; 32], &[u8; 32]),
+    start_slot: u64,
+    end_slot: u64,
+) -> Result<()> {
+    require!(
+        config.bind_proof_hash == 0
+            || *proof_hash
+                == helpers::transition_proof_hash(
+                    state_root_before,
+                    state_root_after,
+                    start_slot,
+                    end_slot
+                ),
+        ZkError::ProofHashMismatch
+    );
+    Ok(())
+}
+
+/// Under `enforce_uniform_da`, reject a proof whose DA scheme differs from the recorded one.
+///
+/// Nothing is recorded after init or an admin clear, so the next proof picks the scheme.
+fn check_da_scheme(
+    config: &Config,
+    aggregator_state: &AggregatorState,
+    da_params: &[u8; 12],
+) -> Result<()> {
+    require!(
+        config.enforce_uniform_da == 0
+            || aggregator_state.da_scheme_set == 0
+            || aggregator_state.da_scheme == da_scheme(da_params),
+        ZkError::DaSchemeMismatch
+    );
+    Ok(())
+}
+
+/// Record the DA scheme of the proof just anchored.
+///
+/// Recorded even with enforcement off, so turning it on binds to the latest scheme.
+const fn record_da_scheme(aggregator_state: &mut AggregatorState, da_params: &[u8; 12]) {
+    aggregator_state.da_scheme = da_scheme(da_params);
+    aggregator_state.da_scheme_set = 1;
+}
+
+/// Forget the recorded DA scheme (admin scheme switch).
+const fn clear_da_scheme(aggregator_state: &mut AggregatorState) {
+    aggregator_state.da_scheme = 0;
+    aggregator_state.da_scheme_set = 0;
+}
+
+/// Check that `start_seq..=end_seq` continues the previous checkpoint and is fully anchored.
+fn check_checkpoint_range(
+    checkpoint: &CheckpointState,
+    aggregator_state: &AggregatorState,
+    start_seq: u64,
+    end_seq: u64,
+) -> Result<()> {
+    let expected_start = checkpoint
+        .end_seq
+        .checked_add(1)
+        .ok_or(ZkError::MathOverflow)?;
+    require!(start_seq == expected_start, ZkError::CheckpointRange);
+    require!(
+        start_seq <= end_seq && end_seq <= aggregator_state.last_seq,
+        ZkError::CheckpointRange
+    );
+    Ok(())
+}
+
+/// Store a checkpoint already validated by `check_checkpoint_range`.
+const fn record_checkpoint(
+    checkpoint: &mut CheckpointState,
+    root: [u8; 32],
+    start_seq: u64,
+    end_seq: u64,
+) {
+    checkpoint.root = root;
+    checkpoint.start_seq = start_seq;
+    checkpoint.end_seq = end_seq;
+    checkpoint.count = checkpoint.count.saturating_add(1);
+}
+
+/// Store `validator`'s region commitment, set at `timestamp`.
+const fn record_region_commitment(
+    metadata: &mut ValidatorMetadata,
+    validator: Pubkey,
+    region_commitment: [u8; 32],
+    timestamp: i64,
+) {
+    metadata.validator = validator;
+    metadata.region_commitment = region_commitment;
+    metadata.updated_at = timestamp;
+}
+
+/// Check that `aggregator_pubkey` is a key allowed to sign `seq` under `config`.
+///
+/// That is the key scheduled for `seq`, or either key while `seq` is within
+/// `Config::rotation_grace` of `activation_seq`. The default key is refused outright: a
+/// config whose keys were never set would otherwise accept a proof "signed" by the all-zero
+/// key.
+fn check_aggregator_key(config: &Config, seq: u64, aggregator_pubkey: Pubkey) -> Result<()> {
+    require!(
+        aggregator_pubkey != Pubkey::default(),
+        ZkError::AggregatorMismatch
+    );
+    if helpers::in_rotation_grace(config.activation_seq, config.rotation_grace, seq) {
+        require!(
+            aggregator_pubkey == config.aggregator_pubkey
+                || aggregator_pubkey == config.next_aggregator_pubkey,
+            ZkError::AggregatorMismatch
+        );
+        return Ok(());
+    }
+    require_keys_eq!(
+        aggregator_pubkey,
+        allowed_aggregator_key(config, seq),
+        ZkError::AggregatorMismatch
+    );
+    Ok(())
+}
+
+/// Reject initialization without a current aggregator key.
+fn check_initialize_args(args: &InitializeArgs) -> Result<()> {
+    require!(
+        args.aggregator_pubkey != Pubkey::default(),
+        ZkError::AggregatorMismatch
+    );
+    check_config_limits(
+        args.max_slots_per_artifact,
+        args.max_past_skew_secs,
+        args.max_future_skew_secs,
+    )
+}
+
+/// Reject a slot window above `MAX_SLOTS_PER_ARTIFACT_LIMIT` or a clock-skew bound above
+/// `MAX_CLOCK_SKEW_LIMIT_SECS`; zero selects the default and is always accepted.
+fn check_config_limits(
+    max_slots_per_artifact: u64,
+    max_past_skew_secs: u32,
+    max_future_skew_secs: u32,
+) -> Result<()> {
+    require!(
+        max_slots_per_artifact <= MAX_SLOTS_PER_ARTIFACT_LIMIT,
+        ZkError::ConfigOutOfRange
+    );
+    require!(
+        max_past_skew_secs <= MAX_CLOCK_SKEW_LIMIT_SECS
+            && max_future_skew_secs <= MAX_CLOCK_SKEW_LIMIT_SECS,
+        ZkError::ConfigOutOfRange
+    );
+    Ok(())
+}
+
 // moved to anchor_items
 
 // moved to anchor_items
@@ -457,6 +1017,60 @@ pub struct InitializeArgs {
     pub activation_seq: u64,
     /// Chain identifier bound into domain separation.
     pub chain_id: u64,
+    /// Escrow each validator locks, in zKSL base units (0 = one token, `10^decimals`).
+    pub min_lock_amount: u64,
+    /// Widest slot range one proof may cover (0 = `MAX_SLOTS_PER_ARTIFACT`).
+    pub max_slots_per_artifact: u64,
+    /// Maximum lag of proof timestamps behind the clock, in seconds (0 = default).
+    pub max_past_skew_secs: u32,
+    /// Maximum lead of proof timestamps ahead of the clock, in seconds (0 = default).
+    pub max_future_skew_secs: u32,
+}
+
+/// One proof of an `anchor_proofs_batch` call; the fields are `anchor_proof`'s arguments.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ProofHeader {
+    /// 16-byte UUID (v4) identifying the artifact.
+    pub artifact_id: [u8; 16],
+    /// 32-byte canonical hash of the artifact JSON.
+    pub proof_hash: [u8; 32],
+    /// Sequence number; the first header continues `AggregatorState::last_seq`.
+    pub seq: u64,
+    /// Inclusive start slot of the artifact window.
+    pub start_slot: u64,
+    /// Inclusive end slot of the artifact window.
+    pub end_slot: u64,
+    /// Artifact JSON length in bytes.
+    pub artifact_len: u32,
+    /// 32-byte state root before the window.
+    pub state_root_before: [u8; 32],
+    /// 32-byte state root after the window.
+    pub state_root_after: [u8; 32],
+    /// Aggregator public key that signed the DS.
+    pub aggregator_pubkey: Pubkey,
+    /// Unix timestamp of submission.
+    pub timestamp: i64,
+    /// BLAKE3 hash of the DS.
+    pub ds_hash: [u8; 32],
+    /// Commitment level (0=processed,1=confirmed,2=finalized).
+    pub commitment_level: u8,
+    /// Data availability parameters; byte 0 is the DA scheme tag.
+    pub da_params: [u8; 12],
+}
+
+/// Anchored fields of a `ProofRecord`, returned by `get_proof_record`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofRecordSummary {
+    /// Sequence number the proof was anchored at.
+    pub seq: u64,
+    /// Inclusive start slot of the artifact window.
+    pub start_slot: u64,
+    /// Inclusive end slot of the artifact window.
+    pub end_slot: u64,
+    /// 32-byte canonical hash of the artifact JSON.
+    pub proof_hash: [u8; 32],
+    /// Commitment level (0=processed,1=confirmed,2=finalized).
+    pub commitment_level: u8,
 }
 
 /// Update config arguments
@@ -471,6 +1085,32 @@ pub struct UpdateConfigArgs {
     pub activation_seq: Option<u64>,
     /// Optional paused flag (true = paused).
     pub paused: Option<bool>,
+    /// Optional update for the maximum lag of proof timestamps behind the clock.
+    pub max_past_skew_secs: Option<u32>,
+    /// Optional update for the maximum lead of proof timestamps ahead of the clock.
+    pub max_future_skew_secs: Option<u32>,
+    /// Optional emergency unlock-all flag (see `Config::allow_emergency_unlock`).
+    pub allow_emergency_unlock: Option<bool>,
+    /// Optional uniform-DA flag (see `Config::enforce_uniform_da`).
+    pub enforce_uniform_da: Option<bool>,
+    /// Forget the stored DA scheme so the next anchored proof may switch schemes.
+    pub clear_da_scheme: bool,
+    /// Optional proof-hash binding flag (see `Config::bind_proof_hash`).
+    pub bind_proof_hash: Option<bool>,
+    /// Optional update for the per-validator lock (see `Config::min_lock_amount`).
+    pub min_lock_amount: Option<u64>,
+    /// Optional update for the proof age limit (see `Config::max_proof_age_slots`).
+    pub max_proof_age_slots: Option<u64>,
+    /// Optional update for the slot window (see `Config::max_slots_per_artifact`).
+    pub max_slots_per_artifact: Option<u64>,
+    /// Optional range-tracking flag (see `Config::per_submitter_range`).
+    pub per_submitter_range: Option<bool>,
+    /// Optional update for the rotation grace window (see `Config::rotation_grace`).
+    pub rotation_grace: Option<u64>,
+    /// Optional update for the unlock cooldown (see `Config::min_lock_duration_secs`).
+    pub min_lock_duration_secs: Option<i64>,
+    /// Optional Token-2022 flag (see `Config::allow_token_2022`).
+    pub allow_token_2022: Option<bool>,
 }
 
 /// Config account
@@ -493,13 +1133,143 @@ pub struct Config {
     pub paused: u8,
     /// PDA bump for `config` account.
     pub bump: u8,
+    /// Maximum seconds a proof timestamp may lag the on-chain clock (0 = default).
+    pub max_past_skew_secs: u32,
+    /// Maximum seconds a proof timestamp may lead the on-chain clock (0 = default).
+    pub max_future_skew_secs: u32,
+    /// Emergency unlock-all flag (0 = off, 1 = on). When set, validators may unlock their
+    /// own escrow even while the program is paused.
+    pub allow_emergency_unlock: u8,
+    /// Total zKSL base units currently escrowed across all validators.
+    pub total_locked: u64,
+    /// Escrow and record PDA seed layout: `SEEDS_VERSION_LEGACY` or `SEEDS_VERSION_POOLED`.
+    pub seeds_version: u8,
+    /// Uniform-DA flag (0 = off, 1 = on). When set, every anchored proof must use the DA
+    /// scheme recorded in `AggregatorState` until an admin clears it.
+    pub enforce_uniform_da: u8,
+    /// Proof-hash binding flag (0 = off, 1 = on). When set, `anchor_proof` requires
+    /// `proof_hash` to commit to the claimed state roots and slot range.
+    pub bind_proof_hash: u8,
+    /// Range-tracking flag (0 = one global `RangeState`, 1 = one per submitter). When set,
+    /// each submitter anchors against its own range PDA, so slot contiguity is enforced
+    /// within one submitter's history only. The global range is left as it was, so switching
+    /// back resumes contiguity from its last end slot.
+    pub per_submitter_range: u8,
     /// Reserved for future fields; must be zeroed.
-    pub reserved: [u8; 22],
+    pub reserved: [u8; 1],
+    /// Escrow `register_validator` locks per validator, in zKSL base units (0 = one token,
+    /// `10^decimals`). Registered validators keep the amount they locked when this changes.
+    pub min_lock_amount: u64,
+    /// Maximum slots a proof's `end_slot` may trail the current slot in `anchor_proof`
+    /// (0 = no limit).
+    pub max_proof_age_slots: u64,
+    /// Admin proposed by `propose_admin`, awaiting `accept_admin` (`Pubkey::default()` =
+    /// none).
+    pub pending_admin: Pubkey,
+    /// Widest slot range one proof may cover (0 = `MAX_SLOTS_PER_ARTIFACT`).
+    pub max_slots_per_artifact: u64,
+    /// Sequences on each side of `activation_seq` for which either aggregator key may sign:
+    /// both are accepted for `seq` in `[activation_seq - rotation_grace, activation_seq +
+    /// rotation_grace)` (0 = hard cutover at `activation_seq`).
+    pub rotation_grace: u64,
+    /// Seconds a validator's lock must age past `ValidatorRecord::lock_timestamp` before it
+    /// can be unlocked (0 = no cooldown). Never negative.
+    pub min_lock_duration_secs: i64,
+    /// Token program flag (0 = legacy SPL Token only, 1 = Token-2022 too). Token-2022 mints
+    /// with extensions that break the exact escrow amount are rejected regardless.
+    pub allow_token_2022: u8,
+    /// Reserved so later fields fit without another resize; must be zeroed.
+    pub reserved_tail: [u8; 63],
 }
 
+/// `Config::seeds_version` of the singleton config at `["zksl", "config"]`: escrow and
+/// record PDAs are keyed by the validator alone. Configs created before the field existed
+/// read as this value.
+pub const SEEDS_VERSION_LEGACY: u8 = 0;
+
+/// `Config::seeds_version` of a pool config created by `initialize_pool`: escrow and record
+/// PDAs insert the config key before the validator.
+pub const SEEDS_VERSION_POOLED: u8 = 1;
+
 impl Config {
     /// Packed on-chain size (bytes) of `Config` without the 8-byte Anchor discriminator.
-    pub const SIZE: usize = 32 + 32 + 32 + 32 + 8 + 8 + 1 + 1 + 22;
+    pub const SIZE: usize = 32
+        + 32
+        + 32
+        + 32
+        + 8
+        + 8
+        + 1
+        + 1
+        + 4
+        + 4
+        + 1
+        + 8
+        + 1
+        + 1
+        + 1
+        + 2
+        + 8
+        + 8
+        + 32
+        + 8
+        + 8
+        + 8
+        + 1
+        + 63;
+
+    /// Packed size of configs created before `min_lock_amount` and the fields after it;
+    /// `resize_config` grows them to `SIZE`.
+    pub const LEGACY_SIZE: usize = 168;
+
+    /// Packed sizes of configs created by earlier program versions. Every field after
+    /// `reserved` arrived in one layout change, so the only one is `LEGACY_SIZE`;
+    /// `resize_config` grows it to `SIZE`, and the zeroed tail reads as the defaults.
+    pub const PRIOR_SIZES: [usize; 1] = [Self::LEGACY_SIZE];
+
+    /// Escrow a new registration locks for a mint with `decimals`.
+    ///
+    /// Zero keeps the one-token lock every config had before `min_lock_amount` existed.
+    ///
+    /// # Errors
+    /// `MathOverflow` when one token does not fit in a `u64`.
+    pub fn lock_amount(&self, decimals: u8) -> Result<u64> {
+        if self.min_lock_amount == 0 {
+            one_token(decimals)
+        } else {
+            Ok(self.min_lock_amount)
+        }
+    }
+
+    /// Effective `(past, future)` clock-skew bounds in seconds.
+    ///
+    /// Configs created before these fields existed carry zeroed reserved bytes, so zero
+    /// selects the defaults rather than rejecting every timestamp.
+    #[must_use]
+    pub const fn clock_skew_bounds(&self) -> (u32, u32) {
+        let past = if self.max_past_skew_secs == 0 {
+            DEFAULT_MAX_PAST_SKEW_SECS
+        } else {
+            self.max_past_skew_secs
+        };
+        let future = if self.max_future_skew_secs == 0 {
+            DEFAULT_MAX_FUTURE_SKEW_SECS
+        } else {
+            self.max_future_skew_secs
+        };
+        (past, future)
+    }
+
+    /// Effective widest slot range one proof may cover; zero selects
+    /// `MAX_SLOTS_PER_ARTIFACT`, as for configs created before the field existed.
+    #[must_use]
+    pub const fn slots_per_artifact_bound(&self) -> u64 {
+        if self.max_slots_per_artifact == 0 {
+            MAX_SLOTS_PER_ARTIFACT
+        } else {
+            self.max_slots_per_artifact
+        }
+    }
 }
 
 /// Validator record
@@ -516,13 +1286,36 @@ pub struct ValidatorRecord {
     pub status: u8,
     /// Number of accepts observed for this validator.
     pub num_accepts: u64,
+    /// zKSL base units locked at registration (0 on records created before the field).
+    pub locked_amount: u64,
     /// Reserved for future fields; must be zeroed.
-    pub reserved: [u8; 55],
+    pub reserved: [u8; 47],
 }
 
 impl ValidatorRecord {
     /// Packed on-chain size (bytes) of `ValidatorRecord` without the 8-byte discriminator.
-    pub const SIZE: usize = 32 + 32 + 8 + 1 + 8 + 55;
+    pub const SIZE: usize = 32 + 32 + 8 + 1 + 8 + 8 + 47;
+
+    /// Escrow `unlock_validator` returns for a mint with `decimals`.
+    ///
+    /// Records created before `locked_amount` existed all locked one token.
+    ///
+    /// # Errors
+    /// `MathOverflow` when one token does not fit in a `u64`.
+    pub fn lock_amount(&self, decimals: u8) -> Result<u64> {
+        if self.locked_amount == 0 {
+            one_token(decimals)
+        } else {
+            Ok(self.locked_amount)
+        }
+    }
+}
+
+/// One whole token of a mint with `decimals`, in base units.
+fn one_token(decimals: u8) -> Result<u64> {
+    Ok(10u64
+        .checked_pow(u32::from(decimals))
+        .ok_or(ZkError::MathOverflow)?)
 }
 
 // Events
@@ -537,34 +1330,90 @@ impl ValidatorRecord {
 /// Aggregator state PDA contents.
 #[account]
 pub struct AggregatorState {
-    /// Aggregator public key in effect for the last sequence.
-    /// Reserved for future rotation verification or audit trails.
-    /// Kept to preserve on-chain layout; not actively read/written today.
+    /// Aggregator key that signed the last anchored sequence, as resolved from the config's
+    /// rotation settings (`Pubkey::default()` before the first anchor).
     pub aggregator_pubkey: Pubkey,
     /// Last anchored sequence number.
     pub last_seq: u64,
+    /// Replay-protection nonce bound into DS; advanced whenever used anchoring state is reset.
+    pub epoch_nonce: u64,
+    /// DA scheme tag (`da_params[0]`) of the last anchored proof; valid when `da_scheme_set`.
+    pub da_scheme: u8,
+    /// Whether `da_scheme` holds a scheme (0 = none recorded since init or the last clear).
+    pub da_scheme_set: u8,
+    /// Timestamp of the last anchored proof (0 before the first anchor since init or reset).
+    pub last_timestamp: i64,
     /// Reserved for future fields; must be zeroed.
-    pub reserved: [u8; 86],
+    pub reserved: [u8; 68],
 }
 
 impl AggregatorState {
     /// Packed size (bytes) without the discriminator.
-    pub const SIZE: usize = 32 + 8 + 86;
+    pub const SIZE: usize = 32 + 8 + 8 + 1 + 1 + 8 + 68;
 }
 
 /// Range state PDA
 /// Range state PDA contents.
+///
+/// The global range lives at `["zksl", "range"]`. Under `Config::per_submitter_range` each
+/// submitter has its own at `["zksl", "range", submitter]`, created on its first anchor.
 #[account]
 pub struct RangeState {
-    /// Last end slot anchored by the validator.
+    /// Last end slot anchored against this range.
     pub last_end_slot: u64,
+    /// Submitter this range belongs to (`Pubkey::default()` for the global range).
+    pub submitter: Pubkey,
+    /// Last sequence anchored against this range (0 before the first anchor or after a reset).
+    pub last_seq: u64,
+    /// `AggregatorState::epoch_nonce` when `last_seq` was anchored.
+    pub epoch_nonce: u64,
     /// Reserved for future fields; must be zeroed.
-    pub reserved: [u8; 120],
+    pub reserved: [u8; 72],
 }
 
 impl RangeState {
     /// Packed size (bytes) without the discriminator.
-    pub const SIZE: usize = 8 + 120;
+    pub const SIZE: usize = 8 + 32 + 8 + 8 + 72;
+}
+
+/// Checkpoint state PDA contents: the latest committed checkpoint.
+///
+/// Earlier checkpoints are only kept in `CheckpointCommitted` events.
+#[account]
+pub struct CheckpointState {
+    /// Merkle root over the `(proof_hash, seq)` leaves of the latest checkpoint.
+    pub root: [u8; 32],
+    /// First sequence covered by the latest checkpoint.
+    pub start_seq: u64,
+    /// Last sequence covered by the latest checkpoint (0 before the first checkpoint).
+    pub end_seq: u64,
+    /// Number of checkpoints committed.
+    pub count: u64,
+    /// Reserved for future fields; must be zeroed.
+    pub reserved: [u8; 64],
+}
+
+impl CheckpointState {
+    /// Packed size (bytes) without the discriminator.
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 64;
+}
+
+/// Validator metadata PDA contents: optional facts a validator commits to about itself.
+#[account]
+pub struct ValidatorMetadata {
+    /// Validator wallet public key.
+    pub validator: Pubkey,
+    /// Hash of the validator's region/ASN (`helpers::region_commitment`); zero if never set.
+    pub region_commitment: [u8; 32],
+    /// Unix timestamp of the last update.
+    pub updated_at: i64,
+    /// Reserved for future fields; must be zeroed.
+    pub reserved: [u8; 64],
+}
+
+impl ValidatorMetadata {
+    /// Packed size (bytes) without the discriminator.
+    pub const SIZE: usize = 32 + 32 + 8 + 64;
 }
 
 /// Proof record PDA
@@ -597,15 +1446,56 @@ pub struct ProofRecord {
     pub ds_hash: [u8; 32],
     /// Commitment level (0=processed,1=confirmed,2=finalized).
     pub commitment_level: u8,
-    /// Data availability parameters (reserved for future use).
+    /// Data availability parameters; byte 0 is the DA scheme tag.
     pub da_params: [u8; 12],
+    /// Genesis flag (1 = first proof anchored since the anchoring state was initialized).
+    pub is_genesis: u8,
     /// Reserved for future fields; must be zeroed.
-    pub reserved: [u8; 5],
+    pub reserved: [u8; 4],
+    /// Epoch of `start_slot` (informational; ranges may straddle epochs).
+    pub start_epoch: u64,
+    /// Epoch of `end_slot`.
+    pub end_epoch: u64,
 }
 
 impl ProofRecord {
     /// Packed on-chain size (bytes) of `ProofRecord` without the 8-byte discriminator.
-    pub const SIZE: usize = 16 + 8 + 8 + 32 + 4 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 1 + 12 + 5;
+    pub const SIZE: usize =
+        16 + 8 + 8 + 32 + 4 + 32 + 32 + 32 + 32 + 8 + 8 + 32 + 1 + 12 + 1 + 4 + 8 + 8;
+
+    /// Packed sizes of records anchored by earlier program versions: before `start_epoch` and
+    /// `end_epoch`. `resize_proof_record` grows them to `SIZE` and tags their epochs.
+    pub const PRIOR_SIZES: [usize; 1] = [262];
+}
+
+/// Artifact dedup PDA: one per anchored `artifact_id`, whatever its `proof_hash` and `seq`.
+#[account]
+pub struct ArtifactRecord {
+    /// Sequence the artifact was anchored at; 0 until claimed.
+    pub seq: u64,
+    /// Reserved for future fields; must be zeroed.
+    pub reserved: [u8; 24],
+}
+
+impl ArtifactRecord {
+    /// Packed size (bytes) without the discriminator.
+    pub const SIZE: usize = 8 + 24;
+}
+
+/// Aggregator committee PDA contents (`["zksl", "committee"]`), set by `set_committee`.
+#[account]
+pub struct AggregatorCommittee {
+    /// Distinct member signatures `anchor_proof` requires (1 = single aggregator signature).
+    pub threshold: u8,
+    /// Committee member keys, at most `MAX_MEMBERS`.
+    pub members: Vec<Pubkey>,
+}
+
+impl AggregatorCommittee {
+    /// Most members a committee may hold.
+    pub const MAX_MEMBERS: usize = 16;
+    /// Packed size (bytes) without the discriminator, with room for `MAX_MEMBERS` members.
+    pub const SIZE: usize = 1 + 4 + 32 * Self::MAX_MEMBERS;
 }
 
 // Anchor macro-generated public items are isolated here to allow missing_docs per policy.
@@ -623,7 +1513,7 @@ mod anchor_items {
         pub payer: Signer<'info>,
         /// CHECK: admin is recorded only
         pub admin: UncheckedAccount<'info>,
-        pub zksl_mint: Account<'info, Mint>,
+        pub zksl_mint: InterfaceAccount<'info, Mint>,
         #[account(init, payer = payer, seeds = [b"zksl".as_ref(), b"config".as_ref()], bump, space = 8 + Config::SIZE)]
         pub config: Account<'info, Config>,
         pub system_program: Program<'info, System>,
@@ -633,7 +1523,7 @@ mod anchor_items {
     pub struct RegisterValidator<'info> {
         #[account(mut)]
         pub validator: Signer<'info>,
-        pub zksl_mint: Account<'info, Mint>,
+        pub zksl_mint: InterfaceAccount<'info, Mint>,
         #[account(mut, has_one = zksl_mint)]
         pub config: Account<'info, Config>,
         #[account(init_if_needed, payer = validator, seeds = [b"zksl".as_ref(), b"validator".as_ref(), validator.key().as_ref()], bump, space = 8 + ValidatorRecord::SIZE)]
@@ -642,10 +1532,10 @@ mod anchor_items {
         #[account(seeds = [b"zksl".as_ref(), b"escrow".as_ref(), validator.key().as_ref()], bump)]
         pub escrow_authority: UncheckedAccount<'info>,
         #[account(init_if_needed, payer = validator, associated_token::mint = zksl_mint, associated_token::authority = escrow_authority, associated_token::token_program = token_program)]
-        pub validator_escrow: Account<'info, TokenAccount>,
+        pub validator_escrow: InterfaceAccount<'info, TokenAccount>,
         #[account(mut)]
-        pub validator_ata: Account<'info, TokenAccount>,
-        pub token_program: Program<'info, Token>,
+        pub validator_ata: InterfaceAccount<'info, TokenAccount>,
+        pub token_program: Interface<'info, TokenInterface>,
         pub associated_token_program: Program<'info, AssociatedToken>,
         pub system_program: Program<'info, System>,
     }
@@ -655,6 +1545,91 @@ mod anchor_items {
         pub admin: Signer<'info>,
         #[account(mut)]
         pub config: Account<'info, Config>,
+        /// Needed only with `clear_da_scheme`; clients may omit this trailing account otherwise.
+        #[account(mut, seeds = [b"zksl".as_ref(), b"aggregator".as_ref()], bump)]
+        pub aggregator_state: Option<Account<'info, AggregatorState>>,
+    }
+
+    #[derive(Accounts)]
+    pub struct ProposeAdmin<'info> {
+        pub admin: Signer<'info>,
+        #[account(mut)]
+        pub config: Account<'info, Config>,
+    }
+
+    #[derive(Accounts)]
+    pub struct AcceptAdmin<'info> {
+        pub pending_admin: Signer<'info>,
+        #[account(mut)]
+        pub config: Account<'info, Config>,
+    }
+
+    #[derive(Accounts)]
+    pub struct ResizeConfig<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        /// CHECK: may predate `Config::SIZE`; `resize_config` decodes it and checks the admin
+        #[account(mut, owner = crate::ID)]
+        pub config: UncheckedAccount<'info>,
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct ResetState<'info> {
+        pub admin: Signer<'info>,
+        #[account(seeds = [b"zksl".as_ref(), b"config".as_ref()], bump)]
+        pub config: Account<'info, Config>,
+        #[account(mut, seeds = [b"zksl".as_ref(), b"aggregator".as_ref()], bump)]
+        pub aggregator_state: Account<'info, AggregatorState>,
+        #[account(mut, seeds = [b"zksl".as_ref(), b"range".as_ref()], bump)]
+        pub range_state: Account<'info, RangeState>,
+    }
+
+    #[derive(Accounts)]
+    pub struct SetCommittee<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        #[account(seeds = [b"zksl".as_ref(), b"config".as_ref()], bump)]
+        pub config: Account<'info, Config>,
+        #[account(init_if_needed, payer = admin, seeds = [b"zksl".as_ref(), b"committee".as_ref()], bump, space = 8 + AggregatorCommittee::SIZE)]
+        pub committee: Account<'info, AggregatorCommittee>,
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(seq: u64, proof_hash: [u8;32])]
+    pub struct SetCommitmentLevel<'info> {
+        pub authority: Signer<'info>,
+        #[account(seeds = [b"zksl".as_ref(), b"config".as_ref()], bump)]
+        pub config: Account<'info, Config>,
+        #[account(mut, seeds = [b"zksl".as_ref(), b"proof".as_ref(), proof_hash.as_ref(), &seq.to_le_bytes()], bump)]
+        pub proof_record: Account<'info, ProofRecord>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(seq: u64, proof_hash: [u8;32])]
+    pub struct ResizeProofRecord<'info> {
+        #[account(mut)]
+        pub payer: Signer<'info>,
+        /// CHECK: may predate `ProofRecord::SIZE`; `resize_proof_record` decodes it
+        #[account(mut, owner = crate::ID, seeds = [b"zksl".as_ref(), b"proof".as_ref(), proof_hash.as_ref(), &seq.to_le_bytes()], bump)]
+        pub proof_record: UncheckedAccount<'info>,
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct GetProofRecord<'info> {
+        pub proof_record: Account<'info, ProofRecord>,
+    }
+
+    #[derive(Accounts)]
+    pub struct CloseProofRecord<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        #[account(seeds = [b"zksl".as_ref(), b"config".as_ref()], bump)]
+        pub config: Account<'info, Config>,
+        #[account(mut, close = admin)]
+        pub proof_record: Account<'info, ProofRecord>,
     }
 
     #[derive(Accounts)]
@@ -668,11 +1643,27 @@ mod anchor_items {
         pub system_program: Program<'info, System>,
     }
 
+    #[derive(Accounts)]
+    pub struct Bootstrap<'info> {
+        #[account(mut)]
+        pub payer: Signer<'info>,
+        /// CHECK: admin is recorded only
+        pub admin: UncheckedAccount<'info>,
+        pub zksl_mint: InterfaceAccount<'info, Mint>,
+        #[account(init, payer = payer, seeds = [b"zksl".as_ref(), b"config".as_ref()], bump, space = 8 + Config::SIZE)]
+        pub config: Account<'info, Config>,
+        #[account(init, payer = payer, seeds = [b"zksl".as_ref(), b"aggregator".as_ref()], bump, space = 8 + AggregatorState::SIZE)]
+        pub aggregator_state: Account<'info, AggregatorState>,
+        #[account(init, payer = payer, seeds = [b"zksl".as_ref(), b"range".as_ref()], bump, space = 8 + RangeState::SIZE)]
+        pub range_state: Account<'info, RangeState>,
+        pub system_program: Program<'info, System>,
+    }
+
     #[derive(Accounts)]
     pub struct UnlockValidator<'info> {
         #[account(mut)]
         pub validator: Signer<'info>,
-        pub zksl_mint: Account<'info, Mint>,
+        pub zksl_mint: InterfaceAccount<'info, Mint>,
         #[account(mut, has_one = zksl_mint)]
         pub config: Account<'info, Config>,
         #[account(mut, seeds = [b"zksl".as_ref(), b"validator".as_ref(), validator.key().as_ref()], bump)]
@@ -681,10 +1672,63 @@ mod anchor_items {
         #[account(seeds = [b"zksl".as_ref(), b"escrow".as_ref(), validator.key().as_ref()], bump)]
         pub escrow_authority: UncheckedAccount<'info>,
         #[account(mut)]
-        pub validator_escrow: Account<'info, TokenAccount>,
+        pub validator_escrow: InterfaceAccount<'info, TokenAccount>,
+        #[account(mut)]
+        pub validator_ata: InterfaceAccount<'info, TokenAccount>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+    #[derive(Accounts)]
+    #[instruction(pool_id: u64)]
+    pub struct InitializePool<'info> {
+        #[account(mut)]
+        pub payer: Signer<'info>,
+        /// CHECK: admin is recorded only
+        pub admin: UncheckedAccount<'info>,
+        pub zksl_mint: InterfaceAccount<'info, Mint>,
+        #[account(init, payer = payer, seeds = [b"zksl".as_ref(), b"config".as_ref(), &pool_id.to_le_bytes()], bump, space = 8 + Config::SIZE)]
+        pub config: Account<'info, Config>,
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct RegisterValidatorPooled<'info> {
+        #[account(mut)]
+        pub validator: Signer<'info>,
+        pub zksl_mint: InterfaceAccount<'info, Mint>,
+        #[account(mut, has_one = zksl_mint)]
+        pub config: Account<'info, Config>,
+        #[account(init_if_needed, payer = validator, seeds = [b"zksl".as_ref(), b"validator".as_ref(), config.key().as_ref(), validator.key().as_ref()], bump, space = 8 + ValidatorRecord::SIZE)]
+        pub validator_record: Account<'info, ValidatorRecord>,
+        /// CHECK: PDA authority for escrow
+        #[account(seeds = [b"zksl".as_ref(), b"escrow".as_ref(), config.key().as_ref(), validator.key().as_ref()], bump)]
+        pub escrow_authority: UncheckedAccount<'info>,
+        #[account(init_if_needed, payer = validator, associated_token::mint = zksl_mint, associated_token::authority = escrow_authority, associated_token::token_program = token_program)]
+        pub validator_escrow: InterfaceAccount<'info, TokenAccount>,
+        #[account(mut)]
+        pub validator_ata: InterfaceAccount<'info, TokenAccount>,
+        pub token_program: Interface<'info, TokenInterface>,
+        pub associated_token_program: Program<'info, AssociatedToken>,
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct UnlockValidatorPooled<'info> {
+        #[account(mut)]
+        pub validator: Signer<'info>,
+        pub zksl_mint: InterfaceAccount<'info, Mint>,
+        #[account(mut, has_one = zksl_mint)]
+        pub config: Account<'info, Config>,
+        #[account(mut, seeds = [b"zksl".as_ref(), b"validator".as_ref(), config.key().as_ref(), validator.key().as_ref()], bump)]
+        pub validator_record: Account<'info, ValidatorRecord>,
+        /// CHECK: PDA authority for escrow
+        #[account(seeds = [b"zksl".as_ref(), b"escrow".as_ref(), config.key().as_ref(), validator.key().as_ref()], bump)]
+        pub escrow_authority: UncheckedAccount<'info>,
+        #[account(mut)]
+        pub validator_escrow: InterfaceAccount<'info, TokenAccount>,
         #[account(mut)]
-        pub validator_ata: Account<'info, TokenAccount>,
-        pub token_program: Program<'info, Token>,
+        pub validator_ata: InterfaceAccount<'info, TokenAccount>,
+        pub token_program: Interface<'info, TokenInterface>,
     }
 
     #[derive(Accounts)]
@@ -692,18 +1736,46 @@ mod anchor_items {
     pub struct AnchorProof<'info> {
         #[account(mut)]
         pub submitted_by: Signer<'info>,
-        #[account(mut)]
+        #[account(mut, seeds = [b"zksl".as_ref(), b"config".as_ref()], bump)]
         pub config: Account<'info, Config>,
         #[account(mut, seeds = [b"zksl".as_ref(), b"aggregator".as_ref()], bump)]
         pub aggregator_state: Account<'info, AggregatorState>,
-        #[account(mut, seeds = [b"zksl".as_ref(), b"range".as_ref()], bump)]
-        pub range_state: Account<'info, RangeState>,
+        /// CHECK: global or per-submitter range PDA, checked (and created) by `load_range_state`
+        #[account(mut)]
+        pub range_state: UncheckedAccount<'info>,
         #[account(init, payer = submitted_by, seeds = [b"zksl".as_ref(), b"proof".as_ref(), proof_hash.as_ref(), &seq.to_le_bytes()], bump, space = 8 + ProofRecord::SIZE)]
         pub proof_record: Account<'info, ProofRecord>,
+        /// One per `artifact_id` (`helpers::artifact_record_address`), so an artifact already
+        /// anchored cannot come back under another `proof_hash` or `seq`.
+        #[account(init, payer = submitted_by, seeds = [b"zksl".as_ref(), b"artifact".as_ref(), artifact_id.as_ref()], bump, space = 8 + ArtifactRecord::SIZE)]
+        pub artifact_record: Account<'info, ArtifactRecord>,
+        /// CHECK: instructions sysvar
+        #[account(address = sysvar_instructions::ID)]
+        pub sysvar_instructions: UncheckedAccount<'info>,
+        pub system_program: Program<'info, System>,
+        /// CHECK: committee PDA, empty until `set_committee`; decoded by `load_committee`
+        #[account(seeds = [b"zksl".as_ref(), b"committee".as_ref()], bump)]
+        pub committee: UncheckedAccount<'info>,
+    }
+
+    #[derive(Accounts)]
+    pub struct AnchorProofsBatch<'info> {
+        #[account(mut)]
+        pub submitted_by: Signer<'info>,
+        #[account(seeds = [b"zksl".as_ref(), b"config".as_ref()], bump)]
+        pub config: Account<'info, Config>,
+        #[account(mut, seeds = [b"zksl".as_ref(), b"aggregator".as_ref()], bump)]
+        pub aggregator_state: Account<'info, AggregatorState>,
+        /// CHECK: global or per-submitter range PDA, checked (and created) by `load_range_state`
+        #[account(mut)]
+        pub range_state: UncheckedAccount<'info>,
         /// CHECK: instructions sysvar
         #[account(address = sysvar_instructions::ID)]
         pub sysvar_instructions: UncheckedAccount<'info>,
         pub system_program: Program<'info, System>,
+        /// CHECK: committee PDA, empty until `set_committee`; decoded by `load_committee`
+        #[account(seeds = [b"zksl".as_ref(), b"committee".as_ref()], bump)]
+        pub committee: UncheckedAccount<'info>,
     }
 
     #[derive(Accounts)]
@@ -712,9 +1784,11 @@ mod anchor_items {
         pub submitted_by: Signer<'info>,
         #[account(mut)]
         pub config: Account<'info, Config>,
-        /// CHECK: debug only
+        /// CHECK: debug only; must still be this program's account, never a look-alike
+        #[account(owner = crate::ID)]
         pub aggregator_state: UncheckedAccount<'info>,
-        /// CHECK: debug only
+        /// CHECK: debug only; must still be this program's account, never a look-alike
+        #[account(owner = crate::ID)]
         pub range_state: UncheckedAccount<'info>,
         /// CHECK: debug only
         pub proof_record: UncheckedAccount<'info>,
@@ -723,6 +1797,40 @@ mod anchor_items {
         pub system_program: Program<'info, System>,
     }
 
+    #[derive(Accounts)]
+    pub struct HeartbeatAccounts<'info> {
+        #[account(seeds = [b"zksl".as_ref(), b"config".as_ref()], bump)]
+        pub config: Account<'info, Config>,
+        #[account(seeds = [b"zksl".as_ref(), b"aggregator".as_ref()], bump)]
+        pub aggregator_state: Account<'info, AggregatorState>,
+        #[account(seeds = [b"zksl".as_ref(), b"range".as_ref()], bump)]
+        pub range_state: Account<'info, RangeState>,
+    }
+
+    #[derive(Accounts)]
+    pub struct CommitCheckpoint<'info> {
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        #[account(seeds = [b"zksl".as_ref(), b"config".as_ref()], bump)]
+        pub config: Account<'info, Config>,
+        #[account(seeds = [b"zksl".as_ref(), b"aggregator".as_ref()], bump)]
+        pub aggregator_state: Account<'info, AggregatorState>,
+        #[account(init_if_needed, payer = admin, seeds = [b"zksl".as_ref(), b"checkpoint".as_ref()], bump, space = 8 + CheckpointState::SIZE)]
+        pub checkpoint_state: Account<'info, CheckpointState>,
+        pub system_program: Program<'info, System>,
+    }
+
+    #[derive(Accounts)]
+    pub struct SetRegionCommitment<'info> {
+        #[account(mut)]
+        pub validator: Signer<'info>,
+        #[account(seeds = [b"zksl".as_ref(), b"validator".as_ref(), validator.key().as_ref()], bump)]
+        pub validator_record: Account<'info, ValidatorRecord>,
+        #[account(init_if_needed, payer = validator, seeds = [b"zksl".as_ref(), b"metadata".as_ref(), validator.key().as_ref()], bump, space = 8 + ValidatorMetadata::SIZE)]
+        pub validator_metadata: Account<'info, ValidatorMetadata>,
+        pub system_program: Program<'info, System>,
+    }
+
     #[derive(Accounts)]
     #[instruction(proof_hash: [u8;32], seq: u64)]
     pub struct EchoAccounts<'info> {
@@ -730,11 +1838,11 @@ mod anchor_items {
         pub submitted_by: Signer<'info>,
         #[account(mut)]
         pub config: Account<'info, Config>,
-        /// CHECK: PDA, observed only
-        #[account(seeds = [b"zksl".as_ref(), b"aggregator".as_ref()], bump)]
+        /// CHECK: PDA owned by this program, observed only
+        #[account(seeds = [b"zksl".as_ref(), b"aggregator".as_ref()], bump, owner = crate::ID)]
         pub aggregator_state: UncheckedAccount<'info>,
-        /// CHECK: PDA, observed only
-        #[account(seeds = [b"zksl".as_ref(), b"range".as_ref()], bump)]
+        /// CHECK: PDA owned by this program, observed only
+        #[account(seeds = [b"zksl".as_ref(), b"range".as_ref()], bump, owner = crate::ID)]
         pub range_state: UncheckedAccount<'info>,
         /// CHECK: PDA, observed only
         #[account(seeds = [b"zksl".as_ref(), b"proof".as_ref(), proof_hash.as_ref(), &seq.to_le_bytes()], bump)]
@@ -742,10 +1850,114 @@ mod anchor_items {
         pub system_program: Program<'info, System>,
     }
 
+    #[event]
+    pub struct Heartbeat {
+        pub aggregator_pubkey: Pubkey,
+        pub paused: bool,
+        pub last_seq: u64,
+        pub last_end_slot: u64,
+        pub timestamp: i64,
+    }
+
+    /// Fields changed by `update_config`; unchanged fields are `None`.
     #[event]
     pub struct ConfigUpdated {
         pub aggregator_pubkey: Option<Pubkey>,
+        pub next_aggregator_pubkey: Option<Pubkey>,
+        pub activation_seq: Option<u64>,
         pub paused: Option<bool>,
+        pub max_past_skew_secs: Option<u32>,
+        pub max_future_skew_secs: Option<u32>,
+        pub allow_emergency_unlock: Option<bool>,
+        pub enforce_uniform_da: Option<bool>,
+        pub bind_proof_hash: Option<bool>,
+        pub min_lock_amount: Option<u64>,
+        pub max_proof_age_slots: Option<u64>,
+        pub max_slots_per_artifact: Option<u64>,
+        pub per_submitter_range: Option<bool>,
+        pub rotation_grace: Option<u64>,
+        pub min_lock_duration_secs: Option<i64>,
+        pub allow_token_2022: Option<bool>,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct ValidatorRegistered {
+        pub validator: Pubkey,
+        pub amount: u64,
+        pub total_locked: u64,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct ValidatorUnlocked {
+        pub validator: Pubkey,
+        pub amount: u64,
+        pub total_locked: u64,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct CheckpointCommitted {
+        pub root: [u8; 32],
+        pub start_seq: u64,
+        pub end_seq: u64,
+        pub count: u64,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct MetadataUpdated {
+        pub validator: Pubkey,
+        pub region_commitment: [u8; 32],
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct EmergencyUnlockEnabled {
+        pub enabled: bool,
+        pub admin: Pubkey,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct AdminTransferred {
+        pub previous_admin: Pubkey,
+        pub new_admin: Pubkey,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct CommitmentAdvanced {
+        pub proof_hash: [u8; 32],
+        pub seq: u64,
+        pub previous_level: u8,
+        pub level: u8,
+        pub authority: Pubkey,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct CommitteeUpdated {
+        pub threshold: u8,
+        pub members: Vec<Pubkey>,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct ProofRecordClosed {
+        pub artifact_id: [u8; 16],
+        pub seq: u64,
+        pub admin: Pubkey,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct StateReset {
+        pub old_last_seq: u64,
+        pub old_last_end_slot: u64,
+        pub epoch_nonce: u64,
+        pub admin: Pubkey,
         pub timestamp: i64,
     }
 
@@ -787,6 +1999,52 @@ mod anchor_items {
         BadDomainSeparation = 6016,
         #[msg("Insufficient compute budget")]
         InsufficientBudget = 6017,
+        #[msg("Invalid commitment level")]
+        InvalidCommitmentLevel = 6018,
+        #[msg("Config seeds version does not match instruction")]
+        SeedsVersionMismatch = 6019,
+        #[msg("Checkpoint range not contiguous or not anchored")]
+        CheckpointRange = 6020,
+        #[msg("DA scheme differs from the previous proof")]
+        DaSchemeMismatch = 6021,
+        #[msg("proof_hash does not commit to the claimed roots and slots")]
+        ProofHashMismatch = 6022,
+        #[msg("Proof timestamp is earlier than the previous proof's")]
+        NonMonotonicTimestamp = 6023,
+        #[msg("Artifact already anchored")]
+        ArtifactAlreadyAnchored = 6024,
+        #[msg("Proof end slot is too far behind the current slot")]
+        ProofTooStale = 6025,
+        #[msg("Batch is empty or holds too many proofs")]
+        InvalidBatchSize = 6026,
+        #[msg("Batch accounts do not match the proof headers")]
+        BatchAccountMismatch = 6027,
+        #[msg("No admin transfer is pending")]
+        NoPendingAdmin = 6028,
+        #[msg("Config value out of range")]
+        ConfigOutOfRange = 6029,
+        #[msg("Program must be paused")]
+        NotPaused = 6030,
+        #[msg("State reset not confirmed")]
+        ResetNotConfirmed = 6031,
+        #[msg("Range state account does not match the submitter")]
+        RangeStateMismatch = 6032,
+        #[msg("Proof record is not finalized")]
+        ProofNotFinalized = 6033,
+        #[msg("Token account is not owned by the validator")]
+        InvalidOwner = 6034,
+        #[msg("Lock has not reached the minimum duration")]
+        LockNotMatured = 6035,
+        #[msg("Invalid aggregator committee")]
+        InvalidCommittee = 6036,
+        #[msg("Signatures do not satisfy the aggregator committee")]
+        CommitteeMismatch = 6037,
+        #[msg("Commitment level may only advance")]
+        CommitmentRegression = 6038,
+        #[msg("Unsupported DS version")]
+        UnsupportedDsVersion = 6039,
+        #[msg("Mint extension incompatible with the escrow")]
+        UnsupportedMintExtension = 6040,
     }
 
     #[event]
@@ -799,6 +2057,29 @@ mod anchor_items {
         pub timestamp: i64,
         pub seq: u64,
         pub ds_hash: [u8; 32],
+        pub start_epoch: u64,
+        pub end_epoch: u64,
+    }
+
+    /// The aggregator key stored in `AggregatorState` changed, i.e. `seq` is the first
+    /// sequence signed under a rotated key.
+    #[event]
+    pub struct AggregatorRotated {
+        pub previous_pubkey: Pubkey,
+        pub aggregator_pubkey: Pubkey,
+        pub seq: u64,
+        pub timestamp: i64,
+    }
+
+    /// First proof of the chain (`ProofRecord.is_genesis == 1`); it has no predecessor.
+    #[event]
+    pub struct GenesisAnchored {
+        pub proof_hash: [u8; 32],
+        pub seq: u64,
+        pub start_slot: u64,
+        pub end_slot: u64,
+        pub epoch_nonce: u64,
+        pub timestamp: i64,
     }
 }
 
@@ -809,53 +2090,3659 @@ pub use anchor_items::*;
 
 // moved to anchor_items
 
-/// Domain separation prefix for the anchor DS message.
-const DS_PREFIX: &[u8] = b"zKSL/anchor/v1"; // 14 bytes
-/// Maximum slot window allowed per artifact.
+/// DS layout version of [`helpers::compute_ds_message`], the only one batches accept.
+const DS_VERSION_V1: u8 = 1;
+/// DS layout version of [`helpers::compute_ds_message_v2`], which binds `artifact_len`.
+const DS_VERSION_V2: u8 = 2;
+/// Bytes the non-zero `epoch_nonce` adds to a DS of any version.
+const DS_NONCE_LEN: usize = 8;
+/// Default maximum slot window per artifact, used while `Config::max_slots_per_artifact` is 0.
 const MAX_SLOTS_PER_ARTIFACT: u64 = 2048;
-/// Maximum acceptable clock skew in seconds.
-const MAX_CLOCK_SKEW_SECS: i64 = 120;
+/// Largest `max_slots_per_artifact` a config accepts: one mainnet epoch.
+const MAX_SLOTS_PER_ARTIFACT_LIMIT: u64 = 432_000;
+/// Largest clock-skew bound, in either direction, a config accepts.
+const MAX_CLOCK_SKEW_LIMIT_SECS: u32 = 3_600;
+/// Default maximum lag (seconds) of a proof timestamp behind the on-chain clock.
+const DEFAULT_MAX_PAST_SKEW_SECS: u32 = 120;
+/// Default maximum lead (seconds) of a proof timestamp ahead of the on-chain clock.
+/// The aggregator stamps proofs before submission, so timestamps should not be ahead.
+const DEFAULT_MAX_FUTURE_SKEW_SECS: u32 = 5;
 /// Maximum allowed artifact size in bytes (defense in depth; mirrored off-chain).
 const MAX_ARTIFACT_SIZE_BYTES: u32 = 512 * 1024;
+/// Most proofs one `anchor_proofs_batch` call may anchor.
+const MAX_BATCH_PROOFS: usize = 8;
+/// Highest `ProofRecord.commitment_level` (finalized).
+const MAX_COMMITMENT_LEVEL: u8 = 2;
 /// Ed25519 program ID (built-in) used to validate preflight signature instruction.
 const ED25519_PROGRAM_ID: Pubkey = pubkey!("Ed25519SigVerify111111111111111111111111111");
 /// Compute Budget program ID.
 /// Presence is required to ensure callers explicitly allocate sufficient compute units
 /// and/or priority fees so proof-anchoring succeeds under congestion (defense in depth).
-const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
-    pubkey!("ComputeBudget111111111111111111111111111111");
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
 
-/// Resolve the allowed aggregator key given the current sequence and activation threshold.
-fn allowed_aggregator_key(config: &Account<Config>, seq: u64) -> Pubkey {
-    if seq >= config.activation_seq {
-        config.next_aggregator_pubkey
-    } else {
-        config.aggregator_pubkey
+/// Require exactly `ed25519_count` Ed25519 instructions and a `ComputeBudget` instruction
+/// among `program_ids`, and at most `helpers::MAX_PREFLIGHT_INSTRUCTIONS` instructions in all.
+///
+/// `ComputeBudget` presence forces explicit CU/priority-fee planning; see
+/// [`helpers::recommend_compute_units`] for the limit clients should request.
+fn check_preflight_programs(
+    program_ids: impl IntoIterator<Item = Pubkey>,
+    ed25519_count: u8,
+) -> Result<()> {
+    let mut ed_count: u32 = 0;
+    let mut has_compute_ok = false;
+    for (index, program_id) in program_ids.into_iter().enumerate() {
+        require!(
+            index < helpers::MAX_PREFLIGHT_INSTRUCTIONS,
+            ZkError::BadEd25519Order
+        );
+        match program_id {
+            ED25519_PROGRAM_ID => ed_count = ed_count.saturating_add(1),
+            COMPUTE_BUDGET_PROGRAM_ID => has_compute_ok = true,
+            _ => {}
+        }
     }
+    require!(
+        ed_count == u32::from(ed25519_count),
+        ZkError::BadEd25519Order
+    );
+    require!(has_compute_ok, ZkError::InsufficientBudget);
+    Ok(())
 }
 
-// moved to anchor_items
+/// Run the preflight scan over the transaction and return its `count` Ed25519
+/// instructions, which must immediately precede the current one, in transaction order.
+fn load_ed25519_preflight(
+    sysvar: &AccountInfo<'_>,
+    count: u8,
+) -> Result<Vec<anchor_lang::solana_program::instruction::Instruction>> {
+    let program_ids = (0..)
+        .take(helpers::MAX_PREFLIGHT_INSTRUCTIONS.saturating_add(1))
+        .map_while(|i| sysvar_instructions::load_instruction_at_checked(i, sysvar).ok())
+        .map(|ix| ix.program_id);
+    check_preflight_programs(program_ids, count)?;
+    // Use the current instruction index to safely reference the immediately preceding instructions
+    let cur_idx = sysvar_instructions::load_current_index_checked(sysvar)
+        .map_err(|_| error!(ZkError::BadEd25519Order))?;
+    let first = usize::from(cur_idx)
+        .checked_sub(usize::from(count))
+        .ok_or(ZkError::BadEd25519Order)?;
+    (first..usize::from(cur_idx))
+        .map(|i| {
+            let ix = sysvar_instructions::load_instruction_at_checked(i, sysvar)
+                .map_err(|_| error!(ZkError::BadEd25519Order))?;
+            require!(
+                ix.program_id == ED25519_PROGRAM_ID,
+                ZkError::BadEd25519Order
+            );
+            Ok(ix)
+        })
+        .collect()
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Load the aggregator committee from `committee_info`, the `["zksl", "committee"]` PDA;
+/// `None` until `set_committee` first creates it.
+///
+/// # Errors
+/// `InvalidCommittee` when the account exists but is not a committee of this program.
+#[cfg(not(feature = "skip-anchor-program"))]
+fn load_committee(
+    committee_info: &AccountInfo<'_>,
+    program_id: &Pubkey,
+) -> Result<Option<AggregatorCommittee>> {
+    if committee_info.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(
+        *committee_info.owner,
+        *program_id,
+        ZkError::InvalidCommittee
+    );
+    let committee =
+        AggregatorCommittee::try_deserialize(&mut &**committee_info.try_borrow_data()?)?;
+    Ok(Some(committee))
+}
 
-    #[test]
-    fn test_account_sizes_match_spec() {
-        assert_eq!(Config::SIZE, 168, "Config size must be 168 bytes");
-        assert_eq!(
-            ValidatorRecord::SIZE,
-            136,
-            "ValidatorRecord size must be 136 bytes"
-        );
-        assert_eq!(ProofRecord::SIZE, 262, "ProofRecord size must be 262 bytes");
+/// Check a batch of `headers` proofs against its `remaining_accounts` count (two PDAs each).
+fn check_batch_size(headers: usize, remaining_accounts: usize) -> Result<()> {
+    require!(
+        (1..=MAX_BATCH_PROOFS).contains(&headers),
+        ZkError::InvalidBatchSize
+    );
+    require!(
+        headers.checked_mul(2) == Some(remaining_accounts),
+        ZkError::BatchAccountMismatch
+    );
+    Ok(())
+}
+
+/// Load the range state an anchor checks contiguity against from `range_info`.
+///
+/// Under `Config::per_submitter_range` that is the `["zksl", "range", submitter]` PDA of the
+/// payer, created (and tagged with the submitter) on its first anchor; otherwise the global
+/// `["zksl", "range"]` PDA. Write it back with [`store_range_state`].
+///
+/// # Errors
+/// `RangeStateMismatch` when `range_info` is not the expected PDA or not owned by the program.
+#[cfg(not(feature = "skip-anchor-program"))]
+fn load_range_state<'info>(
+    config: &Config,
+    (payer, system_program): (&AccountInfo<'info>, &AccountInfo<'info>),
+    range_info: &AccountInfo<'info>,
+    program_id: &Pubkey,
+) -> Result<RangeState> {
+    let submitter = payer.key();
+    let seeds: &[&[u8]] = if config.per_submitter_range == 0 {
+        &[b"zksl", b"range"]
+    } else {
+        &[b"zksl", b"range", submitter.as_ref()]
+    };
+    let (address, _) = Pubkey::find_program_address(seeds, program_id);
+    require_keys_eq!(range_info.key(), address, ZkError::RangeStateMismatch);
+    let created = config.per_submitter_range != 0 && range_info.data_is_empty();
+    if created {
+        let range = (range_info, seeds, ZkError::RangeStateMismatch);
+        create_record_pda::<RangeState>(
+            (payer, system_program),
+            range,
+            RangeState::SIZE,
+            program_id,
+        )?;
+    }
+    require_keys_eq!(*range_info.owner, *program_id, ZkError::RangeStateMismatch);
+    let mut range_state = RangeState::try_deserialize(&mut &**range_info.try_borrow_data()?)?;
+    if created {
+        range_state.submitter = submitter;
     }
+    Ok(range_state)
+}
 
-    #[test]
-    fn test_ds_prefix_and_length() {
-        assert_eq!(DS_PREFIX.len(), 14, "DS prefix must be 14 bytes");
-        // DS length = 14 + 8 (chain_id) + 32 (program_id) + 32 (proof_hash) + 8 (start) + 8 (end) + 8 (seq)
-        let expected_len = 14 + 8 + 32 + 32 + 8 + 8 + 8;
-        assert_eq!(expected_len, 110, "DS length must be 110 bytes");
+/// Write `range_state` back to `range_info`, as `exit` does for an `Account`.
+#[cfg(not(feature = "skip-anchor-program"))]
+fn store_range_state(range_info: &AccountInfo<'_>, range_state: &RangeState) -> Result<()> {
+    let mut data = range_info.try_borrow_mut_data()?;
+    let mut dst: &mut [u8] = &mut data;
+    range_state.try_serialize(&mut dst)
+}
+
+/// Create the record PDA `account` at `seeds` with `space` bytes plus the discriminator,
+/// paid by `payer` and owned by `program_id`, and write `T`'s discriminator, as `init` does.
+///
+/// `account` must be the PDA of `seeds` and not yet exist; an address already holding data
+/// was anchored before and fails with `taken`. A pre-funded address is topped up, allocated
+/// and assigned instead of created.
+#[cfg(not(feature = "skip-anchor-program"))]
+fn create_record_pda<'info, T: Discriminator>(
+    (payer, system_program): (&AccountInfo<'info>, &AccountInfo<'info>),
+    (account, seeds, taken): (&AccountInfo<'info>, &[&[u8]], ZkError),
+    space: usize,
+    program_id: &Pubkey,
+) -> Result<()> {
+    use anchor_lang::system_program as sp;
+    let (address, bump_seed) = Pubkey::find_program_address(seeds, program_id);
+    require_keys_eq!(account.key(), address, ZkError::BatchAccountMismatch);
+    if !account.data_is_empty() {
+        return Err(taken.into());
+    }
+    let bump = [bump_seed];
+    let mut signer_seeds = seeds.to_vec();
+    signer_seeds.push(&bump);
+    let signer: &[&[&[u8]]] = &[&signer_seeds];
+    let len = space.saturating_add(8);
+    let space_u64 = u64::try_from(len).map_err(|_| error!(ZkError::MathOverflow))?;
+    let rent = Rent::get()?.minimum_balance(len);
+    let held = account.lamports();
+    if held == 0 {
+        let accounts = sp::CreateAccount {
+            from: payer.clone(),
+            to: account.clone(),
+        };
+        let cpi = CpiContext::new_with_signer(system_program.clone(), accounts, signer);
+        sp::create_account(cpi, rent, space_u64, program_id)?;
+    } else {
+        let shortfall = rent.saturating_sub(held);
+        if shortfall > 0 {
+            let accounts = sp::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            };
+            sp::transfer(CpiContext::new(system_program.clone(), accounts), shortfall)?;
+        }
+        let accounts = sp::Allocate {
+            account_to_allocate: account.clone(),
+        };
+        sp::allocate(
+            CpiContext::new_with_signer(system_program.clone(), accounts, signer),
+            space_u64,
+        )?;
+        let accounts = sp::Assign {
+            account_to_assign: account.clone(),
+        };
+        sp::assign(
+            CpiContext::new_with_signer(system_program.clone(), accounts, signer),
+            program_id,
+        )?;
+    }
+    let mut data = account.try_borrow_mut_data()?;
+    data.get_mut(..T::DISCRIMINATOR.len())
+        .ok_or(ZkError::MathOverflow)?
+        .copy_from_slice(T::DISCRIMINATOR);
+    Ok(())
+}
+
+/// Populate a freshly created `Config` from `InitializeArgs`; zero skew bounds store the
+/// defaults.
+const fn apply_initialize(
+    cfg: &mut Config,
+    zksl_mint: Pubkey,
+    admin: Pubkey,
+    args: &InitializeArgs,
+) {
+    cfg.zksl_mint = zksl_mint;
+    cfg.admin = admin;
+    cfg.aggregator_pubkey = args.aggregator_pubkey;
+    cfg.next_aggregator_pubkey = args.next_aggregator_pubkey;
+    cfg.activation_seq = args.activation_seq;
+    cfg.chain_id = args.chain_id;
+    cfg.min_lock_amount = args.min_lock_amount;
+    cfg.max_slots_per_artifact = args.max_slots_per_artifact;
+    cfg.paused = 0;
+    cfg.max_past_skew_secs = if args.max_past_skew_secs == 0 {
+        DEFAULT_MAX_PAST_SKEW_SECS
+    } else {
+        args.max_past_skew_secs
+    };
+    cfg.max_future_skew_secs = if args.max_future_skew_secs == 0 {
+        DEFAULT_MAX_FUTURE_SKEW_SECS
+    } else {
+        args.max_future_skew_secs
+    };
+}
+
+/// Check that `admin` may resize the config account `data` and report whether it is still
+/// at `Config::LEGACY_SIZE`.
+fn check_config_resize(data: &[u8], admin: &Pubkey) -> Result<bool> {
+    let view = ConfigView::from_account_data(data)?;
+    require_keys_eq!(view.admin, *admin, ZkError::Unauthorized);
+    Ok(data.len() < Config::DISCRIMINATOR.len().saturating_add(Config::SIZE))
+}
+
+/// Record `new_admin` as the pending admin of `cfg`, if `admin` is its current admin.
+fn propose_admin_transfer(cfg: &mut Config, admin: Pubkey, new_admin: Pubkey) -> Result<()> {
+    require_keys_eq!(admin, cfg.admin, ZkError::Unauthorized);
+    cfg.pending_admin = new_admin;
+    Ok(())
+}
+
+/// Move the pending admin of `cfg` into `admin` if `signer` is it, returning the previous
+/// admin.
+fn accept_admin_transfer(cfg: &mut Config, signer: Pubkey) -> Result<Pubkey> {
+    require_keys_neq!(
+        cfg.pending_admin,
+        Pubkey::default(),
+        ZkError::NoPendingAdmin
+    );
+    require_keys_eq!(signer, cfg.pending_admin, ZkError::Unauthorized);
+    let previous = cfg.admin;
+    cfg.admin = cfg.pending_admin;
+    cfg.pending_admin = Pubkey::default();
+    Ok(previous)
+}
+
+/// Apply the `Some` fields of `args` to `cfg`.
+fn apply_config_update(cfg: &mut Config, args: &UpdateConfigArgs) {
+    if let Some(pk) = args.aggregator_pubkey {
+        cfg.aggregator_pubkey = pk;
+    }
+    if let Some(pk) = args.next_aggregator_pubkey {
+        cfg.next_aggregator_pubkey = pk;
+    }
+    if let Some(seq) = args.activation_seq {
+        cfg.activation_seq = seq;
+    }
+    if let Some(p) = args.paused {
+        cfg.paused = u8::from(p);
+    }
+    if let Some(secs) = args.max_past_skew_secs {
+        cfg.max_past_skew_secs = secs;
+    }
+    if let Some(secs) = args.max_future_skew_secs {
+        cfg.max_future_skew_secs = secs;
+    }
+    if let Some(enabled) = args.allow_emergency_unlock {
+        cfg.allow_emergency_unlock = u8::from(enabled);
+    }
+    if let Some(enabled) = args.enforce_uniform_da {
+        cfg.enforce_uniform_da = u8::from(enabled);
+    }
+    if let Some(enabled) = args.bind_proof_hash {
+        cfg.bind_proof_hash = u8::from(enabled);
+    }
+    if let Some(enabled) = args.per_submitter_range {
+        cfg.per_submitter_range = u8::from(enabled);
+    }
+    if let Some(amount) = args.min_lock_amount {
+        cfg.min_lock_amount = amount;
+    }
+    if let Some(slots) = args.max_proof_age_slots {
+        cfg.max_proof_age_slots = slots;
+    }
+    if let Some(slots) = args.max_slots_per_artifact {
+        cfg.max_slots_per_artifact = slots;
+    }
+    if let Some(grace) = args.rotation_grace {
+        cfg.rotation_grace = grace;
+    }
+    if let Some(secs) = args.min_lock_duration_secs {
+        cfg.min_lock_duration_secs = secs;
+    }
+    if let Some(allow) = args.allow_token_2022 {
+        cfg.allow_token_2022 = u8::from(allow);
+    }
+}
+
+/// Build `ConfigUpdated` with exactly the fields that differ between the two snapshots.
+fn config_updated_event(before: &ConfigView, after: &ConfigView, timestamp: i64) -> ConfigUpdated {
+    let mut event = ConfigUpdated {
+        aggregator_pubkey: None,
+        next_aggregator_pubkey: None,
+        activation_seq: None,
+        paused: None,
+        max_past_skew_secs: None,
+        max_future_skew_secs: None,
+        allow_emergency_unlock: None,
+        enforce_uniform_da: None,
+        bind_proof_hash: None,
+        per_submitter_range: None,
+        min_lock_amount: None,
+        max_proof_age_slots: None,
+        max_slots_per_artifact: None,
+        rotation_grace: None,
+        min_lock_duration_secs: None,
+        allow_token_2022: None,
+        timestamp,
+    };
+    for change in helpers::diff_config(before, after) {
+        match change {
+            ConfigChange::AggregatorPubkey { to, .. } => event.aggregator_pubkey = Some(to),
+            ConfigChange::NextAggregatorPubkey { to, .. } => {
+                event.next_aggregator_pubkey = Some(to);
+            }
+            ConfigChange::ActivationSeq { to, .. } => event.activation_seq = Some(to),
+            ConfigChange::Paused { to, .. } => event.paused = Some(to != 0),
+            ConfigChange::MaxPastSkewSecs { to, .. } => event.max_past_skew_secs = Some(to),
+            ConfigChange::MaxFutureSkewSecs { to, .. } => event.max_future_skew_secs = Some(to),
+            ConfigChange::AllowEmergencyUnlock { to, .. } => {
+                event.allow_emergency_unlock = Some(to != 0);
+            }
+            ConfigChange::EnforceUniformDa { to, .. } => event.enforce_uniform_da = Some(to != 0),
+            ConfigChange::BindProofHash { to, .. } => event.bind_proof_hash = Some(to != 0),
+            ConfigChange::PerSubmitterRange { to, .. } => event.per_submitter_range = Some(to != 0),
+            ConfigChange::MinLockAmount { to, .. } => event.min_lock_amount = Some(to),
+            ConfigChange::MaxProofAgeSlots { to, .. } => event.max_proof_age_slots = Some(to),
+            ConfigChange::MaxSlotsPerArtifact { to, .. } => event.max_slots_per_artifact = Some(to),
+            ConfigChange::RotationGrace { to, .. } => event.rotation_grace = Some(to),
+            ConfigChange::MinLockDurationSecs { to, .. } => event.min_lock_duration_secs = Some(to),
+            ConfigChange::AllowToken2022 { to, .. } => event.allow_token_2022 = Some(to != 0),
+            // Not settable through update_config
+            ConfigChange::ZkslMint { .. }
+            | ConfigChange::Admin { .. }
+            | ConfigChange::PendingAdmin { .. }
+            | ConfigChange::ChainId { .. }
+            | ConfigChange::Bump { .. }
+            | ConfigChange::TotalLocked { .. }
+            | ConfigChange::SeedsVersion { .. } => {}
+        }
+    }
+    event
+}
+
+/// Length in bytes of a DS of `version`, excluding the optional `epoch_nonce` (0 if unknown).
+///
+/// v1: `prefix(14) || chain_id(8) || program_id(32) || proof_hash(32) || start(8) || end(8) ||
+/// seq(8)`; v2 uses its own prefix and adds `artifact_len(4)`.
+const fn ds_len(version: u8) -> usize {
+    const V1: usize = 14 + 8 + 32 + 32 + 8 + 8 + 8;
+    const V2: usize = V1 + 4;
+    match version {
+        1 => V1,
+        2 => V2,
+        _ => 0,
+    }
+}
+
+/// Build the DS message the aggregator signs for `anchor_proof`.
+///
+/// Layout: [`helpers::compute_ds_message`] (110 bytes), followed by `epoch_nonce` (8 bytes,
+/// little-endian) when it is non-zero.
+///
+/// Threat model: `seq` and the range cursors only rule out replays while they keep growing.
+/// Should the anchoring state ever be reset after use, `seq == 1` and old slot ranges become
+/// acceptable again and every previously published signature would verify a second time.
+/// Binding `epoch_nonce`, which the reset advances, makes those signatures sign a different
+/// message. The nonce is omitted while zero so that a never-reset deployment keeps the
+/// original 110-byte DS and existing signers stay valid.
+fn build_ds(
+    chain_id: u64,
+    program_id: &Pubkey,
+    proof_hash: &[u8; 32],
+    start_slot: u64,
+    end_slot: u64,
+    seq: u64,
+    epoch_nonce: u64,
+) -> Vec<u8> {
+    let ds =
+        helpers::compute_ds_message(chain_id, program_id, proof_hash, start_slot, end_slot, seq);
+    with_epoch_nonce(ds, epoch_nonce)
+}
+
+/// Build the DS of layout `ds_version` for `header`: v1 as [`build_ds`], v2 from
+/// [`helpers::compute_ds_message_v2`] with the same `epoch_nonce` suffix.
+fn build_header_ds(
+    ds_version: u8,
+    chain_id: u64,
+    program_id: &Pubkey,
+    header: &ProofHeader,
+    epoch_nonce: u64,
+) -> Result<Vec<u8>> {
+    let ProofHeader {
+        proof_hash,
+        seq,
+        start_slot,
+        end_slot,
+        artifact_len,
+        ..
+    } = *header;
+    match ds_version {
+        DS_VERSION_V1 => Ok(build_ds(
+            chain_id,
+            program_id,
+            &proof_hash,
+            start_slot,
+            end_slot,
+            seq,
+            epoch_nonce,
+        )),
+        DS_VERSION_V2 => {
+            let ds = helpers::compute_ds_message_v2(
+                chain_id,
+                program_id,
+                &proof_hash,
+                start_slot,
+                end_slot,
+                seq,
+                artifact_len,
+            );
+            Ok(with_epoch_nonce(ds, epoch_nonce))
+        }
+        _ => err!(ZkError::UnsupportedDsVersion),
+    }
+}
+
+/// Append `epoch_nonce` (8 bytes, little-endian) to `ds` when it is non-zero.
+fn with_epoch_nonce(mut ds: Vec<u8>, epoch_nonce: u64) -> Vec<u8> {
+    if epoch_nonce != 0 {
+        ds.extend_from_slice(&epoch_nonce.to_le_bytes());
+    }
+    ds
+}
+
+/// Zero the anchoring cursors so the next `anchor_proof` must be `seq == 1` with any start slot.
+///
+/// If anything was anchored before, `epoch_nonce` is advanced so signatures over the old DS
+/// cannot be replayed against the reopened `seq` and slot space (see [`build_ds`]). The stored
+/// aggregator key is cleared too, so the first anchor afterwards is not reported as a rotation.
+const fn reset_anchor_state(aggregator_state: &mut AggregatorState, range_state: &mut RangeState) {
+    if aggregator_state.last_seq != 0 || range_state.last_end_slot != 0 {
+        aggregator_state.epoch_nonce = aggregator_state.epoch_nonce.saturating_add(1);
+    }
+    aggregator_state.aggregator_pubkey = Pubkey::new_from_array([0u8; 32]);
+    aggregator_state.last_seq = 0;
+    aggregator_state.last_timestamp = 0;
+    aggregator_state.da_scheme_set = 0;
+    range_state.last_end_slot = 0;
+    range_state.last_seq = 0;
+}
+
+/// Check `admin` may reset the anchor state: it is the config admin, the program is paused and
+/// `confirm_last_seq` matches the current `last_seq`.
+fn check_state_reset(
+    config: &Config,
+    admin: Pubkey,
+    aggregator_state: &AggregatorState,
+    confirm_last_seq: u64,
+) -> Result<()> {
+    require_keys_eq!(admin, config.admin, ZkError::Unauthorized);
+    require!(config.paused != 0, ZkError::NotPaused);
+    require!(
+        confirm_last_seq == aggregator_state.last_seq,
+        ZkError::ResetNotConfirmed
+    );
+    Ok(())
+}
+
+/// Check `seq` follows `last_seq` and `[start_slot, end_slot]` is bounded and follows `last_end_slot`.
+///
+/// Together with the `proof_record` seeds (`proof_hash || seq`) this keeps a `proof_hash` from
+/// being anchored twice: replaying a `seq` fails monotonicity, and every later `seq` must start
+/// right after the previous range, so the same content can only recur as a claim over new
+/// slots (which the aggregator must sign afresh, since DS binds the range and `seq`). The
+/// contiguity check applies from the first anchor on, keyed on `last_seq` rather than
+/// `last_end_slot`, so a range ending at slot 0 cannot reopen it (see [`range_has_history`]).
+/// Under `Config::per_submitter_range` contiguity holds within each submitter's range only,
+/// so different submitters may claim the same slots under different `seq`s.
+fn check_seq_and_range(
+    aggregator_state: &AggregatorState,
+    range_state: &RangeState,
+    seq: u64,
+    start_slot: u64,
+    end_slot: u64,
+    max_slots: u64,
+) -> Result<()> {
+    let expected_seq = aggregator_state
+        .last_seq
+        .checked_add(1)
+        .ok_or(ZkError::MathOverflow)?;
+    require!(seq == expected_seq, ZkError::NonMonotonicSeq);
+
+    let span = end_slot
+        .checked_sub(start_slot)
+        .and_then(|d| d.checked_add(1))
+        .ok_or(ZkError::MathOverflow)?;
+    require!(span <= max_slots, ZkError::MathOverflow);
+    if range_has_history(aggregator_state, range_state) {
+        let expected_start = range_state
+            .last_end_slot
+            .checked_add(1)
+            .ok_or(ZkError::MathOverflow)?;
+        require!(start_slot == expected_start, ZkError::RangeOverlap);
+    }
+    Ok(())
+}
+
+/// Whether `range_state` holds an end slot the next range must continue from.
+///
+/// The global range has one once anything was anchored. A per-submitter range has one once
+/// its submitter anchored since the last reset; a reset advances `epoch_nonce`, so ranges
+/// recorded before it no longer count.
+fn range_has_history(aggregator_state: &AggregatorState, range_state: &RangeState) -> bool {
+    if range_state.submitter == Pubkey::default() {
+        aggregator_state.last_seq != 0
+    } else {
+        range_state.last_seq != 0 && range_state.epoch_nonce == aggregator_state.epoch_nonce
+    }
+}
+
+/// Whether the proof about to be anchored is the chain's genesis.
+///
+/// Only a proof with no predecessor is genesis, i.e. one anchored while `last_seq == 0`.
+/// [`check_seq_and_range`] admits only `seq == 1` there and [`record_anchor`] then makes
+/// `last_seq` non-zero, so every later proof has a predecessor and exactly one genesis exists
+/// per `epoch_nonce`.
+const fn is_genesis(aggregator_state: &AggregatorState) -> bool {
+    aggregator_state.last_seq == 0
+}
+
+/// Epochs of `start_slot` and `end_slot` as `slot / slots_per_epoch`, which is exact on
+/// clusters without epoch warmup (mainnet-beta); used only to tag records, never to reject.
+fn epoch_tags(start_slot: u64, end_slot: u64, slots_per_epoch: u64) -> Result<(u64, u64)> {
+    let epoch = |slot: u64| {
+        slot.checked_div(slots_per_epoch)
+            .ok_or(ZkError::MathOverflow)
+    };
+    Ok((epoch(start_slot)?, epoch(end_slot)?))
+}
+
+/// Advance the anchor state past an accepted proof.
+const fn record_anchor(
+    aggregator_state: &mut AggregatorState,
+    range_state: &mut RangeState,
+    seq: u64,
+    end_slot: u64,
+    timestamp: i64,
+) {
+    aggregator_state.last_seq = seq;
+    aggregator_state.last_timestamp = timestamp;
+    range_state.last_end_slot = end_slot;
+    range_state.last_seq = seq;
+    range_state.epoch_nonce = aggregator_state.epoch_nonce;
+}
+
+/// Store `key` as the aggregator key of the last anchored sequence.
+///
+/// Returns the key it replaces when that is a rotation: the stored key was set and differs.
+/// Filling the key on the first anchor after deployment is not a rotation.
+fn record_aggregator_key(aggregator_state: &mut AggregatorState, key: Pubkey) -> Option<Pubkey> {
+    let previous = core::mem::replace(&mut aggregator_state.aggregator_pubkey, key);
+    (previous != Pubkey::default() && previous != key).then_some(previous)
+}
+
+/// Build the `Heartbeat` snapshot; `aggregator_pubkey` is the key allowed for the next `seq`.
+const fn heartbeat_event(
+    config: &Config,
+    aggregator_state: &AggregatorState,
+    range_state: &RangeState,
+    timestamp: i64,
+) -> Heartbeat {
+    let last_seq = aggregator_state.last_seq;
+    Heartbeat {
+        aggregator_pubkey: allowed_aggregator_key(config, last_seq.saturating_add(1)),
+        paused: config.paused != 0,
+        last_seq,
+        last_end_slot: range_state.last_end_slot,
+        timestamp,
+    }
+}
+
+/// Check `timestamp` against `now`: at most `max_past` seconds behind, `max_future` ahead.
+fn check_clock_skew(now: i64, timestamp: i64, max_past: u32, max_future: u32) -> Result<()> {
+    if timestamp <= now {
+        require!(
+            now.saturating_sub(timestamp) <= i64::from(max_past),
+            ZkError::ClockSkew
+        );
+    } else {
+        require!(
+            timestamp.saturating_sub(now) <= i64::from(max_future),
+            ZkError::ClockSkew
+        );
+    }
+    Ok(())
+}
+
+/// Require `timestamp` to be no earlier than the last anchored proof's, so timestamps never
+/// go backwards as `seq` advances. Equal timestamps are allowed.
+fn check_timestamp_order(aggregator_state: &AggregatorState, timestamp: i64) -> Result<()> {
+    require!(
+        timestamp >= aggregator_state.last_timestamp,
+        ZkError::NonMonotonicTimestamp
+    );
+    Ok(())
+}
+
+/// Under a non-zero `max_proof_age_slots`, reject a proof whose `end_slot` trails
+/// `current_slot` by more than that many slots. A range ending at or after `current_slot`
+/// has age 0.
+fn check_proof_age(config: &Config, end_slot: u64, current_slot: u64) -> Result<()> {
+    require!(
+        config.max_proof_age_slots == 0
+            || current_slot.saturating_sub(end_slot) <= config.max_proof_age_slots,
+        ZkError::ProofTooStale
+    );
+    Ok(())
+}
+
+/// Clock, program and signer facts shared by every proof of one anchoring instruction.
+struct AnchorEnv<'a> {
+    /// Program configuration.
+    config: &'a Config,
+    /// This program's ID, bound into the DS.
+    program_id: &'a Pubkey,
+    /// Transaction signer recorded as `submitted_by`.
+    submitted_by: Pubkey,
+    /// On-chain unix timestamp.
+    now: i64,
+    /// On-chain slot.
+    current_slot: u64,
+    /// Slots per epoch for the epoch tags.
+    slots_per_epoch: u64,
+    /// DS layout the proofs were signed over (`DS_VERSION_V1` or `DS_VERSION_V2`).
+    ds_version: u8,
+}
+
+/// Check one proof `header` and its Ed25519 `signed` fields against the anchoring state,
+/// then fill `pr`, claim `artifact`, advance the state and emit `ProofAnchored` (and
+/// `GenesisAnchored` for the first proof).
+///
+/// Shared by `anchor_proof` and `anchor_proofs_batch`; the latter calls it once per header
+/// against the state the previous header left, so seq and range continuity carry across
+/// the batch.
+#[allow(clippy::too_many_lines)]
+fn anchor_header(
+    env: &AnchorEnv<'_>,
+    aggregator_state: &mut AggregatorState,
+    range_state: &mut RangeState,
+    header: &ProofHeader,
+    signed: &helpers::Ed25519Fields<'_>,
+    pr: &mut ProofRecord,
+    artifact: &mut ArtifactRecord,
+) -> Result<()> {
+    let ProofHeader {
+        artifact_id,
+        proof_hash,
+        seq,
+        start_slot,
+        end_slot,
+        artifact_len,
+        state_root_before,
+        state_root_after,
+        aggregator_pubkey,
+        timestamp,
+        ds_hash,
+        commitment_level,
+        da_params,
+    } = *header;
+    let config = env.config;
+
+    // seq monotonic (global, across key rotation); range monotonic and bounds
+    let max_slots = config.slots_per_artifact_bound();
+    check_seq_and_range(
+        aggregator_state,
+        range_state,
+        seq,
+        start_slot,
+        end_slot,
+        max_slots,
+    )?;
+    check_proof_age(config, end_slot, env.current_slot)?;
+    let roots = (&state_root_before, &state_root_after);
+    check_proof_hash_binding(config, &proof_hash, roots, start_slot, end_slot)?;
+
+    // clock skew, bounded separately for lagging and leading timestamps
+    let (max_past, max_future) = config.clock_skew_bounds();
+    check_clock_skew(env.now, timestamp, max_past, max_future)?;
+    check_timestamp_order(aggregator_state, timestamp)?;
+
+    // Recompute DS and verify ds_hash and Ed25519 message/public key
+    let ds = build_header_ds(
+        env.ds_version,
+        config.chain_id,
+        env.program_id,
+        header,
+        aggregator_state.epoch_nonce,
+    )?;
+    let mut hasher = Blake3Hasher::new();
+    hasher.update(&ds);
+    let expected_ds_hash = *hasher.finalize().as_bytes();
+    require!(expected_ds_hash == ds_hash, ZkError::BadDomainSeparation);
+    require!(
+        signed.pubkey == aggregator_pubkey.as_ref(),
+        ZkError::InvalidSignature
+    );
+    require!(
+        signed.message == ds.as_slice(),
+        ZkError::BadDomainSeparation
+    );
+
+    // Claim the artifact, then populate ProofRecord
+    claim_artifact(artifact, seq)?;
+    require!(pr.seq == 0, ZkError::ProofAlreadyAnchored);
+    pr.artifact_id = artifact_id;
+    pr.start_slot = start_slot;
+    pr.end_slot = end_slot;
+    pr.proof_hash = proof_hash;
+    // Artifact length bounds guard (defense in depth; also enforced off-chain)
+    require!(
+        artifact_len <= MAX_ARTIFACT_SIZE_BYTES,
+        ZkError::MathOverflow
+    );
+    pr.artifact_len = artifact_len;
+    pr.state_root_before = state_root_before;
+    pr.state_root_after = state_root_after;
+    pr.submitted_by = env.submitted_by;
+    pr.aggregator_pubkey = aggregator_pubkey;
+    pr.timestamp = timestamp;
+    pr.seq = seq;
+    pr.ds_hash = ds_hash;
+    pr.commitment_level = check_commitment_level(commitment_level)?;
+    check_da_scheme(config, aggregator_state, &da_params)?;
+    pr.da_params = da_params;
+    let genesis = is_genesis(aggregator_state);
+    pr.is_genesis = u8::from(genesis);
+    pr.reserved = [0u8; 4];
+    (pr.start_epoch, pr.end_epoch) = epoch_tags(start_slot, end_slot, env.slots_per_epoch)?;
+
+    // Update state
+    record_anchor(aggregator_state, range_state, seq, end_slot, timestamp);
+    record_da_scheme(aggregator_state, &da_params);
+    let active_key = allowed_aggregator_key(config, seq);
+    if let Some(previous_key) = record_aggregator_key(aggregator_state, active_key) {
+        emit!(AggregatorRotated {
+            previous_pubkey: previous_key,
+            aggregator_pubkey: active_key,
+            seq,
+            timestamp
+        });
+    }
+
+    emit!(ProofAnchored {
+        artifact_id,
+        proof_hash,
+        start_slot,
+        end_slot,
+        submitted_by: env.submitted_by,
+        timestamp,
+        seq,
+        ds_hash,
+        start_epoch: pr.start_epoch,
+        end_epoch: pr.end_epoch
+    });
+    if genesis {
+        emit!(GenesisAnchored {
+            proof_hash,
+            seq,
+            start_slot,
+            end_slot,
+            epoch_nonce: aggregator_state.epoch_nonce,
+            timestamp
+        });
+    }
+    Ok(())
+}
+
+/// Claim `artifact` for the proof anchored at `seq`, rejecting an artifact already claimed.
+///
+/// `init` on the artifact PDA already refuses a second claim at account creation; this keeps
+/// the refusal explicit should the record ever be created ahead of the handler.
+fn claim_artifact(artifact: &mut ArtifactRecord, seq: u64) -> Result<()> {
+    require!(artifact.seq == 0, ZkError::ArtifactAlreadyAnchored);
+    artifact.seq = seq;
+    artifact.reserved = [0u8; 24];
+    Ok(())
+}
+
+/// Outcome of `register_validator` given the existing record and escrow balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Registration {
+    /// No record yet: lock the token and initialize the record.
+    New,
+    /// The same validator's earlier registration already landed; nothing to do.
+    AlreadyLocked,
+}
+
+/// Classify a `register_validator` call.
+///
+/// An active record whose escrow holds exactly `lock_amount` is the result of an earlier
+/// successful call, so a retry is a no-op; any other existing record is a conflict.
+fn check_registration(
+    record: &ValidatorRecord,
+    escrow_amount: u64,
+    lock_amount: u64,
+) -> Result<Registration> {
+    if record.validator_pubkey == Pubkey::default() {
+        return Ok(Registration::New);
+    }
+    require!(
+        record.status == 0 && escrow_amount == lock_amount,
+        ZkError::AlreadyRegistered
+    );
+    Ok(Registration::AlreadyLocked)
+}
+
+/// Check that `validator` may unlock `record` under `config`.
+///
+/// The emergency flag lifts the pause but never the ownership or status checks; the
+/// escrow amount is checked separately by the handler.
+fn check_unlock_allowed(
+    config: &Config,
+    record: &ValidatorRecord,
+    validator: Pubkey,
+) -> Result<()> {
+    require!(
+        config.paused == 0 || config.allow_emergency_unlock != 0,
+        ZkError::Paused
+    );
+    require_keys_eq!(record.validator_pubkey, validator, ZkError::Unauthorized);
+    require!(record.status == 0, ZkError::StatusNotActive);
+    Ok(())
+}
+
+/// Check `record`'s lock is at least `Config::min_lock_duration_secs` old at `now`.
+///
+/// The cooldown also holds under an emergency unlock.
+fn check_lock_matured(config: &Config, record: &ValidatorRecord, now: i64) -> Result<()> {
+    let age = now.saturating_sub(record.lock_timestamp);
+    require!(
+        age >= config.min_lock_duration_secs,
+        ZkError::LockNotMatured
+    );
+    Ok(())
+}
+
+/// Count `amount` newly escrowed by `register_validator` in `config.total_locked`.
+fn add_locked(config: &mut Config, amount: u64) -> Result<()> {
+    config.total_locked = config
+        .total_locked
+        .checked_add(amount)
+        .ok_or(ZkError::MathOverflow)?;
+    Ok(())
+}
+
+/// Remove `amount` released by `unlock_validator` from `config.total_locked`.
+///
+/// Escrows funded before the counter existed were never added to it, so the release
+/// floors at zero instead of failing; an error here would leave those tokens stuck.
+const fn release_locked(config: &mut Config, amount: u64) {
+    config.total_locked = config.total_locked.saturating_sub(amount);
+}
+
+/// Reject `config` unless its escrow/record seed layout is `expected`.
+///
+/// Each register/unlock instruction derives its PDAs with one fixed layout, so pairing it
+/// with a config of the other layout would lock or release through the wrong escrow.
+fn check_seeds_version(config: &Config, expected: u8) -> Result<()> {
+    require!(
+        config.seeds_version == expected,
+        ZkError::SeedsVersionMismatch
+    );
+    Ok(())
+}
+
+/// Token accounts shared by the legacy and pooled register/unlock instructions.
+#[cfg(not(feature = "skip-anchor-program"))]
+struct EscrowAccounts<'a, 'info> {
+    /// Validator locking or unlocking.
+    validator: &'a Signer<'info>,
+    /// zKSL mint.
+    zksl_mint: &'a InterfaceAccount<'info, Mint>,
+    /// Escrow authority PDA, already checked against the instruction's seeds.
+    escrow_authority: &'a UncheckedAccount<'info>,
+    /// Escrow ATA owned by `escrow_authority`.
+    validator_escrow: &'a InterfaceAccount<'info, TokenAccount>,
+    /// Validator's own zKSL token account (checked by [`check_validator_ata`]).
+    validator_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    /// SPL Token or, when `Config::allow_token_2022` is set, Token-2022 program.
+    token_program: &'a Interface<'info, TokenInterface>,
+    /// Extra accounts for a Token-2022 transfer hook (the instruction's remaining accounts).
+    hook_accounts: &'a [AccountInfo<'info>],
+}
+
+/// Lock `Config::lock_amount` into the escrow and record the validator and the amount.
+#[cfg(not(feature = "skip-anchor-program"))]
+fn lock_escrow(
+    accounts: &EscrowAccounts<'_, '_>,
+    config: &mut Config,
+    record: &mut ValidatorRecord,
+) -> Result<()> {
+    require!(config.paused == 0, ZkError::Paused);
+    let mint = accounts.zksl_mint.key();
+    require_keys_eq!(mint, config.zksl_mint, ZkError::InvalidMint);
+    let ata = accounts.validator_ata;
+    check_validator_ata(config, accounts.validator.key(), ata.owner, ata.mint)?;
+    let mint_info = accounts.zksl_mint.to_account_info();
+    check_token_program(config, accounts.token_program.key(), *mint_info.owner)?;
+    check_mint_extensions(mint_info.owner, &mint_info.try_borrow_data()?)?;
+    let decimals = accounts.zksl_mint.decimals;
+    let amount = config.lock_amount(decimals)?;
+    // A relayer retry of a registration that already landed succeeds without a second lock
+    let registration = check_registration(
+        record,
+        accounts.validator_escrow.amount,
+        record.lock_amount(decimals)?,
+    )?;
+    if registration == Registration::AlreadyLocked {
+        return Ok(());
+    }
+    // Transfer
+    spl_token_2022::onchain::invoke_transfer_checked(
+        &accounts.token_program.key(),
+        accounts.validator_ata.to_account_info(),
+        mint_info,
+        accounts.validator_escrow.to_account_info(),
+        accounts.validator.to_account_info(),
+        accounts.hook_accounts,
+        amount,
+        decimals,
+        &[],
+    )?;
+    add_locked(config, amount)?;
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    record.validator_pubkey = accounts.validator.key();
+    record.lock_token_account = accounts.validator_escrow.key();
+    record.lock_timestamp = timestamp;
+    record.status = 0;
+    record.num_accepts = 0;
+    record.locked_amount = amount;
+    emit!(ValidatorRegistered {
+        validator: accounts.validator.key(),
+        amount,
+        total_locked: config.total_locked,
+        timestamp
+    });
+    Ok(())
+}
+
+/// Return the recorded lock to the validator, signing as the escrow PDA with `signer_seeds`.
+#[cfg(not(feature = "skip-anchor-program"))]
+fn release_escrow(
+    accounts: &EscrowAccounts<'_, '_>,
+    config: &mut Config,
+    record: &mut ValidatorRecord,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let validator_key = accounts.validator.key();
+    check_unlock_allowed(config, record, validator_key)?;
+    let timestamp = Clock::get()?.unix_timestamp;
+    check_lock_matured(config, record, timestamp)?;
+    let ata = accounts.validator_ata;
+    check_validator_ata(config, validator_key, ata.owner, ata.mint)?;
+    let mint_info = accounts.zksl_mint.to_account_info();
+    check_token_program(config, accounts.token_program.key(), *mint_info.owner)?;
+    // Ensure escrow holds exactly what this validator locked, whatever the config says now
+    let decimals = accounts.zksl_mint.decimals;
+    let amount = record.lock_amount(decimals)?;
+    require!(
+        accounts.validator_escrow.amount == amount,
+        ZkError::InvalidLockAmount
+    );
+    // Transfer back to validator ATA using escrow PDA as signer
+    spl_token_2022::onchain::invoke_transfer_checked(
+        &accounts.token_program.key(),
+        accounts.validator_escrow.to_account_info(),
+        mint_info,
+        accounts.validator_ata.to_account_info(),
+        accounts.escrow_authority.to_account_info(),
+        accounts.hook_accounts,
+        amount,
+        decimals,
+        &[signer_seeds],
+    )?;
+    release_locked(config, amount);
+    record.status = 1;
+    emit!(ValidatorUnlocked {
+        validator: validator_key,
+        amount,
+        total_locked: config.total_locked,
+        timestamp
+    });
+    Ok(())
+}
+
+/// Check the validator's token account holds the config's zKSL mint and is owned by
+/// `validator`, so a lock is funded from (and an unlock paid back to) the validator's own
+/// tokens.
+fn check_validator_ata(
+    config: &Config,
+    validator: Pubkey,
+    ata_owner: Pubkey,
+    ata_mint: Pubkey,
+) -> Result<()> {
+    require_keys_eq!(ata_mint, config.zksl_mint, ZkError::InvalidMint);
+    require_keys_eq!(ata_owner, validator, ZkError::InvalidOwner);
+    Ok(())
+}
+
+/// Check `token_program` may move zKSL: the legacy SPL Token program always, Token-2022 only
+/// under `Config::allow_token_2022`, and in either case the program owning the mint.
+fn check_token_program(config: &Config, token_program: Pubkey, mint_owner: Pubkey) -> Result<()> {
+    let allowed = token_program == anchor_spl::token::ID
+        || (token_program == spl_token_2022::ID && config.allow_token_2022 != 0);
+    require!(allowed, ZkError::InvalidMint);
+    require_keys_eq!(mint_owner, token_program, ZkError::InvalidMint);
+    Ok(())
+}
+
+/// Reject Token-2022 mints whose extensions break the escrow: a non-zero transfer fee (the
+/// escrow could never hold exactly the locked amount), a permanent delegate (it could move
+/// escrowed tokens) or non-transferable tokens (they could never be unlocked).
+///
+/// Legacy SPL Token mints carry no extensions and always pass. Transfer hooks are allowed;
+/// their extra accounts come from the instruction's remaining accounts.
+fn check_mint_extensions(mint_owner: &Pubkey, mint_data: &[u8]) -> Result<()> {
+    use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+    use spl_token_2022::extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+
+    if *mint_owner != spl_token_2022::ID {
+        return Ok(());
+    }
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(mint_data)?;
+    let blocked = mint.get_extension_types()?.into_iter().any(|extension| {
+        matches!(
+            extension,
+            ExtensionType::PermanentDelegate | ExtensionType::NonTransferable
+        )
+    });
+    require!(!blocked, ZkError::UnsupportedMintExtension);
+    if let Ok(fees) = mint.get_extension::<TransferFeeConfig>() {
+        let charged = [fees.older_transfer_fee, fees.newer_transfer_fee]
+            .iter()
+            .any(|fee| u16::from(fee.transfer_fee_basis_points) != 0);
+        require!(!charged, ZkError::UnsupportedMintExtension);
+    }
+    Ok(())
+}
+
+/// Validate the commitment level the proof's witness was fetched at (0=processed,
+/// 1=confirmed, 2=finalized).
+fn check_commitment_level(commitment_level: u8) -> Result<u8> {
+    require!(
+        commitment_level <= MAX_COMMITMENT_LEVEL,
+        ZkError::InvalidCommitmentLevel
+    );
+    Ok(commitment_level)
+}
+
+/// Raise `record.commitment_level` to `level` for the config admin or the record's
+/// aggregator; returns the previous level.
+fn advance_commitment_level(
+    config: &Config,
+    authority: Pubkey,
+    record: &mut ProofRecord,
+    level: u8,
+) -> Result<u8> {
+    require!(
+        authority == config.admin || authority == record.aggregator_pubkey,
+        ZkError::Unauthorized
+    );
+    require!(
+        check_commitment_level(level)? > record.commitment_level,
+        ZkError::CommitmentRegression
+    );
+    Ok(core::mem::replace(&mut record.commitment_level, level))
+}
+
+/// Check a committee update: `1 <= threshold <= members.len() <= MAX_MEMBERS`, with distinct,
+/// non-default members.
+fn check_committee_update(threshold: u8, members: &[Pubkey]) -> Result<()> {
+    require!(
+        threshold >= 1 && usize::from(threshold) <= members.len(),
+        ZkError::InvalidCommittee
+    );
+    require!(
+        members.len() <= AggregatorCommittee::MAX_MEMBERS,
+        ZkError::InvalidCommittee
+    );
+    let mut seen = Vec::with_capacity(members.len());
+    for member in members {
+        require!(
+            *member != Pubkey::default() && !seen.contains(member),
+            ZkError::InvalidCommittee
+        );
+        seen.push(*member);
+    }
+    Ok(())
+}
+
+/// Borsh-encode the [`ProofRecordSummary`] of `record` for `get_proof_record`.
+fn proof_record_return_data(record: &ProofRecord) -> Result<Vec<u8>> {
+    let summary = ProofRecordSummary {
+        seq: record.seq,
+        start_slot: record.start_slot,
+        end_slot: record.end_slot,
+        proof_hash: record.proof_hash,
+        commitment_level: record.commitment_level,
+    };
+    Ok(borsh::to_vec(&summary)?)
+}
+
+/// Decode a proof record from account `data` of `8 + ProofRecord::PRIOR_SIZES` bytes with the
+/// fields it lacks zeroed and its epochs tagged, or `None` for a record already at
+/// `ProofRecord::SIZE`.
+fn grow_proof_record(data: &[u8], slots_per_epoch: u64) -> Result<Option<ProofRecord>> {
+    require!(
+        data.starts_with(ProofRecord::DISCRIMINATOR),
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+    let discriminator_len = ProofRecord::DISCRIMINATOR.len();
+    match data.len().saturating_sub(discriminator_len) {
+        ProofRecord::SIZE => return Ok(None),
+        len if ProofRecord::PRIOR_SIZES.contains(&len) => {}
+        _ => return Err(anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into()),
+    }
+    let mut padded = data.to_vec();
+    padded.resize(discriminator_len.saturating_add(ProofRecord::SIZE), 0);
+    let mut record = ProofRecord::try_deserialize(&mut padded.as_slice())?;
+    (record.start_epoch, record.end_epoch) =
+        epoch_tags(record.start_slot, record.end_slot, slots_per_epoch)?;
+    Ok(Some(record))
+}
+
+/// Check `admin` may close `record`: it is the config admin and the record is finalized.
+fn check_proof_record_close(config: &Config, admin: Pubkey, record: &ProofRecord) -> Result<()> {
+    require_keys_eq!(admin, config.admin, ZkError::Unauthorized);
+    require!(
+        record.commitment_level == MAX_COMMITMENT_LEVEL,
+        ZkError::ProofNotFinalized
+    );
+    Ok(())
+}
+
+/// Resolve the allowed aggregator key given the current sequence and activation threshold.
+const fn allowed_aggregator_key(config: &Config, seq: u64) -> Pubkey {
+    helpers::select_aggregator_key(
+        config.aggregator_pubkey,
+        config.next_aggregator_pubkey,
+        config.activation_seq,
+        seq,
+    )
+}
+
+// moved to anchor_items
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_sizes_match_spec() {
+        assert_eq!(Config::SIZE, 304, "Config size must be 304 bytes");
+        assert_eq!(
+            Config::PRIOR_SIZES,
+            [168],
+            "pre-resize Config size must be 168 bytes"
+        );
+        assert_eq!(
+            ValidatorRecord::SIZE,
+            136,
+            "ValidatorRecord size must be 136 bytes"
+        );
+        assert_eq!(ProofRecord::SIZE, 278, "ProofRecord size must be 278 bytes");
+        assert_eq!(
+            ProofRecord::PRIOR_SIZES,
+            [262],
+            "pre-epoch ProofRecord size must be 262 bytes"
+        );
+        assert_eq!(
+            ArtifactRecord::SIZE,
+            32,
+            "ArtifactRecord size must be 32 bytes"
+        );
+        assert_eq!(
+            CheckpointState::SIZE,
+            120,
+            "CheckpointState size must be 120 bytes"
+        );
+    }
+
+    #[test]
+    fn test_ds_prefix_and_length() {
+        assert_eq!(DS_PREFIX.len(), 14, "DS prefix must be 14 bytes");
+        assert_eq!(ds_len(1), 110, "v1 DS length must be 110 bytes");
+        assert_eq!(ds_len(2), 114, "v2 DS length must be 114 bytes");
+        assert_eq!(ds_len(0), 0);
+        let ds = build_ds(103, &Pubkey::new_unique(), &[1u8; 32], 1, 64, 1, 0);
+        assert_eq!(ds.len(), ds_len(DS_VERSION_V1));
+    }
+
+    #[test]
+    fn test_ds_signature_does_not_survive_state_reset() {
+        let program_id = Pubkey::new_unique();
+        let proof_hash = [7u8; 32];
+        let mut agg = AggregatorState {
+            aggregator_pubkey: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            da_scheme: 0,
+            da_scheme_set: 0,
+            last_timestamp: 0,
+            reserved: [0u8; 68],
+        };
+        let mut range = RangeState {
+            last_end_slot: 0,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        // Initializing fresh state keeps the original 110-byte DS
+        reset_anchor_state(&mut agg, &mut range);
+        assert_eq!(agg.epoch_nonce, 0);
+        let signed = build_ds(103, &program_id, &proof_hash, 1, 64, 1, agg.epoch_nonce);
+        assert_eq!(signed.len(), ds_len(1));
+
+        record_anchor(&mut agg, &mut range, 1, 64, 1_700_000_000);
+        reset_anchor_state(&mut agg, &mut range);
+        assert_eq!(agg.epoch_nonce, 1);
+        // The same (seq, range) passes the cursor checks again, but the old signature does not
+        assert!(check_seq_and_range(&agg, &range, 1, 1, 64, MAX_SLOTS_PER_ARTIFACT).is_ok());
+        let expected = build_ds(103, &program_id, &proof_hash, 1, 64, 1, agg.epoch_nonce);
+        assert_eq!(expected.len(), ds_len(1) + DS_NONCE_LEN);
+        assert_ne!(signed, expected);
+        assert_eq!(expected.get(ds_len(1)..), Some(&1u64.to_le_bytes()[..]));
+    }
+
+    #[test]
+    fn test_clock_skew_past_bound() {
+        let now = 1_700_000_000;
+        assert!(check_clock_skew(now, now - 120, 120, 5).is_ok());
+        assert_eq!(
+            check_clock_skew(now, now - 121, 120, 5).err(),
+            Some(ZkError::ClockSkew.into())
+        );
+    }
+
+    #[test]
+    fn test_clock_skew_future_bound() {
+        let now = 1_700_000_000;
+        assert!(check_clock_skew(now, now, 120, 5).is_ok());
+        assert!(check_clock_skew(now, now + 5, 120, 5).is_ok());
+        assert_eq!(
+            check_clock_skew(now, now + 6, 120, 5).err(),
+            Some(ZkError::ClockSkew.into())
+        );
+        // A zero future bound rejects any lead while still allowing lag
+        assert!(check_clock_skew(now, now + 1, 120, 0).is_err());
+        assert!(check_clock_skew(now, now - 1, 120, 0).is_ok());
+    }
+
+    fn sample_agg_state(last_seq: u64) -> AggregatorState {
+        AggregatorState {
+            aggregator_pubkey: Pubkey::default(),
+            last_seq,
+            epoch_nonce: 0,
+            da_scheme: 0,
+            da_scheme_set: 0,
+            last_timestamp: 0,
+            reserved: [0u8; 68],
+        }
+    }
+
+    fn sample_config(activation_seq: u64) -> Config {
+        Config {
+            zksl_mint: Pubkey::new_unique(),
+            admin: Pubkey::new_unique(),
+            aggregator_pubkey: Pubkey::new_unique(),
+            next_aggregator_pubkey: Pubkey::new_unique(),
+            activation_seq,
+            chain_id: 103,
+            paused: 0,
+            bump: 255,
+            max_past_skew_secs: 0,
+            max_future_skew_secs: 0,
+            allow_emergency_unlock: 0,
+            total_locked: 0,
+            seeds_version: SEEDS_VERSION_LEGACY,
+            enforce_uniform_da: 0,
+            bind_proof_hash: 0,
+            per_submitter_range: 0,
+            reserved: [0u8; 1],
+            min_lock_amount: 0,
+            max_proof_age_slots: 0,
+            pending_admin: Pubkey::default(),
+            max_slots_per_artifact: 0,
+            rotation_grace: 0,
+            min_lock_duration_secs: 0,
+            allow_token_2022: 0,
+            reserved_tail: [0u8; 63],
+        }
+    }
+
+    #[test]
+    fn test_clock_skew_bounds_default_when_unset() {
+        let mut cfg = sample_config(0);
+        assert_eq!(cfg.clock_skew_bounds(), (120, 5));
+        cfg.max_past_skew_secs = 60;
+        cfg.max_future_skew_secs = 2;
+        assert_eq!(cfg.clock_skew_bounds(), (60, 2));
+    }
+
+    #[test]
+    fn test_slot_window_comes_from_config() {
+        let mut cfg = sample_config(0);
+        assert_eq!(cfg.slots_per_artifact_bound(), MAX_SLOTS_PER_ARTIFACT);
+        cfg.max_slots_per_artifact = 64;
+        assert_eq!(cfg.slots_per_artifact_bound(), 64);
+        let agg = sample_agg_state(0);
+        let range = RangeState {
+            last_end_slot: 0,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        let bound = cfg.slots_per_artifact_bound();
+        assert!(check_seq_and_range(&agg, &range, 1, 1, 64, bound).is_ok());
+        assert_eq!(
+            check_seq_and_range(&agg, &range, 1, 1, 65, bound).err(),
+            Some(ZkError::MathOverflow.into())
+        );
+        // A wider window than the default, for a chain with faster slots
+        cfg.max_slots_per_artifact = 8_192;
+        let bound = cfg.slots_per_artifact_bound();
+        assert!(check_seq_and_range(&agg, &range, 1, 1, 8_192, bound).is_ok());
+    }
+
+    #[test]
+    fn test_absurd_window_and_skew_are_rejected() {
+        assert!(check_config_limits(0, 0, 0).is_ok());
+        assert!(check_config_limits(MAX_SLOTS_PER_ARTIFACT_LIMIT, 3_600, 3_600).is_ok());
+        for (slots, past, future) in [
+            (MAX_SLOTS_PER_ARTIFACT_LIMIT + 1, 0, 0),
+            (0, 3_601, 0),
+            (0, 0, 3_601),
+        ] {
+            assert_eq!(
+                check_config_limits(slots, past, future).err(),
+                Some(ZkError::ConfigOutOfRange.into())
+            );
+        }
+
+        // Initialization stores the given bounds, or the defaults for zero
+        let mut args = InitializeArgs {
+            aggregator_pubkey: Pubkey::new_unique(),
+            next_aggregator_pubkey: Pubkey::new_unique(),
+            activation_seq: 10,
+            chain_id: 103,
+            min_lock_amount: 0,
+            max_slots_per_artifact: 0,
+            max_past_skew_secs: 0,
+            max_future_skew_secs: 0,
+        };
+        let mut cfg = sample_config(0);
+        apply_initialize(&mut cfg, Pubkey::new_unique(), Pubkey::new_unique(), &args);
+        assert_eq!(cfg.slots_per_artifact_bound(), MAX_SLOTS_PER_ARTIFACT);
+        assert_eq!(
+            cfg.clock_skew_bounds(),
+            (DEFAULT_MAX_PAST_SKEW_SECS, DEFAULT_MAX_FUTURE_SKEW_SECS)
+        );
+        args.max_slots_per_artifact = 512;
+        args.max_past_skew_secs = 30;
+        args.max_future_skew_secs = 1;
+        apply_initialize(&mut cfg, Pubkey::new_unique(), Pubkey::new_unique(), &args);
+        assert_eq!(
+            (cfg.slots_per_artifact_bound(), cfg.clock_skew_bounds()),
+            (512, (30, 1))
+        );
+        args.max_past_skew_secs = 86_400;
+        assert_eq!(
+            check_initialize_args(&args).err(),
+            Some(ZkError::ConfigOutOfRange.into())
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_reflects_current_state() {
+        let mut cfg = sample_config(5);
+        cfg.paused = 1;
+        let agg = AggregatorState {
+            aggregator_pubkey: Pubkey::default(),
+            last_seq: 3,
+            epoch_nonce: 0,
+            da_scheme: 0,
+            da_scheme_set: 0,
+            last_timestamp: 0,
+            reserved: [0u8; 68],
+        };
+        let range = RangeState {
+            last_end_slot: 4_096,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        let hb = heartbeat_event(&cfg, &agg, &range, 1_700_000_000);
+        assert_eq!(hb.aggregator_pubkey, cfg.aggregator_pubkey);
+        assert!(hb.paused);
+        assert_eq!(hb.last_seq, 3);
+        assert_eq!(hb.last_end_slot, 4_096);
+        assert_eq!(hb.timestamp, 1_700_000_000);
+
+        // Once the next seq reaches activation the rotated key is reported
+        let rotated = AggregatorState { last_seq: 4, ..agg };
+        cfg.paused = 0;
+        let hb = heartbeat_event(&cfg, &rotated, &range, 1_700_000_001);
+        assert_eq!(hb.aggregator_pubkey, cfg.next_aggregator_pubkey);
+        assert!(!hb.paused);
+    }
+
+    #[test]
+    fn test_bootstrap_state_accepts_first_anchor() {
+        let aggregator = Pubkey::new_unique();
+        let args = InitializeArgs {
+            aggregator_pubkey: aggregator,
+            next_aggregator_pubkey: aggregator,
+            activation_seq: 1,
+            chain_id: 103,
+            min_lock_amount: 0,
+            max_slots_per_artifact: 0,
+            max_past_skew_secs: 0,
+            max_future_skew_secs: 0,
+        };
+        let mut cfg = sample_config(0);
+        cfg.paused = 1;
+        let mut agg = AggregatorState {
+            aggregator_pubkey: Pubkey::default(),
+            last_seq: 9,
+            epoch_nonce: 0,
+            da_scheme: 0,
+            da_scheme_set: 0,
+            last_timestamp: 0,
+            reserved: [0u8; 68],
+        };
+        let mut range = RangeState {
+            last_end_slot: 77,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        let mint = Pubkey::new_unique();
+        let admin = Pubkey::new_unique();
+        apply_initialize(&mut cfg, mint, admin, &args);
+        reset_anchor_state(&mut agg, &mut range);
+
+        assert_eq!(cfg.zksl_mint, mint);
+        assert_eq!(cfg.admin, admin);
+        assert_eq!(cfg.chain_id, 103);
+        assert_eq!(cfg.paused, 0);
+        assert_eq!(
+            cfg.clock_skew_bounds(),
+            (DEFAULT_MAX_PAST_SKEW_SECS, DEFAULT_MAX_FUTURE_SKEW_SECS)
+        );
+        assert_eq!(agg.last_seq, 0);
+        assert_eq!(range.last_end_slot, 0);
+
+        // The first anchor_proof is accepted straight away with the configured key
+        assert_eq!(allowed_aggregator_key(&cfg, 1), aggregator);
+        assert!(check_seq_and_range(&agg, &range, 1, 1_000, 1_063, MAX_SLOTS_PER_ARTIFACT).is_ok());
+        assert!(
+            check_seq_and_range(&agg, &range, 2, 1_000, 1_063, MAX_SLOTS_PER_ARTIFACT).is_err()
+        );
+    }
+
+    #[test]
+    fn test_seq_and_range_follow_previous_anchor() {
+        let agg = AggregatorState {
+            aggregator_pubkey: Pubkey::default(),
+            last_seq: 4,
+            epoch_nonce: 0,
+            da_scheme: 0,
+            da_scheme_set: 0,
+            last_timestamp: 0,
+            reserved: [0u8; 68],
+        };
+        let range = RangeState {
+            last_end_slot: 200,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        assert!(check_seq_and_range(&agg, &range, 5, 201, 264, MAX_SLOTS_PER_ARTIFACT).is_ok());
+        assert_eq!(
+            check_seq_and_range(&agg, &range, 4, 201, 264, MAX_SLOTS_PER_ARTIFACT).err(),
+            Some(ZkError::NonMonotonicSeq.into())
+        );
+        assert_eq!(
+            check_seq_and_range(&agg, &range, 5, 200, 264, MAX_SLOTS_PER_ARTIFACT).err(),
+            Some(ZkError::RangeOverlap.into())
+        );
+        assert_eq!(
+            check_seq_and_range(&agg, &range, 5, 264, 201, MAX_SLOTS_PER_ARTIFACT).err(),
+            Some(ZkError::MathOverflow.into())
+        );
+    }
+
+    #[test]
+    fn test_only_first_proof_is_genesis() {
+        let mut agg = AggregatorState {
+            aggregator_pubkey: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            da_scheme: 0,
+            da_scheme_set: 0,
+            last_timestamp: 0,
+            reserved: [0u8; 68],
+        };
+        let mut range = RangeState {
+            last_end_slot: 0,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        let mut flags = Vec::new();
+        for (seq, start, end) in [(1, 100, 163), (2, 164, 227), (3, 228, 291)] {
+            assert!(
+                check_seq_and_range(&agg, &range, seq, start, end, MAX_SLOTS_PER_ARTIFACT).is_ok()
+            );
+            flags.push(is_genesis(&agg));
+            record_anchor(&mut agg, &mut range, seq, end, 1_700_000_000);
+        }
+        assert_eq!(flags, [true, false, false]);
+        // A second genesis cannot follow: with a predecessor, seq 1 is rejected
+        assert_eq!(
+            check_seq_and_range(&agg, &range, 1, 292, 355, MAX_SLOTS_PER_ARTIFACT).err(),
+            Some(ZkError::NonMonotonicSeq.into())
+        );
+    }
+
+    #[test]
+    fn test_same_hash_cannot_be_anchored_under_another_seq() {
+        let mut agg = AggregatorState {
+            aggregator_pubkey: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            da_scheme: 0,
+            da_scheme_set: 0,
+            last_timestamp: 0,
+            reserved: [0u8; 68],
+        };
+        let mut range = RangeState {
+            last_end_slot: 0,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        // A range ending at slot 0 leaves last_end_slot at 0; contiguity must still apply
+        for (seq, start, end) in [(1, 0, 0), (2, 1, 64)] {
+            assert!(
+                check_seq_and_range(&agg, &range, seq, start, end, MAX_SLOTS_PER_ARTIFACT).is_ok()
+            );
+            record_anchor(&mut agg, &mut range, seq, end, 1_700_000_000);
+            // Replaying the same seq (same proof_record PDA) is rejected
+            assert_eq!(
+                check_seq_and_range(&agg, &range, seq, start, end, MAX_SLOTS_PER_ARTIFACT).err(),
+                Some(ZkError::NonMonotonicSeq.into())
+            );
+            // The same content under the next seq (a fresh PDA) claims an already-anchored range
+            assert_eq!(
+                check_seq_and_range(&agg, &range, seq + 1, start, end, MAX_SLOTS_PER_ARTIFACT)
+                    .err(),
+                Some(ZkError::RangeOverlap.into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_same_artifact_cannot_be_anchored_under_another_seq() {
+        let mut agg = sample_agg_state(0);
+        let mut range = RangeState {
+            last_end_slot: 0,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        let artifact_id = [7u8; 16];
+        let program_id = Pubkey::new_unique();
+        let mut artifact = ArtifactRecord {
+            seq: 0,
+            reserved: [0u8; 24],
+        };
+
+        // First anchor claims the artifact
+        assert!(check_seq_and_range(&agg, &range, 1, 0, 63, MAX_SLOTS_PER_ARTIFACT).is_ok());
+        assert!(claim_artifact(&mut artifact, 1).is_ok());
+        record_anchor(&mut agg, &mut range, 1, 63, 1_700_000_000);
+        assert_eq!(artifact.seq, 1);
+
+        // The next seq and range are fine, but the artifact is already anchored; the record
+        // sits at the same PDA whatever the proof_hash and seq
+        assert!(check_seq_and_range(&agg, &range, 2, 64, 127, MAX_SLOTS_PER_ARTIFACT).is_ok());
+        assert_eq!(
+            claim_artifact(&mut artifact, 2).err(),
+            Some(ZkError::ArtifactAlreadyAnchored.into())
+        );
+        assert_eq!(artifact.seq, 1);
+        assert_eq!(
+            helpers::artifact_record_address(&artifact_id, &program_id),
+            Pubkey::find_program_address(&[b"zksl", b"artifact", &artifact_id], &program_id).0
+        );
+        assert_ne!(
+            helpers::artifact_record_address(&artifact_id, &program_id),
+            helpers::artifact_record_address(&[8u8; 16], &program_id)
+        );
+    }
+
+    #[test]
+    fn test_recommended_budget_passes_preflight() {
+        let program_ids = |n: usize| {
+            let mut ids = vec![COMPUTE_BUDGET_PROGRAM_ID, ED25519_PROGRAM_ID, crate::ID];
+            ids.resize(n.max(3), Pubkey::new_unique());
+            ids
+        };
+        let mut prev = 0u32;
+        for n in 3..=12 {
+            let ids = program_ids(n);
+            assert!(check_preflight_programs(ids.clone(), 1).is_ok());
+            let units = helpers::recommend_compute_units(ids.len());
+            assert!(units > prev, "budget must grow with the scan length");
+            assert!(units <= helpers::MAX_COMPUTE_UNITS);
+            prev = units;
+        }
+        // Without the ComputeBudget instruction the recommendation is never applied
+        assert_eq!(
+            check_preflight_programs([ED25519_PROGRAM_ID, crate::ID], 1).err(),
+            Some(ZkError::InsufficientBudget.into())
+        );
+        assert_eq!(
+            check_preflight_programs([COMPUTE_BUDGET_PROGRAM_ID, crate::ID], 1).err(),
+            Some(ZkError::BadEd25519Order.into())
+        );
+    }
+
+    #[test]
+    fn test_preflight_scan_is_capped() {
+        let program_ids = |n: usize| {
+            let mut ids = vec![COMPUTE_BUDGET_PROGRAM_ID, ED25519_PROGRAM_ID, crate::ID];
+            ids.resize(n, Pubkey::new_unique());
+            ids
+        };
+        let at_cap = program_ids(helpers::MAX_PREFLIGHT_INSTRUCTIONS);
+        assert!(check_preflight_programs(at_cap, 1).is_ok());
+        let over_cap = program_ids(helpers::MAX_PREFLIGHT_INSTRUCTIONS + 1);
+        assert_eq!(
+            check_preflight_programs(over_cap, 1).err(),
+            Some(ZkError::BadEd25519Order.into())
+        );
+        // The scan stops at the first instruction past the cap, however long the transaction
+        let mut visited = 0usize;
+        let endless = core::iter::repeat(crate::ID).inspect(|_| visited += 1);
+        let with_required = [COMPUTE_BUDGET_PROGRAM_ID, ED25519_PROGRAM_ID]
+            .into_iter()
+            .chain(endless);
+        assert!(check_preflight_programs(with_required, 1).is_err());
+        assert_eq!(visited, helpers::MAX_PREFLIGHT_INSTRUCTIONS - 1);
+    }
+
+    #[test]
+    fn test_commitment_level_recorded_from_proof() {
+        // A proof generated over confirmed data records level 1
+        assert_eq!(check_commitment_level(1).ok(), Some(1));
+        for level in 0..=MAX_COMMITMENT_LEVEL {
+            assert_eq!(check_commitment_level(level).ok(), Some(level));
+        }
+        assert_eq!(
+            check_commitment_level(3).err(),
+            Some(ZkError::InvalidCommitmentLevel.into())
+        );
+    }
+
+    #[test]
+    fn test_epoch_tags_match_slot_division() {
+        for (start, end) in [
+            (0, 0),
+            (1, 431_999),
+            (431_999, 432_000),
+            (432_000, 864_001),
+            (5, 2_000_000),
+        ] {
+            assert_eq!(
+                epoch_tags(start, end, 432_000).ok(),
+                Some((start / 432_000, end / 432_000))
+            );
+        }
+        // Straddling ranges are tagged, not rejected
+        assert_eq!(epoch_tags(431_999, 432_000, 432_000).ok(), Some((0, 1)));
+        assert_eq!(
+            epoch_tags(1, 2, 0).err(),
+            Some(ZkError::MathOverflow.into())
+        );
+    }
+
+    #[test]
+    fn test_proof_timestamps_never_go_backwards() {
+        let mut agg = sample_agg_state(0);
+        let mut range = RangeState {
+            last_end_slot: 0,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        // Any timestamp follows a fresh state
+        assert!(check_timestamp_order(&agg, 1_700_000_000).is_ok());
+        record_anchor(&mut agg, &mut range, 1, 63, 1_700_000_000);
+
+        // Increasing and equal timestamps are accepted
+        assert!(check_timestamp_order(&agg, 1_700_000_001).is_ok());
+        assert!(check_timestamp_order(&agg, 1_700_000_000).is_ok());
+        record_anchor(&mut agg, &mut range, 2, 127, 1_700_000_000);
+        assert_eq!(agg.last_timestamp, 1_700_000_000);
+
+        // Decreasing timestamps are rejected
+        assert_eq!(
+            check_timestamp_order(&agg, 1_699_999_999).err(),
+            Some(ZkError::NonMonotonicTimestamp.into())
+        );
+
+        // A state reset starts a new series
+        reset_anchor_state(&mut agg, &mut range);
+        assert!(check_timestamp_order(&agg, 1_699_999_999).is_ok());
+    }
+
+    #[test]
+    fn test_proofs_older_than_max_age_are_rejected() {
+        let mut cfg = sample_config(0);
+        let now = 10_000;
+        // Off by default: any age is accepted
+        assert!(check_proof_age(&cfg, 0, now).is_ok());
+
+        cfg.max_proof_age_slots = 150;
+        // Fresh range, including one ending ahead of the clock
+        assert!(check_proof_age(&cfg, now, now).is_ok());
+        assert!(check_proof_age(&cfg, now + 5, now).is_ok());
+        // Just within the window
+        assert!(check_proof_age(&cfg, now - 150, now).is_ok());
+        // Stale
+        assert_eq!(
+            check_proof_age(&cfg, now - 151, now).err(),
+            Some(ZkError::ProofTooStale.into())
+        );
+        assert_eq!(
+            check_proof_age(&cfg, 0, now).err(),
+            Some(ZkError::ProofTooStale.into())
+        );
+    }
+
+    /// Empty proof and artifact records, as their PDAs are created.
+    fn empty_records() -> (ProofRecord, ArtifactRecord) {
+        let pr = ProofRecord {
+            artifact_id: [0u8; 16],
+            start_slot: 0,
+            end_slot: 0,
+            proof_hash: [0u8; 32],
+            artifact_len: 0,
+            state_root_before: [0u8; 32],
+            state_root_after: [0u8; 32],
+            submitted_by: Pubkey::default(),
+            aggregator_pubkey: Pubkey::default(),
+            timestamp: 0,
+            seq: 0,
+            ds_hash: [0u8; 32],
+            commitment_level: 0,
+            da_params: [0u8; 12],
+            is_genesis: 0,
+            reserved: [0u8; 4],
+            start_epoch: 0,
+            end_epoch: 0,
+        };
+        let artifact = ArtifactRecord {
+            seq: 0,
+            reserved: [0u8; 24],
+        };
+        (pr, artifact)
+    }
+
+    #[test]
+    fn test_resize_proof_record_grows_pre_epoch_records() {
+        let (mut record, _) = empty_records();
+        (record.start_slot, record.end_slot, record.seq) = (431_999, 432_001, 7);
+        let data = account_data(&record);
+        let prior = data.get(..8 + 262).unwrap_or_default();
+        // A record anchored before the epoch tags does not decode as it is
+        assert!(ProofRecord::try_deserialize(&mut &*prior).is_err());
+
+        let grown = grow_proof_record(prior, 432_000).ok().flatten();
+        assert_eq!(
+            grown.as_ref().map(|r| (r.seq, r.start_epoch, r.end_epoch)),
+            Some((7, 0, 1))
+        );
+        let regrown = grown.map(|r| account_data(&r));
+        assert_eq!(regrown.as_ref().map(Vec::len), Some(8 + ProofRecord::SIZE));
+
+        assert!(matches!(grow_proof_record(&data, 432_000), Ok(None)));
+        assert_eq!(
+            grow_proof_record(data.get(..100).unwrap_or_default(), 432_000).err(),
+            Some(anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into())
+        );
+        assert_eq!(
+            grow_proof_record(&account_data(&sample_config(0)), 432_000).err(),
+            Some(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_commitment_level_advances_monotonically() {
+        let cfg = sample_config(0);
+        let (mut record, _) = empty_records();
+        record.aggregator_pubkey = Pubkey::new_unique();
+        record.commitment_level = 0;
+        assert_eq!(
+            advance_commitment_level(&cfg, Pubkey::new_unique(), &mut record, 1).err(),
+            Some(ZkError::Unauthorized.into())
+        );
+        // 0 -> 1 by the aggregator, 1 -> 2 by the admin
+        let aggregator = record.aggregator_pubkey;
+        assert_eq!(
+            advance_commitment_level(&cfg, aggregator, &mut record, 1).ok(),
+            Some(0)
+        );
+        assert_eq!(
+            advance_commitment_level(&cfg, cfg.admin, &mut record, 2).ok(),
+            Some(1)
+        );
+        assert_eq!(record.commitment_level, MAX_COMMITMENT_LEVEL);
+        assert!(check_proof_record_close(&cfg, cfg.admin, &record).is_ok());
+        // Finalized records never regress, nor re-apply the same level
+        for level in [0, 1, 2] {
+            assert_eq!(
+                advance_commitment_level(&cfg, cfg.admin, &mut record, level).err(),
+                Some(ZkError::CommitmentRegression.into())
+            );
+        }
+        assert_eq!(
+            advance_commitment_level(&cfg, cfg.admin, &mut record, 3).err(),
+            Some(ZkError::InvalidCommitmentLevel.into())
+        );
+        assert_eq!(record.commitment_level, MAX_COMMITMENT_LEVEL);
+    }
+
+    #[test]
+    fn test_only_finalized_records_close_and_only_by_admin() {
+        let cfg = sample_config(0);
+        let (mut record, _) = empty_records();
+        record.seq = 3;
+        for level in 0..MAX_COMMITMENT_LEVEL {
+            record.commitment_level = level;
+            assert_eq!(
+                check_proof_record_close(&cfg, cfg.admin, &record).err(),
+                Some(ZkError::ProofNotFinalized.into())
+            );
+        }
+        record.commitment_level = MAX_COMMITMENT_LEVEL;
+        assert!(check_proof_record_close(&cfg, cfg.admin, &record).is_ok());
+        assert_eq!(
+            check_proof_record_close(&cfg, Pubkey::new_unique(), &record).err(),
+            Some(ZkError::Unauthorized.into())
+        );
+    }
+
+    #[test]
+    fn test_committee_updates_and_preflight_count() {
+        let members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for threshold in 1..=3 {
+            assert!(check_committee_update(threshold, &members).is_ok());
+        }
+        let invalid = Some(ZkError::InvalidCommittee.into());
+        assert_eq!(check_committee_update(0, &members).err(), invalid);
+        assert_eq!(check_committee_update(4, &members).err(), invalid);
+        let repeated: Vec<Pubkey> = members
+            .iter()
+            .take(2)
+            .chain(members.first())
+            .copied()
+            .collect();
+        assert_eq!(check_committee_update(2, &repeated).err(), invalid);
+        assert_eq!(
+            check_committee_update(1, &[Pubkey::default()]).err(),
+            invalid
+        );
+        let oversized = vec![Pubkey::new_unique(); AggregatorCommittee::MAX_MEMBERS + 1];
+        assert_eq!(check_committee_update(1, &oversized).err(), invalid);
+
+        // A 2-of-N committee needs exactly two Ed25519 instructions
+        let ids = [
+            COMPUTE_BUDGET_PROGRAM_ID,
+            ED25519_PROGRAM_ID,
+            ED25519_PROGRAM_ID,
+            crate::ID,
+        ];
+        assert!(check_preflight_programs(ids, 2).is_ok());
+        assert_eq!(
+            check_preflight_programs(ids, 1).err(),
+            Some(ZkError::BadEd25519Order.into())
+        );
+        assert_eq!(
+            check_preflight_programs(ids, 3).err(),
+            Some(ZkError::BadEd25519Order.into())
+        );
+    }
+
+    #[test]
+    fn test_anchor_header_checks_the_selected_ds_version() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let program_id = Pubkey::new_unique();
+        let signing_key = SigningKey::from_bytes(&[42u8; 32]);
+        let aggregator = Pubkey::new_from_array(signing_key.verifying_key().to_bytes());
+        let mut cfg = sample_config(0);
+        cfg.next_aggregator_pubkey = aggregator;
+        let env = |ds_version: u8| AnchorEnv {
+            config: &cfg,
+            program_id: &program_id,
+            submitted_by: Pubkey::new_unique(),
+            now: 1_700_000_000,
+            current_slot: 2_000,
+            slots_per_epoch: 432_000,
+            ds_version,
+        };
+        let header = |ds: &[u8], artifact_len: u32| ProofHeader {
+            artifact_id: [1u8; 16],
+            proof_hash: [9u8; 32],
+            seq: 1,
+            start_slot: 1_000,
+            end_slot: 1_063,
+            artifact_len,
+            state_root_before: [1u8; 32],
+            state_root_after: [2u8; 32],
+            aggregator_pubkey: aggregator,
+            timestamp: 1_700_000_000,
+            ds_hash: *blake3::hash(ds).as_bytes(),
+            commitment_level: 1,
+            da_params: [0u8; 12],
+        };
+        let anchor = |ds_version: u8, header: &ProofHeader, ds: &[u8]| {
+            let (key, signature) = (aggregator.to_bytes(), signing_key.sign(ds).to_bytes());
+            let signed = helpers::Ed25519Fields {
+                signature: &signature,
+                pubkey: &key,
+                message: ds,
+            };
+            let (mut agg, mut range) = (sample_agg_state(0), empty_range(Pubkey::default()));
+            let (mut pr, mut artifact) = empty_records();
+            anchor_header(
+                &env(ds_version),
+                &mut agg,
+                &mut range,
+                header,
+                &signed,
+                &mut pr,
+                &mut artifact,
+            )
+            .map(|()| pr.artifact_len)
+        };
+        let v1 = build_ds(cfg.chain_id, &program_id, &[9u8; 32], 1_000, 1_063, 1, 0);
+        let v2 = helpers::compute_ds_message_v2(
+            cfg.chain_id,
+            &program_id,
+            &[9u8; 32],
+            1_000,
+            1_063,
+            1,
+            4_096,
+        );
+        assert_eq!(
+            (v1.len(), v2.len()),
+            (ds_len(DS_VERSION_V1), ds_len(DS_VERSION_V2))
+        );
+
+        // v1 leaves the length unsigned: any reported length anchors
+        assert_eq!(anchor(DS_VERSION_V1, &header(&v1, 7), &v1).ok(), Some(7));
+        // v2 binds it: the signed length anchors, a different one does not
+        assert_eq!(
+            anchor(DS_VERSION_V2, &header(&v2, 4_096), &v2).ok(),
+            Some(4_096)
+        );
+        let bad = Some(ZkError::BadDomainSeparation.into());
+        assert_eq!(anchor(DS_VERSION_V2, &header(&v2, 4_097), &v2).err(), bad);
+        // A signature over one layout never verifies under the other
+        assert_eq!(anchor(DS_VERSION_V1, &header(&v2, 4_096), &v2).err(), bad);
+        assert_eq!(anchor(DS_VERSION_V2, &header(&v1, 4_096), &v1).err(), bad);
+        assert_eq!(
+            anchor(3, &header(&v1, 4_096), &v1).err(),
+            Some(ZkError::UnsupportedDsVersion.into())
+        );
+    }
+
+    #[test]
+    fn test_get_proof_record_returns_anchored_fields() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let program_id = Pubkey::new_unique();
+        let signing_key = SigningKey::from_bytes(&[42u8; 32]);
+        let aggregator = Pubkey::new_from_array(signing_key.verifying_key().to_bytes());
+        let mut cfg = sample_config(0);
+        cfg.next_aggregator_pubkey = aggregator;
+        let env = AnchorEnv {
+            config: &cfg,
+            program_id: &program_id,
+            submitted_by: Pubkey::new_unique(),
+            now: 1_700_000_000,
+            current_slot: 2_000,
+            slots_per_epoch: 432_000,
+            ds_version: DS_VERSION_V1,
+        };
+        let (seq, start_slot, end_slot, proof_hash) = (1u64, 1_000u64, 1_063u64, [9u8; 32]);
+        let ds = build_ds(
+            cfg.chain_id,
+            &program_id,
+            &proof_hash,
+            start_slot,
+            end_slot,
+            seq,
+            0,
+        );
+        let header = ProofHeader {
+            artifact_id: [1u8; 16],
+            proof_hash,
+            seq,
+            start_slot,
+            end_slot,
+            artifact_len: 1_024,
+            state_root_before: [1u8; 32],
+            state_root_after: [2u8; 32],
+            aggregator_pubkey: aggregator,
+            timestamp: 1_700_000_000,
+            ds_hash: *blake3::hash(&ds).as_bytes(),
+            commitment_level: 1,
+            da_params: [0u8; 12],
+        };
+        let (key, signature) = (aggregator.to_bytes(), signing_key.sign(&ds).to_bytes());
+        let signed = helpers::Ed25519Fields {
+            signature: &signature,
+            pubkey: &key,
+            message: &ds,
+        };
+        let mut agg = sample_agg_state(0);
+        let mut range = empty_range(Pubkey::default());
+        let (mut pr, mut artifact) = empty_records();
+        assert!(anchor_header(
+            &env,
+            &mut agg,
+            &mut range,
+            &header,
+            &signed,
+            &mut pr,
+            &mut artifact
+        )
+        .is_ok());
+
+        let data = proof_record_return_data(&pr).ok().unwrap_or_default();
+        assert_eq!(data.len(), 8 + 8 + 8 + 32 + 1);
+        assert_eq!(
+            ProofRecordSummary::deserialize(&mut data.as_slice()).ok(),
+            Some(ProofRecordSummary {
+                seq,
+                start_slot,
+                end_slot,
+                proof_hash,
+                commitment_level: 1
+            })
+        );
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn test_batch_anchors_consecutive_headers_cumulatively() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let program_id = Pubkey::new_unique();
+        let signing_key = SigningKey::from_bytes(&[42u8; 32]);
+        let aggregator = Pubkey::new_from_array(signing_key.verifying_key().to_bytes());
+        let mut cfg = sample_config(0);
+        cfg.next_aggregator_pubkey = aggregator;
+        let env = AnchorEnv {
+            config: &cfg,
+            program_id: &program_id,
+            submitted_by: Pubkey::new_unique(),
+            now: 1_700_000_000,
+            current_slot: 2_000,
+            slots_per_epoch: 432_000,
+            ds_version: DS_VERSION_V1,
+        };
+        let header = |seq: u64, start_slot: u64| {
+            let (end_slot, proof_hash) = (start_slot + 63, [u8::try_from(seq).unwrap_or(0); 32]);
+            let ds = build_ds(
+                cfg.chain_id,
+                &program_id,
+                &proof_hash,
+                start_slot,
+                end_slot,
+                seq,
+                0,
+            );
+            let ds_hash = *blake3::hash(&ds).as_bytes();
+            let header = ProofHeader {
+                artifact_id: [u8::try_from(seq).unwrap_or(0); 16],
+                proof_hash,
+                seq,
+                start_slot,
+                end_slot,
+                artifact_len: 1_024,
+                state_root_before: [1u8; 32],
+                state_root_after: [2u8; 32],
+                aggregator_pubkey: aggregator,
+                timestamp: 1_700_000_000,
+                ds_hash,
+                commitment_level: 2,
+                da_params: [0u8; 12],
+            };
+            (header, ds.clone(), signing_key.sign(&ds).to_bytes())
+        };
+        let batch = [header(1, 1_000), header(2, 1_064), header(3, 1_128)];
+        assert!(check_batch_size(batch.len(), 2 * batch.len()).is_ok());
+        let key = aggregator.to_bytes();
+        let entries: Vec<(&[u8; 32], &[u8; 64], &[u8])> = batch
+            .iter()
+            .map(|(_, ds, sig)| (&key, sig, ds.as_slice()))
+            .collect();
+        let ed_data = helpers::ed25519_instruction_data_multi(&entries)
+            .ok()
+            .unwrap_or_default();
+        let signed = helpers::parse_ed25519_multi(&ed_data, batch.len())
+            .ok()
+            .unwrap_or_default();
+        let [sig_1, _, sig_3] = signed.as_slice() else {
+            return assert_eq!(signed.len(), 3);
+        };
+        let [(header_1, _, _), (header_2, _, _), (header_3, _, _)] = &batch;
+
+        let mut agg = sample_agg_state(0);
+        let mut range = RangeState {
+            last_end_slot: 0,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        let mut genesis = Vec::new();
+        for ((header, _, _), fields) in batch.iter().zip(&signed) {
+            assert!(check_aggregator_key(&cfg, header.seq, header.aggregator_pubkey).is_ok());
+            let (mut pr, mut artifact) = empty_records();
+            assert!(anchor_header(
+                &env,
+                &mut agg,
+                &mut range,
+                header,
+                fields,
+                &mut pr,
+                &mut artifact
+            )
+            .is_ok());
+            assert_eq!(
+                (pr.seq, pr.end_slot, artifact.seq),
+                (header.seq, header.end_slot, header.seq)
+            );
+            genesis.push(pr.is_genesis);
+        }
+        assert_eq!(genesis, [1, 0, 0]);
+        assert_eq!((agg.last_seq, range.last_end_slot), (3, 1_191));
+
+        // Out-of-order headers fail: the batch's second entry skips a seq
+        let mut agg = sample_agg_state(0);
+        let mut range = RangeState {
+            last_end_slot: 0,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        let (mut pr, mut artifact) = empty_records();
+        assert!(anchor_header(
+            &env,
+            &mut agg,
+            &mut range,
+            header_1,
+            sig_1,
+            &mut pr,
+            &mut artifact
+        )
+        .is_ok());
+        let (mut pr, mut artifact) = empty_records();
+        assert_eq!(
+            anchor_header(
+                &env,
+                &mut agg,
+                &mut range,
+                header_3,
+                sig_3,
+                &mut pr,
+                &mut artifact
+            )
+            .err(),
+            Some(ZkError::NonMonotonicSeq.into())
+        );
+        // A signature belonging to another header is rejected
+        let (mut pr, mut artifact) = empty_records();
+        assert_eq!(
+            anchor_header(
+                &env,
+                &mut agg,
+                &mut range,
+                header_2,
+                sig_1,
+                &mut pr,
+                &mut artifact
+            )
+            .err(),
+            Some(ZkError::BadDomainSeparation.into())
+        );
+    }
+
+    #[test]
+    fn test_stored_aggregator_key_flips_once_at_activation() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let program_id = Pubkey::new_unique();
+        let [old_key, new_key] = [7u8, 8u8].map(|b| SigningKey::from_bytes(&[b; 32]));
+        let pubkey = |k: &SigningKey| Pubkey::new_from_array(k.verifying_key().to_bytes());
+        let mut cfg = sample_config(3);
+        cfg.aggregator_pubkey = pubkey(&old_key);
+        cfg.next_aggregator_pubkey = pubkey(&new_key);
+        let env = AnchorEnv {
+            config: &cfg,
+            program_id: &program_id,
+            submitted_by: Pubkey::new_unique(),
+            now: 1_700_000_000,
+            current_slot: 2_000,
+            slots_per_epoch: 432_000,
+            ds_version: DS_VERSION_V1,
+        };
+        let mut agg = sample_agg_state(0);
+        let mut range = RangeState {
+            last_end_slot: 0,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        let mut stored = Vec::new();
+        for seq in 1..=5u64 {
+            let signing_key = if seq < cfg.activation_seq {
+                &old_key
+            } else {
+                &new_key
+            };
+            let start_slot = 1_000 + (seq - 1) * 64;
+            let proof_hash = [u8::try_from(seq).unwrap_or(0); 32];
+            let ds = build_ds(
+                cfg.chain_id,
+                &program_id,
+                &proof_hash,
+                start_slot,
+                start_slot + 63,
+                seq,
+                0,
+            );
+            let header = ProofHeader {
+                artifact_id: [u8::try_from(seq).unwrap_or(0); 16],
+                proof_hash,
+                seq,
+                start_slot,
+                end_slot: start_slot + 63,
+                artifact_len: 1_024,
+                state_root_before: [1u8; 32],
+                state_root_after: [2u8; 32],
+                aggregator_pubkey: pubkey(signing_key),
+                timestamp: 1_700_000_000,
+                ds_hash: *blake3::hash(&ds).as_bytes(),
+                commitment_level: 2,
+                da_params: [0u8; 12],
+            };
+            let key = header.aggregator_pubkey.to_bytes();
+            let ed_data =
+                helpers::ed25519_instruction_data(&key, &signing_key.sign(&ds).to_bytes(), &ds)
+                    .ok()
+                    .unwrap_or_default();
+            let Ok(signed) = parse_ed25519_single(&ed_data) else {
+                return assert!(ed_data.is_empty(), "ed25519 data did not parse");
+            };
+            assert!(check_aggregator_key(&cfg, seq, header.aggregator_pubkey).is_ok());
+            let (mut pr, mut artifact) = empty_records();
+            assert!(anchor_header(
+                &env,
+                &mut agg,
+                &mut range,
+                &header,
+                &signed,
+                &mut pr,
+                &mut artifact
+            )
+            .is_ok());
+            stored.push(agg.aggregator_pubkey);
+        }
+        let (old, new) = (pubkey(&old_key), pubkey(&new_key));
+        assert_eq!(stored, [old, old, new, new, new]);
+
+        // Only a change away from a stored key is reported as a rotation
+        let mut agg = sample_agg_state(0);
+        assert_eq!(record_aggregator_key(&mut agg, old), None);
+        assert_eq!(record_aggregator_key(&mut agg, old), None);
+        assert_eq!(record_aggregator_key(&mut agg, new), Some(old));
+        assert_eq!(record_aggregator_key(&mut agg, new), None);
+    }
+
+    #[test]
+    fn test_batch_size_and_account_count() {
+        assert!(check_batch_size(1, 2).is_ok());
+        assert!(check_batch_size(MAX_BATCH_PROOFS, 2 * MAX_BATCH_PROOFS).is_ok());
+        for headers in [0, MAX_BATCH_PROOFS + 1] {
+            assert_eq!(
+                check_batch_size(headers, 2 * headers).err(),
+                Some(ZkError::InvalidBatchSize.into())
+            );
+        }
+        // Two PDAs per header, no more and no fewer
+        for remaining in [0, 3, 5] {
+            assert_eq!(
+                check_batch_size(2, remaining).err(),
+                Some(ZkError::BatchAccountMismatch.into())
+            );
+        }
+    }
+
+    fn sample_record(validator: Pubkey) -> ValidatorRecord {
+        ValidatorRecord {
+            validator_pubkey: validator,
+            lock_token_account: Pubkey::new_unique(),
+            lock_timestamp: 1_700_000_000,
+            status: 0,
+            num_accepts: 0,
+            locked_amount: 0,
+            reserved: [0u8; 47],
+        }
+    }
+
+    #[test]
+    fn test_bound_proof_hash_must_commit_to_roots_and_slots() {
+        let mut cfg = sample_config(10);
+        let (before, after) = ([1u8; 32], [2u8; 32]);
+        let bound = helpers::transition_proof_hash(&before, &after, 100, 199);
+        let free = [0xABu8; 32];
+        // Off by default: any hash is accepted
+        assert!(check_proof_hash_binding(&cfg, &free, (&before, &after), 100, 199).is_ok());
+
+        cfg.bind_proof_hash = 1;
+        assert!(check_proof_hash_binding(&cfg, &bound, (&before, &after), 100, 199).is_ok());
+        for (hash, roots, slots) in [
+            (free, (before, after), (100, 199)),
+            (bound, (after, before), (100, 199)),
+            (bound, (before, [3u8; 32]), (100, 199)),
+            (bound, (before, after), (101, 199)),
+            (bound, (before, after), (100, 200)),
+        ] {
+            assert_eq!(
+                check_proof_hash_binding(&cfg, &hash, (&roots.0, &roots.1), slots.0, slots.1).err(),
+                Some(ZkError::ProofHashMismatch.into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_uniform_da_rejects_mid_chain_scheme_switch() {
+        let mut cfg = sample_config(0);
+        cfg.enforce_uniform_da = 1;
+        let mut agg = sample_agg_state(0);
+        let mut scheme_1 = [0u8; 12];
+        scheme_1[0] = 1;
+        scheme_1[4] = 0xAA; // Scheme-specific bytes do not matter
+        let mut scheme_2 = [0u8; 12];
+        scheme_2[0] = 2;
+
+        // The first proof picks the scheme; later proofs on it are accepted
+        assert!(check_da_scheme(&cfg, &agg, &scheme_1).is_ok());
+        record_da_scheme(&mut agg, &scheme_1);
+        let mut same_scheme = scheme_1;
+        same_scheme[4] = 0xBB;
+        assert!(check_da_scheme(&cfg, &agg, &same_scheme).is_ok());
+        record_da_scheme(&mut agg, &same_scheme);
+
+        assert_eq!(
+            check_da_scheme(&cfg, &agg, &scheme_2).err(),
+            Some(ZkError::DaSchemeMismatch.into())
+        );
+        // Switching is only possible through an explicit clear, or with the flag off
+        clear_da_scheme(&mut agg);
+        assert!(check_da_scheme(&cfg, &agg, &scheme_2).is_ok());
+        record_da_scheme(&mut agg, &scheme_2);
+        cfg.enforce_uniform_da = 0;
+        assert!(check_da_scheme(&cfg, &agg, &scheme_1).is_ok());
+    }
+
+    #[test]
+    fn test_region_commitment_is_stored_and_opens_only_to_its_reveal() {
+        let validator = Pubkey::new_unique();
+        let salt = [0x5Au8; 32];
+        let commitment = helpers::region_commitment("eu-west/AS16509", &salt);
+        let mut metadata = ValidatorMetadata {
+            validator: Pubkey::default(),
+            region_commitment: [0u8; 32],
+            updated_at: 0,
+            reserved: [0u8; 64],
+        };
+        record_region_commitment(&mut metadata, validator, commitment, 1_700_000_000);
+        assert_eq!(metadata.validator, validator);
+        assert_eq!(metadata.region_commitment, commitment);
+        assert_eq!(metadata.updated_at, 1_700_000_000);
+
+        assert!(helpers::verify_region_reveal(
+            &metadata.region_commitment,
+            "eu-west/AS16509",
+            &salt
+        ));
+        assert!(!helpers::verify_region_reveal(
+            &metadata.region_commitment,
+            "us-east/AS16509",
+            &salt
+        ));
+        assert!(!helpers::verify_region_reveal(
+            &metadata.region_commitment,
+            "eu-west/AS16509",
+            &[0u8; 32]
+        ));
+
+        // The account keeps its size as the commitment is replaced
+        let mut data = Vec::new();
+        assert!(metadata.try_serialize(&mut data).is_ok());
+        assert_eq!(data.len(), 8 + ValidatorMetadata::SIZE);
+    }
+
+    #[test]
+    fn test_checkpoints_must_be_contiguous_and_anchored() {
+        let agg = sample_agg_state(20);
+        let mut checkpoint = CheckpointState {
+            root: [0u8; 32],
+            start_seq: 0,
+            end_seq: 0,
+            count: 0,
+            reserved: [0u8; 64],
+        };
+        assert!(check_checkpoint_range(&checkpoint, &agg, 1, 10).is_ok());
+        record_checkpoint(&mut checkpoint, [1u8; 32], 1, 10);
+        assert!(check_checkpoint_range(&checkpoint, &agg, 11, 20).is_ok());
+        record_checkpoint(&mut checkpoint, [2u8; 32], 11, 20);
+        assert_eq!(
+            (
+                checkpoint.root,
+                checkpoint.start_seq,
+                checkpoint.end_seq,
+                checkpoint.count
+            ),
+            ([2u8; 32], 11, 20, 2)
+        );
+
+        let rejected = |start, end| {
+            check_checkpoint_range(&checkpoint, &agg, start, end).err()
+                == Some(ZkError::CheckpointRange.into())
+        };
+        // Gap after the previous checkpoint, overlap with it, and an empty range
+        assert!(rejected(22, 22));
+        assert!(rejected(20, 20));
+        assert!(rejected(21, 20));
+        // Not yet anchored
+        assert!(rejected(21, 21));
+    }
+
+    #[test]
+    fn test_rotation_grace_accepts_either_key_near_activation() {
+        let mut cfg = sample_config(100);
+        let (old, new) = (cfg.aggregator_pubkey, cfg.next_aggregator_pubkey);
+        let mismatch = Some(ZkError::AggregatorMismatch.into());
+
+        // No grace: hard cutover at activation_seq
+        assert!(check_aggregator_key(&cfg, 99, old).is_ok());
+        assert_eq!(check_aggregator_key(&cfg, 99, new).err(), mismatch);
+        assert!(check_aggregator_key(&cfg, 100, new).is_ok());
+        assert_eq!(check_aggregator_key(&cfg, 100, old).err(), mismatch);
+
+        // Inside [activation_seq - 3, activation_seq + 3) both keys sign
+        cfg.rotation_grace = 3;
+        for seq in 97..103 {
+            assert!(
+                check_aggregator_key(&cfg, seq, old).is_ok(),
+                "old key at seq {seq}"
+            );
+            assert!(
+                check_aggregator_key(&cfg, seq, new).is_ok(),
+                "new key at seq {seq}"
+            );
+            assert_eq!(
+                check_aggregator_key(&cfg, seq, Pubkey::new_unique()).err(),
+                mismatch
+            );
+        }
+        // Outside it, only the scheduled key
+        assert!(check_aggregator_key(&cfg, 96, old).is_ok());
+        assert_eq!(check_aggregator_key(&cfg, 96, new).err(), mismatch);
+        assert!(check_aggregator_key(&cfg, 103, new).is_ok());
+        assert_eq!(check_aggregator_key(&cfg, 103, old).err(), mismatch);
+
+        // The window saturates instead of wrapping near either end
+        assert!(helpers::in_rotation_grace(2, 5, 1));
+        assert!(helpers::in_rotation_grace(u64::MAX, 5, u64::MAX - 1));
+        assert!(!helpers::in_rotation_grace(100, 0, 100));
+    }
+
+    #[test]
+    fn test_zero_aggregator_key_is_never_accepted() {
+        // A misconfigured init left both keys at default
+        let mut cfg = sample_config(0);
+        cfg.aggregator_pubkey = Pubkey::default();
+        cfg.next_aggregator_pubkey = Pubkey::default();
+        assert_eq!(
+            check_aggregator_key(&cfg, 1, Pubkey::default()).err(),
+            Some(ZkError::AggregatorMismatch.into())
+        );
+
+        let cfg = sample_config(10);
+        assert!(check_aggregator_key(&cfg, 1, cfg.aggregator_pubkey).is_ok());
+        assert_eq!(
+            check_aggregator_key(&cfg, 1, Pubkey::new_unique()).err(),
+            Some(ZkError::AggregatorMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_initialize_rejects_zero_aggregator_key() {
+        let mut args = InitializeArgs {
+            aggregator_pubkey: Pubkey::default(),
+            next_aggregator_pubkey: Pubkey::new_unique(),
+            activation_seq: 10,
+            chain_id: 103,
+            min_lock_amount: 0,
+            max_slots_per_artifact: 0,
+            max_past_skew_secs: 0,
+            max_future_skew_secs: 0,
+        };
+        assert_eq!(
+            check_initialize_args(&args).err(),
+            Some(ZkError::AggregatorMismatch.into())
+        );
+        args.aggregator_pubkey = Pubkey::new_unique();
+        assert!(check_initialize_args(&args).is_ok());
+    }
+
+    #[test]
+    fn test_seeds_version_gates_legacy_and_pooled_instructions() {
+        let mut cfg = sample_config(0);
+        assert!(check_seeds_version(&cfg, SEEDS_VERSION_LEGACY).is_ok());
+        assert_eq!(
+            check_seeds_version(&cfg, SEEDS_VERSION_POOLED).err(),
+            Some(ZkError::SeedsVersionMismatch.into())
+        );
+
+        cfg.seeds_version = SEEDS_VERSION_POOLED;
+        assert!(check_seeds_version(&cfg, SEEDS_VERSION_POOLED).is_ok());
+        assert_eq!(
+            check_seeds_version(&cfg, SEEDS_VERSION_LEGACY).err(),
+            Some(ZkError::SeedsVersionMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_total_locked_tracks_register_and_unlock() {
+        let lock_amount = 1_000_000u64;
+        let mut cfg = sample_config(0);
+        let first = ValidatorRecord {
+            validator_pubkey: Pubkey::default(),
+            ..sample_record(Pubkey::default())
+        };
+        // Two validators register
+        for _ in 0..2 {
+            assert_eq!(
+                check_registration(&first, 0, lock_amount).ok(),
+                Some(Registration::New)
+            );
+            assert!(add_locked(&mut cfg, lock_amount).is_ok());
+        }
+        assert_eq!(cfg.total_locked, 2 * lock_amount);
+
+        // A retried registration that already landed returns before counting again
+        let landed = sample_record(Pubkey::new_unique());
+        assert_eq!(
+            check_registration(&landed, lock_amount, lock_amount).ok(),
+            Some(Registration::AlreadyLocked)
+        );
+        assert_eq!(cfg.total_locked, 2 * lock_amount);
+
+        release_locked(&mut cfg, lock_amount);
+        assert_eq!(cfg.total_locked, lock_amount);
+        release_locked(&mut cfg, lock_amount);
+        assert_eq!(cfg.total_locked, 0);
+
+        // Escrows funded before the counter existed still unlock
+        release_locked(&mut cfg, lock_amount);
+        assert_eq!(cfg.total_locked, 0);
+
+        cfg.total_locked = u64::MAX;
+        assert_eq!(
+            add_locked(&mut cfg, 1).err(),
+            Some(ZkError::MathOverflow.into())
+        );
+        assert_eq!(cfg.total_locked, u64::MAX);
+    }
+
+    #[test]
+    fn test_lock_unlock_round_trip_at_configured_amounts() {
+        let decimals = 6;
+        // (min_lock_amount, escrowed amount); 0 keeps the legacy one-token lock
+        for (configured, locked) in [
+            (0, 1_000_000),
+            (1, 1),
+            (2_500_000, 2_500_000),
+            (10_000_000_000, 10_000_000_000),
+            (u64::MAX, u64::MAX),
+        ] {
+            let mut cfg = sample_config(0);
+            cfg.min_lock_amount = configured;
+            let mut record = ValidatorRecord {
+                validator_pubkey: Pubkey::default(),
+                ..sample_record(Pubkey::default())
+            };
+            // register_validator
+            assert_eq!(cfg.lock_amount(decimals).ok(), Some(locked));
+            assert_eq!(
+                check_registration(&record, 0, locked).ok(),
+                Some(Registration::New)
+            );
+            assert!(add_locked(&mut cfg, locked).is_ok());
+            record.validator_pubkey = Pubkey::new_unique();
+            record.locked_amount = locked;
+
+            // The admin changes the tier; the retry and the unlock still use the recorded lock
+            cfg.min_lock_amount = configured.wrapping_add(7);
+            let recorded = record.lock_amount(decimals).ok();
+            assert_eq!(recorded, Some(locked));
+            assert_eq!(
+                check_registration(&record, locked, locked).ok(),
+                Some(Registration::AlreadyLocked)
+            );
+            assert!(check_unlock_allowed(&cfg, &record, record.validator_pubkey).is_ok());
+            release_locked(&mut cfg, locked);
+            assert_eq!(cfg.total_locked, 0);
+        }
+
+        // Records from before `locked_amount` existed locked one token
+        let legacy = sample_record(Pubkey::new_unique());
+        assert_eq!(legacy.lock_amount(9).ok(), Some(1_000_000_000));
+        assert_eq!(
+            sample_config(0).lock_amount(20).err(),
+            Some(ZkError::MathOverflow.into())
+        );
+    }
+
+    #[test]
+    fn test_resize_config_grows_legacy_configs_for_the_admin() {
+        let cfg = Config {
+            min_lock_amount: 3,
+            ..sample_config(0)
+        };
+        let mut data = Vec::new();
+        assert!(cfg.try_serialize(&mut data).is_ok());
+        let legacy = data.get(..8 + Config::LEGACY_SIZE).unwrap_or_default();
+        assert_eq!(check_config_resize(legacy, &cfg.admin).ok(), Some(true));
+        assert_eq!(check_config_resize(&data, &cfg.admin).ok(), Some(false));
+        for len in Config::PRIOR_SIZES {
+            let prior = data.get(..8 + len).unwrap_or_default();
+            assert_eq!(check_config_resize(prior, &cfg.admin).ok(), Some(true));
+        }
+        assert_eq!(
+            check_config_resize(legacy, &Pubkey::new_unique()).err(),
+            Some(ZkError::Unauthorized.into())
+        );
+        assert!(check_config_resize(data.get(..100).unwrap_or_default(), &cfg.admin).is_err());
+
+        // The zeroed tail reads as the legacy one-token lock
+        let mut grown = legacy.to_vec();
+        grown.resize(8 + Config::SIZE, 0);
+        let resized = Config::try_deserialize(&mut grown.as_slice()).ok();
+        assert_eq!(
+            resized.map(|c| (c.admin, c.min_lock_amount)),
+            Some((cfg.admin, 0))
+        );
+    }
+
+    #[test]
+    fn test_admin_transfer_needs_proposal_and_acceptance() {
+        let mut cfg = sample_config(0);
+        let admin = cfg.admin;
+        let successor = Pubkey::new_unique();
+        assert_eq!(
+            accept_admin_transfer(&mut cfg, successor).err(),
+            Some(ZkError::NoPendingAdmin.into())
+        );
+        assert_eq!(
+            propose_admin_transfer(&mut cfg, successor, successor).err(),
+            Some(ZkError::Unauthorized.into())
+        );
+
+        assert!(propose_admin_transfer(&mut cfg, admin, successor).is_ok());
+        assert_eq!((cfg.admin, cfg.pending_admin), (admin, successor));
+        // Only the proposed key can accept
+        assert_eq!(
+            accept_admin_transfer(&mut cfg, admin).err(),
+            Some(ZkError::Unauthorized.into())
+        );
+        assert_eq!(accept_admin_transfer(&mut cfg, successor).ok(), Some(admin));
+        assert_eq!(
+            (cfg.admin, cfg.pending_admin),
+            (successor, Pubkey::default())
+        );
+        assert_eq!(
+            accept_admin_transfer(&mut cfg, successor).err(),
+            Some(ZkError::NoPendingAdmin.into())
+        );
+
+        // A proposal can be withdrawn before it is accepted
+        let stray = Pubkey::new_unique();
+        assert!(propose_admin_transfer(&mut cfg, successor, stray).is_ok());
+        assert!(propose_admin_transfer(&mut cfg, successor, Pubkey::default()).is_ok());
+        assert_eq!(
+            accept_admin_transfer(&mut cfg, stray).err(),
+            Some(ZkError::NoPendingAdmin.into())
+        );
+    }
+
+    #[test]
+    fn test_register_retry_does_not_double_lock() {
+        let lock_amount = 1_000_000u64;
+        let empty = ValidatorRecord {
+            validator_pubkey: Pubkey::default(),
+            ..sample_record(Pubkey::default())
+        };
+        assert_eq!(
+            check_registration(&empty, 0, lock_amount).ok(),
+            Some(Registration::New)
+        );
+
+        // First submission landed: record set, escrow funded. The retry must not transfer again.
+        let record = sample_record(Pubkey::new_unique());
+        assert_eq!(
+            check_registration(&record, lock_amount, lock_amount).ok(),
+            Some(Registration::AlreadyLocked)
+        );
+
+        // Anything else that already exists is a conflict
+        let unlocked = ValidatorRecord {
+            status: 1,
+            ..sample_record(record.validator_pubkey)
+        };
+        assert_eq!(
+            check_registration(&unlocked, 0, lock_amount).err(),
+            Some(ZkError::AlreadyRegistered.into())
+        );
+        assert_eq!(
+            check_registration(&record, lock_amount * 2, lock_amount).err(),
+            Some(ZkError::AlreadyRegistered.into())
+        );
+    }
+
+    #[test]
+    fn test_validator_ata_must_be_owned_by_validator_and_hold_zksl() {
+        let cfg = sample_config(0);
+        let validator = Pubkey::new_unique();
+        assert!(check_validator_ata(&cfg, validator, validator, cfg.zksl_mint).is_ok());
+        // A token account the validator can authorize but does not own
+        assert_eq!(
+            check_validator_ata(&cfg, validator, Pubkey::new_unique(), cfg.zksl_mint).err(),
+            Some(ZkError::InvalidOwner.into())
+        );
+        assert_eq!(
+            check_validator_ata(&cfg, validator, validator, Pubkey::new_unique()).err(),
+            Some(ZkError::InvalidMint.into())
+        );
+    }
+
+    /// A packed, initialized Token-2022 mint sized for `extensions`, which `init` sets up.
+    fn token_2022_mint(
+        extensions: &[spl_token_2022::extension::ExtensionType],
+        init: impl FnOnce(
+            &mut spl_token_2022::extension::StateWithExtensionsMut<'_, spl_token_2022::state::Mint>,
+        ),
+    ) -> Vec<u8> {
+        use spl_token_2022::extension::{
+            BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+        };
+
+        let len =
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extensions);
+        let mut data = vec![0u8; len.unwrap_or_default()];
+        if let Ok(mut mint) =
+            StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(&mut data)
+        {
+            init(&mut mint);
+            mint.base.decimals = 6;
+            mint.base.is_initialized = true;
+            mint.pack_base();
+            if !extensions.is_empty() {
+                assert!(mint.init_account_type().is_ok());
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_token_2022_needs_the_flag_and_a_compatible_mint() {
+        use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+        use spl_token_2022::extension::{
+            non_transferable::NonTransferable, permanent_delegate::PermanentDelegate,
+            transfer_hook::TransferHook, BaseStateWithExtensionsMut, ExtensionType,
+        };
+
+        let (legacy, token_2022) = (anchor_spl::token::ID, spl_token_2022::ID);
+        let mut cfg = sample_config(0);
+        assert!(check_token_program(&cfg, legacy, legacy).is_ok());
+        let invalid_mint = Some(ZkError::InvalidMint.into());
+        assert_eq!(
+            check_token_program(&cfg, token_2022, token_2022).err(),
+            invalid_mint
+        );
+        cfg.allow_token_2022 = 1;
+        assert!(check_token_program(&cfg, token_2022, token_2022).is_ok());
+        assert!(check_token_program(&cfg, legacy, legacy).is_ok());
+        // The program must own the mint, and nothing else moves zKSL
+        assert_eq!(
+            check_token_program(&cfg, token_2022, legacy).err(),
+            invalid_mint
+        );
+        assert_eq!(
+            check_token_program(&cfg, legacy, token_2022).err(),
+            invalid_mint
+        );
+        let other = Pubkey::new_unique();
+        assert_eq!(check_token_program(&cfg, other, other).err(), invalid_mint);
+
+        // Legacy mints carry no extensions; plain and hooked Token-2022 mints are fine
+        assert!(check_mint_extensions(&legacy, &[0u8; 82]).is_ok());
+        assert!(check_mint_extensions(&token_2022, &token_2022_mint(&[], |_| {})).is_ok());
+        let hooked = token_2022_mint(&[ExtensionType::TransferHook], |m| {
+            assert!(m.init_extension::<TransferHook>(true).is_ok());
+        });
+        assert!(check_mint_extensions(&token_2022, &hooked).is_ok());
+        let fee_mint = |bps: u16| {
+            token_2022_mint(&[ExtensionType::TransferFeeConfig], |m| {
+                if let Ok(fees) = m.init_extension::<TransferFeeConfig>(true) {
+                    fees.newer_transfer_fee.transfer_fee_basis_points = bps.into();
+                }
+            })
+        };
+        assert!(check_mint_extensions(&token_2022, &fee_mint(0)).is_ok());
+
+        let unsupported = Some(ZkError::UnsupportedMintExtension.into());
+        assert_eq!(
+            check_mint_extensions(&token_2022, &fee_mint(25)).err(),
+            unsupported
+        );
+        let delegated = token_2022_mint(&[ExtensionType::PermanentDelegate], |m| {
+            assert!(m.init_extension::<PermanentDelegate>(true).is_ok());
+        });
+        assert_eq!(
+            check_mint_extensions(&token_2022, &delegated).err(),
+            unsupported
+        );
+        let soulbound = token_2022_mint(&[ExtensionType::NonTransferable], |m| {
+            assert!(m.init_extension::<NonTransferable>(true).is_ok());
+        });
+        assert_eq!(
+            check_mint_extensions(&token_2022, &soulbound).err(),
+            unsupported
+        );
+    }
+
+    #[test]
+    fn test_unlock_waits_for_min_lock_duration() {
+        let owner = Pubkey::new_unique();
+        let record = sample_record(owner);
+        let mut cfg = sample_config(0);
+        // The default keeps unlock available right after registering
+        assert!(check_lock_matured(&cfg, &record, record.lock_timestamp).is_ok());
+
+        cfg.min_lock_duration_secs = 86_400;
+        for now in [
+            record.lock_timestamp,
+            record.lock_timestamp + 86_399,
+            record.lock_timestamp - 60,
+        ] {
+            assert_eq!(
+                check_lock_matured(&cfg, &record, now).err(),
+                Some(ZkError::LockNotMatured.into())
+            );
+        }
+        assert!(check_lock_matured(&cfg, &record, record.lock_timestamp + 86_400).is_ok());
+        assert!(check_lock_matured(&cfg, &record, i64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_emergency_unlock_lets_owner_exit_paused_program() {
+        let owner = Pubkey::new_unique();
+        let record = sample_record(owner);
+        let mut cfg = sample_config(0);
+        cfg.paused = 1;
+        assert_eq!(
+            check_unlock_allowed(&cfg, &record, owner).err(),
+            Some(ZkError::Paused.into())
+        );
+
+        cfg.allow_emergency_unlock = 1;
+        assert!(check_unlock_allowed(&cfg, &record, owner).is_ok());
+        assert_eq!(
+            check_unlock_allowed(&cfg, &record, Pubkey::new_unique()).err(),
+            Some(ZkError::Unauthorized.into())
+        );
+        let unlocked = ValidatorRecord {
+            status: 1,
+            ..sample_record(owner)
+        };
+        assert_eq!(
+            check_unlock_allowed(&cfg, &unlocked, owner).err(),
+            Some(ZkError::StatusNotActive.into())
+        );
+    }
+
+    fn anchored_state(last_seq: u64, last_end_slot: u64) -> (AggregatorState, RangeState) {
+        let agg = AggregatorState {
+            aggregator_pubkey: Pubkey::new_unique(),
+            last_seq,
+            epoch_nonce: 0,
+            da_scheme: 0,
+            da_scheme_set: 0,
+            last_timestamp: 1_700_000_000,
+            reserved: [0u8; 68],
+        };
+        let range = RangeState {
+            last_end_slot,
+            submitter: Pubkey::default(),
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        };
+        (agg, range)
+    }
+
+    #[test]
+    fn test_reset_state_is_guarded_and_reopens_anchoring() {
+        let mut cfg = sample_config(0);
+        cfg.paused = 1;
+        let admin = cfg.admin;
+        let (mut agg, mut range) = anchored_state(5, 320);
+
+        assert_eq!(
+            check_state_reset(&cfg, Pubkey::new_unique(), &agg, 5).err(),
+            Some(ZkError::Unauthorized.into())
+        );
+        // The confirmation must repeat the current last_seq
+        assert_eq!(
+            check_state_reset(&cfg, admin, &agg, 4).err(),
+            Some(ZkError::ResetNotConfirmed.into())
+        );
+        assert!(check_state_reset(&cfg, admin, &agg, 5).is_ok());
+
+        reset_anchor_state(&mut agg, &mut range);
+        assert_eq!(
+            (agg.last_seq, agg.last_timestamp, range.last_end_slot),
+            (0, 0, 0)
+        );
+        assert_eq!(agg.aggregator_pubkey, Pubkey::default());
+        assert_eq!(agg.epoch_nonce, 1);
+        assert!(check_seq_and_range(&agg, &range, 1, 1, 64, MAX_SLOTS_PER_ARTIFACT).is_ok());
+        // A second reset confirms against the reset state
+        assert!(check_state_reset(&cfg, admin, &agg, 0).is_ok());
+    }
+
+    #[test]
+    fn test_reset_state_requires_paused_program() {
+        let cfg = sample_config(0);
+        let (agg, _) = anchored_state(5, 320);
+        assert_eq!(cfg.paused, 0);
+        assert_eq!(
+            check_state_reset(&cfg, cfg.admin, &agg, 5).err(),
+            Some(ZkError::NotPaused.into())
+        );
+    }
+
+    #[test]
+    fn test_config_updated_event_carries_only_changed_fields() {
+        let mut cfg = sample_config(7);
+        let before = ConfigView::from(&cfg);
+        let args = UpdateConfigArgs {
+            // Re-setting the current key is not a change
+            aggregator_pubkey: Some(cfg.aggregator_pubkey),
+            next_aggregator_pubkey: None,
+            activation_seq: Some(9),
+            paused: Some(true),
+            max_past_skew_secs: None,
+            max_future_skew_secs: None,
+            allow_emergency_unlock: Some(false),
+            enforce_uniform_da: None,
+            clear_da_scheme: false,
+            bind_proof_hash: None,
+            per_submitter_range: None,
+            min_lock_amount: None,
+            max_proof_age_slots: None,
+            max_slots_per_artifact: None,
+            rotation_grace: None,
+            min_lock_duration_secs: None,
+            allow_token_2022: None,
+        };
+        apply_config_update(&mut cfg, &args);
+        let event = config_updated_event(&before, &ConfigView::from(&cfg), 1_700_000_000);
+        assert_eq!(event.activation_seq, Some(9));
+        assert_eq!(event.paused, Some(true));
+        assert_eq!(event.aggregator_pubkey, None);
+        assert_eq!(event.next_aggregator_pubkey, None);
+        assert_eq!(event.max_past_skew_secs, None);
+        assert_eq!(event.max_future_skew_secs, None);
+        assert_eq!(event.allow_emergency_unlock, None);
+    }
+
+    fn empty_range(submitter: Pubkey) -> RangeState {
+        RangeState {
+            last_end_slot: 0,
+            submitter,
+            last_seq: 0,
+            epoch_nonce: 0,
+            reserved: [0u8; 72],
+        }
+    }
+
+    #[test]
+    fn test_per_submitter_ranges_anchor_disjoint_ranges_concurrently() {
+        let (mut agg, mut global) = anchored_state(0, 0);
+        let mut range_a = empty_range(Pubkey::new_unique());
+        let mut range_b = empty_range(Pubkey::new_unique());
+        let max = MAX_SLOTS_PER_ARTIFACT;
+
+        // A single global range makes the second submitter's independent range overlap
+        record_anchor(&mut agg, &mut global, 1, 64, 1);
+        assert_eq!(
+            check_seq_and_range(&agg, &global, 2, 1_001, 1_064, max).err(),
+            Some(ZkError::RangeOverlap.into())
+        );
+
+        // Per submitter, both histories interleave under the one global seq
+        let (mut agg, _) = anchored_state(0, 0);
+        let anchors = [
+            (1, 1, 64),
+            (2, 1_001, 1_064),
+            (3, 65, 128),
+            (4, 1_065, 1_128),
+        ];
+        for (seq, start, end) in anchors {
+            let range = if start < 1_000 {
+                &mut range_a
+            } else {
+                &mut range_b
+            };
+            assert!(check_seq_and_range(&agg, range, seq, start, end, max).is_ok());
+            record_anchor(&mut agg, range, seq, end, 1);
+        }
+        assert_eq!((range_a.last_seq, range_a.last_end_slot), (3, 128));
+        assert_eq!((range_b.last_seq, range_b.last_end_slot), (4, 1_128));
+        // Contiguity still holds within one submitter's history
+        assert_eq!(
+            check_seq_and_range(&agg, &range_a, 5, 1_065, 1_100, max).err(),
+            Some(ZkError::RangeOverlap.into())
+        );
+        assert!(check_seq_and_range(&agg, &range_a, 5, 129, 192, max).is_ok());
+
+        // A reset advances epoch_nonce, so ranges recorded before it start afresh
+        reset_anchor_state(&mut agg, &mut global);
+        assert!(check_seq_and_range(&agg, &range_a, 1, 1, 64, max).is_ok());
+    }
+
+    #[test]
+    #[cfg(not(feature = "skip-anchor-program"))]
+    fn test_load_range_state_checks_the_expected_pda() {
+        let system = anchor_lang::system_program::ID;
+        let submitter = Pubkey::new_unique();
+        let global_key = Pubkey::find_program_address(&[b"zksl", b"range"], &crate::ID).0;
+        let own_key =
+            Pubkey::find_program_address(&[b"zksl", b"range", submitter.as_ref()], &crate::ID).0;
+        let mut own_data = Vec::new();
+        assert!(empty_range(submitter).try_serialize(&mut own_data).is_ok());
+        let mut global_data = Vec::new();
+        assert!(empty_range(Pubkey::default())
+            .try_serialize(&mut global_data)
+            .is_ok());
+        let mut lamports = [1_000_000u64; 4];
+        let [payer_lamports, system_lamports, own_lamports, global_lamports] = &mut lamports;
+        let (mut payer_data, mut system_data) = (Vec::new(), Vec::new());
+        let payer = AccountInfo::new(
+            &submitter,
+            true,
+            true,
+            payer_lamports,
+            &mut payer_data,
+            &system,
+            false,
+            0,
+        );
+        let system_info = AccountInfo::new(
+            &system,
+            false,
+            false,
+            system_lamports,
+            &mut system_data,
+            &system,
+            true,
+            0,
+        );
+        let own = AccountInfo::new(
+            &own_key,
+            false,
+            true,
+            own_lamports,
+            &mut own_data,
+            &crate::ID,
+            false,
+            0,
+        );
+        let global = AccountInfo::new(
+            &global_key,
+            false,
+            true,
+            global_lamports,
+            &mut global_data,
+            &crate::ID,
+            false,
+            0,
+        );
+        let load = |per_submitter: u8, info| {
+            let mut cfg = sample_config(0);
+            cfg.per_submitter_range = per_submitter;
+            load_range_state(&cfg, (&payer, &system_info), info, &crate::ID).map(|r| r.submitter)
+        };
+
+        assert_eq!(load(0, &global).ok(), Some(Pubkey::default()));
+        assert_eq!(load(1, &own).ok(), Some(submitter));
+        // Each mode only accepts its own PDA
+        assert_eq!(
+            load(0, &own).err(),
+            Some(ZkError::RangeStateMismatch.into())
+        );
+        assert_eq!(
+            load(1, &global).err(),
+            Some(ZkError::RangeStateMismatch.into())
+        );
+    }
+
+    /// Run `f` over the accounts of `ping` (with the instructions sysvar) or `echo_accounts`
+    /// (without), the aggregator and range state PDAs owned by `state_owner`.
+    fn with_debug_accounts<R>(
+        state_owner: &Pubkey,
+        with_sysvar: bool,
+        f: impl for<'a> FnOnce(&'a [AccountInfo<'a>]) -> R,
+    ) -> R {
+        let system = anchor_lang::system_program::ID;
+        let mut config_data = Vec::new();
+        assert!(sample_config(0).try_serialize(&mut config_data).is_ok());
+        // (key, owner, signer, writable, executable, data)
+        let mut accounts = vec![
+            (Pubkey::new_unique(), system, true, true, false, Vec::new()),
+            (
+                Pubkey::new_unique(),
+                crate::ID,
+                false,
+                true,
+                false,
+                config_data,
+            ),
+            (
+                Pubkey::find_program_address(&[b"zksl", b"aggregator"], &crate::ID).0,
+                *state_owner,
+                false,
+                false,
+                false,
+                Vec::new(),
+            ),
+            (
+                Pubkey::find_program_address(&[b"zksl", b"range"], &crate::ID).0,
+                *state_owner,
+                false,
+                false,
+                false,
+                Vec::new(),
+            ),
+            (
+                helpers::proof_record_address(&[4u8; 32], 1, &crate::ID),
+                system,
+                false,
+                false,
+                false,
+                Vec::new(),
+            ),
+        ];
+        if with_sysvar {
+            accounts.push((
+                sysvar_instructions::ID,
+                Pubkey::new_unique(),
+                false,
+                false,
+                false,
+                Vec::new(),
+            ));
+        }
+        accounts.push((system, Pubkey::default(), false, false, true, Vec::new()));
+        with_account_infos(accounts, f)
+    }
+
+    /// Run `f` over `AccountInfo`s for `accounts`, given as
+    /// `(key, owner, signer, writable, executable, data)`.
+    fn with_account_infos<R>(
+        mut accounts: Vec<(Pubkey, Pubkey, bool, bool, bool, Vec<u8>)>,
+        f: impl for<'a> FnOnce(&'a [AccountInfo<'a>]) -> R,
+    ) -> R {
+        let mut balances = vec![1_000_000u64; accounts.len()];
+        let infos: Vec<AccountInfo> = accounts
+            .iter_mut()
+            .zip(balances.iter_mut())
+            .map(
+                |((key, owner, signer, writable, executable, data), lamports)| {
+                    AccountInfo::new(
+                        key,
+                        *signer,
+                        *writable,
+                        lamports,
+                        data,
+                        owner,
+                        *executable,
+                        0,
+                    )
+                },
+            )
+            .collect();
+        f(&infos)
+    }
+
+    fn account_data(account: &impl AccountSerialize) -> Vec<u8> {
+        let mut data = Vec::new();
+        assert!(account.try_serialize(&mut data).is_ok());
+        data
+    }
+
+    #[test]
+    fn test_update_config_takes_aggregator_state_only_when_given() {
+        use std::collections::BTreeSet;
+        let admin = (
+            Pubkey::new_unique(),
+            anchor_lang::system_program::ID,
+            true,
+            false,
+            false,
+            Vec::new(),
+        );
+        let config = (
+            Pubkey::new_unique(),
+            crate::ID,
+            false,
+            true,
+            false,
+            account_data(&sample_config(0)),
+        );
+        let aggregator = Pubkey::find_program_address(&[b"zksl", b"aggregator"], &crate::ID).0;
+        let aggregator_state = (
+            aggregator,
+            crate::ID,
+            false,
+            true,
+            false,
+            account_data(&sample_agg_state(0)),
+        );
+        let update = |accounts| {
+            with_account_infos(accounts, |infos| {
+                let mut infos = infos;
+                let mut bumps = UpdateConfigBumps::default();
+                UpdateConfig::try_accounts(
+                    &crate::ID,
+                    &mut infos,
+                    &[],
+                    &mut bumps,
+                    &mut BTreeSet::new(),
+                )
+                .map(|a| a.aggregator_state.is_some())
+            })
+        };
+        // Clients built before `clear_da_scheme` pass the admin and config only
+        assert_eq!(
+            update(vec![admin.clone(), config.clone()]).ok(),
+            Some(false)
+        );
+        assert_eq!(
+            update(vec![admin, config, aggregator_state]).ok(),
+            Some(true)
+        );
+    }
+
+    /// A pool config is a `Config` owned by this program too, so only the seeds keep it from
+    /// standing in for the legacy config on instructions over the global PDAs.
+    #[test]
+    fn test_admin_instructions_reject_pool_configs() {
+        use std::collections::BTreeSet;
+        let legacy = Pubkey::find_program_address(&[b"zksl", b"config"], &crate::ID).0;
+        let pool = helpers::pool_config_address(1, &crate::ID);
+        let config = |key: Pubkey| {
+            (
+                key,
+                crate::ID,
+                false,
+                false,
+                false,
+                account_data(&sample_config(0)),
+            )
+        };
+        let admin = (
+            Pubkey::new_unique(),
+            anchor_lang::system_program::ID,
+            true,
+            true,
+            false,
+            Vec::new(),
+        );
+        let global = |seed: &[u8], data: Vec<u8>| {
+            (
+                Pubkey::find_program_address(&[b"zksl", seed], &crate::ID).0,
+                crate::ID,
+                false,
+                true,
+                false,
+                data,
+            )
+        };
+
+        let reset = |config_key: Pubkey| {
+            let accounts = vec![
+                admin.clone(),
+                config(config_key),
+                global(b"aggregator", account_data(&sample_agg_state(0))),
+                global(b"range", account_data(&empty_range(Pubkey::default()))),
+            ];
+            with_account_infos(accounts, |infos| {
+                let mut infos = infos;
+                ResetState::try_accounts(
+                    &crate::ID,
+                    &mut infos,
+                    &[],
+                    &mut ResetStateBumps::default(),
+                    &mut BTreeSet::new(),
+                )
+                .map(|_| ())
+            })
+        };
+        let (record, _) = empty_records();
+        let record_key = helpers::proof_record_address(&[4u8; 32], 1, &crate::ID);
+        let proof_record = (
+            record_key,
+            crate::ID,
+            false,
+            true,
+            false,
+            account_data(&record),
+        );
+        let set_level = |config_key: Pubkey| {
+            let mut ix_data = 1u64.to_le_bytes().to_vec();
+            ix_data.extend_from_slice(&[4u8; 32]);
+            let accounts = vec![admin.clone(), config(config_key), proof_record.clone()];
+            with_account_infos(accounts, |infos| {
+                let mut infos = infos;
+                let mut bumps = SetCommitmentLevelBumps::default();
+                SetCommitmentLevel::try_accounts(
+                    &crate::ID,
+                    &mut infos,
+                    &ix_data,
+                    &mut bumps,
+                    &mut BTreeSet::new(),
+                )
+                .map(|_| ())
+            })
+        };
+        let close = |config_key: Pubkey| {
+            let accounts = vec![admin.clone(), config(config_key), proof_record.clone()];
+            with_account_infos(accounts, |infos| {
+                let mut infos = infos;
+                let mut bumps = CloseProofRecordBumps::default();
+                CloseProofRecord::try_accounts(
+                    &crate::ID,
+                    &mut infos,
+                    &[],
+                    &mut bumps,
+                    &mut BTreeSet::new(),
+                )
+                .map(|_| ())
+            })
+        };
+        let rejected = Some(anchor_lang::error::ErrorCode::ConstraintSeeds.into());
+        assert!(reset(legacy).is_ok());
+        assert_eq!(reset(pool).err(), rejected);
+        assert!(set_level(legacy).is_ok());
+        assert_eq!(set_level(pool).err(), rejected);
+        assert!(close(legacy).is_ok());
+        assert_eq!(close(pool).err(), rejected);
+    }
+
+    #[test]
+    fn test_debug_contexts_reject_state_accounts_of_another_program() {
+        use std::collections::BTreeSet;
+        let ping = |owner: Pubkey| {
+            with_debug_accounts(&owner, true, |infos| {
+                let mut infos = infos;
+                Ping::try_accounts(
+                    &crate::ID,
+                    &mut infos,
+                    &[],
+                    &mut PingBumps::default(),
+                    &mut BTreeSet::new(),
+                )
+                .map(|_| ())
+            })
+        };
+        let echo = |owner: Pubkey| {
+            let mut ix_data = [4u8; 32].to_vec();
+            ix_data.extend_from_slice(&1u64.to_le_bytes());
+            with_debug_accounts(&owner, false, |infos| {
+                let mut infos = infos;
+                let mut bumps = EchoAccountsBumps::default();
+                EchoAccounts::try_accounts(
+                    &crate::ID,
+                    &mut infos,
+                    &ix_data,
+                    &mut bumps,
+                    &mut BTreeSet::new(),
+                )
+                .map(|_| ())
+            })
+        };
+        assert!(ping(crate::ID).is_ok());
+        assert!(echo(crate::ID).is_ok());
+        // Same addresses, but the accounts belong to another program
+        let look_alike = Pubkey::new_unique();
+        let rejected = Some(anchor_lang::error::ErrorCode::ConstraintOwner.into());
+        assert_eq!(ping(look_alike).err(), rejected);
+        assert_eq!(echo(look_alike).err(), rejected);
+    }
+
+    /// End-to-end client flow over the handlers' checks and state transitions: initialize,
+    /// register (escrow one token), anchor with a genuine Ed25519 preflight over DS, unlock.
+    ///
+    /// Token transfers are CPIs and not executed here; balances are tracked alongside.
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn test_register_anchor_unlock_flow() {
+        use ed25519_dalek::{Signer, SigningKey, Verifier};
+
+        let program_id = Pubkey::new_unique();
+        let signing_key = SigningKey::from_bytes(&[42u8; 32]);
+        let aggregator = Pubkey::new_from_array(signing_key.verifying_key().to_bytes());
+        let lock_amount = 1_000_000u64; // 1 token at 6 decimals
+
+        // initialize + init_state
+        let mut cfg = sample_config(0);
+        let args = InitializeArgs {
+            aggregator_pubkey: aggregator,
+            next_aggregator_pubkey: aggregator,
+            activation_seq: 1,
+            chain_id: 103,
+            min_lock_amount: 0,
+            max_slots_per_artifact: 0,
+            max_past_skew_secs: 0,
+            max_future_skew_secs: 0,
+        };
+        apply_initialize(&mut cfg, Pubkey::new_unique(), Pubkey::new_unique(), &args);
+        let mut agg = sample_agg_state(0);
+        let mut range = empty_range(Pubkey::default());
+        reset_anchor_state(&mut agg, &mut range);
+
+        // register_validator: escrow one token
+        let validator = Pubkey::new_unique();
+        let escrow = helpers::escrow_ata(
+            &validator,
+            &cfg.zksl_mint,
+            &program_id,
+            &anchor_spl::token::ID,
+        );
+        let mut record = ValidatorRecord {
+            validator_pubkey: Pubkey::default(),
+            ..sample_record(Pubkey::default())
+        };
+        let (mut validator_balance, mut escrow_balance) = (lock_amount, 0u64);
+        assert_eq!(
+            check_registration(&record, escrow_balance, lock_amount).ok(),
+            Some(Registration::New)
+        );
+        validator_balance -= lock_amount;
+        escrow_balance += lock_amount;
+        record.validator_pubkey = validator;
+        record.lock_token_account = escrow;
+        assert_eq!((validator_balance, escrow_balance), (0, lock_amount));
+
+        // anchor_proof: [ComputeBudget, Ed25519, anchor_proof]
+        let (seq, start_slot, end_slot) = (agg.last_seq + 1, 1_000u64, 1_063u64);
+        let proof_hash = [3u8; 32];
+        let ds = build_ds(
+            cfg.chain_id,
+            &program_id,
+            &proof_hash,
+            start_slot,
+            end_slot,
+            seq,
+            agg.epoch_nonce,
+        );
+        let signature = signing_key.sign(&ds).to_bytes();
+        let ed_data = helpers::ed25519_instruction_data(&aggregator.to_bytes(), &signature, &ds)
+            .ok()
+            .unwrap_or_default();
+
+        assert!(check_preflight_programs(
+            [COMPUTE_BUDGET_PROGRAM_ID, ED25519_PROGRAM_ID, program_id],
+            1
+        )
+        .is_ok());
+        assert_eq!(allowed_aggregator_key(&cfg, seq), aggregator);
+        assert!(check_seq_and_range(
+            &agg,
+            &range,
+            seq,
+            start_slot,
+            end_slot,
+            MAX_SLOTS_PER_ARTIFACT
+        )
+        .is_ok());
+        assert!(check_clock_skew(1_700_000_000, 1_700_000_000, 120, 5).is_ok());
+        let signed = parse_ed25519_single(&ed_data).ok();
+        assert_eq!(signed.map(|f| f.pubkey), Some(aggregator.as_ref()));
+        assert_eq!(signed.map(|f| f.message), Some(ds.as_slice()));
+        // What the Ed25519 program checks before anchor_proof runs
+        let sig = signed
+            .and_then(|f| ed25519_dalek::Signature::from_slice(f.signature).ok())
+            .map(|s| signing_key.verifying_key().verify(&ds, &s).is_ok());
+        assert_eq!(sig, Some(true));
+        assert_eq!(check_commitment_level(2).ok(), Some(2));
+        record_anchor(&mut agg, &mut range, seq, end_slot, 1_700_000_000);
+
+        let hb = heartbeat_event(&cfg, &agg, &range, 1_700_000_001);
+        assert_eq!(hb.aggregator_pubkey, aggregator);
+        assert_eq!((hb.last_seq, hb.last_end_slot), (1, 1_063));
+        // The same signed DS cannot be anchored again
+        assert_eq!(
+            check_seq_and_range(
+                &agg,
+                &range,
+                seq,
+                start_slot,
+                end_slot,
+                MAX_SLOTS_PER_ARTIFACT
+            )
+            .err(),
+            Some(ZkError::NonMonotonicSeq.into())
+        );
+
+        // unlock_validator: escrow returns to the validator, record becomes inactive
+        assert_eq!(
+            check_unlock_allowed(&cfg, &record, Pubkey::new_unique()).err(),
+            Some(ZkError::Unauthorized.into())
+        );
+        assert!(check_unlock_allowed(&cfg, &record, validator).is_ok());
+        assert_eq!(escrow_balance, lock_amount);
+        escrow_balance -= lock_amount;
+        validator_balance += lock_amount;
+        record.status = 1;
+        assert_eq!((validator_balance, escrow_balance), (lock_amount, 0));
+        assert_eq!(
+            check_unlock_allowed(&cfg, &record, validator).err(),
+            Some(ZkError::StatusNotActive.into())
+        );
     }
 }
@@ -0,0 +1,85 @@
+//! Backend comparison: proves and verifies one synthetic slot range through both the
+//! `stark` (f62 / Blake3-256) and `north_star` (f64 / Rp64-256, quadratic extension) provers.
+//!
+//! Run with `cargo bench --features stark`. Each backend gets a `prove` and a `verify`
+//! benchmark; proof sizes are printed once per backend before its timings.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use zksl_prover::backend::{self, BackendInputs};
+use zksl_prover::witness::{synthetic_witnesses, Commitment, SlotWitness};
+use zksl_prover::{north_star, stark};
+
+/// First slot of the synthetic range.
+const START_SLOT: u64 = 1_000;
+/// Slots in the synthetic range; a power of two >= 8.
+const SLOTS: usize = 8;
+/// Vote accounts per synthetic slot.
+const ACCOUNTS_PER_SLOT: usize = 16;
+
+/// Base64 payload length to decoded byte count.
+const fn decoded_len(b64: &str) -> usize {
+    b64.len() / 4 * 3
+}
+
+fn stark_inputs(first: &SlotWitness, last: &SlotWitness, witnesses: &[SlotWitness]) -> BackendInputs<'static> {
+    BackendInputs::Stark(stark::PublicInputs {
+        start: first.slot,
+        end: last.slot,
+        before: first.state_root,
+        after: last.state_root,
+        proof_hash: [0u8; 32],
+        c_in_hex: String::new(),
+        c_out_hex: String::new(),
+        h_b_hex: String::new(),
+        s_in: Vec::new(),
+        s_out: Vec::new(),
+        commitment: Commitment::Finalized,
+        chain_id: 0,
+        endpoints: stark::AggregateEndpoints::from_witnesses(witnesses),
+        participation_ppm: stark::vote_participation_ppm(witnesses),
+        merkle_version: first.merkle_version,
+    })
+}
+
+fn north_star_inputs(first: &SlotWitness, last: &SlotWitness, witnesses: &[SlotWitness]) -> BackendInputs<'static> {
+    BackendInputs::NorthStar {
+        pub_inputs: north_star::PublicInputs {
+            start_slot: first.slot,
+            end_slot: last.slot,
+            initial_state_root: first.state_root,
+            final_state_root: north_star::chain_final_root(&first.state_root, witnesses.len()),
+            blockhash: [0u8; 32],
+            scope: north_star::ProofScope::Full,
+        },
+        coverage: north_star::SlotCoverage::Contiguous,
+    }
+}
+
+/// Benchmark proving and verification of `inputs` through [`backend::generate`] and
+/// [`backend::verify`].
+fn bench_backend(c: &mut Criterion, inputs: &BackendInputs<'_>, witnesses: &[SlotWitness]) {
+    let name = inputs.backend().name();
+    let proof = backend::generate(inputs.clone(), witnesses).expect("prove");
+    println!("{name}: {SLOTS} slots, proof {} bytes", decoded_len(proof.proof_b64()));
+
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    group.bench_function("prove", |b| b.iter(|| backend::generate(inputs.clone(), witnesses).expect("prove")));
+    group.bench_function("verify", |b| {
+        b.iter_batched(|| proof.clone(), |p| backend::verify(p).expect("verify"), BatchSize::SmallInput);
+    });
+    group.finish();
+}
+
+fn backends(c: &mut Criterion) {
+    let witnesses = synthetic_witnesses(START_SLOT, SLOTS, ACCOUNTS_PER_SLOT);
+    let (Some(first), Some(last)) = (witnesses.first(), witnesses.last()) else {
+        panic!("no witnesses to benchmark");
+    };
+    bench_backend(c, &stark_inputs(first, last, &witnesses), &witnesses);
+    bench_backend(c, &north_star_inputs(first, last, &witnesses), &witnesses);
+}
+
+criterion_group!(benches, backends);
+criterion_main!(benches);
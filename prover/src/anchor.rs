@@ -0,0 +1,225 @@
+//! Off-chain mirror of `validator_lock::anchor_proof`'s domain separation and argument checks
+
+use blake3::Hasher as Blake3;
+
+/// DS prefix (`DS_PREFIX` in the program).
+pub const DS_PREFIX: &[u8] = b"zKSL/anchor/v1";
+/// Length of a [`build_ds`] message without `epoch_nonce` (`ds_len(1)` in the program).
+pub const DS_LEN: usize = DS_PREFIX.len() + 8 + 32 + 32 + 8 + 8 + 8;
+/// Bytes a non-zero `epoch_nonce` appends (`DS_NONCE_LEN` in the program).
+pub const DS_NONCE_LEN: usize = 8;
+/// Prefix of the v2 DS, which also binds `artifact_len` (`DS_PREFIX_V2` in the program).
+#[cfg(feature = "stark")]
+pub const DS_PREFIX_V2: &[u8] = b"zKSL/anchor/v2";
+/// `ds_version` of the [`build_ds`] layout.
+#[cfg(feature = "stark")]
+pub const DS_VERSION_V1: u8 = 1;
+/// `ds_version` of the [`build_ds_v2`] layout.
+#[cfg(feature = "stark")]
+pub const DS_VERSION_V2: u8 = 2;
+/// Largest inclusive slot span one artifact may cover (`MAX_SLOTS_PER_ARTIFACT`).
+#[cfg(feature = "stark")]
+pub const MAX_SLOTS_PER_ARTIFACT: u64 = 2048;
+/// Largest `artifact_len` the program records (`MAX_ARTIFACT_SIZE_BYTES`).
+#[cfg(feature = "stark")]
+pub const MAX_ARTIFACT_SIZE_BYTES: u32 = 512 * 1024;
+/// Highest accepted `commitment_level` (finalized).
+#[cfg(feature = "stark")]
+pub const MAX_COMMITMENT_LEVEL: u8 = 2;
+
+/// The DS message the aggregator signs, laid out exactly as the program's `build_ds`.
+///
+/// `DS_PREFIX || chain_id || program_id || proof_hash || start || end || seq [|| epoch_nonce]`,
+/// integers little-endian and `epoch_nonce` only when non-zero.
+#[must_use]
+pub fn build_ds(
+    chain_id: u64,
+    program_id: &[u8; 32],
+    proof_hash: &[u8; 32],
+    start_slot: u64,
+    end_slot: u64,
+    seq: u64,
+    epoch_nonce: u64,
+) -> Vec<u8> {
+    let mut ds = Vec::with_capacity(DS_LEN.saturating_add(DS_NONCE_LEN));
+    ds.extend_from_slice(DS_PREFIX);
+    ds.extend_from_slice(&chain_id.to_le_bytes());
+    ds.extend_from_slice(program_id);
+    ds.extend_from_slice(proof_hash);
+    ds.extend_from_slice(&start_slot.to_le_bytes());
+    ds.extend_from_slice(&end_slot.to_le_bytes());
+    ds.extend_from_slice(&seq.to_le_bytes());
+    if epoch_nonce != 0 {
+        ds.extend_from_slice(&epoch_nonce.to_le_bytes());
+    }
+    ds
+}
+
+/// The v2 DS, laid out as the program's `compute_ds_message_v2`: [`build_ds`] under
+/// [`DS_PREFIX_V2`] with `artifact_len` (little-endian) before the optional `epoch_nonce`.
+#[cfg(feature = "stark")]
+#[allow(clippy::too_many_arguments)]
+#[must_use]
+pub fn build_ds_v2(
+    chain_id: u64,
+    program_id: &[u8; 32],
+    proof_hash: &[u8; 32],
+    start_slot: u64,
+    end_slot: u64,
+    seq: u64,
+    artifact_len: u32,
+    epoch_nonce: u64,
+) -> Vec<u8> {
+    let mut ds = build_ds(chain_id, program_id, proof_hash, start_slot, end_slot, seq, 0);
+    ds.splice(..DS_PREFIX.len(), DS_PREFIX_V2.iter().copied());
+    ds.extend_from_slice(&artifact_len.to_le_bytes());
+    if epoch_nonce != 0 {
+        ds.extend_from_slice(&epoch_nonce.to_le_bytes());
+    }
+    ds
+}
+
+/// `ds_hash` argument for `ds`.
+#[must_use]
+pub fn ds_hash(ds: &[u8]) -> [u8; 32] {
+    let mut hasher = Blake3::new();
+    hasher.update(ds);
+    *hasher.finalize().as_bytes()
+}
+
+/// Positional arguments of `anchor_proof`, in instruction order.
+#[cfg(feature = "stark")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnchorProofArgs {
+    /// Arg 0: artifact UUID bytes.
+    pub artifact_id: [u8; 16],
+    /// Arg 1: canonical proof hash.
+    pub proof_hash: [u8; 32],
+    /// Arg 2: anchoring sequence number.
+    pub seq: u64,
+    /// Arg 3: first slot of the range.
+    pub start_slot: u64,
+    /// Arg 4: last slot of the range.
+    pub end_slot: u64,
+    /// Arg 5: size of the artifact file in bytes.
+    pub artifact_len: u32,
+    /// Arg 6: state root before the range.
+    pub state_root_before: [u8; 32],
+    /// Arg 7: state root after the range.
+    pub state_root_after: [u8; 32],
+    /// Arg 8: aggregator key that signs the DS.
+    pub aggregator_pubkey: [u8; 32],
+    /// Arg 9: unix timestamp checked against the cluster clock.
+    pub timestamp: i64,
+    /// Arg 10: blake3 of the DS.
+    pub ds_hash: [u8; 32],
+    /// Arg 11: RPC commitment level of the witness (0 processed, 1 confirmed, 2 finalized).
+    pub commitment_level: u8,
+    /// Arg 12: DA parameters; byte 0 is the scheme tag.
+    pub da_params: [u8; 12],
+    /// Arg 13: DS layout, [`DS_VERSION_V1`] or [`DS_VERSION_V2`].
+    pub ds_version: u8,
+}
+
+#[cfg(feature = "stark")]
+impl AnchorProofArgs {
+    /// The DS these arguments make the program rebuild for `(chain_id, program_id, epoch_nonce)`.
+    ///
+    /// Any `ds_version` other than [`DS_VERSION_V2`] is treated as v1; `preflight` rejects it.
+    pub fn ds(&self, chain_id: u64, program_id: &[u8; 32], epoch_nonce: u64) -> Vec<u8> {
+        let (proof_hash, start, end, seq) = (&self.proof_hash, self.start_slot, self.end_slot, self.seq);
+        if self.ds_version == DS_VERSION_V2 {
+            build_ds_v2(chain_id, program_id, proof_hash, start, end, seq, self.artifact_len, epoch_nonce)
+        } else {
+            build_ds(chain_id, program_id, proof_hash, start, end, seq, epoch_nonce)
+        }
+    }
+
+    /// Run the checks `anchor_proof` makes that need no on-chain state, so a bad argument
+    /// fails here instead of in a submitted transaction.
+    ///
+    /// Not covered: `seq`/slot continuity with the last anchored proof, clock skew, the
+    /// aggregator rotation schedule and the Ed25519 instruction itself.
+    pub fn preflight(&self, chain_id: u64, program_id: &[u8; 32], epoch_nonce: u64) -> anyhow::Result<()> {
+        if self.seq == 0 {
+            anyhow::bail!("seq must be at least 1");
+        }
+        let span = self.end_slot.checked_sub(self.start_slot).and_then(|d| d.checked_add(1));
+        if !span.is_some_and(|span| span <= MAX_SLOTS_PER_ARTIFACT) {
+            anyhow::bail!(
+                "slot range {}..={} is empty or spans more than {MAX_SLOTS_PER_ARTIFACT} slots",
+                self.start_slot,
+                self.end_slot
+            );
+        }
+        if self.artifact_len > MAX_ARTIFACT_SIZE_BYTES {
+            anyhow::bail!("artifact_len {} exceeds {MAX_ARTIFACT_SIZE_BYTES}", self.artifact_len);
+        }
+        if self.aggregator_pubkey == [0u8; 32] {
+            anyhow::bail!("aggregator_pubkey is the default key");
+        }
+        if self.ds_version != DS_VERSION_V1 && self.ds_version != DS_VERSION_V2 {
+            anyhow::bail!("ds_version {} is neither {DS_VERSION_V1} nor {DS_VERSION_V2}", self.ds_version);
+        }
+        if self.commitment_level > MAX_COMMITMENT_LEVEL {
+            anyhow::bail!("commitment_level {} exceeds {MAX_COMMITMENT_LEVEL}", self.commitment_level);
+        }
+        if ds_hash(&self.ds(chain_id, program_id, epoch_nonce)) != self.ds_hash {
+            anyhow::bail!("ds_hash does not match the DS for chain {chain_id} and epoch nonce {epoch_nonce}");
+        }
+        Ok(())
+    }
+
+    /// JSON view with byte fields hex-encoded, keyed by the program's argument names.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "artifact_id": hex::encode(self.artifact_id),
+            "proof_hash": hex::encode(self.proof_hash),
+            "seq": self.seq,
+            "start_slot": self.start_slot,
+            "end_slot": self.end_slot,
+            "artifact_len": self.artifact_len,
+            "state_root_before": hex::encode(self.state_root_before),
+            "state_root_after": hex::encode(self.state_root_after),
+            "aggregator_pubkey": bs58::encode(self.aggregator_pubkey).into_string(),
+            "timestamp": self.timestamp,
+            "ds_hash": hex::encode(self.ds_hash),
+            "commitment_level": self.commitment_level,
+            "da_params": hex::encode(self.da_params),
+            "ds_version": self.ds_version,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ds_layout_matches_program() {
+        let ds = build_ds(103, &[1u8; 32], &[2u8; 32], 10, 20, 3, 0);
+        // 14-byte prefix, chain, program, proof hash, start, end, seq
+        assert_eq!(ds.len(), 110);
+        assert_eq!(DS_LEN, 110);
+        assert!(ds.starts_with(DS_PREFIX));
+        assert_eq!(ds.get(102..), Some(&3u64.to_le_bytes()[..]));
+        let with_nonce = build_ds(103, &[1u8; 32], &[2u8; 32], 10, 20, 3, 5);
+        assert_eq!(with_nonce.len(), DS_LEN + DS_NONCE_LEN);
+        assert_eq!(with_nonce.split_at(DS_LEN), (&ds[..], &5u64.to_le_bytes()[..]));
+        assert_ne!(ds_hash(&ds), ds_hash(&with_nonce));
+    }
+
+    #[cfg(feature = "stark")]
+    #[test]
+    fn test_ds_v2_binds_artifact_len() {
+        let v1 = build_ds(103, &[1u8; 32], &[2u8; 32], 10, 20, 3, 5);
+        let v2 = build_ds_v2(103, &[1u8; 32], &[2u8; 32], 10, 20, 3, 4_096, 5);
+        // 14-byte v2 prefix, the v1 fields, artifact_len, then the nonce
+        assert_eq!(v2.len(), 122);
+        assert!(v2.starts_with(DS_PREFIX_V2));
+        assert_eq!(v2.get(14..DS_LEN), v1.get(14..DS_LEN));
+        assert_eq!(v2.get(DS_LEN..114), Some(&4_096u32.to_le_bytes()[..]));
+        assert_eq!(v2.get(114..), Some(&5u64.to_le_bytes()[..]));
+        assert_ne!(v2, build_ds_v2(103, &[1u8; 32], &[2u8; 32], 10, 20, 3, 4_097, 5));
+    }
+}
@@ -0,0 +1,157 @@
+//! The two proof backends behind one entry point.
+//!
+//! `stark` (`stark::SolanaStateAir`, f62 / Blake3-256) and `north_star`
+//! (`north_star::SolanaStateAir`, f64 / Rp64-256) each define their own `PublicInputs` and
+//! proof format, and neither verifies the other's proofs. [`generate`] and [`verify`] take
+//! the backend from their argument, so callers do not have to pick the matching module.
+//!
+//! On chain, `validator_lock::anchor_proof` runs no STARK verifier: it checks the
+//! aggregator's Ed25519 signature over a DS binding the proof hash and slot range. Only
+//! `stark` outputs produce those arguments (`StarkOutput::to_anchor_args`), so anchored
+//! proofs are `stark` proofs, verified off chain with `stark::verify_stark_proof`.
+//! `north_star` envelopes cannot be anchored yet.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::witness::SlotWitness;
+use crate::{north_star, stark};
+
+/// Prover a proof was generated by; a proof only verifies under its own backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofBackend {
+    /// `stark`: f62 field, Blake3-256, revisioned AIR (`stark::AirRevision`). Anchored proofs
+    /// use this backend.
+    Stark,
+    /// `north_star`: f64 field, Rp64-256 with the quadratic extension (Option A AIR).
+    NorthStar,
+}
+
+impl ProofBackend {
+    /// Name of the backend's module, as used in reports.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Stark => "stark",
+            Self::NorthStar => "north_star",
+        }
+    }
+}
+
+/// Public inputs of either backend; the variant selects the backend that proves them.
+#[derive(Clone, Debug)]
+pub enum BackendInputs<'a> {
+    /// Inputs of a `stark` proof under `AirRevision::CURRENT`.
+    Stark(stark::PublicInputs),
+    /// Inputs of a `north_star` proof and the slots its witnesses claim to cover.
+    NorthStar {
+        /// Public inputs bound to the proof.
+        pub_inputs: north_star::PublicInputs,
+        /// Slots between the endpoints the witnesses cover.
+        coverage: north_star::SlotCoverage<'a>,
+    },
+}
+
+impl BackendInputs<'_> {
+    /// Backend these inputs are proven by.
+    pub const fn backend(&self) -> ProofBackend {
+        match self {
+            Self::Stark(_) => stark::BACKEND,
+            Self::NorthStar { .. } => north_star::BACKEND,
+        }
+    }
+}
+
+/// A proof of either backend.
+#[derive(Clone)]
+pub enum BackendProof {
+    /// `stark` output, signed or not.
+    Stark(stark::StarkOutput),
+    /// `north_star` envelope.
+    NorthStar(north_star::StarkProofEnvelope),
+}
+
+impl BackendProof {
+    /// Backend that generated this proof.
+    pub const fn backend(&self) -> ProofBackend {
+        match self {
+            Self::Stark(_) => stark::BACKEND,
+            Self::NorthStar(_) => north_star::BACKEND,
+        }
+    }
+
+    /// Base64-encoded proof bytes.
+    pub fn proof_b64(&self) -> &str {
+        match self {
+            Self::Stark(out) => &out.proof_b64,
+            Self::NorthStar(envelope) => &envelope.proof,
+        }
+    }
+}
+
+/// Prove `witnesses` with the backend `inputs` belong to.
+pub fn generate(inputs: BackendInputs<'_>, witnesses: &[SlotWitness]) -> Result<BackendProof> {
+    match inputs {
+        BackendInputs::Stark(pub_inputs) => {
+            stark::prove_from_witnesses(pub_inputs, witnesses, stark::AirRevision::CURRENT).map(BackendProof::Stark)
+        }
+        BackendInputs::NorthStar { pub_inputs, coverage } => {
+            north_star::generate_proof(pub_inputs, witnesses, coverage).map(BackendProof::NorthStar)
+        }
+    }
+}
+
+/// Verify `proof` with the backend that generated it; a rejected proof is an error.
+pub fn verify(proof: BackendProof) -> Result<()> {
+    match proof {
+        BackendProof::Stark(out) => stark::verify_stark_proof(&out),
+        BackendProof::NorthStar(envelope) => match north_star::verify_proof(envelope)? {
+            north_star::VerifyOutcome::Valid => Ok(()),
+            north_star::VerifyOutcome::Invalid { reason } => anyhow::bail!("north_star proof rejected: {reason}"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::witness::synthetic_witnesses;
+
+    #[test]
+    fn test_each_backend_verifies_its_own_proof() {
+        let witnesses = synthetic_witnesses(1_000, 8, 2);
+        let (first, last) = (&witnesses[0], &witnesses[7]);
+        let stark_inputs = stark::PublicInputs {
+            start: first.slot,
+            end: last.slot,
+            before: first.state_root,
+            after: last.state_root,
+            proof_hash: [0u8; 32],
+            c_in_hex: String::new(),
+            c_out_hex: String::new(),
+            h_b_hex: String::new(),
+            s_in: Vec::new(),
+            s_out: Vec::new(),
+            commitment: crate::witness::Commitment::Finalized,
+            chain_id: 0,
+            endpoints: stark::AggregateEndpoints::from_witnesses(&witnesses),
+            participation_ppm: stark::vote_participation_ppm(&witnesses),
+            merkle_version: first.merkle_version,
+        };
+        // winter-prover's debug-build degree check needs north_star witnesses that vary in
+        // every column, which contiguous synthetic slots do not
+        let (north_star_inputs, north_star_witnesses) = north_star::tests::sample(north_star::ProofScope::Full);
+        let coverage = north_star::SlotCoverage::Explicit(&north_star::tests::SAMPLE_SLOTS);
+        for (inputs, witnesses, name) in [
+            (BackendInputs::Stark(stark_inputs), &witnesses, "stark"),
+            (BackendInputs::NorthStar { pub_inputs: north_star_inputs, coverage }, &north_star_witnesses, "north_star"),
+        ] {
+            let backend = inputs.backend();
+            assert_eq!(backend.name(), name);
+            assert_eq!(serde_json::to_value(backend).expect("json"), name);
+            let proof = generate(inputs, witnesses).expect("prove");
+            assert_eq!(proof.backend(), backend);
+            verify(proof).expect("verify");
+        }
+    }
+}
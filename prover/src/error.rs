@@ -0,0 +1,227 @@
+//! Typed prover errors for conditions callers may want to match on.
+//!
+//! Functions still return `anyhow::Result`; these ride inside it and can be recovered with
+//! `anyhow::Error::downcast_ref::<ProverError>()`.
+
+use std::fmt;
+
+use winter_air::{Assertion, TraceInfo};
+use winter_math::fields::f62::BaseElement;
+use winter_math::StarkField;
+use winter_prover::{Trace, TraceTable};
+
+/// Shortest trace winterfell will prove.
+const MIN_TRACE_LENGTH: usize = TraceInfo::MIN_TRACE_LENGTH;
+
+/// Errors raised before proving starts, when the request can never produce an anchorable proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProverError {
+    /// More witnesses than the on-chain window allows; refused before allocating the trace.
+    TooManyWitnesses {
+        /// Number of witnesses supplied.
+        got: usize,
+        /// Maximum accepted (`MAX_WITNESSES`).
+        max: usize,
+    },
+    /// An envelope field disagrees with the anchored `ProofRecord`.
+    RecordMismatch {
+        /// Name of the mismatching envelope public input.
+        field: &'static str,
+    },
+    /// The proof does not verify against its own public inputs.
+    ProofRejected,
+    /// A North Star public input disagrees with the one regenerated from the chain.
+    NorthStarMismatch {
+        /// Name of the diverging input (`c_in`, `c_out`, `h_b`, `s_in` or `s_out`).
+        field: &'static str,
+    },
+    /// A vote account in the RPC response has more epoch-credit entries than Solana keeps;
+    /// the response is refused rather than truncated so the committed root stays deterministic.
+    TooManyEpochCredits {
+        /// Entries on the offending account.
+        got: usize,
+        /// Maximum accepted (`MAX_EPOCH_CREDITS`).
+        max: usize,
+    },
+    /// The trace length winterfell would be handed is not a power of two of at least
+    /// `TraceInfo::MIN_TRACE_LENGTH`; refused before the trace is built.
+    TraceLengthNotPow2 {
+        /// Trace length implied by the request.
+        len: usize,
+    },
+    /// The witnesses do not cover exactly the slots the proof claims: a contiguous range
+    /// needs one witness per slot, an explicit slot list one witness per listed slot, in order.
+    WitnessCountMismatch {
+        /// Witnesses the claimed slots require.
+        expected: usize,
+        /// Witnesses supplied.
+        got: usize,
+    },
+    /// The proof is valid but bound to a chain outside the verifier's allowed set.
+    ChainNotAllowed {
+        /// `chain_id` bound into the proof's public inputs.
+        got: u64,
+    },
+    /// A boundary cell of the built trace disagrees with the value the public inputs make
+    /// the AIR assert there; proving would fail with an opaque unsatisfied assertion.
+    TraceBoundaryMismatch {
+        /// Trace column of the assertion.
+        column: usize,
+        /// Row of the assertion.
+        step: usize,
+        /// Value required by the public inputs.
+        expected: u64,
+        /// Value in the trace.
+        got: u64,
+    },
+    /// A slot is not below the modulus of the field element the STARK AIR encodes it in, so
+    /// it would alias a smaller slot. The North Star AIR keeps slots as full u64.
+    SlotExceedsField {
+        /// The offending slot.
+        slot: u64,
+    },
+    /// The RPC endpoint failed in a way that may clear up if the request is repeated.
+    RpcUnavailable {
+        /// HTTP status of the response (429 or 5xx); `None` for a timeout or refused connection.
+        status: Option<u16>,
+    },
+    /// Fetching a slot's witness data from RPC failed; the underlying RPC error is the cause.
+    SlotFetchFailed {
+        /// The slot that could not be fetched.
+        slot: u64,
+    },
+    /// A claimed slot range ends before it starts.
+    SlotRangeReversed {
+        /// Claimed first slot.
+        start: u64,
+        /// Claimed last slot, below `start`.
+        end: u64,
+    },
+}
+
+impl fmt::Display for ProverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyWitnesses { got, max } => {
+                write!(f, "too many witnesses: got {got}, max {max}")
+            }
+            Self::RecordMismatch { field } => {
+                write!(f, "envelope {field} does not match the anchored proof record")
+            }
+            Self::ProofRejected => f.write_str("proof rejected by the verifier"),
+            Self::NorthStarMismatch { field } => {
+                write!(f, "North Star {field} does not match the one regenerated from the chain")
+            }
+            Self::TooManyEpochCredits { got, max } => {
+                write!(f, "vote account has {got} epoch credit entries, max {max}")
+            }
+            Self::TraceLengthNotPow2 { len } => write!(
+                f,
+                "trace length {len} is not a power of two >= {MIN_TRACE_LENGTH}; \
+                 adjust the slot range or witness count so the trace has {} rows",
+                len.next_power_of_two().max(MIN_TRACE_LENGTH)
+            ),
+            Self::WitnessCountMismatch { expected, got } => {
+                write!(f, "witnesses do not cover the claimed slots: need {expected}, got {got}")
+            }
+            Self::ChainNotAllowed { got } => write!(f, "proof is for chain_id {got}, which is not allowed"),
+            Self::TraceBoundaryMismatch { column, step, expected, got } => write!(
+                f,
+                "trace column {column} row {step} is {got} but the public inputs require {expected}"
+            ),
+            Self::SlotExceedsField { slot } => {
+                write!(f, "slot {slot} exceeds the field modulus of the STARK AIR")
+            }
+            Self::RpcUnavailable { status: Some(status) } => write!(f, "RPC endpoint answered HTTP {status}"),
+            Self::RpcUnavailable { status: None } => f.write_str("RPC endpoint timed out or refused the connection"),
+            Self::SlotFetchFailed { slot } => write!(f, "failed to fetch witness data for slot {slot}"),
+            Self::SlotRangeReversed { start, end } => write!(f, "slot range ends at {end}, before its start {start}"),
+        }
+    }
+}
+
+impl std::error::Error for ProverError {}
+
+/// Check that `len` is a trace length winterfell accepts.
+pub fn check_trace_length(len: usize) -> Result<(), ProverError> {
+    if len >= MIN_TRACE_LENGTH && len.is_power_of_two() {
+        Ok(())
+    } else {
+        Err(ProverError::TraceLengthNotPow2 { len })
+    }
+}
+
+/// The field element the STARK AIR stores `slot` as, refusing slots that would alias.
+pub fn check_slot_fits(slot: u64) -> Result<BaseElement, ProverError> {
+    if slot < BaseElement::MODULUS {
+        Ok(BaseElement::new(slot))
+    } else {
+        Err(ProverError::SlotExceedsField { slot })
+    }
+}
+
+/// Steps from `start` to `end`, refusing a range that ends before it starts.
+pub fn check_slot_range(start: u64, end: u64) -> Result<u64, ProverError> {
+    end.checked_sub(start).ok_or(ProverError::SlotRangeReversed { start, end })
+}
+
+/// Check every single-cell assertion against `trace` before it is handed to the prover.
+///
+/// Periodic and sequence assertions are skipped; the AIRs here only pin boundary cells. A
+/// cell outside the trace is reported with `got: 0`.
+pub fn check_trace_boundaries<E: StarkField<PositiveInteger = u64>>(
+    trace: &TraceTable<E>,
+    assertions: &[Assertion<E>],
+) -> Result<(), ProverError> {
+    for assertion in assertions.iter().filter(|a| a.is_single()) {
+        let (column, step) = (assertion.column(), assertion.first_step());
+        let expected = assertion.values().first().copied().unwrap_or(E::ZERO);
+        if column >= trace.main_trace_width() || step >= trace.length() {
+            return Err(ProverError::TraceBoundaryMismatch {
+                column,
+                step,
+                expected: expected.as_int(),
+                got: 0,
+            });
+        }
+        let got = trace.get(column, step);
+        if got != expected {
+            return Err(ProverError::TraceBoundaryMismatch {
+                column,
+                step,
+                expected: expected.as_int(),
+                got: got.as_int(),
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_length_must_be_power_of_two_above_minimum() {
+        for len in [8, 16, 1024] {
+            assert_eq!(check_trace_length(len), Ok(()));
+        }
+        for len in [0, 1, 4, 7, 9, 24] {
+            assert_eq!(check_trace_length(len), Err(ProverError::TraceLengthNotPow2 { len }));
+        }
+        let msg = ProverError::TraceLengthNotPow2 { len: 9 }.to_string();
+        assert!(msg.contains("16 rows"), "{msg}");
+        let msg = ProverError::TraceLengthNotPow2 { len: 3 }.to_string();
+        assert!(msg.contains("8 rows"), "{msg}");
+    }
+
+    #[test]
+    fn test_slot_must_fit_the_field() {
+        for slot in [0, u64::from(u32::MAX), 1 << 32, BaseElement::MODULUS - 1] {
+            assert_eq!(check_slot_fits(slot).map(|e| e.as_int()), Ok(slot));
+        }
+        for slot in [BaseElement::MODULUS, u64::MAX] {
+            assert_eq!(check_slot_fits(slot), Err(ProverError::SlotExceedsField { slot }));
+        }
+    }
+}
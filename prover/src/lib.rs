@@ -0,0 +1,38 @@
+#![forbid(unsafe_code)]
+#![deny(warnings, clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic, clippy::todo, clippy::unimplemented)]
+#![deny(clippy::indexing_slicing, clippy::arithmetic_side_effects, clippy::float_arithmetic, clippy::as_conversions)]
+#![deny(clippy::else_if_without_else, clippy::shadow_reuse, clippy::wildcard_enum_match_arm)]
+// `missing_docs` never fired on these modules while they were private to the binary; clippy's
+// `missing_docs_in_private_items` keeps covering every item, exported or not.
+#![deny(rustdoc::broken_intra_doc_links)]
+#![deny(clippy::missing_docs_in_private_items)]
+// Duplicate versions come from reqwest's dependency tree, not from anything this crate pins
+#![allow(clippy::multiple_crate_versions)]
+//! zksl-prover library: the modules behind the `prover` binary, shared with its benchmarks
+
+pub mod anchor;
+#[cfg(feature = "stark")]
+pub mod backend;
+#[cfg(feature = "stark")]
+pub mod error;
+#[cfg(feature = "stark")]
+pub mod limbs;
+#[cfg(feature = "stark")]
+pub mod params;
+#[cfg(feature = "stark")]
+pub mod rpc;
+#[cfg(all(feature = "stark", any(test, feature = "test-sim")))]
+pub mod sim;
+/// Blake3 / f62 STARK over a slot range: AIR revisions, proving and verification
+#[cfg(feature = "stark")]
+pub mod stark;
+
+/// North Star Option A AIR (enabled when REQUIRE_STARK=1 at runtime)
+#[cfg(feature = "stark")]
+#[allow(dead_code)]
+pub mod north_star;
+#[cfg(feature = "stark")]
+pub mod witness;
+#[cfg(feature = "stark")]
+pub mod merkle;
@@ -0,0 +1,52 @@
+//! Split of u64 values into the 32-bit limbs both AIRs keep in their trace
+//!
+//! Stake is stored as `(lo, hi)` with `x = lo + hi * 2^32`; the AIR carry constraints assume
+//! exactly this convention, so trace builders must not decompose values inline.
+
+/// `(lo, hi)` 32-bit limbs of `x`, little end first.
+pub const fn u64_to_limbs(x: u64) -> (u32, u32) {
+    let [b0, b1, b2, b3, b4, b5, b6, b7] = x.to_le_bytes();
+    (u32::from_le_bytes([b0, b1, b2, b3]), u32::from_le_bytes([b4, b5, b6, b7]))
+}
+
+/// Inverse of [`u64_to_limbs`].
+pub const fn limbs_to_u64(lo: u32, hi: u32) -> u64 {
+    let [l0, l1, l2, l3] = lo.to_le_bytes();
+    let [h0, h1, h2, h3] = hi.to_le_bytes();
+    u64::from_le_bytes([l0, l1, l2, l3, h0, h1, h2, h3])
+}
+
+// The carry constraints rely on the low limb being the first one
+const _: () = assert!(limbs_to_u64(1, 0) == 1 && limbs_to_u64(0, 1) == 1 << 32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limb_round_trip_at_boundaries() {
+        for (x, limbs) in [
+            (0, (0, 0)),
+            (u64::from(u32::MAX), (u32::MAX, 0)),
+            (1 << 32, (0, 1)),
+            (u64::MAX, (u32::MAX, u32::MAX)),
+            (0x0123_4567_89AB_CDEF, (0x89AB_CDEF, 0x0123_4567)),
+        ] {
+            assert_eq!(u64_to_limbs(x), limbs, "{x:#x}");
+            assert_eq!(limbs_to_u64(limbs.0, limbs.1), x);
+        }
+    }
+
+    #[test]
+    fn test_limb_round_trip_random() {
+        use rand::{Rng, SeedableRng};
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0x11AB5);
+        for _ in 0..10_000 {
+            let x: u64 = rng.gen();
+            let (lo, hi) = u64_to_limbs(x);
+            assert_eq!(limbs_to_u64(lo, hi), x);
+            assert_eq!(u64::from(lo), x & 0xFFFF_FFFF);
+            assert_eq!(u64::from(hi), x >> 32);
+        }
+    }
+}
@@ -11,7 +11,7 @@ use blake3::Hasher as Blake3;
 use clap::Parser;
 #[cfg(feature = "stark")]
 use clap::Subcommand;
-use ed25519_dalek::{Signer, Keypair};
+use ed25519_dalek::{Signer, SigningKey};
 use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
 
@@ -34,6 +34,9 @@ struct Args {
     program_id: String,
     #[arg(long, default_value_t = 1u64)]
     seq: u64,
+    /// `AggregatorState.epoch_nonce`; bound into DS only when non-zero
+    #[arg(long, default_value_t = 0u64)]
+    epoch_nonce: u64,
 }
 
 #[cfg(feature = "stark")]
@@ -45,26 +48,101 @@ enum Cmd {
         #[arg(long)] end: u64,
         #[arg(long, value_name="HEX32")] before: Option<String>,
         #[arg(long, value_name="HEX32")] after: Option<String>,
+        /// Expected proof hash; the proof hash is derived from the public inputs and checked against this
         #[arg(long, value_name="HEX32")] proof_hash: Option<String>,
+        /// RPC commitment level to prove over (recorded in the public inputs)
+        #[arg(long, value_enum, default_value_t)] commitment: witness::Commitment,
+        /// Chain the slots are taken from, bound into the public inputs (0 = unbound)
+        #[arg(long = "chain", default_value_t = 0u64)] chain: u64,
+        /// Prover key file (`{"secretKey": hex64}`) to sign the output with
+        #[arg(long)] prover_key: Option<String>,
         #[arg(long)] out: String,
+        /// Directory that a relative --out is resolved against
+        #[arg(long)] out_dir: Option<PathBuf>,
     },
     /// Verify a STARK proof generated by StarkProve
     StarkVerify {
         #[arg(long)] proof: String,
+        /// Accepted chain_id (repeatable); when given, proofs bound to other chains are rejected
+        #[arg(long = "allow-chain")] allow_chains: Vec<u64>,
+        /// Hex Ed25519 key of the trusted prover; when given, the output must carry its signature
+        #[arg(long, value_name="HEX32")] prover_pubkey: Option<String>,
+        /// Constraint that must be enforced by the proof's AIR (repeatable), e.g. `stake_low@0`
+        #[arg(long = "require-constraint")] required_constraints: Vec<String>,
+    },
+    /// Verify several STARK proofs in parallel; reports one result per proof, in order
+    StarkVerifyBatch {
+        #[arg(long = "proof", required = true)] proofs: Vec<String>,
+    },
+    /// Print the anchor_proof arguments for a proof file, after the program's stateless checks
+    StarkAnchorArgs {
+        #[arg(long)] proof: String,
+        /// Artifact UUID (hex, dashes optional)
+        #[arg(long)] artifact_id: String,
+        #[arg(long)] seq: u64,
+        /// Aggregator public key (base58)
+        #[arg(long)] aggregator_pubkey: String,
+        #[arg(long)] timestamp: i64,
+        /// Config.chain_id of the target deployment
+        #[arg(long)] chain_id: u64,
+        /// validator_lock program id (base58)
+        #[arg(long)] program_id: String,
+        /// `AggregatorState.epoch_nonce`; bound into DS only when non-zero
+        #[arg(long, default_value_t = 0u64)] epoch_nonce: u64,
+    },
+    /// Check a North Star C_in/C_out commitment against its S_in/S_out KV array (JSON file)
+    VerifyCommitment {
+        #[arg(long)] kv: String,
+        #[arg(long, value_name="HEX32")] commitment: String,
     },
     /// Generate witness from Solana RPC for given slot range
     GenerateWitness {
         #[arg(long)] rpc: String,
         #[arg(long)] start: u64,
         #[arg(long)] end: u64,
+        /// RPC commitment level to fetch at
+        #[arg(long, value_enum, default_value_t)] commitment: witness::Commitment,
         #[arg(long)] out: String,
+        /// Slots fetched concurrently; 1 fetches them one after another
+        #[arg(long, default_value_t = 1usize)] max_in_flight: usize,
+        /// Attempts per RPC request before a rate limit, 5xx or timeout is given up on
+        #[arg(long, default_value_t = 5u32)] max_attempts: u32,
+        /// Witness cache for this start slot and commitment: reused, extended and saved back
+        #[arg(long)] cache: Option<PathBuf>,
+        /// Tree hashing of the witness roots; a --cache keeps the version it was written with
+        #[arg(long, value_enum, default_value_t = merkle::MerkleVersion::CURRENT)] merkle_version: merkle::MerkleVersion,
     },
     /// Generate STARK proof from REAL RPC data (fetches witness automatically)
     StarkProveReal {
         #[arg(long)] rpc: String,
         #[arg(long)] start: u64,
         #[arg(long)] end: u64,
-        #[arg(long, value_name="HEX32")] proof_hash: String,
+        /// Expected proof hash; the proof hash is derived from the public inputs and checked against this
+        #[arg(long, value_name="HEX32")] proof_hash: Option<String>,
+        /// RPC commitment level to prove over (recorded in the public inputs)
+        #[arg(long, value_enum, default_value_t)] commitment: witness::Commitment,
+        /// Chain the slots are taken from, bound into the public inputs (0 = unbound)
+        #[arg(long = "chain", default_value_t = 0u64)] chain: u64,
+        /// Whether state roots are bound to their slot; `unbound` commits bare validator-set
+        /// tree roots for external verifiers, which repeat across slots with the same set
+        #[arg(long, value_enum, default_value_t)] slot_binding: witness::SlotBinding,
+        /// Tree hashing of the witness roots; `v1` re-derives the roots of proofs made before V2
+        #[arg(long, value_enum, default_value_t = merkle::MerkleVersion::CURRENT)] merkle_version: merkle::MerkleVersion,
+        /// Prover key file (`{"secretKey": hex64}`) to sign the output with
+        #[arg(long)] prover_key: Option<String>,
+        #[arg(long)] out: String,
+        /// Directory that a relative --out is resolved against
+        #[arg(long)] out_dir: Option<PathBuf>,
+    },
+    /// Generate a STARK proof over a deterministic simulated cluster (no RPC)
+    #[cfg(feature = "test-sim")]
+    StarkProveSim {
+        /// Seed the simulated blocks and vote accounts are derived from
+        #[arg(long, default_value_t = 0u64)] seed: u64,
+        #[arg(long)] start: u64,
+        #[arg(long)] end: u64,
+        /// Chain id bound into the public inputs (0 = unbound)
+        #[arg(long = "chain", default_value_t = 0u64)] chain: u64,
         #[arg(long)] out: String,
     },
 }
@@ -78,16 +156,11 @@ struct Artifact {
     state_root_after: String,
 }
 
+use zksl_prover::anchor;
+#[cfg(feature = "test-sim")]
+use zksl_prover::sim;
 #[cfg(feature = "stark")]
-mod stark;
-
-// North Star Option A AIR (enabled when REQUIRE_STARK=1 at runtime)
-#[allow(dead_code)]
-mod north_star;
-#[cfg(feature = "stark")]
-mod witness;
-#[cfg(feature = "stark")]
-mod merkle;
+use zksl_prover::{merkle, params, rpc, stark, witness};
 
 fn main() -> anyhow::Result<()> {
     // Allow env override for program_id if default placeholder is present
@@ -104,68 +177,133 @@ fn main() -> anyhow::Result<()> {
     #[cfg(feature = "stark")]
     if let Some(cmd) = args.cmd.clone() {
         match cmd {
-            Cmd::StarkProve { start, end, before: _before, after: _after, proof_hash, out } => {
+            Cmd::StarkProve { start, end, before: _before, after: _after, proof_hash, commitment, chain, prover_key, out, out_dir } => {
                 // Prefer North Star Route: fetch witness from RPC and generate proof bound to PI set
                 let rpc = std::env::var("RPC_URL").unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
-                let proof_hash_hex = proof_hash.unwrap_or_else(|| "0".repeat(64));
-                let proof_hash_arr = stark::hex32_to_array(&proof_hash_hex)?;
-                let proof = stark::generate_stark_proof_from_witness(&rpc, start, end, proof_hash_arr)?;
-                let pi = &proof.public_inputs;
-                let json = serde_json::json!({
-                    "public_inputs": {
-                        "start": pi.start,
-                        "end": pi.end,
-                        "before": hex::encode(pi.before),
-                        "after": hex::encode(pi.after),
-                        "proof_hash": hex::encode(pi.proof_hash),
-                        "c_in_hex": pi.c_in_hex,
-                        "c_out_hex": pi.c_out_hex,
-                        "h_b_hex": pi.h_b_hex,
-                        "s_in": pi.s_in,
-                        "s_out": pi.s_out
-                    },
-                    "proof_b64": proof.proof_b64
-                });
-                std::fs::write(out, serde_json::to_vec_pretty(&json)?)?;
+                let mut proof = stark::generate_stark_proof_from_witness(
+                    &rpc,
+                    start,
+                    end,
+                    commitment,
+                    chain,
+                    witness::SlotBinding::Bound,
+                    merkle::MerkleVersion::CURRENT,
+                    &params::ProofParams::testnet(),
+                    None,
+                )?;
+                check_expected_proof_hash(proof_hash.as_deref(), &proof)?;
+                if let Some(path) = prover_key {
+                    stark::sign_output(&read_signing_key(&path)?, &mut proof);
+                }
+                stark::write_proof_atomic(resolve_out(out_dir, &out), &proof)?;
+                return Ok(());
+            }
+            Cmd::StarkVerify { proof, allow_chains, prover_pubkey, required_constraints } => {
+                let p = read_stark_envelope(&proof)?;
+                if !required_constraints.is_empty() {
+                    stark::require_constraints(&p, &required_constraints)?;
+                }
+                if let Some(pubkey) = prover_pubkey {
+                    let pubkey = ed25519_dalek::VerifyingKey::from_bytes(&stark::hex32_to_array(&pubkey)?)?;
+                    if !stark::verify_output_signature(&p, &pubkey) {
+                        anyhow::bail!("missing or invalid prover signature");
+                    }
+                }
+                if allow_chains.is_empty() {
+                    stark::verify_stark_proof(&p)?;
+                } else {
+                    stark::verify_stark_proof_for_chains(&p, &allow_chains)?;
+                }
+                println!("{{\"status\":\"ok\"}}");
                 return Ok(());
             }
-            Cmd::StarkVerify { proof } => {
-                let raw = std::fs::read_to_string(proof)?;
-                let v: serde_json::Value = serde_json::from_str(&raw)?;
-                let proof_b64 = v.get("proof_b64").and_then(|x| x.as_str()).ok_or_else(|| anyhow::anyhow!("missing proof_b64"))?;
-                let start = v.get("public_inputs").and_then(|o| o.get("start")).and_then(|x| x.as_u64()).unwrap_or(1);
-                let end = v.get("public_inputs").and_then(|o| o.get("end")).and_then(|x| x.as_u64()).unwrap_or(1);
-                let before_hex = v.get("public_inputs").and_then(|o| o.get("before")).and_then(|x| x.as_str()).unwrap_or("0");
-                let after_hex = v.get("public_inputs").and_then(|o| o.get("after")).and_then(|x| x.as_str()).unwrap_or("0");
-                let ph_hex = v.get("public_inputs").and_then(|o| o.get("proof_hash")).and_then(|x| x.as_str()).unwrap_or("0");
-                let before_arr = stark::hex32_to_array(before_hex)?;
-                let after_arr = stark::hex32_to_array(after_hex)?;
-                let ph_arr = stark::hex32_to_array(ph_hex)?;
-				let p = stark::StarkOutput {
-					public_inputs: stark::PublicInputs {
-						start,
-						end,
-						before: before_arr,
-						after: after_arr,
-						proof_hash: ph_arr,
-						c_in_hex: String::new(),
-						c_out_hex: String::new(),
-						h_b_hex: String::new(),
-						s_in: Vec::new(),
-						s_out: Vec::new(),
-					},
-					proof_b64: proof_b64.to_string(),
-				};
-                stark::verify_stark_proof(&p)?;
+            Cmd::StarkVerifyBatch { proofs } => {
+                let envelopes = proofs.iter().map(|path| read_stark_envelope(path)).collect::<anyhow::Result<Vec<_>>>()?;
+                let results: Vec<serde_json::Value> = proofs
+                    .iter()
+                    .zip(stark::verify_batch(&envelopes))
+                    .map(|(path, result)| match result {
+                        Ok(()) => serde_json::json!({ "proof": path, "status": "ok" }),
+                        Err(e) => serde_json::json!({ "proof": path, "status": "error", "error": e.to_string() }),
+                    })
+                    .collect();
+                println!("{}", serde_json::Value::Array(results));
+                return Ok(());
+            }
+            Cmd::StarkAnchorArgs { proof, artifact_id, seq, aggregator_pubkey, timestamp, chain_id, program_id, epoch_nonce } => {
+                let artifact_id: [u8; 16] = hex::decode(artifact_id.replace('-', ""))?
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("artifact_id must be 16 bytes"))?;
+                let aggregator_pubkey = decode_pubkey(&aggregator_pubkey)?;
+                let program_id = decode_pubkey(&program_id)?;
+                let args = read_stark_envelope(&proof)?.to_anchor_args(
+                    artifact_id,
+                    seq,
+                    aggregator_pubkey,
+                    timestamp,
+                    chain_id,
+                    &program_id,
+                    epoch_nonce,
+                )?;
+                args.preflight(chain_id, &program_id, epoch_nonce)?;
+                println!("{}", serde_json::to_string_pretty(&args.to_json())?);
+                return Ok(());
+            }
+            Cmd::VerifyCommitment { kv, commitment } => {
+                let pairs: Vec<stark::KVPair> = serde_json::from_str(&fs::read_to_string(&kv)?)?;
+                let expected = stark::hex32_to_array(&commitment)?;
+                if !witness::verify_c(&pairs, &expected) {
+                    anyhow::bail!("commitment does not match {kv}: computed {}", hex::encode(witness::compute_c(&pairs)));
+                }
                 println!("{{\"status\":\"ok\"}}");
                 return Ok(());
             }
-            Cmd::GenerateWitness { rpc, start, end, out } => {
-                let witnesses = witness::generate_witness_from_rpc(&rpc, start, end)?;
-                let (before, after) = witness::generate_state_roots(&rpc, start, end)?;
+            Cmd::GenerateWitness {
+                rpc,
+                start,
+                end,
+                commitment,
+                out,
+                max_in_flight,
+                max_attempts,
+                cache,
+                merkle_version,
+            } => {
+                let fetch = witness::WitnessFetchConfig { max_attempts, ..witness::WitnessFetchConfig::default() };
+                let transport = crate::rpc::HttpTransport::with_timeout(&rpc, crate::rpc::DEFAULT_REQUEST_TIMEOUT)?;
+                let witnesses = if let Some(cache) = cache {
+                    witness::generate_witness_from_rpc_cached(&transport, start, end, commitment, &fetch, cache)?
+                } else if max_in_flight > 1 {
+                    witness::generate_witness_from_rpc_parallel(
+                        &transport,
+                        start,
+                        end,
+                        commitment,
+                        &witness::Blake3LeafCodec,
+                        witness::SlotBinding::Bound,
+                        merkle_version,
+                        &fetch,
+                        max_in_flight,
+                    )?
+                } else {
+                    witness::generate_witness_from_rpc_with_codec(
+                        &transport,
+                        start,
+                        end,
+                        commitment,
+                        &witness::Blake3LeafCodec,
+                        witness::SlotBinding::Bound,
+                        merkle_version,
+                        &fetch,
+                        None,
+                    )?
+                };
+                let (before, after) = witness::state_roots(&witnesses)?;
                 let output = serde_json::json!({
                     "start_slot": start,
                     "end_slot": end,
+                    "commitment": commitment,
+                    "merkle_version": witnesses.first().map_or(merkle_version, |w| w.merkle_version),
                     "state_root_before": hex::encode(before),
                     "state_root_after": hex::encode(after),
                     "witnesses": witnesses,
@@ -174,29 +312,58 @@ fn main() -> anyhow::Result<()> {
                 println!("{{\"status\":\"ok\",\"before\":\"{}\",\"after\":\"{}\"}}",hex::encode(before),hex::encode(after));
                 return Ok(());
             }
-            Cmd::StarkProveReal { rpc, start, end, proof_hash, out } => {
-                let proof_hash_arr = stark::hex32_to_array(&proof_hash)?;
+            Cmd::StarkProveReal {
+                rpc,
+                start,
+                end,
+                proof_hash,
+                commitment,
+                chain,
+                slot_binding,
+                merkle_version,
+                prover_key,
+                out,
+                out_dir,
+            } => {
                 println!("Fetching REAL validator vote accounts from RPC: {}", rpc);
-                let proof = stark::generate_stark_proof_from_witness(&rpc, start, end, proof_hash_arr)?;
-                let json = serde_json::json!({
-                    "public_inputs": {
-                        "start": proof.public_inputs.start,
-                        "end": proof.public_inputs.end,
-                        "before": hex::encode(proof.public_inputs.before),
-                        "after": hex::encode(proof.public_inputs.after),
-                        "proof_hash": hex::encode(proof.public_inputs.proof_hash),
-                        "c_in_hex": proof.public_inputs.c_in_hex,
-                        "c_out_hex": proof.public_inputs.c_out_hex,
-                        "h_b_hex": proof.public_inputs.h_b_hex,
-                        "s_in": proof.public_inputs.s_in,
-                        "s_out": proof.public_inputs.s_out
-                    },
-                    "proof_b64": proof.proof_b64
-                });
-                std::fs::write(out, serde_json::to_vec_pretty(&json)?)?;
+                let mut proof =
+                    stark::generate_stark_proof_from_witness(
+                    &rpc,
+                    start,
+                    end,
+                    commitment,
+                    chain,
+                    slot_binding,
+                    merkle_version,
+                    &params::ProofParams::testnet(),
+                    None,
+                )?;
+                check_expected_proof_hash(proof_hash.as_deref(), &proof)?;
+                if let Some(path) = prover_key {
+                    stark::sign_output(&read_signing_key(&path)?, &mut proof);
+                }
+                stark::write_proof_atomic(resolve_out(out_dir, &out), &proof)?;
                 println!("{{\"status\":\"ok\",\"proof_generated_from_real_rpc\":true}}");
                 return Ok(());
             }
+            #[cfg(feature = "test-sim")]
+            Cmd::StarkProveSim { seed, start, end, chain, out } => {
+                let cluster = sim::SimCluster::new(seed, start, end);
+                let proof = stark::generate_stark_proof_over(
+                    &cluster,
+                    start,
+                    end,
+                    witness::Commitment::Finalized,
+                    chain,
+                    witness::SlotBinding::Bound,
+                    merkle::MerkleVersion::CURRENT,
+                    &params::ProofParams::testnet(),
+                    None,
+                )?;
+                stark::write_proof_atomic(&out, &proof)?;
+                println!("{{\"status\":\"ok\",\"seed\":{seed}}}");
+                return Ok(());
+            }
         }
     }
     let input = args.input.as_ref().ok_or_else(|| anyhow::anyhow!("--input required"))?;
@@ -214,17 +381,16 @@ fn main() -> anyhow::Result<()> {
     let program_id = bs58::decode(args.program_id).into_vec()?;
     let program_id: [u8; 32] = program_id.try_into().map_err(|_| anyhow::anyhow!("invalid program_id"))?;
     let seq: u64 = args.seq;
-    let mut ds = Vec::new();
-    ds.extend_from_slice(b"zKSL/anchor/v1");
-    ds.extend_from_slice(&chain_id.to_le_bytes());
-    ds.extend_from_slice(&program_id);
-    ds.extend_from_slice(&proof_hash);
-    ds.extend_from_slice(&artifact.start_slot.to_le_bytes());
-    ds.extend_from_slice(&artifact.end_slot.to_le_bytes());
-    ds.extend_from_slice(&seq.to_le_bytes());
-    let mut hd = Blake3::new();
-    hd.update(&ds);
-    let ds_hash = *hd.finalize().as_bytes();
+    let ds = anchor::build_ds(
+        chain_id,
+        &program_id,
+        &proof_hash,
+        artifact.start_slot,
+        artifact.end_slot,
+        seq,
+        args.epoch_nonce,
+    );
+    let ds_hash = anchor::ds_hash(&ds);
     // sign DS using aggregator secret key from file (hex 64 bytes seed+key)
     let agg_bytes = read_aggregator_secret(&args.agg_key)?;
     let keypair_bytes: [u8; 64] = if agg_bytes.len() == 64 {
@@ -232,7 +398,7 @@ fn main() -> anyhow::Result<()> {
     } else {
         anyhow::bail!("Invalid aggregator key format, expected 64 bytes");
     };
-    let keypair = Keypair::from_bytes(&keypair_bytes)?;
+    let keypair = SigningKey::from_keypair_bytes(&keypair_bytes)?;
     let sig = keypair.sign(&ds);
 
     let output_json = serde_json::json!({
@@ -245,6 +411,68 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Resolve `--out` against `--out-dir` when given (absolute `--out` wins, as with `Path::join`).
+#[cfg(feature = "stark")]
+fn resolve_out(out_dir: Option<PathBuf>, out: &str) -> PathBuf {
+    out_dir.map_or_else(|| PathBuf::from(out), |dir| dir.join(out))
+}
+
+/// Read a proof envelope written by `StarkProve` (hex-encoded public inputs).
+#[cfg(feature = "stark")]
+fn read_stark_envelope(path: &str) -> anyhow::Result<stark::StarkOutput> {
+    let raw = std::fs::read_to_string(path)?;
+    let v: serde_json::Value = serde_json::from_str(&raw)?;
+    let proof_b64 = v.get("proof_b64").and_then(|x| x.as_str()).ok_or_else(|| anyhow::anyhow!("missing proof_b64"))?;
+    let start = v.get("public_inputs").and_then(|o| o.get("start")).and_then(|x| x.as_u64()).unwrap_or(1);
+    let end = v.get("public_inputs").and_then(|o| o.get("end")).and_then(|x| x.as_u64()).unwrap_or(1);
+    let before_hex = v.get("public_inputs").and_then(|o| o.get("before")).and_then(|x| x.as_str()).unwrap_or("0");
+    let after_hex = v.get("public_inputs").and_then(|o| o.get("after")).and_then(|x| x.as_str()).unwrap_or("0");
+    let ph_hex = v.get("public_inputs").and_then(|o| o.get("proof_hash")).and_then(|x| x.as_str()).unwrap_or("0");
+    let commitment = v.get("public_inputs").and_then(|o| o.get("commitment")).cloned()
+        .map(serde_json::from_value::<witness::Commitment>).transpose()?.unwrap_or_default();
+    let chain_id = v.get("public_inputs").and_then(|o| o.get("chain_id")).and_then(|x| x.as_u64()).unwrap_or(0);
+    let pi_str = |key: &str| v.get("public_inputs").and_then(|o| o.get(key)).and_then(|x| x.as_str()).unwrap_or_default().to_string();
+    let pi_kv = |key: &str| -> anyhow::Result<Vec<stark::KVPair>> {
+        Ok(v.get("public_inputs").and_then(|o| o.get(key)).cloned().map(serde_json::from_value).transpose()?.unwrap_or_default())
+    };
+    Ok(stark::StarkOutput {
+        air_id: stark::AirRevision::from_envelope(&v)?,
+        public_inputs: stark::PublicInputs {
+            start,
+            end,
+            before: stark::hex32_to_array(before_hex)?,
+            after: stark::hex32_to_array(after_hex)?,
+            proof_hash: stark::hex32_to_array(ph_hex)?,
+            c_in_hex: pi_str("c_in_hex"),
+            c_out_hex: pi_str("c_out_hex"),
+            h_b_hex: pi_str("h_b_hex"),
+            s_in: pi_kv("s_in")?,
+            s_out: pi_kv("s_out")?,
+            commitment,
+            chain_id,
+            endpoints: v.get("public_inputs").and_then(|o| o.get("endpoints")).cloned()
+                .map(serde_json::from_value).transpose()?,
+            participation_ppm: v.get("public_inputs").and_then(|o| o.get("participation_ppm")).cloned()
+                .map(serde_json::from_value).transpose()?,
+            merkle_version: v.get("public_inputs").and_then(|o| o.get("merkle_version")).cloned()
+                .map(serde_json::from_value).transpose()?.unwrap_or_default(),
+        },
+        proof_b64: proof_b64.to_string(),
+        prover_signature: v.get("prover_signature").and_then(|x| x.as_str()).map(str::to_string),
+    })
+}
+
+/// Fail when `--proof-hash` was given and differs from the hash derived from the public inputs.
+#[cfg(feature = "stark")]
+fn check_expected_proof_hash(expected: Option<&str>, proof: &stark::StarkOutput) -> anyhow::Result<()> {
+    let Some(expected) = expected else { return Ok(()) };
+    let derived = proof.public_inputs.proof_hash;
+    if stark::hex32_to_array(expected)? != derived {
+        anyhow::bail!("--proof-hash {expected} does not match derived proof hash {}", hex::encode(derived));
+    }
+    Ok(())
+}
+
 fn canonicalize<T: Serialize>(value: &T) -> String {
     // Deterministic map key ordering
     // Serialize, parse, and re-serialize with sorted keys
@@ -277,6 +505,24 @@ fn stringify_canonical(v: &serde_json::Value) -> String {
     }
 }
 
+/// 32-byte key from base58.
+#[cfg(feature = "stark")]
+fn decode_pubkey(b58: &str) -> anyhow::Result<[u8; 32]> {
+    bs58::decode(b58)
+        .into_vec()?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("{b58} is not a 32-byte base58 key"))
+}
+
+/// Ed25519 keypair from a `{"secretKey": hex64}` file, the aggregator key format.
+#[cfg(feature = "stark")]
+fn read_signing_key(path: &str) -> anyhow::Result<SigningKey> {
+    let bytes: [u8; 64] = read_aggregator_secret(path)?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("secretKey must be 64 hex bytes"))?;
+    Ok(SigningKey::from_keypair_bytes(&bytes)?)
+}
+
 fn read_aggregator_secret(path: &str) -> anyhow::Result<Vec<u8>> {
     let p = PathBuf::from(path);
     let raw = fs::read_to_string(p)?;
@@ -10,12 +10,83 @@ pub struct MerkleNode {
     pub hash: [u8; 32],
 }
 
+/// How a [`MerkleTree`] hashes its leaves and interior nodes.
+///
+/// Witnesses and proof public inputs record the version their roots were built with, so a
+/// root is always re-derived under the hashing that produced it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum MerkleVersion {
+    /// Leaves are used as given and `node = blake3(left || right)`. A 64-byte leaf preimage
+    /// is indistinguishable from a node, so an interior node can be proven as a leaf.
+    #[default]
+    V1,
+    /// `leaf = blake3(0x00 || value)` and `node = blake3(0x01 || left || right)`, so leaves
+    /// and nodes can never be confused.
+    V2,
+}
+
+impl MerkleVersion {
+    /// Version new trees are built with.
+    pub const CURRENT: Self = Self::V2;
+
+    /// Tree node for the leaf `value`.
+    fn leaf(self, value: &[u8; 32]) -> [u8; 32] {
+        match self {
+            Self::V1 => *value,
+            Self::V2 => *Hasher::new().update(&[LEAF_TAG]).update(value).finalize().as_bytes(),
+        }
+    }
+
+    /// Hash two nodes to create parent
+    fn node(self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Hasher::new();
+        if self == Self::V2 {
+            hasher.update(&[NODE_TAG]);
+        }
+        hasher.update(left);
+        hasher.update(right);
+        *hasher.finalize().as_bytes()
+    }
+}
+
+/// Domain tag prefixed to leaves under [`MerkleVersion::V2`].
+const LEAF_TAG: u8 = 0x00;
+
+/// Domain tag prefixed to interior nodes under [`MerkleVersion::V2`].
+const NODE_TAG: u8 = 0x01;
+
 /// Merkle proof for a leaf
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct MerkleProof {
     pub leaf_index: usize,
     pub siblings: Vec<[u8; 32]>,
+    /// Hashing of the tree the proof was taken from; proofs serialized before versions
+    /// existed are `V1`.
+    #[serde(default)]
+    pub version: MerkleVersion,
+}
+
+/// Proof for several leaves of one tree at once, sharing the siblings their paths have in
+/// common and omitting those the opened leaves supply themselves.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct MultiProof {
+    /// Leaves in the (padded) tree; fixes its depth.
+    pub leaf_count: usize,
+    /// Sibling nodes not derivable from the opened leaves, level by level from the leaves up
+    /// and by ascending index within a level.
+    pub siblings: Vec<[u8; 32]>,
+    /// Hashing of the tree the proof was taken from.
+    pub version: MerkleVersion,
+}
+
+/// Committed per-slot state root: `blake3(slot_le || tree_root)`, binding the tree to its slot.
+pub fn slot_bound_root(slot: u64, tree_root: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Hasher::new();
+    hasher.update(&slot.to_le_bytes());
+    hasher.update(tree_root);
+    *hasher.finalize().as_bytes()
 }
 
 /// Real Merkle tree implementation
@@ -24,15 +95,22 @@ pub struct MerkleProof {
 pub struct MerkleTree {
     /// Leaf layer values.
     leaves: Vec<[u8; 32]>,
-    /// All tree levels bottom-up (nodes[level][index]).
+    /// Hashing of leaves and interior nodes.
+    version: MerkleVersion,
+    /// All tree levels bottom-up (nodes[level][index]); level 0 holds the hashed leaves.
     nodes: Vec<Vec<[u8; 32]>>, // nodes[level][index]
     /// Merkle root for the current tree.
     root: [u8; 32],
 }
 
 impl MerkleTree {
-    /// Build real Merkle tree from leaf hashes
-    pub fn new(mut leaves: Vec<[u8; 32]>) -> Self {
+    /// Build real Merkle tree from leaf hashes, under [`MerkleVersion::CURRENT`]
+    pub fn new(leaves: Vec<[u8; 32]>) -> Self {
+        Self::with_version(leaves, MerkleVersion::CURRENT)
+    }
+
+    /// Build a Merkle tree hashing its leaves and nodes per `version`
+    pub fn with_version(mut leaves: Vec<[u8; 32]>, version: MerkleVersion) -> Self {
         if leaves.is_empty() {
             leaves.push([0u8; 32]); // Empty tree has zero leaf
         }
@@ -43,8 +121,8 @@ impl MerkleTree {
             leaves.push([0u8; 32]); // Pad with zeros
         }
         
-        let mut nodes = vec![leaves.clone()];
-        let mut current_level = leaves;
+        let mut current_level: Vec<[u8; 32]> = leaves.iter().map(|leaf| version.leaf(leaf)).collect();
+        let mut nodes = vec![current_level.clone()];
         
         // Build tree bottom-up
         while current_level.len() > 1 {
@@ -53,7 +131,7 @@ impl MerkleTree {
             for chunk in current_level.chunks(2) {
                 let left = chunk[0];
                 let right = if chunk.len() > 1 { chunk[1] } else { [0u8; 32] };
-                let parent = Self::hash_pair(&left, &right);
+                let parent = version.node(&left, &right);
                 next_level.push(parent);
             }
             
@@ -64,20 +142,13 @@ impl MerkleTree {
         let root = current_level[0];
         
         Self {
-            leaves: nodes[0].clone(),
+            leaves,
+            version,
             nodes,
             root,
         }
     }
     
-    /// Hash two nodes to create parent
-    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
-        let mut hasher = Hasher::new();
-        hasher.update(left);
-        hasher.update(right);
-        *hasher.finalize().as_bytes()
-    }
-    
     /// Get Merkle root
     pub fn root(&self) -> [u8; 32] {
         self.root
@@ -106,25 +177,119 @@ impl MerkleTree {
         Some(MerkleProof {
             leaf_index,
             siblings,
+            version: self.version,
         })
     }
     
+    /// Generate one proof opening every leaf in `indices` (in any order, repeats allowed)
+    #[allow(dead_code)]
+    pub fn prove_many(&self, indices: &[usize]) -> Option<MultiProof> {
+        let mut known: Vec<usize> = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+        if known.is_empty() || known.last().is_some_and(|last| *last >= self.leaves.len()) {
+            return None;
+        }
+
+        let mut siblings = Vec::new();
+        for level in &self.nodes[..self.nodes.len() - 1] {
+            for idx in &known {
+                if known.binary_search(&(idx ^ 1)).is_err() {
+                    siblings.push(level[idx ^ 1]);
+                }
+            }
+            known = known.iter().map(|idx| idx / 2).collect();
+            known.dedup();
+        }
+
+        Some(MultiProof {
+            leaf_count: self.leaves.len(),
+            siblings,
+            version: self.version,
+        })
+    }
+
+    /// Verify that `leaves` (`(index, leaf)` pairs) are all in the tree with `root`
+    #[allow(dead_code)]
+    pub fn verify_many(root: &[u8; 32], leaves: &[(usize, [u8; 32])], proof: &MultiProof) -> bool {
+        if !proof.leaf_count.is_power_of_two() {
+            return false;
+        }
+        let mut level: Vec<(usize, [u8; 32])> =
+            leaves.iter().map(|(idx, leaf)| (*idx, proof.version.leaf(leaf))).collect();
+        level.sort_unstable_by_key(|(idx, _)| *idx);
+        // The same index opened twice must carry the same leaf
+        if level.windows(2).any(|pair| pair[0].0 == pair[1].0 && pair[0].1 != pair[1].1) {
+            return false;
+        }
+        level.dedup_by_key(|(idx, _)| *idx);
+        if level.is_empty() || level.last().is_some_and(|(idx, _)| *idx >= proof.leaf_count) {
+            return false;
+        }
+
+        let mut siblings = proof.siblings.iter();
+        for _ in 0..proof.leaf_count.trailing_zeros() {
+            let mut parents = Vec::with_capacity(level.len());
+            let mut i = 0;
+            while i < level.len() {
+                let (idx, node) = level[i];
+                let paired = level.get(i + 1).filter(|(next, _)| idx % 2 == 0 && *next == idx + 1);
+                let parent = match (paired, idx % 2 == 0) {
+                    (Some((_, right)), _) => {
+                        i += 1;
+                        proof.version.node(&node, right)
+                    }
+                    (None, is_left) => {
+                        let Some(sibling) = siblings.next() else { return false };
+                        if is_left { proof.version.node(&node, sibling) } else { proof.version.node(sibling, &node) }
+                    }
+                };
+                parents.push((idx / 2, parent));
+                i += 1;
+            }
+            level = parents;
+        }
+
+        siblings.next().is_none() && level == [(0, *root)]
+    }
+
     /// Verify Merkle proof
     #[allow(dead_code)]
     pub fn verify(root: &[u8; 32], leaf: &[u8; 32], proof: &MerkleProof) -> bool {
-        let mut current = *leaf;
+        Self::root_from_proof(leaf, proof) == *root
+    }
+
+    /// Verify a leaf against the committed `SlotWitness::state_root`, i.e.
+    /// `blake3(slot || tree_root)`, rather than the bare tree root.
+    ///
+    /// This is the check light clients need: a proof against `root()` alone does not tie
+    /// the leaf to the state root a verifier actually sees.
+    #[allow(dead_code)]
+    pub fn verify_against_state_root(
+        state_root: &[u8; 32],
+        slot: u64,
+        leaf: &[u8; 32],
+        proof: &MerkleProof,
+    ) -> bool {
+        slot_bound_root(slot, &Self::root_from_proof(leaf, proof)) == *state_root
+    }
+
+    /// Recompute the tree root implied by `leaf` and `proof`, hashed per `proof.version`.
+    fn root_from_proof(leaf: &[u8; 32], proof: &MerkleProof) -> [u8; 32] {
+        let version = proof.version;
+        let mut current = version.leaf(leaf);
         let mut idx = proof.leaf_index;
-        
+
         for sibling in &proof.siblings {
             current = if idx % 2 == 0 {
-                Self::hash_pair(&current, sibling)
+                version.node(&current, sibling)
             } else {
-                Self::hash_pair(sibling, &current)
+                version.node(sibling, &current)
             };
             idx /= 2;
         }
-        
-        current == *root
+
+        current
     }
     
     /// Get number of leaves
@@ -194,5 +359,130 @@ mod tests {
         let wrong_leaf = [99u8; 32];
         assert!(!MerkleTree::verify(&root, &wrong_leaf, &proof));
     }
-}
 
+    #[test]
+    fn test_verify_against_state_root() {
+        let leaves = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let tree = MerkleTree::new(leaves.clone());
+        let slot = 4242;
+        let state_root = slot_bound_root(slot, &tree.root());
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = tree.prove(i).unwrap();
+            assert!(MerkleTree::verify_against_state_root(&state_root, slot, leaf, &proof));
+        }
+        // The bare tree root is not the committed state root
+        let proof = tree.prove(0).unwrap();
+        assert!(!MerkleTree::verify(&state_root, &leaves[0], &proof));
+        assert!(!MerkleTree::verify_against_state_root(&tree.root(), slot, &leaves[0], &proof));
+    }
+
+    #[test]
+    fn test_verify_against_state_root_rejects_other_slot() {
+        let leaves = vec![[1u8; 32], [2u8; 32]];
+        let tree = MerkleTree::new(leaves.clone());
+        let state_root = slot_bound_root(100, &tree.root());
+        let proof = tree.prove(1).unwrap();
+
+        assert!(MerkleTree::verify_against_state_root(&state_root, 100, &leaves[1], &proof));
+        assert!(!MerkleTree::verify_against_state_root(&state_root, 101, &leaves[1], &proof));
+        assert!(!MerkleTree::verify_against_state_root(&state_root, 100, &[9u8; 32], &proof));
+    }
+
+    #[test]
+    fn test_interior_node_cannot_be_proven_as_a_leaf() {
+        let leaves = vec![[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32]];
+        // Claim the parent of leaves 0 and 1 is itself a leaf one level up, with its sibling
+        // node as the only proof step
+        let forge = |tree: &MerkleTree| {
+            let (node, sibling) = (tree.nodes[1][0], tree.nodes[1][1]);
+            let proof = MerkleProof { leaf_index: 0, siblings: vec![sibling], version: tree.version };
+            MerkleTree::verify(&tree.root(), &node, &proof)
+        };
+        let legacy = MerkleTree::with_version(leaves.clone(), MerkleVersion::V1);
+        assert!(forge(&legacy), "untagged hashing admits the forgery");
+
+        let tree = MerkleTree::new(leaves.clone());
+        assert_eq!(tree.version, MerkleVersion::V2);
+        assert!(!forge(&tree));
+        // Nodes hash the tagged leaves under their own tag
+        let tagged = |leaf: &[u8; 32]| *Hasher::new().update(&[LEAF_TAG]).update(leaf).finalize().as_bytes();
+        let preimage = [tagged(&leaves[0]), tagged(&leaves[1])].concat();
+        assert_eq!(tree.nodes[1][0], *Hasher::new().update(&[NODE_TAG]).update(&preimage).finalize().as_bytes());
+        assert_ne!(tree.root(), legacy.root());
+
+        // Genuine proofs verify under either version, and only under their own
+        for tree in [&legacy, &tree] {
+            for (i, leaf) in leaves.iter().enumerate() {
+                let mut proof = tree.prove(i).unwrap();
+                assert!(MerkleTree::verify(&tree.root(), leaf, &proof));
+                proof.version = if proof.version == MerkleVersion::V1 { MerkleVersion::V2 } else { MerkleVersion::V1 };
+                assert!(!MerkleTree::verify(&tree.root(), leaf, &proof));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unversioned_proof_json_reads_as_v1() {
+        let proof: MerkleProof = serde_json::from_value(serde_json::json!({ "leaf_index": 1, "siblings": [vec![0u8; 32]] }))
+            .expect("legacy proof");
+        assert_eq!(proof.version, MerkleVersion::V1);
+    }
+
+    #[test]
+    fn test_multi_proofs_recompute_the_root_for_random_index_sets() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for leaf_count in [1usize, 2, 5, 16, 37] {
+            let leaves: Vec<[u8; 32]> = (0..leaf_count).map(|_| rng.gen()).collect();
+            let tree = MerkleTree::new(leaves.clone());
+            for _ in 0..20 {
+                let picks = rng.gen_range(1..=leaf_count.min(8));
+                let indices: Vec<usize> = (0..picks).map(|_| rng.gen_range(0..leaf_count)).collect();
+                let proof = tree.prove_many(&indices).unwrap();
+                let opened: Vec<(usize, [u8; 32])> = indices.iter().map(|i| (*i, leaves[*i])).collect();
+                assert!(MerkleTree::verify_many(&tree.root(), &opened, &proof), "{leaf_count} leaves, {indices:?}");
+                // Never more siblings than separate proofs would carry
+                let separate: usize = indices.iter().map(|i| tree.prove(*i).unwrap().siblings.len()).sum();
+                assert!(proof.siblings.len() <= separate);
+
+                // A changed leaf, a moved leaf or a tampered sibling is rejected
+                let mut changed = opened.clone();
+                changed[0].1[0] ^= 1;
+                assert!(!MerkleTree::verify_many(&tree.root(), &changed, &proof));
+                if let Some(free) = (0..tree.len()).find(|i| !indices.contains(i)) {
+                    let mut moved = opened.clone();
+                    moved[0].0 = free;
+                    assert!(!MerkleTree::verify_many(&tree.root(), &moved, &proof));
+                }
+                if let Some(first) = proof.siblings.first() {
+                    let mut tampered = proof.clone();
+                    tampered.siblings[0] = [!first[0]; 32];
+                    assert!(!MerkleTree::verify_many(&tree.root(), &opened, &tampered));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_proof_shares_siblings_within_a_subtree() {
+        let leaves: Vec<[u8; 32]> = (0..8u8).map(|i| [i; 32]).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        // Leaves 0-3 fill the left subtree: only its sibling, the right subtree's root, is needed
+        let proof = tree.prove_many(&[3, 1, 0, 2, 1]).unwrap();
+        assert_eq!(proof.siblings, vec![tree.nodes[2][1]]);
+        let opened: Vec<(usize, [u8; 32])> = (0..4).map(|i| (i, leaves[i])).collect();
+        assert!(MerkleTree::verify_many(&tree.root(), &opened, &proof));
+        // Leaves the proof was not built for, a spare sibling or a conflicting repeat all fail
+        assert!(!MerkleTree::verify_many(&tree.root(), &opened[..3], &proof));
+        let mut spare = proof.clone();
+        spare.siblings.push([0u8; 32]);
+        assert!(!MerkleTree::verify_many(&tree.root(), &opened, &spare));
+        let mut repeated = opened.clone();
+        repeated.push((2, [9u8; 32]));
+        assert!(!MerkleTree::verify_many(&tree.root(), &repeated, &proof));
+        assert!(tree.prove_many(&[]).is_none());
+        assert!(tree.prove_many(&[8]).is_none());
+    }
+}
@@ -1,25 +1,37 @@
 #![allow(clippy::missing_errors_doc)]
-use anyhow::Result;
+use anyhow::{Context, Result};
 use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
 use serde::{Deserialize, Serialize};
 use winter_air::{
-    Air, AirContext, Assertion, EvaluationFrame, FieldExtension, ProofOptions, TraceInfo,
-    TransitionConstraintDegree,
+    Air, AirContext, Assertion, EvaluationFrame, PartitionOptions,
+    ProofOptions, TraceInfo, TransitionConstraintDegree,
 };
 use winter_crypto::{hashers::Rp64_256, DefaultRandomCoin, MerkleTree};
-use winter_math::{fields::f64::BaseElement as Felt, FieldElement, StarkField, ToElements};
+use winter_math::{fields::f64::BaseElement as Felt, FieldElement, ToElements};
 use winter_prover::{
-    matrix::ColMatrix, DefaultConstraintCommitment, DefaultConstraintEvaluator, DefaultTraceLde,
-    Proof, Prover, StarkDomain, TracePolyTable, TraceTable,
+    matrix::ColMatrix, CompositionPoly, CompositionPolyTrace, DefaultConstraintCommitment,
+    DefaultConstraintEvaluator, DefaultTraceLde, Proof, Prover, StarkDomain, Trace, TracePolyTable,
+    TraceTable,
 };
 use winter_verifier::{verify, AcceptableOptions, VerifierError};
 
+use crate::error::{check_trace_boundaries, check_trace_length, ProverError};
+use crate::limbs::u64_to_limbs;
+use crate::params::ProofParams;
+
 const TWO_32: u64 = 4294967296;
 const RPO_ALPHA: u64 = 7;
 const STATE_WIDTH: usize = 12;
 const NUM_ROUNDS: usize = 7;
 const ROUNDS_PER_WITNESS: usize = NUM_ROUNDS + 1; // 7 hash rounds + 1 transition row
 
+/// Upper bound on witnesses per proof, one per slot: mirrors `MAX_SLOTS_PER_ARTIFACT` (2048)
+/// in the validator_lock program, which rejects any wider range at anchoring time.
+pub const MAX_WITNESSES: usize = 2048;
+
+/// Backend this module implements; its proofs only verify here (see `crate::backend`).
+pub const BACKEND: crate::backend::ProofBackend = crate::backend::ProofBackend::NorthStar;
+
 #[rustfmt::skip]
 const MDS: [[u64; 12]; 12] = [
     [7, 23, 8, 26, 13, 10, 9, 4, 5, 2, 3, 1],
@@ -61,6 +73,51 @@ const ARK: [[u64; 12]; NUM_ROUNDS] = [
      0xd4e5f60718293a4b, 0x5c6d7e8f90a1b2c3, 0xd4e5f60718293a4b, 0x5c6d7e8f90a1b2c3],
 ];
 
+/// Which constraint families (and therefore which trace columns) a proof covers.
+///
+/// Columns are grouped so each scope's trace is a prefix of the next wider one; dropping the
+/// stake arithmetic removes the 128 bit-decomposition columns that dominate trace width.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofScope {
+    /// Slot progression only.
+    SlotsOnly,
+    /// Slot progression plus the state-root hash chain.
+    SlotsAndRoots,
+    /// Everything, including stake-delta arithmetic with 32-bit limb range checks.
+    #[default]
+    Full,
+}
+
+impl ProofScope {
+    /// Trace width for this scope.
+    pub const fn num_cols(self) -> usize {
+        match self {
+            Self::SlotsOnly => SLOTS_COLS,
+            Self::SlotsAndRoots => ROOTS_COLS,
+            Self::Full => NUM_COLS,
+        }
+    }
+
+    /// Whether the hash-chain columns and constraints are present.
+    pub const fn has_roots(self) -> bool {
+        matches!(self, Self::SlotsAndRoots | Self::Full)
+    }
+
+    /// Whether the stake arithmetic columns and constraints are present.
+    pub const fn has_stake(self) -> bool {
+        matches!(self, Self::Full)
+    }
+
+    /// Field encoding bound into the public inputs.
+    const fn tag(self) -> u64 {
+        match self {
+            Self::SlotsOnly => 0,
+            Self::SlotsAndRoots => 1,
+            Self::Full => 2,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PublicInputs {
     pub start_slot: u64,
@@ -68,40 +125,164 @@ pub struct PublicInputs {
     pub initial_state_root: [u8; 32],
     pub final_state_root: [u8; 32],
     pub blockhash: [u8; 32],
+    /// Constraint scope; part of the transcript, so prover and verifier must agree on it.
+    #[serde(default)]
+    pub scope: ProofScope,
 }
 
 impl ToElements<Felt> for PublicInputs {
     fn to_elements(&self) -> Vec<Felt> {
-        let mut res = Vec::with_capacity(14);
+        let mut res = Vec::with_capacity(15);
         res.push(Felt::new(self.start_slot));
         res.push(Felt::new(self.end_slot));
         res.extend(bytes_to_felts(&self.initial_state_root));
         res.extend(bytes_to_felts(&self.final_state_root));
         res.extend(bytes_to_felts(&self.blockhash));
+        res.push(Felt::new(self.scope.tag()));
         res
     }
 }
 
-#[derive(Serialize, Deserialize)]
+impl PublicInputs {
+    /// Public inputs an envelope must carry to match `record`.
+    ///
+    /// The record does not store the constraint scope, so the caller supplies the one the
+    /// proof was generated under; `blockhash` is taken from `ds_hash`, as in
+    /// [`verify_envelope_against_record`].
+    pub fn from_proof_record(record: &ProofRecordView, scope: ProofScope) -> Self {
+        Self {
+            start_slot: record.start_slot,
+            end_slot: record.end_slot,
+            initial_state_root: record.state_root_before,
+            final_state_root: record.state_root_after,
+            blockhash: record.ds_hash,
+            scope,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StarkProofEnvelope {
     pub proof: String,
     pub public_inputs: PublicInputs,
 }
 
-// NUM_COLS without explicit next_root columns (Option A)
-const NUM_COLS: usize = 157; // 161 - 4
+// Column layout (Option A: no explicit next_root columns). The round schedule and the
+// transition flag are fixed per 8-row block, so they are periodic columns, not trace columns.
+// Groups are ordered by scope: slots, then roots, then stake.
+const COL_SLOT: usize = 0;
+const COL_SLOT_BITS: usize = 1; // 8 bits
+const SLOTS_COLS: usize = 9;
+const COL_HASH: usize = SLOTS_COLS; // 12 lanes
+const ROOTS_COLS: usize = COL_HASH + STATE_WIDTH;
+const COL_STAKE_LO: usize = ROOTS_COLS;
+const COL_STAKE_HI: usize = COL_STAKE_LO + 1;
+const COL_DELTA_LO: usize = COL_STAKE_LO + 2;
+const COL_DELTA_HI: usize = COL_STAKE_LO + 3;
+const COL_AUX: usize = COL_STAKE_LO + 4;
+const COL_SIGN: usize = COL_STAKE_LO + 5;
+const COL_BITS: usize = COL_STAKE_LO + 6; // 4 limbs x 32 bits
+const NUM_COLS: usize = COL_BITS + 128; // 155
+
+// Periodic column layout: transition flag, then one round-constant column per hash lane.
+const PERIODIC_FLAG: usize = 0;
+const PERIODIC_ARK: usize = 1;
+
+/// One RPO-like round: `(state + ARK[round])^ALPHA`, then MDS.
+fn apply_round(state: &[Felt; STATE_WIDTH], round: usize) -> [Felt; STATE_WIDTH] {
+    let mut after_sbox = [Felt::ZERO; STATE_WIDTH];
+    for i in 0..STATE_WIDTH {
+        after_sbox[i] = (state[i] + Felt::new(ARK[round][i])).exp(RPO_ALPHA);
+    }
+    let mut next_state = [Felt::ZERO; STATE_WIDTH];
+    for i in 0..STATE_WIDTH {
+        for j in 0..STATE_WIDTH {
+            next_state[i] += after_sbox[j] * Felt::new(MDS[i][j]);
+        }
+    }
+    next_state
+}
+
+/// Hash state at the start of a witness block (Option A: seeded by the previous root only).
+fn initial_hash_state(prev_root: &[Felt]) -> [Felt; STATE_WIDTH] {
+    let mut state = [Felt::ZERO; STATE_WIDTH];
+    state[..4].copy_from_slice(&prev_root[..4]);
+    state
+}
+
+/// Root the hash chain reaches after `num_witnesses` blocks; this is the `final_state_root`
+/// a valid proof must bind.
+pub fn chain_final_root(initial_state_root: &[u8; 32], num_witnesses: usize) -> [u8; 32] {
+    let mut root = bytes_to_felts(initial_state_root);
+    for _ in 0..num_witnesses {
+        let mut state = initial_hash_state(&root);
+        for round in 0..NUM_ROUNDS {
+            state = apply_round(&state, round);
+        }
+        root = state[..4].to_vec();
+    }
+    felts_to_bytes(&root)
+}
+
+fn witness_stake(w: &crate::witness::SlotWitness) -> Result<u64> {
+    let total: u128 = w.vote_accounts.iter().map(|v| v.activated_stake as u128).sum();
+    u64::try_from(total).map_err(|_| anyhow::anyhow!("Stake overflow at slot {}", w.slot))
+}
+
+/// Which slots between `start_slot` and `end_slot` the witnesses are claimed to cover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotCoverage<'a> {
+    /// Every slot in `[start_slot, end_slot]`.
+    Contiguous,
+    /// Exactly these slots, e.g. the produced slots of a range with skipped slots.
+    Explicit(&'a [u64]),
+}
+
+/// Check the witnesses cover exactly the claimed slots, so none can be silently dropped.
+fn check_witness_count(
+    witnesses: &[crate::witness::SlotWitness],
+    pub_inputs: &PublicInputs,
+    coverage: SlotCoverage<'_>,
+) -> Result<(), ProverError> {
+    let got = witnesses.len();
+    let (expected, covered) = match coverage {
+        SlotCoverage::Contiguous => {
+            let span = pub_inputs
+                .end_slot
+                .checked_sub(pub_inputs.start_slot)
+                .and_then(|span| usize::try_from(span).ok())
+                .and_then(|span| span.checked_add(1))
+                .unwrap_or(0);
+            // Endpoints and strict ordering are checked by the caller, so the count suffices
+            (span, span == got)
+        }
+        SlotCoverage::Explicit(slots) => {
+            (slots.len(), witnesses.iter().map(|w| w.slot).eq(slots.iter().copied()))
+        }
+    };
+    if covered {
+        Ok(())
+    } else {
+        Err(ProverError::WitnessCountMismatch { expected, got })
+    }
+}
 
 pub fn build_trace(
     witnesses: &[crate::witness::SlotWitness],
     pub_inputs: &PublicInputs,
+    coverage: SlotCoverage<'_>,
 ) -> Result<TraceTable<Felt>> {
-    if witnesses.is_empty() {
-        anyhow::bail!("Witnesses cannot be empty");
+    let (first, last) = witnesses
+        .first()
+        .zip(witnesses.last())
+        .ok_or_else(|| anyhow::anyhow!("Witnesses cannot be empty"))?;
+    if witnesses.len() > MAX_WITNESSES {
+        return Err(ProverError::TooManyWitnesses { got: witnesses.len(), max: MAX_WITNESSES }.into());
     }
-    if witnesses[0].slot != pub_inputs.start_slot {
+    if first.slot != pub_inputs.start_slot {
         anyhow::bail!("Start slot mismatch");
     }
-    if witnesses.last().unwrap().slot != pub_inputs.end_slot {
+    if last.slot != pub_inputs.end_slot {
         anyhow::bail!("End slot mismatch");
     }
     for i in 1..witnesses.len() {
@@ -109,114 +290,79 @@ pub fn build_trace(
             anyhow::bail!("Slots must be strictly increasing");
         }
     }
+    check_witness_count(witnesses, pub_inputs, coverage)?;
 
+    let scope = pub_inputs.scope;
     let trace_len = witnesses.len() * ROUNDS_PER_WITNESS;
-    let mut trace = vec![Vec::with_capacity(trace_len); NUM_COLS];
+    check_trace_length(trace_len)?;
+    let mut trace = vec![Vec::with_capacity(trace_len); scope.num_cols()];
 
-    let blockhash_felts = bytes_to_felts(&pub_inputs.blockhash);
     let mut prev_root = bytes_to_felts(&pub_inputs.initial_state_root);
 
     for (witness_idx, w) in witnesses.iter().enumerate() {
         let is_last_witness = witness_idx == witnesses.len() - 1;
 
         // Compute stake limbs and delta (used for constraints only)
-        let total_stake_u128: u128 = w
-            .vote_accounts
-            .iter()
-            .map(|v| v.activated_stake as u128)
-            .sum();
-        if total_stake_u128 > u64::MAX as u128 {
-            anyhow::bail!("Stake overflow at slot {}", w.slot);
-        }
-        let total_stake = total_stake_u128 as u64;
-        let next_stake = if is_last_witness {
-            total_stake
-        } else {
-            let s: u128 = witnesses[witness_idx + 1]
-                .vote_accounts
-                .iter()
-                .map(|v| v.activated_stake as u128)
-                .sum();
-            if s > u64::MAX as u128 {
-                anyhow::bail!("Stake overflow at next slot {}", witnesses[witness_idx + 1].slot);
-            }
-            s as u64
-        };
+        let total_stake = witness_stake(w)?;
+        let next_stake =
+            if is_last_witness { total_stake } else { witness_stake(&witnesses[witness_idx + 1])? };
         let (delta_abs, sign) = if next_stake >= total_stake {
             (next_stake - total_stake, 0u64)
         } else {
             (total_stake - next_stake, 1u64)
         };
-        let stake_lo = total_stake & 0xFFFF_FFFF;
-        let stake_hi = total_stake >> 32;
-        let delta_lo = delta_abs & 0xFFFF_FFFF;
-        let delta_hi = delta_abs >> 32;
+        let [stake_lo, stake_hi, delta_lo, delta_hi] = {
+            let ((s_lo, s_hi), (d_lo, d_hi)) = (u64_to_limbs(total_stake), u64_to_limbs(delta_abs));
+            [s_lo, s_hi, d_lo, d_hi].map(u64::from)
+        };
         let slot_delta = if is_last_witness { 0 } else { witnesses[witness_idx + 1].slot - w.slot };
         if slot_delta >= 256 {
             anyhow::bail!("Slot delta too large");
         }
+        let aux = if sign == 0 {
+            (stake_lo + delta_lo) / TWO_32
+        } else if stake_lo < delta_lo {
+            1
+        } else {
+            0
+        };
 
-        let mut hash_state = [Felt::ZERO; STATE_WIDTH];
+        // Row r holds the state *before* round r, so the transition row (r = 7) holds the
+        // state after all NUM_ROUNDS rounds; its first 4 lanes seed the next block.
+        let mut hash_state = initial_hash_state(&prev_root);
 
         for round in 0..ROUNDS_PER_WITNESS {
-            // round counter
-            trace[12].push(Felt::new(round as u64));
-
-            if round < NUM_ROUNDS {
-                if round == 0 {
-                    // Option A: initialize from prev_root only; do not inject message.
-                    for i in 0..4 {
-                        hash_state[i] = prev_root[i];
-                    }
-                    for i in 4..STATE_WIDTH {
-                        hash_state[i] = Felt::ZERO;
-                    }
-                }
-                // one RPO-like round: (state + ARK)^ALPHA then MDS
-                let mut after_sbox = [Felt::ZERO; STATE_WIDTH];
-                for i in 0..STATE_WIDTH {
-                    let ark = Felt::new(ARK[round][i]);
-                    after_sbox[i] = (hash_state[i] + ark).exp(Felt::from(RPO_ALPHA));
-                }
-                let mut next_state = [Felt::ZERO; STATE_WIDTH];
+            // slot
+            trace[COL_SLOT].push(Felt::new(w.slot));
+            // slot delta bits
+            push_bits(&mut trace, COL_SLOT_BITS, slot_delta, 8);
+            if scope.has_roots() {
+                // hash_state lanes 0..11
                 for i in 0..STATE_WIDTH {
-                    for j in 0..STATE_WIDTH {
-                        next_state[i] += after_sbox[j] * Felt::new(MDS[i][j]);
-                    }
+                    trace[COL_HASH + i].push(hash_state[i]);
                 }
-                hash_state = next_state;
             }
-
-            // push hash_state lanes 0..11
-            for i in 0..STATE_WIDTH {
-                trace[i].push(hash_state[i]);
+            if round < NUM_ROUNDS {
+                hash_state = apply_round(&hash_state, round);
             }
-            // slot
-            trace[13].push(Felt::new(w.slot));
-            // slot delta bits
-            for b in 0..8 {
-                trace[14 + b].push(Felt::new((slot_delta >> b) & 1));
+            if scope.has_stake() {
+                // arithmetic lanes
+                trace[COL_STAKE_LO].push(Felt::new(stake_lo));
+                trace[COL_STAKE_HI].push(Felt::new(stake_hi));
+                trace[COL_DELTA_LO].push(Felt::new(delta_lo));
+                trace[COL_DELTA_HI].push(Felt::new(delta_hi));
+                trace[COL_AUX].push(Felt::new(aux));
+                trace[COL_SIGN].push(Felt::new(sign));
+                // bit decompositions for limbs
+                push_bits(&mut trace, COL_BITS, stake_lo, 32);
+                push_bits(&mut trace, COL_BITS + 32, stake_hi, 32);
+                push_bits(&mut trace, COL_BITS + 64, delta_lo, 32);
+                push_bits(&mut trace, COL_BITS + 96, delta_hi, 32);
             }
-            // arithmetic lanes
-            trace[22].push(Felt::new(stake_lo));
-            trace[23].push(Felt::new(stake_hi));
-            trace[24].push(Felt::new(delta_lo));
-            trace[25].push(Felt::new(delta_hi));
-            let aux = if sign == 0 { (stake_lo + delta_lo) / TWO_32 } else { if stake_lo < delta_lo { 1 } else { 0 } };
-            trace[26].push(Felt::new(aux));
-            trace[27].push(Felt::new(sign));
-            // bit decompositions for limbs
-            push_bits(&mut trace, 28, stake_lo, 32);
-            push_bits(&mut trace, 60, stake_hi, 32);
-            push_bits(&mut trace, 92, delta_lo, 32);
-            push_bits(&mut trace, 124, delta_hi, 32);
-            // transition flag
-            let is_transition = if round == ROUNDS_PER_WITNESS - 1 { 1u64 } else { 0u64 };
-            trace[156].push(Felt::new(is_transition)); // last column index (0-based): 156
         }
 
         // carry root forward
-        prev_root = [hash_state[0], hash_state[1], hash_state[2], hash_state[3]];
+        prev_root = hash_state[..4].to_vec();
     }
 
     Ok(TraceTable::init(trace))
@@ -233,38 +379,47 @@ impl Air for SolanaStateAir {
     type PublicInputs = PublicInputs;
 
     fn new(trace_info: TraceInfo, pub_inputs: PublicInputs, options: ProofOptions) -> Self {
+        // Degrees are exact: winter-prover checks them in debug builds. Masking by the
+        // periodic transition flag adds one 8-cycle to the constraint's degree.
+        let masked = |base| TransitionConstraintDegree::with_cycles(base, vec![ROUNDS_PER_WITNESS]);
+        let scope = pub_inputs.scope;
         let mut degrees = Vec::new();
-        // 0..11: hash constraints
-        for _ in 0..12 {
-            degrees.push(TransitionConstraintDegree::new(7));
-        }
-        // 12: round counter
-        degrees.push(TransitionConstraintDegree::new(1));
-        // 13: slot transition
-        degrees.push(TransitionConstraintDegree::new(2));
-        // 14..21: slot bits
+        // slot transition, 8 slot bits, slot constancy on hash rows
+        degrees.push(masked(1));
         for _ in 0..8 {
             degrees.push(TransitionConstraintDegree::new(2));
         }
-        // 22..27: arithmetic
-        for _ in 0..6 {
-            degrees.push(TransitionConstraintDegree::new(2));
+        degrees.push(masked(1));
+        if scope.has_roots() {
+            // 12 hash rounds (x^7 with periodic round constants), 4 root carries
+            for _ in 0..STATE_WIDTH {
+                degrees.push(masked(RPO_ALPHA as usize));
+            }
+            for _ in 0..4 {
+                degrees.push(masked(1));
+            }
         }
-        // 28..155: bit validity
-        for _ in 0..128 {
+        if scope.has_stake() {
+            // add/sub lo and hi, aux/sign binary, delta constancy
+            degrees.push(masked(2));
+            degrees.push(masked(2));
+            degrees.push(TransitionConstraintDegree::new(2));
             degrees.push(TransitionConstraintDegree::new(2));
+            degrees.push(masked(1));
+            degrees.push(masked(1));
+            // 128 bit validity, 4 limb recompositions, stake constancy on hash rows
+            for _ in 0..128 {
+                degrees.push(TransitionConstraintDegree::new(2));
+            }
+            for _ in 0..4 {
+                degrees.push(TransitionConstraintDegree::new(1));
+            }
+            degrees.push(masked(1));
+            degrees.push(masked(1));
         }
-        // 156: transition_flag constraints will be added inline
-        // add a few slots for binary and gating constraints
-        degrees.push(TransitionConstraintDegree::new(2)); // transition_flag binary
-        degrees.push(TransitionConstraintDegree::new(1)); // round gating
-        // plus extra for intra-witness constancy (slot/stake const on hash rows)
-        degrees.push(TransitionConstraintDegree::new(1));
-        degrees.push(TransitionConstraintDegree::new(1));
-        degrees.push(TransitionConstraintDegree::new(1));
-
-        let options = options.with_field_extension(FieldExtension::Quadratic);
-        let context = AirContext::new(trace_info, degrees, 10, options);
+
+        let num_assertions = if scope.has_roots() { 10 } else { 2 };
+        let context = AirContext::new(trace_info, degrees, num_assertions, options);
         Self { context, pub_inputs }
     }
 
@@ -275,149 +430,172 @@ impl Air for SolanaStateAir {
     fn evaluate_transition<E: FieldElement<BaseField = Felt>>(
         &self,
         frame: &EvaluationFrame<E>,
-        _periodic_values: &[E],
+        periodic_values: &[E],
         result: &mut [E],
     ) {
         let cur = frame.current();
         let next = frame.next();
         let one = E::ONE;
         let two = E::from(2u32);
-        let seven = E::from(7u32);
 
-        let round = cur[12];
-        let t = cur[156]; // transition_flag
+        let t = periodic_values[PERIODIC_FLAG]; // transition_flag
         let is_hash_round = one - t;
         let is_transition_round = t;
 
         let mut idx = 0;
-        // 1) Hash constraints on hash rows
-        let round_idx = (round.as_int() as usize) % NUM_ROUNDS;
-        let mut sbox = [E::ZERO; STATE_WIDTH];
-        for j in 0..STATE_WIDTH {
-            let ark = E::from(ARK[round_idx][j]);
-            sbox[j] = (cur[j] + ark).exp(E::PositiveInteger::from(RPO_ALPHA));
-        }
-        for i in 0..STATE_WIDTH {
-            let mut mds_res = E::ZERO;
-            for j in 0..STATE_WIDTH {
-                mds_res += sbox[j] * E::from(MDS[i][j]);
-            }
-            result[idx] = (next[i] - mds_res) * is_hash_round;
-            idx += 1;
-        }
-
-        // 2) Round counter: next = round+1, or reset to 0 on transition (8-cycle)
-        let next_round_expected = (round + one) - (t * E::from(8u32));
-        result[idx] = next[12] - next_round_expected;
-        idx += 1;
 
-        // 3) Slot transition only on transition rows (recompose from bits)
+        // 1) Slot transition only on transition rows (recompose from bits)
         let mut slot_delta = E::ZERO;
         let mut p2 = E::ONE;
         for i in 0..8 {
-            slot_delta += cur[14 + i] * p2;
+            slot_delta += cur[COL_SLOT_BITS + i] * p2;
             p2 *= two;
         }
-        result[idx] = (next[13] - (cur[13] + slot_delta)) * is_transition_round;
+        result[idx] = (next[COL_SLOT] - (cur[COL_SLOT] + slot_delta)) * is_transition_round;
         idx += 1;
 
-        // 4) Slot bits binary
+        // 2) Slot bits binary
         for i in 0..8 {
-            let bit = cur[14 + i];
+            let bit = cur[COL_SLOT_BITS + i];
             result[idx] = bit * (bit - one);
             idx += 1;
         }
 
-        // 5) Arithmetic lane on transition rows (stake +/- delta with aux)
-        let stake_lo = cur[22];
-        let stake_hi = cur[23];
-        let delta_lo = cur[24];
-        let delta_hi = cur[25];
-        let aux = cur[26];
-        let sign = cur[27];
-        let stake_lo_next = next[22];
-        let stake_hi_next = next[23];
-        let is_add = one - sign;
-        let is_sub = sign;
-        let two32 = E::from(TWO_32);
-        let add_lo = (stake_lo + delta_lo) - (stake_lo_next + aux * two32);
-        let sub_lo = (stake_lo - delta_lo + aux * two32) - stake_lo_next;
-        result[idx] = (is_add * add_lo + is_sub * sub_lo) * is_transition_round;
-        idx += 1;
-        let add_hi = (stake_hi + delta_hi + aux) - stake_hi_next;
-        let sub_hi = (stake_hi - delta_hi - aux) - stake_hi_next;
-        result[idx] = (is_add * add_hi + is_sub * sub_hi) * is_transition_round;
-        idx += 1;
-        // aux binary
-        result[idx] = aux * (aux - one);
-        idx += 1;
-        // sign binary
-        result[idx] = sign * (sign - one);
-        idx += 1;
-        // delta const on hash rows
-        result[idx] = (next[24] - delta_lo) * is_hash_round;
-        idx += 1;
-        result[idx] = (next[25] - delta_hi) * is_hash_round;
+        // 3) Slot constant on hash rows
+        result[idx] = (next[COL_SLOT] - cur[COL_SLOT]) * is_hash_round;
         idx += 1;
 
-        // 6) Bit validity 0/1
-        for i in 0..128 {
-            let bit = cur[28 + i];
-            result[idx] = bit * (bit - one);
-            idx += 1;
-        }
+        if self.pub_inputs.scope.has_roots() {
+            // 4) Hash constraints on hash rows, with this row's round constants
+            let mut sbox = [E::ZERO; STATE_WIDTH];
+            for j in 0..STATE_WIDTH {
+                sbox[j] = (cur[COL_HASH + j] + periodic_values[PERIODIC_ARK + j])
+                    .exp(E::PositiveInteger::from(RPO_ALPHA));
+            }
+            for i in 0..STATE_WIDTH {
+                let mut mds_res = E::ZERO;
+                for j in 0..STATE_WIDTH {
+                    mds_res += sbox[j] * E::from(Felt::new(MDS[i][j]));
+                }
+                result[idx] = (next[COL_HASH + i] - mds_res) * is_hash_round;
+                idx += 1;
+            }
 
-        // 7) Bit recomposition for 4 limbs
-        for (limb_col, bit_start) in [(22usize, 28usize), (23, 60), (24, 92), (25, 124)] {
-            let mut reconstructed = E::ZERO;
-            let mut p = E::ONE;
-            for i in 0..32 {
-                reconstructed += cur[bit_start + i] * p;
-                p *= two;
+            // 5) Root carry at transition: next state lanes == current state lanes
+            for i in 0..4 {
+                result[idx] = (next[COL_HASH + i] - cur[COL_HASH + i]) * is_transition_round;
+                idx += 1;
             }
-            result[idx] = cur[limb_col] - reconstructed;
-            idx += 1;
         }
 
-        // 8) Root carry at transition: next state lanes == current state lanes
-        for i in 0..4 {
-            result[idx] = (next[i] - cur[i]) * is_transition_round;
+        if self.pub_inputs.scope.has_stake() {
+            // 6) Arithmetic lane on transition rows (stake +/- delta with aux)
+            let stake_lo = cur[COL_STAKE_LO];
+            let stake_hi = cur[COL_STAKE_HI];
+            let delta_lo = cur[COL_DELTA_LO];
+            let delta_hi = cur[COL_DELTA_HI];
+            let aux = cur[COL_AUX];
+            let sign = cur[COL_SIGN];
+            let stake_lo_next = next[COL_STAKE_LO];
+            let stake_hi_next = next[COL_STAKE_HI];
+            let is_add = one - sign;
+            let is_sub = sign;
+            let two32 = E::from(Felt::new(TWO_32));
+            let add_lo = (stake_lo + delta_lo) - (stake_lo_next + aux * two32);
+            let sub_lo = (stake_lo - delta_lo + aux * two32) - stake_lo_next;
+            result[idx] = (is_add * add_lo + is_sub * sub_lo) * is_transition_round;
+            idx += 1;
+            let add_hi = (stake_hi + delta_hi + aux) - stake_hi_next;
+            let sub_hi = (stake_hi - delta_hi - aux) - stake_hi_next;
+            result[idx] = (is_add * add_hi + is_sub * sub_hi) * is_transition_round;
+            idx += 1;
+            // aux binary
+            result[idx] = aux * (aux - one);
+            idx += 1;
+            // sign binary
+            result[idx] = sign * (sign - one);
+            idx += 1;
+            // delta const on hash rows
+            result[idx] = (next[COL_DELTA_LO] - delta_lo) * is_hash_round;
+            idx += 1;
+            result[idx] = (next[COL_DELTA_HI] - delta_hi) * is_hash_round;
             idx += 1;
-        }
 
-        // 9) Intra-witness constancy on hash rows: slot and stake are constant
-        result[idx] = (next[13] - cur[13]) * is_hash_round;
-        idx += 1;
-        result[idx] = (next[22] - cur[22]) * is_hash_round;
-        idx += 1;
-        result[idx] = (next[23] - cur[23]) * is_hash_round;
-        idx += 1;
+            // 7) Bit validity 0/1
+            for i in 0..128 {
+                let bit = cur[COL_BITS + i];
+                result[idx] = bit * (bit - one);
+                idx += 1;
+            }
 
-        // 10) transition_flag binary and round gating (only when round==7)
-        result[idx] = t * (t - one);
-        idx += 1;
-        result[idx] = (round - seven) * t;
-        idx += 1;
+            // 8) Bit recomposition for 4 limbs
+            for (limb, limb_col) in
+                [COL_STAKE_LO, COL_STAKE_HI, COL_DELTA_LO, COL_DELTA_HI].into_iter().enumerate()
+            {
+                let bit_start = COL_BITS + limb * 32;
+                let mut reconstructed = E::ZERO;
+                let mut p = E::ONE;
+                for i in 0..32 {
+                    reconstructed += cur[bit_start + i] * p;
+                    p *= two;
+                }
+                result[idx] = cur[limb_col] - reconstructed;
+                idx += 1;
+            }
+
+            // 9) Intra-witness constancy on hash rows: stake is constant
+            result[idx] = (next[COL_STAKE_LO] - stake_lo) * is_hash_round;
+            idx += 1;
+            result[idx] = (next[COL_STAKE_HI] - stake_hi) * is_hash_round;
+            idx += 1;
+        }
+
+        debug_assert_eq!(idx, result.len());
     }
 
-    fn get_assertions(&self) -> Vec<Assertion<Felt>> {
-        let last_step = self.trace_length() - 1;
-        let mut assertions = Vec::new();
-        // Bind endpoints: slots and roots
-        assertions.push(Assertion::single(13, 0, Felt::new(self.pub_inputs.start_slot)));
-        assertions.push(Assertion::single(13, last_step, Felt::new(self.pub_inputs.end_slot)));
-        // Initial root lanes 0..3
-        let init = bytes_to_felts(&self.pub_inputs.initial_state_root);
-        for i in 0..4 {
-            assertions.push(Assertion::single(i, 0, init[i]));
+    fn get_periodic_column_values(&self) -> Vec<Vec<Felt>> {
+        let mut columns = Vec::with_capacity(1 + STATE_WIDTH);
+        // transition flag: set on the last row of each block
+        let mut flag = vec![Felt::ZERO; ROUNDS_PER_WITNESS];
+        flag[NUM_ROUNDS] = Felt::ONE;
+        columns.push(flag);
+        if !self.pub_inputs.scope.has_roots() {
+            return columns;
         }
-        // Final root lanes 0..3
-        let fin = bytes_to_felts(&self.pub_inputs.final_state_root);
-        for i in 0..4 {
-            assertions.push(Assertion::single(i, last_step, fin[i]));
+        // round constants: ARK[r] on hash row r, unused on the transition row
+        for lane in 0..STATE_WIDTH {
+            let mut col: Vec<Felt> = ARK.iter().map(|ark| Felt::new(ark[lane])).collect();
+            col.push(Felt::ZERO);
+            columns.push(col);
         }
-        assertions
+        columns
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Felt>> {
+        boundary_assertions(&self.pub_inputs, self.trace_length() - 1)
+    }
+}
+
+/// Boundary assertions `pub_inputs` imply for a trace whose last row is `last_step`.
+fn boundary_assertions(pub_inputs: &PublicInputs, last_step: usize) -> Vec<Assertion<Felt>> {
+    let mut assertions = Vec::new();
+    // Bind endpoints: slots and, when in scope, roots
+    assertions.push(Assertion::single(COL_SLOT, 0, Felt::new(pub_inputs.start_slot)));
+    assertions.push(Assertion::single(COL_SLOT, last_step, Felt::new(pub_inputs.end_slot)));
+    if !pub_inputs.scope.has_roots() {
+        return assertions;
+    }
+    // Initial root lanes 0..3
+    let init = bytes_to_felts(&pub_inputs.initial_state_root);
+    for i in 0..4 {
+        assertions.push(Assertion::single(COL_HASH + i, 0, init[i]));
     }
+    // Final root lanes 0..3
+    let fin = bytes_to_felts(&pub_inputs.final_state_root);
+    for i in 0..4 {
+        assertions.push(Assertion::single(COL_HASH + i, last_step, fin[i]));
+    }
+    assertions
 }
 
 struct SolanaProver {
@@ -452,11 +630,26 @@ impl Prover for SolanaProver {
         trace_info: &TraceInfo,
         main_trace: &ColMatrix<Felt>,
         domain: &StarkDomain<Felt>,
-        partition_options: winter_prover::PartitionOptions,
+        partition_options: PartitionOptions,
     ) -> (Self::TraceLde<E>, TracePolyTable<E>) {
         DefaultTraceLde::new(trace_info, main_trace, domain, partition_options)
     }
 
+    fn build_constraint_commitment<E: FieldElement<BaseField = Felt>>(
+        &self,
+        composition_poly_trace: CompositionPolyTrace<E>,
+        num_constraint_composition_columns: usize,
+        domain: &StarkDomain<Felt>,
+        partition_options: PartitionOptions,
+    ) -> (Self::ConstraintCommitment<E>, CompositionPoly<E>) {
+        DefaultConstraintCommitment::new(
+            composition_poly_trace,
+            num_constraint_composition_columns,
+            domain,
+            partition_options,
+        )
+    }
+
     fn new_evaluator<'a, E: FieldElement<BaseField = Felt>>(
         &self,
         air: &'a Self::Air,
@@ -467,12 +660,27 @@ impl Prover for SolanaProver {
     }
 }
 
+/// Proof options shared by prover and verifier.
+fn proof_options() -> ProofOptions {
+    ProofParams::NORTH_STAR.build()
+}
+
 pub fn generate_proof(
     pub_inputs: PublicInputs,
     witnesses: &[crate::witness::SlotWitness],
+    coverage: SlotCoverage<'_>,
+) -> Result<StarkProofEnvelope> {
+    generate_proof_with_options(pub_inputs, witnesses, coverage, proof_options())
+}
+
+fn generate_proof_with_options(
+    pub_inputs: PublicInputs,
+    witnesses: &[crate::witness::SlotWitness],
+    coverage: SlotCoverage<'_>,
+    options: ProofOptions,
 ) -> Result<StarkProofEnvelope> {
-    let trace = build_trace(witnesses, &pub_inputs)?;
-    let options = ProofOptions::new(64, 16, 20, FieldExtension::Quadratic, 8, 31);
+    let trace = build_trace(witnesses, &pub_inputs, coverage)?;
+    check_trace_boundaries(&trace, &boundary_assertions(&pub_inputs, trace.length().saturating_sub(1)))?;
     let prover = SolanaProver { options, pub_inputs: pub_inputs.clone() };
     let proof = prover
         .prove(trace)
@@ -483,31 +691,639 @@ pub fn generate_proof(
     })
 }
 
-pub fn verify_proof(envelope: StarkProofEnvelope) -> Result<bool> {
-    let proof_bytes = B64
-        .decode(envelope.proof)
-        .context("Failed to decode base64 proof")?;
-    let proof = Proof::from_bytes(&proof_bytes).context("Failed to deserialize proof")?;
-    let options = ProofOptions::new(64, 16, 20, FieldExtension::Quadratic, 8, 31);
-    let acceptable = AcceptableOptions::Option(options);
-    match verify::<SolanaStateAir, Rp64_256, DefaultRandomCoin<Rp64_256>, MerkleTree<Rp64_256>>(
+/// Why [`verify_proof`] rejected a proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvalidReason {
+    /// The proof is not valid base64.
+    Encoding,
+    /// The proof bytes do not deserialize into a winterfell proof.
+    Malformed,
+    /// The trace width does not match the claimed scope, so it was proven under another one.
+    ScopeMismatch {
+        /// Width the claimed scope implies.
+        expected: usize,
+        /// Width the proof commits to.
+        got: usize,
+    },
+    /// The proof was generated under options (or at a security level) the verifier does not
+    /// accept.
+    Options,
+    /// The constraints or boundary assertions do not hold for these public inputs.
+    Constraints,
+    /// A trace or constraint query opening does not match its Merkle commitment.
+    MerkleOpening,
+    /// The proof-of-work on the query seed is missing or wrong.
+    ProofOfWork,
+    /// The FRI low-degree test failed.
+    Fri,
+}
+
+impl std::fmt::Display for InvalidReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Encoding => f.write_str("proof is not valid base64"),
+            Self::Malformed => f.write_str("proof bytes do not deserialize"),
+            Self::ScopeMismatch { expected, got } => {
+                write!(f, "proof has {got} trace columns but its scope needs {expected}")
+            }
+            Self::Options => f.write_str("proof options are not accepted"),
+            Self::Constraints => f.write_str("constraints do not hold for the public inputs"),
+            Self::MerkleOpening => f.write_str("query opening does not match its commitment"),
+            Self::ProofOfWork => f.write_str("query seed proof-of-work failed"),
+            Self::Fri => f.write_str("FRI low-degree test failed"),
+        }
+    }
+}
+
+/// Result of verifying a well-formed request: the proof either holds or fails for a reason.
+///
+/// `Err` from [`verify_proof`] is reserved for the verifier itself failing (e.g. a field the
+/// AIR does not support), which says nothing about the proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    /// The proof holds for its public inputs.
+    Valid,
+    /// The proof does not hold.
+    Invalid {
+        /// First check it failed.
+        reason: InvalidReason,
+    },
+}
+
+impl VerifyOutcome {
+    /// Whether the proof holds.
+    pub const fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+}
+
+/// Verify `envelope` under the `north_star` proof options, reporting why a proof is invalid.
+pub fn verify_proof(envelope: StarkProofEnvelope) -> Result<VerifyOutcome> {
+    verify_proof_with_options(envelope, proof_options())
+}
+
+/// [`verify_proof`] collapsed to whether the proof holds, for callers that only need that.
+pub fn verify_proof_bool(envelope: StarkProofEnvelope) -> Result<bool> {
+    Ok(verify_proof(envelope)?.is_valid())
+}
+
+fn verify_proof_with_options(envelope: StarkProofEnvelope, options: ProofOptions) -> Result<VerifyOutcome> {
+    let invalid = |reason| Ok(VerifyOutcome::Invalid { reason });
+    let Ok(proof_bytes) = B64.decode(envelope.proof) else {
+        return invalid(InvalidReason::Encoding);
+    };
+    let Ok(proof) = Proof::from_bytes(&proof_bytes) else {
+        return invalid(InvalidReason::Malformed);
+    };
+    // The claimed scope selects the AIR layout; a trace of another width was proven under a
+    // different scope and must not be evaluated against this one.
+    let (expected, got) = (envelope.public_inputs.scope.num_cols(), proof.trace_info().main_trace_width());
+    if got != expected {
+        return invalid(InvalidReason::ScopeMismatch { expected, got });
+    }
+    let acceptable = AcceptableOptions::OptionSet(vec![options]);
+    let reason = match verify::<SolanaStateAir, Rp64_256, DefaultRandomCoin<Rp64_256>, MerkleTree<Rp64_256>>(
         proof,
         envelope.public_inputs,
         &acceptable,
     ) {
-        Ok(_) => Ok(true),
-        Err(VerifierError::ProofVerificationError(_)) => Ok(false),
-        Err(e) => Err(anyhow::anyhow!("Verification system error: {}", e)),
+        Ok(()) => return Ok(VerifyOutcome::Valid),
+        Err(VerifierError::ProofDeserializationError(_)) => InvalidReason::Malformed,
+        Err(
+            VerifierError::UnacceptableProofOptions
+            | VerifierError::InsufficientConjecturedSecurity(..)
+            | VerifierError::InsufficientProvenSecurity(..),
+        ) => InvalidReason::Options,
+        Err(VerifierError::InconsistentOodConstraintEvaluations) => InvalidReason::Constraints,
+        Err(VerifierError::TraceQueryDoesNotMatchCommitment | VerifierError::ConstraintQueryDoesNotMatchCommitment) => {
+            InvalidReason::MerkleOpening
+        }
+        Err(VerifierError::QuerySeedProofOfWorkVerificationFailed) => InvalidReason::ProofOfWork,
+        Err(VerifierError::FriVerificationFailed(_)) => InvalidReason::Fri,
+        Err(e) => return Err(anyhow::anyhow!("Verification system error: {}", e)),
+    };
+    invalid(reason)
+}
+
+/// The `ProofRecord` fields an envelope is checked against, as read from the anchored account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofRecordView {
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub state_root_before: [u8; 32],
+    pub state_root_after: [u8; 32],
+    /// The record has no dedicated blockhash field; the envelope `blockhash` binds to `ds_hash`.
+    pub ds_hash: [u8; 32],
+}
+
+/// Verify `envelope` and check its public inputs are the ones anchored in `record`.
+///
+/// Fails with `ProverError::RecordMismatch` naming the first differing field, or
+/// `ProverError::ProofRejected` if the proof itself does not verify.
+pub fn verify_envelope_against_record(
+    envelope: &StarkProofEnvelope,
+    record: &ProofRecordView,
+) -> Result<()> {
+    verify_envelope_against_record_with_options(envelope, record, proof_options())
+}
+
+fn verify_envelope_against_record_with_options(
+    envelope: &StarkProofEnvelope,
+    record: &ProofRecordView,
+    options: ProofOptions,
+) -> Result<()> {
+    let pi = &envelope.public_inputs;
+    let checks = [
+        ("start_slot", pi.start_slot == record.start_slot),
+        ("end_slot", pi.end_slot == record.end_slot),
+        ("initial_state_root", pi.initial_state_root == record.state_root_before),
+        ("final_state_root", pi.final_state_root == record.state_root_after),
+        ("blockhash", pi.blockhash == record.ds_hash),
+    ];
+    if let Some((field, _)) = checks.iter().find(|(_, ok)| !ok) {
+        return Err(ProverError::RecordMismatch { field }.into());
+    }
+    let envelope = StarkProofEnvelope { proof: envelope.proof.clone(), public_inputs: pi.clone() };
+    if !verify_proof_with_options(envelope, options)?.is_valid() {
+        return Err(ProverError::ProofRejected.into());
     }
+    Ok(())
 }
 
+fn push_bits(trace: &mut [Vec<Felt>], start: usize, value: u64, num_bits: usize) {
+    for b in 0..num_bits {
+        trace[start + b].push(Felt::new((value >> b) & 1));
+    }
+}
+
+/// Four little-endian u64 lanes of `bytes`; the array type rules out a short chunk.
 fn bytes_to_felts(bytes: &[u8; 32]) -> Vec<Felt> {
     (0..4)
-        .map(|i| {
-            let start = i * 8;
-            let chunk = &bytes[start..start + 8];
-            Felt::new(u64::from_le_bytes(chunk.try_into().unwrap()))
-        })
+        .map(|i| Felt::new(u64::from_le_bytes(std::array::from_fn(|j| bytes[i * 8 + j]))))
         .collect()
 }
 
+
+fn felts_to_bytes(felts: &[Felt]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (chunk, felt) in out.chunks_exact_mut(8).zip(felts) {
+        chunk.copy_from_slice(&felt.as_int().to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::witness::{SlotWitness, VoteAccountWitness};
+    use std::sync::OnceLock;
+    use winter_air::FieldExtension;
+    use winter_prover::Trace;
+
+    /// Small, fast parameters for tests; production parameters are covered by `proof_options()`.
+    const TEST_PARAMS: ProofParams = ProofParams {
+        num_queries: 4,
+        blowup_factor: 8,
+        grinding_factor: 0,
+        field_extension: FieldExtension::None,
+        fri_folding_factor: 8,
+        fri_remainder_max_degree: 31,
+    };
+
+    fn test_options() -> ProofOptions {
+        TEST_PARAMS.to_options().expect("valid test params")
+    }
+
+    fn witness(slot: u64, stake: u64) -> SlotWitness {
+        SlotWitness {
+            slot,
+            vote_accounts: vec![VoteAccountWitness {
+                vote_pubkey: "vote".to_string(),
+                node_pubkey: "node".to_string(),
+                activated_stake: stake,
+                commission: 5,
+                last_vote: slot,
+                root_slot: slot.saturating_sub(32),
+                epoch_credits: Vec::new(),
+            }],
+            state_root: [0u8; 32],
+            account_hashes: Vec::new(),
+            merkle_version: crate::merkle::MerkleVersion::CURRENT,
+        }
+    }
+
+    /// Four blocks whose slot, stake, delta, aux and sign values differ between blocks 0/2 or
+    /// 1/3 in every bit, so each column has full degree and winter-prover's debug-build check
+    /// sees every declared constraint degree exactly.
+    pub(crate) const SAMPLE_SLOTS: [u64; 4] = [100, 101, 356, 357];
+
+    pub(crate) fn sample(scope: ProofScope) -> (PublicInputs, Vec<SlotWitness>) {
+        let [s0, s1, s2, s3] = SAMPLE_SLOTS;
+        let witnesses = vec![
+            witness(s0, 0),
+            witness(s1, 0xAAAA_AAAA_AAAA_AAAA),
+            witness(s2, 0xFFFF_FFFF),
+            witness(s3, 0x5555_5555_5555_5555),
+        ];
+        let initial_state_root = [7u8; 32];
+        let pub_inputs = PublicInputs {
+            start_slot: 100,
+            end_slot: 357,
+            initial_state_root,
+            final_state_root: chain_final_root(&initial_state_root, witnesses.len()),
+            blockhash: [9u8; 32],
+            scope,
+        };
+        (pub_inputs, witnesses)
+    }
+
+    /// One proof per scope, generated once and shared across tests.
+    fn proof_for(scope: ProofScope) -> &'static str {
+        static PROOFS: OnceLock<[String; 3]> = OnceLock::new();
+        let proofs = PROOFS.get_or_init(|| {
+            [ProofScope::SlotsOnly, ProofScope::SlotsAndRoots, ProofScope::Full].map(|scope| {
+                let (pub_inputs, witnesses) = sample(scope);
+                generate_proof_with_options(pub_inputs, &witnesses, SlotCoverage::Explicit(&SAMPLE_SLOTS), test_options())
+                    .expect("proof generation")
+                    .proof
+            })
+        });
+        &proofs[scope.tag() as usize]
+    }
+
+    fn verify(proof: &str, public_inputs: PublicInputs) -> bool {
+        let envelope = StarkProofEnvelope { proof: proof.to_string(), public_inputs };
+        verify_proof_with_options(envelope, test_options()).expect("verify").is_valid()
+    }
+
+    #[test]
+    fn test_trace_ends_on_chain_final_root() {
+        let (pub_inputs, witnesses) = sample(ProofScope::Full);
+        let trace = build_trace(&witnesses, &pub_inputs, SlotCoverage::Explicit(&SAMPLE_SLOTS)).expect("trace");
+        let last = trace.length() - 1;
+        let lanes: Vec<Felt> = (0..4).map(|i| trace.get(COL_HASH + i, last)).collect();
+        assert_eq!(felts_to_bytes(&lanes), pub_inputs.final_state_root);
+    }
+
+    #[test]
+    fn test_empty_witnesses_are_an_error_not_a_panic() {
+        let (pub_inputs, _) = sample(ProofScope::Full);
+        for coverage in [SlotCoverage::Contiguous, SlotCoverage::Explicit(&[])] {
+            let err = build_trace(&[], &pub_inputs, coverage).expect_err("no witnesses");
+            assert!(err.to_string().contains("cannot be empty"), "{err}");
+            let err = generate_proof_with_options(pub_inputs.clone(), &[], coverage, test_options())
+                .expect_err("no witnesses");
+            assert!(err.to_string().contains("cannot be empty"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_build_trace_rejects_too_many_witnesses() {
+        let (mut pub_inputs, _) = sample(ProofScope::SlotsOnly);
+        let witnesses: Vec<_> = (0..=MAX_WITNESSES as u64).map(|i| witness(100 + i, 0)).collect();
+        pub_inputs.end_slot = witnesses.last().expect("non-empty").slot;
+        let err = build_trace(&witnesses, &pub_inputs, SlotCoverage::Contiguous).expect_err("over the cap");
+        assert_eq!(
+            err.downcast_ref::<ProverError>(),
+            Some(&ProverError::TooManyWitnesses { got: MAX_WITNESSES + 1, max: MAX_WITNESSES })
+        );
+    }
+
+    #[test]
+    fn test_build_trace_rejects_non_power_of_two_witness_count() {
+        let (mut pub_inputs, mut witnesses) = sample(ProofScope::SlotsOnly);
+        witnesses.pop();
+        pub_inputs.end_slot = witnesses.last().expect("non-empty").slot;
+        let slots = SAMPLE_SLOTS.get(..3).expect("3 slots");
+        let err = build_trace(&witnesses, &pub_inputs, SlotCoverage::Explicit(slots)).expect_err("3 witnesses = 24 rows");
+        assert_eq!(err.downcast_ref::<ProverError>(), Some(&ProverError::TraceLengthNotPow2 { len: 24 }));
+    }
+
+    #[test]
+    fn test_desynced_final_root_is_caught_before_proving() {
+        let (mut pub_inputs, witnesses) = sample(ProofScope::SlotsAndRoots);
+        let real_final = bytes_to_felts(&pub_inputs.final_state_root);
+        pub_inputs.final_state_root = [0xEE; 32];
+        let err = generate_proof_with_options(pub_inputs, &witnesses, SlotCoverage::Explicit(&SAMPLE_SLOTS), test_options())
+            .err()
+            .expect("desynced");
+        assert_eq!(
+            err.downcast_ref::<ProverError>(),
+            Some(&ProverError::TraceBoundaryMismatch {
+                column: COL_HASH,
+                step: witnesses.len() * ROUNDS_PER_WITNESS - 1,
+                expected: bytes_to_felts(&[0xEE; 32])[0].as_int(),
+                got: real_final[0].as_int(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_trace_rejects_missing_interior_slot() {
+        let (mut pub_inputs, _) = sample(ProofScope::SlotsOnly);
+        // Slots 200..=208 claimed contiguously, with 204 missing: 8 witnesses, a valid trace length
+        let witnesses: Vec<_> = (200..=208).filter(|&s| s != 204).map(|s| witness(s, 0)).collect();
+        pub_inputs.start_slot = 200;
+        pub_inputs.end_slot = 208;
+        let err = build_trace(&witnesses, &pub_inputs, SlotCoverage::Contiguous).expect_err("gap");
+        assert_eq!(
+            err.downcast_ref::<ProverError>(),
+            Some(&ProverError::WitnessCountMismatch { expected: 9, got: 8 })
+        );
+
+        // The same gap against an explicit list that names the missing slot
+        let listed: Vec<u64> = (200..=208).collect();
+        let err = build_trace(&witnesses, &pub_inputs, SlotCoverage::Explicit(&listed)).expect_err("gap");
+        assert_eq!(
+            err.downcast_ref::<ProverError>(),
+            Some(&ProverError::WitnessCountMismatch { expected: 9, got: 8 })
+        );
+
+        // A list of the right length naming a different slot is still a mismatch
+        let other: Vec<u64> = (200..=208).filter(|&s| s != 205).collect();
+        let err = build_trace(&witnesses, &pub_inputs, SlotCoverage::Explicit(&other)).expect_err("wrong slot");
+        assert_eq!(
+            err.downcast_ref::<ProverError>(),
+            Some(&ProverError::WitnessCountMismatch { expected: 8, got: 8 })
+        );
+    }
+
+    fn record_for(pub_inputs: &PublicInputs) -> ProofRecordView {
+        ProofRecordView {
+            start_slot: pub_inputs.start_slot,
+            end_slot: pub_inputs.end_slot,
+            state_root_before: pub_inputs.initial_state_root,
+            state_root_after: pub_inputs.final_state_root,
+            ds_hash: pub_inputs.blockhash,
+        }
+    }
+
+    fn check_record(record: &ProofRecordView) -> Result<()> {
+        let (public_inputs, _) = sample(ProofScope::Full);
+        let envelope =
+            StarkProofEnvelope { proof: proof_for(ProofScope::Full).to_string(), public_inputs };
+        verify_envelope_against_record_with_options(&envelope, record, test_options())
+    }
+
+    fn mismatch(err: &anyhow::Error) -> Option<&'static str> {
+        match err.downcast_ref::<ProverError>() {
+            Some(ProverError::RecordMismatch { field }) => Some(field),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_envelope_matches_its_record() {
+        let (pub_inputs, _) = sample(ProofScope::Full);
+        check_record(&record_for(&pub_inputs)).expect("matching record");
+    }
+
+    #[test]
+    fn test_public_inputs_from_record_round_trip() {
+        let (pub_inputs, _) = sample(ProofScope::Full);
+        let record = record_for(&pub_inputs);
+        let rebuilt = PublicInputs::from_proof_record(&record, ProofScope::Full);
+        assert_eq!(rebuilt.to_elements(), pub_inputs.to_elements());
+        assert_eq!(record_for(&rebuilt), record);
+
+        let envelope = StarkProofEnvelope {
+            proof: proof_for(ProofScope::Full).to_string(),
+            public_inputs: rebuilt,
+        };
+        verify_envelope_against_record_with_options(&envelope, &record, test_options())
+            .expect("rebuilt inputs verify against their record");
+    }
+
+    #[test]
+    fn test_envelope_record_mismatch_per_field() {
+        let (pub_inputs, _) = sample(ProofScope::Full);
+        let base = record_for(&pub_inputs);
+        let cases: [(&str, fn(&mut ProofRecordView)); 5] = [
+            ("start_slot", |r| r.start_slot += 1),
+            ("end_slot", |r| r.end_slot += 1),
+            ("initial_state_root", |r| r.state_root_before[0] ^= 1),
+            ("final_state_root", |r| r.state_root_after[31] ^= 1),
+            ("blockhash", |r| r.ds_hash[5] ^= 1),
+        ];
+        for (field, tamper) in cases {
+            let mut record = base.clone();
+            tamper(&mut record);
+            let err = check_record(&record).expect_err(field);
+            assert_eq!(mismatch(&err), Some(field));
+        }
+    }
+
+    #[test]
+    fn test_envelope_with_bad_proof_is_rejected() {
+        let (public_inputs, _) = sample(ProofScope::Full);
+        let record = record_for(&public_inputs);
+        // A valid proof for another scope, relabelled: fields match but the proof does not
+        let envelope = StarkProofEnvelope {
+            proof: proof_for(ProofScope::SlotsOnly).to_string(),
+            public_inputs,
+        };
+        let err = verify_envelope_against_record_with_options(&envelope, &record, test_options())
+            .expect_err("bad proof");
+        assert_eq!(err.downcast_ref::<ProverError>(), Some(&ProverError::ProofRejected));
+    }
+
+    #[test]
+    fn test_fri_folding_factor_mismatch_is_rejected() {
+        let (public_inputs, _) = sample(ProofScope::SlotsOnly);
+        let envelope = StarkProofEnvelope {
+            proof: proof_for(ProofScope::SlotsOnly).to_string(),
+            public_inputs,
+        };
+        let folding_4 = ProofParams { fri_folding_factor: 4, ..TEST_PARAMS };
+        let options = folding_4.to_options().expect("valid params");
+        assert_eq!(
+            verify_proof_with_options(envelope, options).expect("verify"),
+            VerifyOutcome::Invalid { reason: InvalidReason::Options }
+        );
+    }
+
+    #[test]
+    fn test_tampered_envelopes_report_distinct_reasons() {
+        let (public_inputs, _) = sample(ProofScope::Full);
+        let outcome = |proof: String, public_inputs: PublicInputs| {
+            match verify_proof_with_options(StarkProofEnvelope { proof, public_inputs }, test_options()) {
+                Ok(VerifyOutcome::Invalid { reason }) => reason,
+                other => panic!("tampered envelope was not rejected: {other:?}"),
+            }
+        };
+        let valid = proof_for(ProofScope::Full).to_string();
+        let mut moved = public_inputs.clone();
+        moved.start_slot += 1;
+        let reasons = [
+            outcome("not base64!".to_string(), public_inputs.clone()),
+            outcome(B64.encode([1u8, 2, 3]), public_inputs.clone()),
+            outcome(proof_for(ProofScope::SlotsOnly).to_string(), public_inputs.clone()),
+            outcome(valid, moved),
+        ];
+        assert_eq!(
+            reasons,
+            [
+                InvalidReason::Encoding,
+                InvalidReason::Malformed,
+                InvalidReason::ScopeMismatch { expected: 155, got: 9 },
+                InvalidReason::Constraints,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_width_per_scope() {
+        for (scope, width) in [
+            (ProofScope::SlotsOnly, 9),
+            (ProofScope::SlotsAndRoots, 21),
+            (ProofScope::Full, 155),
+        ] {
+            let (pub_inputs, witnesses) = sample(scope);
+            let trace = build_trace(&witnesses, &pub_inputs, SlotCoverage::Explicit(&SAMPLE_SLOTS)).expect("trace");
+            assert_eq!(trace.main_trace_width(), width);
+        }
+    }
+
+    #[test]
+    fn test_each_scope_proves_and_verifies() {
+        for scope in [ProofScope::SlotsOnly, ProofScope::SlotsAndRoots, ProofScope::Full] {
+            let (pub_inputs, _) = sample(scope);
+            assert!(verify(proof_for(scope), pub_inputs), "{scope:?} proof rejected");
+        }
+    }
+
+    #[test]
+    fn test_roots_are_bound_when_in_scope() {
+        let (mut pub_inputs, _) = sample(ProofScope::SlotsAndRoots);
+        pub_inputs.final_state_root = [1u8; 32];
+        assert!(!verify(proof_for(ProofScope::SlotsAndRoots), pub_inputs));
+    }
+
+    #[test]
+    fn test_slots_only_proof_is_cheaper() {
+        let slots_only = proof_for(ProofScope::SlotsOnly).len();
+        let full = proof_for(ProofScope::Full).len();
+        assert!(slots_only < full, "SlotsOnly proof ({slots_only} b64 bytes) not smaller than Full ({full})");
+    }
+
+    #[test]
+    fn test_scope_mismatch_is_rejected() {
+        // Narrower proof presented as a wider scope, and vice versa.
+        let (mut pub_inputs, _) = sample(ProofScope::SlotsOnly);
+        pub_inputs.scope = ProofScope::Full;
+        assert!(!verify(proof_for(ProofScope::SlotsOnly), pub_inputs));
+
+        let (mut pub_inputs, _) = sample(ProofScope::SlotsAndRoots);
+        pub_inputs.scope = ProofScope::SlotsOnly;
+        assert!(!verify(proof_for(ProofScope::SlotsAndRoots), pub_inputs));
+    }
+
+    #[test]
+    fn test_scope_tag_is_bound_in_transcript() {
+        // The scope is absorbed through to_elements, so two scopes never share a transcript.
+        let (pub_inputs, _) = sample(ProofScope::SlotsOnly);
+        let elements = |scope| PublicInputs { scope, ..pub_inputs.clone() }.to_elements();
+        assert_ne!(elements(ProofScope::SlotsOnly), elements(ProofScope::SlotsAndRoots));
+        assert_ne!(elements(ProofScope::SlotsAndRoots), elements(ProofScope::Full));
+        assert_eq!(elements(ProofScope::Full).last(), Some(&Felt::new(2)));
+
+        // A transcript-only difference is enough for rejection: the blockhash is not used by
+        // any constraint or assertion, exactly like the scope tag once widths agree.
+        let (mut pub_inputs, _) = sample(ProofScope::SlotsOnly);
+        pub_inputs.blockhash = [0u8; 32];
+        assert!(!verify(proof_for(ProofScope::SlotsOnly), pub_inputs));
+    }
+
+    // Transition-row frames fed straight to `evaluate_transition`, pinning the stake
+    // arithmetic without a proof. Constraint indices follow the evaluation order (full scope).
+    const C_STAKE: usize = 10 + STATE_WIDTH + 4;
+    const C_ADD_LO: usize = C_STAKE;
+    const C_ADD_HI: usize = C_STAKE + 1;
+    const C_BIT: usize = C_STAKE + 6;
+    const C_RECOMPOSE: usize = C_BIT + 128;
+
+    /// Adjacent transition-row frame moving `stake` by `delta` (subtracting when `sub`),
+    /// with the carry or borrow in `aux` and every limb's bits filled in.
+    fn stake_frame(stake: u64, delta: u64, sub: bool) -> (Vec<Felt>, Vec<Felt>) {
+        let (stake_lo, delta_lo) = (stake & 0xFFFF_FFFF, delta & 0xFFFF_FFFF);
+        let (next_stake, aux) = if sub {
+            (stake.wrapping_sub(delta), u64::from(stake_lo < delta_lo))
+        } else {
+            (stake.wrapping_add(delta), (stake_lo + delta_lo) >> 32)
+        };
+        let mut cur = vec![Felt::ZERO; NUM_COLS];
+        let mut next = vec![Felt::ZERO; NUM_COLS];
+        cur[COL_SLOT] = Felt::new(100);
+        cur[COL_SLOT_BITS] = Felt::ONE;
+        next[COL_SLOT] = Felt::new(101);
+        let limbs = [stake_lo, stake >> 32, delta_lo, delta >> 32];
+        for (limb, (col, value)) in [COL_STAKE_LO, COL_STAKE_HI, COL_DELTA_LO, COL_DELTA_HI]
+            .into_iter()
+            .zip(limbs)
+            .enumerate()
+        {
+            cur[col] = Felt::new(value);
+            for b in 0..32 {
+                cur[COL_BITS + limb * 32 + b] = Felt::new((value >> b) & 1);
+            }
+        }
+        cur[COL_AUX] = Felt::new(aux);
+        cur[COL_SIGN] = Felt::new(u64::from(sub));
+        next[COL_STAKE_LO] = Felt::new(next_stake & 0xFFFF_FFFF);
+        next[COL_STAKE_HI] = Felt::new(next_stake >> 32);
+        (cur, next)
+    }
+
+    /// Indices of the constraints a transition-row frame violates.
+    fn violated(cur: Vec<Felt>, next: Vec<Felt>) -> Vec<usize> {
+        let (pub_inputs, _) = sample(ProofScope::Full);
+        let air = SolanaStateAir::new(TraceInfo::new(NUM_COLS, 8), pub_inputs, test_options());
+        let mut periodic = vec![Felt::ZERO; 1 + STATE_WIDTH];
+        periodic[PERIODIC_FLAG] = Felt::ONE;
+        let mut result = vec![Felt::ZERO; air.context().num_main_transition_constraints()];
+        air.evaluate_transition(&EvaluationFrame::from_rows(cur, next), &periodic, &mut result);
+        result.iter().enumerate().filter(|(_, r)| **r != Felt::ZERO).map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn test_valid_add_and_subtract_satisfy_every_constraint() {
+        for (stake, delta, sub) in [
+            (0xFFFF_FFFF, 1, false),            // carry into the high limb
+            (0x1_0000_0000, 1, true),           // borrow from the high limb
+            (0xAAAA_AAAA_AAAA_AAAA, 0x5555_5555_5555_5555, false),
+            (0xAAAA_AAAA_AAAA_AAAA, 0x2222_2222_3333_3333, true),
+            (42, 0, false),
+        ] {
+            let (cur, next) = stake_frame(stake, delta, sub);
+            assert_eq!(violated(cur, next), Vec::<usize>::new(), "{stake:#x} {delta:#x} sub={sub}");
+        }
+    }
+
+    #[test]
+    fn test_wrong_carry_fires_both_limb_constraints() {
+        for sub in [false, true] {
+            let (mut cur, next) = stake_frame(0x1_FFFF_FFFF, 1, sub);
+            cur[COL_AUX] = Felt::ONE - cur[COL_AUX];
+            assert_eq!(violated(cur, next), vec![C_ADD_LO, C_ADD_HI], "sub={sub}");
+        }
+    }
+
+    #[test]
+    fn test_non_binary_bit_fires_bit_validity() {
+        // Stake 2 has bits 0b10; bits 0b(0)(2) recompose to the same limb, so only the
+        // validity constraint can catch it.
+        let (mut cur, next) = stake_frame(2, 0, false);
+        cur[COL_BITS] = Felt::new(2);
+        cur[COL_BITS + 1] = Felt::ZERO;
+        assert_eq!(violated(cur, next), vec![C_BIT]);
+    }
+
+    #[test]
+    fn test_bad_recomposition_fires_limb_constraint() {
+        let (mut cur, next) = stake_frame(5, 7, false);
+        // Flip a high-limb delta bit without touching the limb itself
+        let bit = COL_BITS + 3 * 32 + 4;
+        cur[bit] = Felt::ONE - cur[bit];
+        assert_eq!(violated(cur, next), vec![C_RECOMPOSE + 3]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Proof parameter profiles, one per backend, shared by its prover and verifier.
+//!
+//! The verifier only accepts proofs generated under exactly these options, so both sides of
+//! a backend must build their `ProofOptions` from the same profile.
+
+use anyhow::Result;
+use winter_air::{BatchingMethod, FieldExtension, ProofOptions};
+
+/// Largest query count winterfell accepts.
+const MAX_NUM_QUERIES: usize = 255;
+/// Largest grinding factor winterfell accepts.
+const MAX_GRINDING_FACTOR: u32 = 32;
+/// FRI folding factors winterfell supports.
+const FRI_FOLDING_FACTORS: [usize; 4] = [2, 4, 8, 16];
+/// Largest FRI remainder polynomial degree winterfell accepts.
+const FRI_MAX_REMAINDER_DEGREE: usize = 255;
+
+/// STARK proof parameters; validated before they reach `ProofOptions::new`, which panics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofParams {
+    /// Number of FRI queries.
+    pub num_queries: usize,
+    /// LDE blowup factor (power of two, 2..=128).
+    pub blowup_factor: usize,
+    /// Proof-of-work bits required for the query seed.
+    pub grinding_factor: u32,
+    /// Extension field used for composition and FRI.
+    pub field_extension: FieldExtension,
+    /// FRI folding factor: one of 2, 4, 8 or 16.
+    pub fri_folding_factor: usize,
+    /// FRI remainder polynomial degree; one less than a power of two, at most 255.
+    pub fri_remainder_max_degree: usize,
+}
+
+impl ProofParams {
+    /// Profile of the `stark` backend (`SolanaStateAir`).
+    pub const STARK: Self = Self {
+        num_queries: 32,   // 32 queries ≈ 96-bit security
+        blowup_factor: 8,  // 8x for efficiency
+        grinding_factor: 0, // 0 for testnet (increase for production)
+        field_extension: FieldExtension::None,
+        fri_folding_factor: 8,
+        fri_remainder_max_degree: 1,
+    };
+
+    /// Profile of the `north_star` backend.
+    pub const NORTH_STAR: Self = Self {
+        num_queries: 64,
+        blowup_factor: 16,
+        grinding_factor: 20,
+        field_extension: FieldExtension::Quadratic,
+        fri_folding_factor: 8,
+        fri_remainder_max_degree: 31,
+    };
+
+    /// Fast preset for testnets and development: the [`Self::STARK`] profile.
+    pub const fn testnet() -> Self {
+        Self::STARK
+    }
+
+    /// Preset for mainnet proofs: more queries, a wider blowup, grinding and the quadratic
+    /// extension, whose ~124-bit field keeps soundness from being capped by the 62-bit base.
+    pub const fn production() -> Self {
+        Self {
+            num_queries: 64,
+            blowup_factor: 16,
+            grinding_factor: 20,
+            field_extension: FieldExtension::Quadratic,
+            fri_folding_factor: 8,
+            fri_remainder_max_degree: 31,
+        }
+    }
+
+    /// Check every field against winterfell's limits, returning the first violation.
+    pub const fn check(&self) -> Result<(), &'static str> {
+        if self.num_queries == 0 || self.num_queries > MAX_NUM_QUERIES {
+            return Err("num_queries must be in 1..=255");
+        }
+        if !self.blowup_factor.is_power_of_two()
+            || self.blowup_factor < ProofOptions::MIN_BLOWUP_FACTOR
+            || self.blowup_factor > 128
+        {
+            return Err("blowup_factor must be a power of two in 2..=128");
+        }
+        if self.grinding_factor > MAX_GRINDING_FACTOR {
+            return Err("grinding_factor must be at most 32");
+        }
+        let mut supported = false;
+        let mut i = 0;
+        while i < FRI_FOLDING_FACTORS.len() {
+            if FRI_FOLDING_FACTORS[i] == self.fri_folding_factor {
+                supported = true;
+            }
+            i += 1;
+        }
+        if !supported {
+            return Err("fri_folding_factor must be one of 2, 4, 8, 16");
+        }
+        if self.fri_remainder_max_degree > FRI_MAX_REMAINDER_DEGREE
+            || !(self.fri_remainder_max_degree + 1).is_power_of_two()
+        {
+            return Err("fri_remainder_max_degree must be one less than a power of two, at most 255");
+        }
+        Ok(())
+    }
+
+    /// Validate and build the `ProofOptions` for this profile.
+    pub fn to_options(&self) -> Result<ProofOptions> {
+        self.check().map_err(|e| anyhow::anyhow!("invalid proof parameters: {e}"))?;
+        Ok(self.build())
+    }
+
+    /// Build without validation; only for the built-in profiles, which are checked at compile
+    /// time below.
+    pub(crate) fn build(&self) -> ProofOptions {
+        ProofOptions::new(
+            self.num_queries,
+            self.blowup_factor,
+            self.grinding_factor,
+            self.field_extension,
+            self.fri_folding_factor,
+            self.fri_remainder_max_degree,
+            BatchingMethod::Linear,
+            BatchingMethod::Linear,
+        )
+    }
+}
+
+const _: () = assert!(ProofParams::STARK.check().is_ok());
+const _: () = assert!(ProofParams::NORTH_STAR.check().is_ok());
+const _: () = assert!(ProofParams::production().check().is_ok());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_profiles_match_options() {
+        let stark = ProofParams::STARK.to_options().expect("stark profile");
+        assert_eq!(stark.to_fri_options().folding_factor(), 8);
+        assert_eq!(stark.to_fri_options().remainder_max_degree(), 1);
+        let north_star = ProofParams::NORTH_STAR.to_options().expect("north_star profile");
+        assert_eq!(north_star.to_fri_options().remainder_max_degree(), 31);
+    }
+
+    #[test]
+    fn test_rejects_unsupported_fri_parameters() {
+        for folding in [0, 1, 3, 6, 32] {
+            let params = ProofParams { fri_folding_factor: folding, ..ProofParams::STARK };
+            assert!(params.to_options().is_err(), "accepted folding factor {folding}");
+        }
+        for remainder in [2, 30, 256, 511] {
+            let params = ProofParams { fri_remainder_max_degree: remainder, ..ProofParams::STARK };
+            assert!(params.to_options().is_err(), "accepted remainder degree {remainder}");
+        }
+        for folding in FRI_FOLDING_FACTORS {
+            let params = ProofParams { fri_folding_factor: folding, ..ProofParams::STARK };
+            assert!(params.to_options().is_ok(), "rejected folding factor {folding}");
+        }
+    }
+}
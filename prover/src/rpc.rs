@@ -0,0 +1,128 @@
+//! JSON-RPC transport used by the witness pipeline, so it can run against canned responses
+
+use crate::error::ProverError;
+use anyhow::Result;
+use serde_json::json;
+use std::time::Duration;
+
+/// Sends one Solana JSON-RPC request and returns the full response envelope
+/// (`{"jsonrpc", "id", "result" | "error"}`), leaving interpretation to the caller.
+///
+/// Failures worth retrying carry [`ProverError::RpcUnavailable`]; any other error is final.
+pub trait RpcTransport {
+    /// Call `method` with `params` (the JSON-RPC `params` array).
+    fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value>;
+}
+
+/// [`RpcTransport`] over HTTP to a live endpoint.
+pub struct HttpTransport {
+    /// Endpoint URL.
+    url: String,
+    /// Reused blocking client.
+    client: reqwest::blocking::Client,
+}
+
+/// Per-request timeout of the [`HttpTransport`]s the CLI builds.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+impl HttpTransport {
+    /// Transport posting to `url` whose requests give up after `timeout`.
+    ///
+    /// This is the only request timeout: the witness pipeline retries a request that timed
+    /// out (see `WitnessFetchConfig`) but never bounds one itself.
+    pub fn with_timeout(url: &str, timeout: Duration) -> Result<Self> {
+        let client = reqwest::blocking::Client::builder().timeout(timeout).build()?;
+        Ok(Self { url: url.to_string(), client })
+    }
+}
+
+/// Whether an HTTP `status` means the endpoint may answer if asked again: rate limited (429)
+/// or a server error (5xx).
+fn is_retryable_status(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+impl RpcTransport for HttpTransport {
+    fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params
+            }))
+            .send()
+            .map_err(|err| {
+                let transient = err.is_timeout() || err.is_connect();
+                let err = anyhow::Error::new(err);
+                if transient { err.context(ProverError::RpcUnavailable { status: None }) } else { err }
+            })?;
+        let status = response.status().as_u16();
+        if is_retryable_status(status) {
+            return Err(ProverError::RpcUnavailable { status: Some(status) }.into());
+        }
+        Ok(response.json()?)
+    }
+}
+
+/// [`RpcTransport`] answering from canned responses, for tests.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockTransport {
+    /// Responses keyed by `(method, params)`; `None` params match any call to the method.
+    responses: std::collections::HashMap<(String, Option<String>), serde_json::Value>,
+}
+
+#[cfg(test)]
+impl MockTransport {
+    /// Answer every `method` call with `response`, unless a params-specific entry matches.
+    pub fn on(mut self, method: &str, response: serde_json::Value) -> Self {
+        self.responses.insert((method.to_string(), None), response);
+        self
+    }
+
+    /// Answer `method` called with exactly `params` with `response`.
+    pub fn on_params(mut self, method: &str, params: &serde_json::Value, response: serde_json::Value) -> Self {
+        self.responses.insert((method.to_string(), Some(params.to_string())), response);
+        self
+    }
+}
+
+#[cfg(test)]
+impl RpcTransport for MockTransport {
+    fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        self.responses
+            .get(&(method.to_string(), Some(params.to_string())))
+            .or_else(|| self.responses.get(&(method.to_string(), None)))
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("no canned response for {method} {params}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_prefers_exact_params_over_method_default() {
+        let exact = json!([7]);
+        let mock = MockTransport::default()
+            .on("getBlock", json!({ "result": null }))
+            .on_params("getBlock", &exact, json!({ "result": { "blockhash": "h" } }));
+        assert_eq!(mock.call("getBlock", exact).unwrap()["result"]["blockhash"], "h");
+        assert!(mock.call("getBlock", json!([8])).unwrap()["result"].is_null());
+        assert!(mock.call("getVoteAccounts", json!([])).is_err());
+    }
+
+    #[test]
+    fn test_only_rate_limits_and_server_errors_are_retryable() {
+        for status in [429, 500, 502, 503, 599] {
+            assert!(is_retryable_status(status), "{status}");
+        }
+        for status in [200, 400, 401, 404, 413, 600] {
+            assert!(!is_retryable_status(status), "{status}");
+        }
+    }
+}
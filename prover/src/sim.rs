@@ -0,0 +1,272 @@
+//! Deterministic in-memory cluster answering the JSON-RPC calls of the witness pipeline
+
+use crate::rpc::RpcTransport;
+use anyhow::Result;
+use blake3::Hasher as Blake3;
+use serde_json::{json, Value};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+
+/// Validators in the simulated vote-account set.
+const VALIDATORS: u64 = 6;
+/// Wallets the simulated transactions move lamports between.
+const WALLETS: u64 = 8;
+/// Transfers in every produced block.
+const TXS_PER_BLOCK: u64 = 3;
+/// One slot in this many is skipped.
+const SKIP_EVERY: u64 = 4;
+/// One validator vote in this many is missing from a produced block.
+const MISS_VOTE_EVERY: u64 = 3;
+/// The vote program, named by every vote transaction.
+const VOTE_PROGRAM: &str = "Vote111111111111111111111111111111111111111";
+
+/// A simulated cluster over `start_slot..=end_slot`, answering `getBlock`, `getBlocks`,
+/// `getSlot` and `getVoteAccounts` from one seed.
+///
+/// The answers agree with each other: `getBlocks` lists exactly the slots `getBlock`
+/// returns, parents chain through skipped slots, every transfer's pre-balances are the
+/// post-balances the wallets were last seen with, and each block carries vote transactions
+/// from most (but not always all) of the validators `getVoteAccounts` lists. Skipped slots and slots outside the range
+/// answer with the JSON-RPC errors a Solana node returns for them. The commitment level is
+/// ignored: the simulated range is already final.
+///
+/// Unlike a live node, which reports vote accounts at its tip, `getVoteAccounts` answers as
+/// of the slot last asked for with `getBlock` (the tip before any), and stakes move from slot
+/// to slot. The witness pipeline reads each slot's block before its vote accounts, so every
+/// witness gets its own stakes and proofs over the simulator exercise nonzero stake deltas.
+/// Answers are a pure function of the seed, the range and the requests made so far.
+pub struct SimCluster {
+    /// Seed every block and vote account is derived from.
+    seed: u64,
+    /// First slot of the range.
+    start_slot: u64,
+    /// Last slot of the range, reported by `getSlot`.
+    end_slot: u64,
+    /// `getBlock` result of every produced slot.
+    blocks: BTreeMap<u64, Value>,
+    /// Slot `getVoteAccounts` answers at.
+    cursor: Cell<u64>,
+}
+
+impl SimCluster {
+    /// Cluster for `start_slot..=end_slot` derived from `seed`.
+    pub fn new(seed: u64, start_slot: u64, end_slot: u64) -> Self {
+        let mut balances: Vec<u64> =
+            (0..WALLETS).map(|w| 1_000_000_000u64.saturating_add(draw(seed, "balance", w, 0) % 1_000_000)).collect();
+        let mut blocks = BTreeMap::new();
+        let mut parent = start_slot.saturating_sub(1);
+        for slot in start_slot..=end_slot {
+            if draw(seed, "skip", slot, 0) % SKIP_EVERY == 0 {
+                continue;
+            }
+            let mut transactions: Vec<Value> =
+                (0..TXS_PER_BLOCK).map(|t| transfer(seed, slot, t, &mut balances)).collect();
+            transactions.extend(
+                (0..VALIDATORS)
+                    .filter(|v| draw(seed, "voted", *v, slot) % MISS_VOTE_EVERY != 0)
+                    .map(|v| vote(seed, slot, v)),
+            );
+            blocks.insert(
+                slot,
+                json!({
+                    "blockhash": address(seed, "blockhash", slot),
+                    "previousBlockhash": address(seed, "blockhash", parent),
+                    "parentSlot": parent,
+                    "blockHeight": slot,
+                    "transactions": transactions,
+                }),
+            );
+            parent = slot;
+        }
+        Self { seed, start_slot, end_slot, blocks, cursor: Cell::new(end_slot) }
+    }
+
+    /// `getVoteAccounts` result as of `slot`.
+    fn vote_accounts(&self, slot: u64) -> Value {
+        let seed = self.seed;
+        let current: Vec<Value> = (0..VALIDATORS)
+            .map(|v| {
+                json!({
+                    "votePubkey": address(seed, "vote", v),
+                    "nodePubkey": address(seed, "node", v),
+                    "activatedStake": 1_000_000_000_000u64.saturating_add(draw(seed, "stake", v, slot) % 1_000_000_000_000),
+                    "commission": draw(seed, "commission", v, 0) % 11,
+                    "lastVote": slot,
+                    "rootSlot": slot.saturating_sub(32),
+                    "epochCredits": [[1, 400, 0], [2, 800, 400]],
+                    "epochVoteAccount": true,
+                })
+            })
+            .collect();
+        ok(json!({ "current": current, "delinquent": [] }))
+    }
+
+    /// `getBlock` response for `slot`.
+    fn block(&self, slot: u64) -> Value {
+        match self.blocks.get(&slot) {
+            Some(block) => ok(block.clone()),
+            None if (self.start_slot..=self.end_slot).contains(&slot) => error(
+                -32007,
+                &format!("Slot {slot} was skipped, or missing due to ledger jump to recent snapshot"),
+            ),
+            None => error(-32004, &format!("Block not available for slot {slot}")),
+        }
+    }
+
+    /// `getBlocks` response: the produced slots in `[start, end]`, `end` defaulting to the tip.
+    fn produced(&self, start: u64, end: Option<u64>) -> Value {
+        let end = end.unwrap_or(self.end_slot);
+        if start > end {
+            return ok(json!([]));
+        }
+        ok(json!(self.blocks.range(start..=end).map(|(slot, _)| *slot).collect::<Vec<u64>>()))
+    }
+}
+
+impl RpcTransport for SimCluster {
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let arg = |i: usize| params.get(i).and_then(Value::as_u64);
+        Ok(match (method, arg(0)) {
+            ("getBlock", Some(slot)) => {
+                self.cursor.set(slot);
+                self.block(slot)
+            }
+            ("getBlocks", Some(start)) => self.produced(start, arg(1)),
+            ("getSlot", _) => ok(json!(self.end_slot)),
+            ("getVoteAccounts", _) => self.vote_accounts(self.cursor.get()),
+            ("getBlock" | "getBlocks", None) => error(-32602, "Invalid params"),
+            _ => error(-32601, "Method not found"),
+        })
+    }
+}
+
+/// Successful JSON-RPC response envelope.
+fn ok(result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "result": result })
+}
+
+/// JSON-RPC error envelope.
+fn error(code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": code, "message": message } })
+}
+
+/// Transaction `t` of `slot`: a transfer between two wallets, applied to `balances`.
+fn transfer(seed: u64, slot: u64, t: u64, balances: &mut [u64]) -> Value {
+    let from = draw(seed, "from", slot, t) % WALLETS;
+    let to = (from.saturating_add(1).saturating_add(draw(seed, "to", slot, t) % (WALLETS - 1))) % WALLETS;
+    let wallet = |w: u64| usize::try_from(w).unwrap_or_default();
+    let pre = [
+        balances.get(wallet(from)).copied().unwrap_or_default(),
+        balances.get(wallet(to)).copied().unwrap_or_default(),
+    ];
+    let amount = (draw(seed, "amount", slot, t) % 1_000_000).min(pre[0]);
+    let post = [pre[0].saturating_sub(amount), pre[1].saturating_add(amount)];
+    for (w, balance) in [(from, post[0]), (to, post[1])] {
+        if let Some(b) = balances.get_mut(wallet(w)) {
+            *b = balance;
+        }
+    }
+    let signature = {
+        let mut hasher = Blake3::new();
+        hasher.update(b"signature");
+        hasher.update(&seed.to_le_bytes());
+        hasher.update(&slot.to_le_bytes());
+        hasher.update(&t.to_le_bytes());
+        let mut bytes = [0u8; 64];
+        hasher.finalize_xof().fill(&mut bytes);
+        bs58::encode(bytes).into_string()
+    };
+    json!({
+        "transaction": {
+            "signatures": [signature],
+            "message": { "accountKeys": [address(seed, "wallet", from), address(seed, "wallet", to)] },
+        },
+        "meta": { "err": null, "fee": 0, "preBalances": pre, "postBalances": post, "logMessages": [] },
+    })
+}
+
+/// Vote transaction of validator `v` landing in `slot`; votes are fee-free here, so every
+/// account keeps its balance.
+fn vote(seed: u64, slot: u64, v: u64) -> Value {
+    let balances = [1_000_000_000u64.saturating_add(draw(seed, "node_balance", v, 0) % 1_000_000), 27_074_400, 1];
+    json!({
+        "transaction": {
+            "signatures": [bs58::encode(digest(seed, "vote_signature", slot, v)).into_string()],
+            "message": { "accountKeys": [address(seed, "node", v), address(seed, "vote", v), VOTE_PROGRAM] },
+        },
+        "meta": { "err": null, "fee": 0, "preBalances": balances, "postBalances": balances, "logMessages": [] },
+    })
+}
+
+/// Base58 address of the `index`-th `kind` account.
+fn address(seed: u64, kind: &str, index: u64) -> String {
+    bs58::encode(digest(seed, kind, index, 0)).into_string()
+}
+
+/// Deterministic value for `(seed, tag, a, b)`.
+fn draw(seed: u64, tag: &str, a: u64, b: u64) -> u64 {
+    let [d0, d1, d2, d3, d4, d5, d6, d7, ..] = digest(seed, tag, a, b);
+    u64::from_le_bytes([d0, d1, d2, d3, d4, d5, d6, d7])
+}
+
+/// blake3 of `(seed, tag, a, b)`.
+fn digest(seed: u64, tag: &str, a: u64, b: u64) -> [u8; 32] {
+    let mut hasher = Blake3::new();
+    hasher.update(&seed.to_le_bytes());
+    hasher.update(tag.as_bytes());
+    hasher.update(&a.to_le_bytes());
+    hasher.update(&b.to_le_bytes());
+    *hasher.finalize().as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sim_answers_agree_across_methods() {
+        let sim = SimCluster::new(42, 100, 131);
+        assert_eq!(sim.call("getSlot", json!([])).unwrap()["result"], 131);
+        let produced: Vec<u64> = serde_json::from_value(sim.call("getBlocks", json!([100, 131])).unwrap()["result"].clone()).unwrap();
+        assert!(!produced.is_empty() && produced.len() < 32, "some but not all slots are skipped");
+        let mut last_post: BTreeMap<String, u64> = BTreeMap::new();
+        let mut parent = 99;
+        for slot in 100..=131 {
+            let response = sim.call("getBlock", json!([slot, { "commitment": "finalized" }])).unwrap();
+            if !produced.contains(&slot) {
+                assert_eq!(response["error"]["code"], -32007, "slot {slot}");
+                continue;
+            }
+            let block = &response["result"];
+            assert_eq!(block["parentSlot"], parent);
+            parent = slot;
+            for tx in block["transactions"].as_array().unwrap() {
+                let keys = tx["transaction"]["message"]["accountKeys"].as_array().unwrap();
+                for (i, key) in keys.iter().enumerate() {
+                    let key = key.as_str().unwrap().to_string();
+                    if let Some(post) = last_post.get(&key) {
+                        assert_eq!(tx["meta"]["preBalances"][i], *post, "{key} at slot {slot}");
+                    }
+                    last_post.insert(key, tx["meta"]["postBalances"][i].as_u64().unwrap());
+                }
+            }
+        }
+        assert_eq!(sim.call("getBlocks", json!([120])).unwrap()["result"].as_array().unwrap().len(),
+            produced.iter().filter(|s| **s >= 120).count());
+        assert_eq!(sim.call("getBlock", json!([132])).unwrap()["error"]["code"], -32004);
+        assert!(sim.call("getAccountInfo", json!([])).unwrap().get("result").is_none());
+
+        // Vote accounts follow the last block read; same seed, same answers
+        let again = SimCluster::new(42, 100, 131);
+        let other = SimCluster::new(43, 100, 131);
+        let votes_at = |s: &SimCluster, slot: u64| {
+            s.call("getBlock", json!([slot])).unwrap();
+            s.call("getVoteAccounts", json!([])).unwrap()["result"].clone()
+        };
+        assert_eq!(votes_at(&sim, 110), votes_at(&again, 110));
+        assert_ne!(votes_at(&sim, 110), votes_at(&other, 110));
+        assert_ne!(votes_at(&sim, 110), votes_at(&sim, 111));
+        assert_eq!(votes_at(&sim, 111)["current"][0]["lastVote"], 111);
+        assert_eq!(votes_at(&sim, 111)["current"].as_array().unwrap().len(), 6);
+    }
+}
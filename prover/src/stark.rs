@@ -1,12 +1,18 @@
 #![allow(clippy::missing_errors_doc)]
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use blake3::Hasher as Blake3;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use winter_math::{fields::f62::BaseElement, FieldElement, StarkField, ToElements};
 use winter_air::{
     Air, AirContext, Assertion, EvaluationFrame, ProofOptions, TransitionConstraintDegree,
-    FieldExtension, BatchingMethod,
 };
 use winter_prover::{
     TraceTable, Proof, Prover, TraceInfo, TracePolyTable, StarkDomain,
@@ -19,8 +25,18 @@ use winter_crypto::hashers::Blake3_256;
 use winter_crypto::{DefaultRandomCoin, MerkleTree};
 use winter_air::PartitionOptions;
 
+use crate::anchor::AnchorProofArgs;
+use crate::error::{check_slot_fits, check_slot_range, check_trace_boundaries, check_trace_length, ProverError};
+use crate::limbs::{limbs_to_u64, u64_to_limbs};
+use crate::merkle::MerkleVersion;
+use crate::params::ProofParams;
+use crate::witness::Commitment;
+
 type Felt = BaseElement;
 
+/// Backend this module implements; its proofs only verify here (see `crate::backend`).
+pub const BACKEND: crate::backend::ProofBackend = crate::backend::ProofBackend::Stark;
+
 // REAL zkSTARK Implementation for Solana Validator State Verification
 // =====================================================================
 //
@@ -66,18 +82,23 @@ const ROUND_CONSTANTS: [[u64; 4]; RESCUE_ROUNDS] = [
 
 /// Split a 32-byte array into eight field elements (little-endian u32 limbs).
 fn bytes32_to_elements(bytes: &[u8; 32]) -> Vec<Felt> {
-    (0..8)
-        .map(|i| {
-            let start = i * 4;
-            let limb = u32::from_le_bytes([
-                bytes[start],
-                bytes[start + 1],
-                bytes[start + 2],
-                bytes[start + 3],
-            ]);
-            Felt::from(limb)
-        })
-        .collect()
+    // 32 is a multiple of 4, so the remainder is always empty
+    let (limbs, _) = bytes.as_chunks::<4>();
+    limbs.iter().map(|limb| Felt::from(u32::from_le_bytes(*limb))).collect()
+}
+
+/// Limb views over a 32-byte hash.
+trait FirstLimb {
+    /// Interpret the first 4 bytes as a u32 limb (LE) and convert to a field element.
+    /// Only those 4 bytes are read; the remaining 28 do not affect the result.
+    fn extract_first_limb(&self) -> Felt;
+}
+
+impl FirstLimb for [u8; 32] {
+    fn extract_first_limb(&self) -> Felt {
+        let [b0, b1, b2, b3, ..] = *self;
+        Felt::from(u32::from_le_bytes([b0, b1, b2, b3]))
+    }
 }
 
 pub fn hex32_to_array(hex_str: &str) -> anyhow::Result<[u8; 32]> {
@@ -115,10 +136,89 @@ pub struct PublicInputs {
     pub s_in: Vec<KVPair>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub s_out: Vec<KVPair>,
+    /// RPC commitment level the witness was fetched at.
+    #[serde(default)]
+    pub commitment: Commitment,
+    /// Chain the proven range was taken from (0 = unbound, as in proofs predating the field).
+    #[serde(default)]
+    pub chain_id: u64,
+    /// Aggregate stake and vote counts at the range endpoints; required from revision 4.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoints: Option<AggregateEndpoints>,
+    /// Share of stake that voted over the range, in parts per million (see
+    /// [`vote_participation_ppm`]); required from revision 5.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub participation_ppm: Option<u32>,
+    /// Hashing of the witness trees `before` and `after` commit to (`V1` in proofs predating
+    /// the field). Not bound into the proof: it only says how to re-derive the roots.
+    #[serde(default)]
+    pub merkle_version: MerkleVersion,
+}
+
+/// Aggregate validator state at the first and last slot of a proven range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateEndpoints {
+    /// Total activated stake at the start slot.
+    pub stake_before: u64,
+    /// Total activated stake at the end slot.
+    pub stake_after: u64,
+    /// Vote accounts that have voted, at the start slot.
+    pub votes_before: u64,
+    /// Vote accounts that have voted, at the end slot.
+    pub votes_after: u64,
+}
+
+impl AggregateEndpoints {
+    /// Endpoints of the range `witnesses` cover, or `None` without witnesses.
+    pub fn from_witnesses(witnesses: &[crate::witness::SlotWitness]) -> Option<Self> {
+        let (stake_before, votes_before) = witness_totals(witnesses.first()?);
+        let (stake_after, votes_after) = witness_totals(witnesses.last()?);
+        Some(Self { stake_before, stake_after, votes_before, votes_after })
+    }
+}
+
+/// Total activated stake and number of vote accounts that have voted in `witness`.
+fn witness_totals(witness: &crate::witness::SlotWitness) -> (u64, u64) {
+    witness.vote_accounts.iter().fold((0u64, 0u64), |(stake, votes), acc| {
+        (stake.saturating_add(acc.activated_stake), votes + u64::from(acc.last_vote > 0))
+    })
+}
+
+/// Activated stake of the vote accounts in `witness` that have voted.
+fn witness_voting_stake(witness: &crate::witness::SlotWitness) -> u64 {
+    witness
+        .vote_accounts
+        .iter()
+        .filter(|acc| acc.last_vote > 0)
+        .fold(0u64, |stake, acc| stake.saturating_add(acc.activated_stake))
+}
+
+/// Fixed-point scale of participation ratios (parts per million).
+pub const PPM: u64 = 1_000_000;
+
+/// `voting / total` in parts per million, rounded down; 0 when there is no stake.
+fn ppm_of(voting: u128, total: u128) -> u32 {
+    let ppm = (voting * u128::from(PPM)).checked_div(total).unwrap_or(0);
+    // voting never exceeds total, so this is at most PPM
+    ppm.min(u128::from(PPM)) as u32
+}
+
+/// Stake-weighted vote participation over `witnesses`: the activated stake that has voted,
+/// summed over every slot, as a share of the total activated stake summed the same way.
+///
+/// `None` without witnesses.
+pub fn vote_participation_ppm(witnesses: &[crate::witness::SlotWitness]) -> Option<u32> {
+    if witnesses.is_empty() {
+        return None;
+    }
+    let (voting, total) = witnesses.iter().fold((0u128, 0u128), |(voting, total), w| {
+        (voting + u128::from(witness_voting_stake(w)), total + u128::from(witness_totals(w).0))
+    });
+    Some(ppm_of(voting, total))
 }
 
 /// A key/value pair used in North Star PI sets (account, value).
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct KVPair {
     /// Account address (base58 or string).
     pub account: String,
@@ -128,29 +228,324 @@ pub struct KVPair {
 
 impl ToElements<Felt> for PublicInputs {
     fn to_elements(&self) -> Vec<Felt> {
-        let mut out = vec![Felt::from(self.start as u32), Felt::from(self.end as u32)];
+        let mut out = vec![Felt::new(self.start), Felt::new(self.end)];
         out.extend(bytes32_to_elements(&self.before));
         out.extend(bytes32_to_elements(&self.after));
         out.extend(bytes32_to_elements(&self.proof_hash));
+        // Endpoints add eight u32 limbs; every tail below is shorter, so lengths stay distinct
+        if let Some(e) = &self.endpoints {
+            for value in [e.stake_before, e.stake_after, e.votes_before, e.votes_after] {
+                let (lo, hi) = u64_to_limbs(value);
+                out.extend([Felt::from(lo), Felt::from(hi)]);
+            }
+        }
+        // Participation is tagged above 2^32, a value no u32 limb reaches, so it cannot be
+        // mistaken for any of the tails below
+        if let Some(ppm) = self.participation_ppm {
+            out.push(Felt::new(PARTICIPATION_TAG + u64::from(ppm)));
+        }
+        // Finalized is the implicit level of proofs predating this field, so only weaker
+        // levels are appended; they remain bound without changing existing transcripts.
+        // A bound chain always brings the level along, so the two tails differ in length and
+        // can never be confused; the id is split into u32 limbs to stay below the modulus.
+        if self.chain_id != 0 {
+            out.push(Felt::from(self.commitment.level()));
+            let (lo, hi) = u64_to_limbs(self.chain_id);
+            out.extend([Felt::from(lo), Felt::from(hi)]);
+        } else if self.commitment != Commitment::Finalized {
+            out.push(Felt::from(self.commitment.level()));
+        }
         out
     }
 }
 
+/// Offset added to `participation_ppm` in [`PublicInputs::to_elements`].
+const PARTICIPATION_TAG: u64 = 1 << 32;
+
+/// Domain-separation context prefixed to the canonical proof hash preimage.
+pub const PROOF_HASH_CONTEXT: &[u8] = b"zKSL/proof_hash/v1";
+
+impl PublicInputs {
+    /// Canonical artifact hash binding the North Star commitments to the range it proves:
+    /// `blake3(PROOF_HASH_CONTEXT || c_in || c_out || h_b || before || after || start || end)`,
+    /// slots little-endian. Clients recompute it from an envelope and compare with `proof_hash`.
+    pub fn derive_proof_hash(&self) -> Result<[u8; 32]> {
+        let c_in = hex32_to_array(&self.c_in_hex).context("c_in_hex")?;
+        let c_out = hex32_to_array(&self.c_out_hex).context("c_out_hex")?;
+        let h_b = hex32_to_array(&self.h_b_hex).context("h_b_hex")?;
+        let mut hasher = Blake3::new();
+        hasher.update(PROOF_HASH_CONTEXT);
+        for part in [&c_in, &c_out, &h_b, &self.before, &self.after] {
+            hasher.update(part);
+        }
+        hasher.update(&self.start.to_le_bytes());
+        hasher.update(&self.end.to_le_bytes());
+        Ok(*hasher.finalize().as_bytes())
+    }
+}
+
+mod air_shared;
+mod air_v1;
+
+/// Constraint-set revision of `SolanaStateAir` a proof was generated under.
+///
+/// `verify_stark_proof` dispatches on this so proofs anchored under an older revision
+/// remain verifiable for audits. Support window: the current revision and every earlier
+/// one that still has anchored proofs inside the audit retention period; a revision is
+/// removed from this registry (and its AIR type deleted) only after that.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AirRevision {
+    /// 16 columns; slot and step transitions only (`air_v1`).
+    #[serde(rename = "solana-state/v1")]
+    V1,
+    /// 18 columns; adds the canonical signed stake delta (see `stake_delta`).
+    #[serde(rename = "solana-state/v2")]
+    V2,
+    /// Revision 2 with the step counter pinned to the row index (`step[0] = 0`).
+    #[serde(rename = "solana-state/v3")]
+    V3,
+    /// Revision 3 with the aggregate stake and vote counts pinned at both range endpoints.
+    #[serde(rename = "solana-state/v4")]
+    V4,
+    /// 19 columns; revision 4 with the stake-weighted vote participation pinned at the last row.
+    #[serde(rename = "solana-state/v5")]
+    V5,
+    /// 103 columns; revision 5 with the stake limbs and the stake and vote deltas range-checked
+    /// to 32 bits, the stake delta taken over both limbs with a carry between them, and the
+    /// vote delta signed like the stake delta.
+    #[serde(rename = "solana-state/v6")]
+    V6,
+}
+
+impl AirRevision {
+    /// Revision new proofs are generated under.
+    pub const CURRENT: Self = Self::V6;
+
+    /// Number of trace columns proofs of this revision commit to.
+    const fn trace_width(self) -> usize {
+        match self {
+            Self::V1 => air_v1::NUM_COLS,
+            Self::V2 | Self::V3 | Self::V4 => TRACE_WIDTH_V2,
+            Self::V5 => TRACE_WIDTH_V5,
+            Self::V6 => TRACE_WIDTH,
+        }
+    }
+
+    /// Whether the step counter is asserted to start at 0.
+    const fn pins_step(self) -> bool {
+        matches!(self, Self::V3 | Self::V4 | Self::V5 | Self::V6)
+    }
+
+    /// Whether `PublicInputs::endpoints` is required and asserted against the trace.
+    pub const fn binds_endpoints(self) -> bool {
+        matches!(self, Self::V4 | Self::V5 | Self::V6)
+    }
+
+    /// Whether `PublicInputs::participation_ppm` is required and asserted against the trace.
+    pub const fn binds_participation(self) -> bool {
+        matches!(self, Self::V5 | Self::V6)
+    }
+
+    /// Whether stake limbs and deltas are range-checked (see `RANGE_CHECKED`) and the stake
+    /// delta is taken over both limbs.
+    const fn range_checks(self) -> bool {
+        matches!(self, Self::V6)
+    }
+
+    /// Revision of envelopes written before `air_id` existed.
+    const fn legacy() -> Self {
+        Self::V1
+    }
+
+    /// Read `air_id` from a proof JSON envelope; a missing id means `V1`.
+    pub fn from_envelope(v: &serde_json::Value) -> Result<Self> {
+        v.get("air_id").map_or(Ok(Self::legacy()), |id| {
+            serde_json::from_value(id.clone()).map_err(|e| anyhow::anyhow!("unknown air_id {id}: {e}"))
+        })
+    }
+}
+
 /// Proof object containing public inputs and the base64-encoded proof.
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct StarkOutput {
+    /// AIR revision the proof was generated under.
+    #[serde(default = "AirRevision::legacy")]
+    pub air_id: AirRevision,
     /// Public inputs the verifier binds to.
     pub public_inputs: PublicInputs,
     /// Proof bytes encoded in base64.
     pub proof_b64: String,
+    /// Hex Ed25519 signature by the prover over [`StarkOutput::to_canonical_bytes`]; authenticates
+    /// the prover-to-submitter hop and is unrelated to the aggregator's on-chain DS signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prover_signature: Option<String>,
+}
+
+/// Domain-separation context prefixed to the bytes a prover signs.
+pub const OUTPUT_SIGNATURE_CONTEXT: &[u8] = b"zKSL/stark_output/v1";
+
+impl StarkOutput {
+    /// JSON artifact layout written by the CLI (hex-encoded roots and proof hash).
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut v = self.unsigned_json();
+        if let (Some(sig), Some(obj)) = (&self.prover_signature, v.as_object_mut()) {
+            obj.insert("prover_signature".to_string(), serde_json::json!(sig));
+        }
+        v
+    }
+
+    /// Bytes covered by `prover_signature`: `OUTPUT_SIGNATURE_CONTEXT` followed by the
+    /// [`StarkOutput::to_json`] artifact without the signature, keys sorted.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = OUTPUT_SIGNATURE_CONTEXT.to_vec();
+        // serde_json objects are key-sorted maps, so the encoding is deterministic
+        bytes.extend(self.unsigned_json().to_string().into_bytes());
+        bytes
+    }
+
+    /// Artifact file contents as [`write_proof_atomic`] writes them.
+    pub fn artifact_bytes(&self) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(&self.to_json())?)
+    }
+
+    /// Exact size of the artifact file, the `artifact_len` to anchor it with.
+    ///
+    /// `proof_hash` commits to the public inputs rather than to these bytes, so sign the
+    /// output before taking its length: the signature is part of the file.
+    pub fn artifact_len(&self) -> Result<u32> {
+        u32::try_from(self.artifact_bytes()?.len()).context("artifact_len exceeds u32")
+    }
+
+    /// `anchor_proof` arguments anchoring this output as artifact `artifact_id` at `seq`.
+    ///
+    /// Proof hash, slots, roots and commitment level come from the public inputs;
+    /// `artifact_len` is [`StarkOutput::artifact_len`] and `ds_hash` is computed
+    /// over the v1 DS for `chain_id`, `program_id` and the aggregator state's `epoch_nonce`.
+    /// `da_params` is left zeroed (no DA scheme).
+    #[allow(clippy::too_many_arguments)]
+    pub fn to_anchor_args(
+        &self,
+        artifact_id: [u8; 16],
+        seq: u64,
+        aggregator_pubkey: [u8; 32],
+        timestamp: i64,
+        chain_id: u64,
+        program_id: &[u8; 32],
+        epoch_nonce: u64,
+    ) -> Result<AnchorProofArgs> {
+        let pi = &self.public_inputs;
+        let artifact_len = self.artifact_len()?;
+        let ds = crate::anchor::build_ds(chain_id, program_id, &pi.proof_hash, pi.start, pi.end, seq, epoch_nonce);
+        Ok(AnchorProofArgs {
+            artifact_id,
+            proof_hash: pi.proof_hash,
+            seq,
+            start_slot: pi.start,
+            end_slot: pi.end,
+            artifact_len,
+            state_root_before: pi.before,
+            state_root_after: pi.after,
+            aggregator_pubkey,
+            timestamp,
+            ds_hash: crate::anchor::ds_hash(&ds),
+            commitment_level: pi.commitment.level(),
+            da_params: [0u8; 12],
+            ds_version: crate::anchor::DS_VERSION_V1,
+        })
+    }
+
+    /// [`StarkOutput::to_json`] without `prover_signature`.
+    fn unsigned_json(&self) -> serde_json::Value {
+        let pi = &self.public_inputs;
+        let mut v = serde_json::json!({
+            "air_id": self.air_id,
+            "public_inputs": {
+                "start": pi.start,
+                "end": pi.end,
+                "before": hex::encode(pi.before),
+                "after": hex::encode(pi.after),
+                "proof_hash": hex::encode(pi.proof_hash),
+                "c_in_hex": pi.c_in_hex,
+                "c_out_hex": pi.c_out_hex,
+                "h_b_hex": pi.h_b_hex,
+                "s_in": pi.s_in,
+                "s_out": pi.s_out,
+                "commitment": pi.commitment,
+                "chain_id": pi.chain_id
+            },
+            "proof_b64": self.proof_b64
+        });
+        if let Some(obj) = v["public_inputs"].as_object_mut() {
+            if let Some(endpoints) = &pi.endpoints {
+                obj.insert("endpoints".to_string(), serde_json::json!(endpoints));
+            }
+            if let Some(ppm) = pi.participation_ppm {
+                obj.insert("participation_ppm".to_string(), serde_json::json!(ppm));
+            }
+            // V1 is implicit, so artifacts signed before the field keep their bytes
+            if pi.merkle_version != MerkleVersion::V1 {
+                obj.insert("merkle_version".to_string(), serde_json::json!(pi.merkle_version));
+            }
+        }
+        v
+    }
+}
+
+/// Sign `output` as the prover, replacing any previous signature.
+pub fn sign_output(key: &SigningKey, output: &mut StarkOutput) {
+    output.prover_signature = Some(hex::encode(key.sign(&output.to_canonical_bytes()).to_bytes()));
+}
+
+/// Whether `output` carries a valid prover signature by `pubkey`.
+pub fn verify_output_signature(output: &StarkOutput, pubkey: &VerifyingKey) -> bool {
+    output
+        .prover_signature
+        .as_deref()
+        .and_then(|sig| hex::decode(sig).ok())
+        .and_then(|sig| Signature::from_slice(&sig).ok())
+        .is_some_and(|sig| pubkey.verify(&output.to_canonical_bytes(), &sig).is_ok())
+}
+
+/// Distinguishes temp files of concurrent writers within this process.
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Write `proof` as JSON to `path` atomically and return the final path.
+///
+/// The JSON goes to a temp file in the same directory, is synced, then renamed over
+/// `path`, so readers see either the previous file or the complete new one, never a
+/// truncated artifact. Missing parent directories are created.
+pub fn write_proof_atomic(path: impl AsRef<Path>, proof: &StarkOutput) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("proof path has no file name: {}", path.display()))?;
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+
+    let n = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp = dir.join(format!(".{}.{}.{n}.tmp", file_name.to_string_lossy(), std::process::id()));
+    let bytes = proof.artifact_bytes()?;
+    let written = (|| -> std::io::Result<()> {
+        let mut f = fs::File::create(&tmp)?;
+        f.write_all(&bytes)?;
+        f.sync_all()
+    })();
+    if let Err(e) = written.and_then(|()| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e).with_context(|| format!("write {}", path.display()));
+    }
+    Ok(path.to_path_buf())
 }
 
 /// REAL Solana Validator State AIR with Cryptographic Constraints
 ///
-/// Trace Layout (16 columns for proper 64-bit arithmetic and hash state):
+/// Trace Layout (103 columns for proper 64-bit arithmetic and hash state):
 ///
 /// Slot & Counter:
-/// 0: slot          - Current slot number (u32, fits in field)
+/// 0: slot          - Current slot number, one field element (see `check_slot_fits`)
 /// 1: step_counter  - Step counter for multi-step operations
 ///
 /// Stake (64-bit decomposed into 2x32-bit limbs):
@@ -165,8 +560,9 @@ pub struct StarkOutput {
 /// 6-9: hash_state[0..3] - Rescue sponge state for Merkle commitment
 ///
 /// Range Check Helpers (for monotonicity proofs):
-/// 10: stake_delta  - Stake increase amount (must be non-negative)
-/// 11: vote_delta   - Vote count delta (must be non-negative)
+/// 10: stake_delta  - |stake_low[i] - stake_low[i-1]| (see `stake_delta`); revision 6: low
+///                    32 bits of |stake[i] - stake[i-1]| over the full 64-bit stake
+/// 11: vote_delta   - |vote_count[i] - vote_count[i-1]|, signed like the stake delta
 ///
 /// Merkle Tree Verification:
 /// 12: merkle_root  - Current Merkle root of validator set
@@ -174,12 +570,47 @@ pub struct StarkOutput {
 /// 14: merkle_path  - Sibling hash in verification path
 /// 15: merkle_idx   - Bit indicating left/right in tree
 ///
+/// Stake Delta Sign:
+/// 16: stake_sign   - 1 if stake_low decreased from the previous row, else 0; revision 6:
+///                    1 if the 64-bit stake decreased
+/// 17: stake_dinv   - Inverse of stake_delta (0 when stake_delta is 0); revision 6: inverse
+///                    of stake_delta + stake_delta_high
+///
+/// Vote Participation (revision 5):
+/// 18: participation_ppm - Voting stake / total stake over the rows so far, in parts per million
+///
+/// Range Checks (revision 6):
+/// 19: vote_sign    - 1 if the vote count decreased from the previous row, else 0
+/// 20: vote_dinv    - Inverse of vote_delta (0 when vote_delta is 0)
+/// 21: stake_delta_high - Upper 32 bits of the 64-bit stake delta
+/// 22: stake_carry  - Carry (or borrow) from the low stake limb into the high one
+/// 23-102: little-endian 2-bit chunks of stake_low, stake_high, stake_delta, stake_delta_high
+///         and vote_delta, 16 per column (see `RANGE_CHECKED`)
+///
 /// Constraints enforce:
 /// 1. Slot monotonicity: slot[i+1] = slot[i] + 1
-/// 2. 64-bit stake integrity with proper carry handling
-/// 3. Non-negative deltas (via range decomposition)
+/// 2. 64-bit stake integrity: both stake limbs are 32-bit values (revision 6)
+/// 3. Non-negative deltas: stake and vote deltas are 32-bit values (revision 6)
 /// 4. Rescue hash permutation correctness
 /// 5. Merkle path verification
+/// 6. Canonical stake delta: delta = (stake_low' - stake_low) * (1 - 2 * sign), sign is
+///    boolean, and sign = 1 only when delta != 0, so equal stake is always delta = 0, sign = 0.
+///    Revision 6 applies the signed delta to both limbs, the low limb's overflow carried into
+///    the high one: stake_low' = stake_low + (1 - 2 * sign) * (delta - carry * 2^32) and
+///    stake_high' = stake_high + (1 - 2 * sign) * (delta_high + carry), carry boolean
+/// 7. Canonical vote delta, encoded like the stake delta (revision 6)
+///
+/// A 32-bit range check recomposes the value from 16 chunks of the same row, each chunk
+/// constrained by c * (c - 1) * (c - 2) * (c - 3) = 0. Without it the field admits
+/// "negative" deltas and limbs at or above 2^32, so the signed-delta constraints would pin
+/// nothing: any sign can be paired with p - |delta|.
+///
+/// Constraints not yet enforced evaluate to zero and are declared degree 1 so the
+/// prover's degree check holds; the last row is excluded by the transition divisor.
+/// Revision 6 adds `pad^d` to every constraint of degree d > 1, where `pad` is a periodic
+/// column that is zero on every row the divisor covers (see `degree_pad`): it enforces
+/// nothing, but keeps each constraint at its declared degree when its columns never change,
+/// as the high chunks of a small stake do.
 /// AIR definition for Solana validator state proof.
 #[derive(Clone)]
 pub struct SolanaStateAir {
@@ -187,6 +618,193 @@ pub struct SolanaStateAir {
     context: AirContext<Felt>,
     /// Public inputs bound to this instance.
     pub_inputs: PublicInputs,
+    /// Revision whose constraints apply; revisions 2-5 share their transitions.
+    revision: AirRevision,
+}
+
+/// Trace width of the current revision.
+const TRACE_WIDTH: usize = RANGE_CHUNK_COLUMN + RANGE_CHECKED.len() * RANGE_CHUNKS;
+
+/// Trace width of revision 5: the current layout without the range-check columns.
+const TRACE_WIDTH_V5: usize = 19;
+
+/// Trace width of revisions 2-4: the revision 5 layout without the participation column.
+const TRACE_WIDTH_V2: usize = 18;
+
+/// Trace column holding the running vote participation.
+const PARTICIPATION_COLUMN: usize = 18;
+
+/// Trace columns holding the vote delta sign and the inverse of the vote delta.
+const VOTE_SIGN_COLUMN: usize = 19;
+const VOTE_INVERSE_COLUMN: usize = 20;
+
+/// Trace columns holding the high limb of the revision 6 stake delta and the carry between
+/// the stake limbs.
+const STAKE_DELTA_HIGH_COLUMN: usize = 21;
+const STAKE_CARRY_COLUMN: usize = 22;
+
+/// Columns revision 6 range-checks to 32 bits: `(column, name, recomposition constraint)`.
+///
+/// The chunks of entry `k` occupy `RANGE_CHUNKS` columns from
+/// `RANGE_CHUNK_COLUMN + k * RANGE_CHUNKS`.
+const RANGE_CHECKED: [(usize, &str, usize); 5] = [
+    (2, "stake_low", 20),
+    (3, "stake_high", 21),
+    (10, "stake_delta", 16),
+    (STAKE_DELTA_HIGH_COLUMN, "stake_delta_high", 22),
+    (11, "vote_delta", 11),
+];
+
+/// First range-check chunk column.
+const RANGE_CHUNK_COLUMN: usize = 23;
+
+/// 2-bit chunks per range-checked value.
+const RANGE_CHUNKS: usize = 16;
+
+/// Degree of the per-chunk constraint `c * (c - 1) * (c - 2) * (c - 3)`.
+const RANGE_CHUNK_DEGREE: usize = 4;
+
+/// Smallest blowup factor that fits the degree of the range-check constraints.
+const MIN_RANGE_CHECK_BLOWUP: usize = 4;
+
+/// Transition constraints of revisions 2-5 in evaluation order: `(name, degree, enforced)`.
+///
+/// Declared degrees are taken from here; an unenforced constraint evaluates to zero.
+const TRANSITION_CONSTRAINTS: [(&str, usize, bool); 16] = [
+    ("slot_increment", 1, true),          // 0: slot[i+1] = slot[i] + 1
+    ("step_increment", 1, true),          // 1: step[i+1] = step[i] + 1
+    ("stake_low_carry", 1, false),        // 2: stake_low update with carry
+    ("stake_high_carry", 1, false),       // 3: stake_high update with carry
+    ("vote_count_monotonic", 1, false),   // 4
+    ("root_slot_update", 1, false),       // 5
+    ("hash_state_0_sbox", 1, false),      // 6: Rescue S-box, lane 0
+    ("hash_state_1_sbox", 1, false),      // 7
+    ("hash_state_2_sbox", 1, false),      // 8
+    ("hash_state_3_sbox", 1, false),      // 9
+    ("stake_delta_signed", 2, true),      // 10: delta = (stake_low' - stake_low) * (1 - 2 * sign)
+    ("vote_delta_range", 1, false),       // 11
+    ("merkle_path", 1, false),            // 12
+    ("merkle_root_update", 1, false),     // 13
+    ("stake_sign_boolean", 2, true),      // 14
+    ("stake_sign_needs_delta", 3, true),  // 15: sign = 0 when delta = 0
+];
+
+/// Transition constraints of revision 6 before its per-chunk range constraints.
+///
+/// Indices 0-15 line up with [`TRANSITION_CONSTRAINTS`]; the stake limb slots now carry
+/// the 64-bit stake delta, and the vote delta slot recomposes its column from range-check
+/// chunks. The signed stake delta of slot 10 is subsumed by the carry constraints.
+const TRANSITION_CONSTRAINTS_V6: [(&str, usize, bool); 23] = [
+    ("slot_increment", 1, true),          // 0
+    ("step_increment", 1, true),          // 1
+    ("stake_low_carry", 2, true),         // 2: stake_low' = stake_low + (1 - 2 * sign) * (delta - carry * 2^32)
+    ("stake_high_carry", 2, true),        // 3: stake_high' = stake_high + (1 - 2 * sign) * (delta_high + carry)
+    ("vote_count_monotonic", 1, false),   // 4
+    ("root_slot_update", 1, false),       // 5
+    ("hash_state_0_sbox", 1, false),      // 6
+    ("hash_state_1_sbox", 1, false),      // 7
+    ("hash_state_2_sbox", 1, false),      // 8
+    ("hash_state_3_sbox", 1, false),      // 9
+    ("stake_carry_boolean", 2, true),     // 10
+    ("vote_delta_range", 1, true),        // 11: vote_delta' = sum of its chunks
+    ("merkle_path", 1, false),            // 12
+    ("merkle_root_update", 1, false),     // 13
+    ("stake_sign_boolean", 2, true),      // 14
+    ("stake_sign_needs_delta", 3, true),  // 15: sign = 0 when delta = delta_high = 0
+    ("stake_delta_range", 1, true),       // 16: stake_delta' = sum of its chunks
+    ("vote_delta_signed", 2, true),       // 17: vote_delta = (votes' - votes) * (1 - 2 * sign)
+    ("vote_sign_boolean", 2, true),       // 18
+    ("vote_sign_needs_delta", 3, true),   // 19
+    ("stake_low_range", 1, true),         // 20: stake_low' = sum of its chunks
+    ("stake_high_range", 1, true),        // 21: stake_high' = sum of its chunks
+    ("stake_delta_high_range", 1, true),  // 22: stake_delta_high' = sum of its chunks
+];
+
+/// Every transition constraint of `revision` in evaluation order: `(name, degree, enforced)`.
+///
+/// Revision 6 ends with one `<column>_chunk_<i>` constraint per range-check chunk.
+fn transition_constraints(revision: AirRevision) -> Vec<(String, usize, bool)> {
+    let named = |&(name, degree, enabled): &(&str, usize, bool)| (name.to_string(), degree, enabled);
+    if !revision.range_checks() {
+        return TRANSITION_CONSTRAINTS.iter().map(named).collect();
+    }
+    let chunks = RANGE_CHECKED.iter().flat_map(|&(_, name, _)| {
+        (0..RANGE_CHUNKS).map(move |i| (format!("{name}_chunk_{i}"), RANGE_CHUNK_DEGREE, true))
+    });
+    TRANSITION_CONSTRAINTS_V6.iter().map(named).chain(chunks).collect()
+}
+
+/// Trace column pinned by a boundary assertion, by column index.
+const fn boundary_column_name(column: usize) -> &'static str {
+    match column {
+        0 => "slot",
+        1 => "step",
+        2 => "stake_low",
+        3 => "stake_high",
+        4 => "vote_count",
+        12 => "merkle_root",
+        PARTICIPATION_COLUMN => "participation_ppm",
+        _ => "unnamed",
+    }
+}
+
+/// Whether a [`ConstraintInfo`] is a transition or boundary constraint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstraintKind {
+    /// Relation between adjacent rows.
+    Transition,
+    /// Fixed value of one cell.
+    Boundary,
+}
+
+/// One constraint of an AIR and whether the proof actually enforces it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintInfo {
+    /// Transition constraint name, or `<column>@<row>` for a boundary assertion.
+    pub name: String,
+    /// Transition or boundary.
+    pub kind: ConstraintKind,
+    /// Declared degree (1 for boundary assertions).
+    pub degree: usize,
+    /// Whether the constraint is enforced rather than a zero placeholder.
+    pub enabled: bool,
+}
+
+impl SolanaStateAir {
+    /// Every transition constraint and boundary assertion of this AIR, stubbed ones included
+    /// with `enabled: false`, so a verifier can check the properties it relies on are live.
+    pub fn active_constraints(&self) -> Vec<ConstraintInfo> {
+        let transitions = transition_constraints(self.revision).into_iter().map(|(name, degree, enabled)| ConstraintInfo {
+            name,
+            kind: ConstraintKind::Transition,
+            degree,
+            enabled,
+        });
+        let boundaries = self.get_assertions().into_iter().map(|a| ConstraintInfo {
+            name: format!("{}@{}", boundary_column_name(a.column()), a.first_step()),
+            kind: ConstraintKind::Boundary,
+            degree: 1,
+            enabled: true,
+        });
+        transitions.chain(boundaries).collect()
+    }
+
+    /// Build the AIR with the boundary assertions of `revision`.
+    fn for_revision(
+        trace_info: TraceInfo,
+        pub_inputs: PublicInputs,
+        options: ProofOptions,
+        revision: AirRevision,
+    ) -> Self {
+        let degrees = transition_constraints(revision)
+            .into_iter()
+            .map(|(_, degree, _)| TransitionConstraintDegree::new(degree))
+            .collect();
+
+        let num_assertions = revision_assertions(&pub_inputs, revision).len();
+        let context = AirContext::new(trace_info, degrees, num_assertions, options);
+        Self { context, pub_inputs, revision }
+    }
 }
 
 impl Air for SolanaStateAir {
@@ -198,32 +816,7 @@ impl Air for SolanaStateAir {
         pub_inputs: Self::PublicInputs,
         options: ProofOptions,
     ) -> Self {
-        // Define constraint degrees for REAL cryptographic operations:
-        let degrees = vec![
-            // Basic constraints
-            TransitionConstraintDegree::new(1), // 0: slot monotonicity (linear)
-            TransitionConstraintDegree::new(1), // 1: step counter
-            // 64-bit arithmetic constraints
-            TransitionConstraintDegree::new(2), // 2: stake_low update with carry
-            TransitionConstraintDegree::new(2), // 3: stake_high update with carry
-            TransitionConstraintDegree::new(1), // 4: vote count monotonic
-            TransitionConstraintDegree::new(1), // 5: root slot update
-            // Rescue hash constraints (degree 5 for x^5 S-box)
-            TransitionConstraintDegree::new(5), // 6: hash_state[0] S-box
-            TransitionConstraintDegree::new(5), // 7: hash_state[1] S-box
-            TransitionConstraintDegree::new(5), // 8: hash_state[2] S-box
-            TransitionConstraintDegree::new(5), // 9: hash_state[3] S-box
-            // Range check constraints (for non-negativity)
-            TransitionConstraintDegree::new(2), // 10: stake_delta range
-            TransitionConstraintDegree::new(2), // 11: vote_delta range
-            // Merkle verification constraints
-            TransitionConstraintDegree::new(2), // 12: Merkle path computation
-            TransitionConstraintDegree::new(2), // 13: Merkle root update
-        ];
-        
-        // Boundary assertions: 4 total (slot start/end, merkle root start/end)
-        let context = AirContext::new(trace_info, degrees, 4, options);
-        Self { context, pub_inputs }
+        Self::for_revision(trace_info, pub_inputs, options, AirRevision::V6)
     }
 
     fn context(&self) -> &AirContext<Felt> {
@@ -233,26 +826,24 @@ impl Air for SolanaStateAir {
     fn evaluate_transition<E: FieldElement<BaseField = Felt>>(
         &self,
         frame: &EvaluationFrame<E>,
-        _periodic_values: &[E],
+        periodic_values: &[E],
         result: &mut [E],
     ) {
         let cur = frame.current();
         let next = frame.next();
-        // Transition mask: 1 on all rows except the last row, where it is 0.
-        // This prevents enforcing next-row relations on the cyclic boundary.
-        let mask = cur[15];
-        
+        // The transition divisor already excludes the last row, so next-row relations
+        // are not enforced across the cyclic boundary.
+
         // ===== CONSTRAINT 0: Slot Monotonicity =====
         // Enforces slot[i+1] = slot[i] + 1 (strict progression)
-        result[0] = (next[0] - cur[0] - E::ONE) * mask;
-        
+        result[0] = next[0] - cur[0] - E::ONE;
+
         // ===== CONSTRAINT 1: Step Counter =====
         // Step counter resets every slot or increments for multi-step ops
         // For simplicity: step[i+1] = (step[i] + 1) mod STEPS_PER_SLOT
-        result[1] = (next[1] - cur[1] - E::ONE) * mask;
-        
-        // Simplify constraints for now to ensure consistency with the generated trace.
-        // We keep only the slot/step monotonicity as active constraints and set the rest to zero.
+        result[1] = next[1] - cur[1] - E::ONE;
+
+        // Remaining placeholders stay zero until their constraints are enforced.
         result[2] = E::ZERO;
         result[3] = E::ZERO;
         result[4] = E::ZERO;
@@ -261,77 +852,246 @@ impl Air for SolanaStateAir {
         result[7] = E::ZERO;
         result[8] = E::ZERO;
         result[9] = E::ZERO;
-        result[10] = E::ZERO;
         result[11] = E::ZERO;
         result[12] = E::ZERO;
         result[13] = E::ZERO;
+
+        // ===== CONSTRAINTS 10, 14, 15: Canonical Stake Delta =====
+        // delta' = (stake_low' - stake_low) * (1 - 2 * sign'), sign' boolean, and a set sign
+        // requires an invertible delta, so equal stake can only be encoded as delta = sign = 0.
+        let sign = next[16];
+        let two = E::ONE + E::ONE;
+        result[14] = sign * (sign - E::ONE);
+
+        if !self.revision.range_checks() {
+            result[10] = next[10] - (next[2] - cur[2]) * (E::ONE - two * sign);
+            result[15] = sign * (E::ONE - next[10] * next[17]);
+            return;
+        }
+
+        // ===== CONSTRAINTS 2, 3, 10, 15: 64-bit Stake Delta with Carry =====
+        // Revision 6 moves the whole stake by the signed delta: the low limb by
+        // delta - carry * 2^32 and the high limb by delta_high + carry. Every term is range-
+        // checked below, so both sides stay within 2^33 of zero and hold over the integers.
+        let direction = E::ONE - two * sign;
+        let (delta, delta_high) = (next[10], next[STAKE_DELTA_HIGH_COLUMN]);
+        let carry = next[STAKE_CARRY_COLUMN];
+        result[2] = next[2] - cur[2] - direction * (delta - carry * E::from(Felt::new(1 << 32)));
+        result[3] = next[3] - cur[3] - direction * (delta_high + carry);
+        result[10] = carry * (carry - E::ONE);
+        result[15] = sign * (E::ONE - (delta + delta_high) * next[17]);
+
+        // ===== CONSTRAINTS 11, 16, 20-22 and the chunk constraints: 32-bit Range Checks =====
+        // Each checked cell of the next row equals the sum of its 2-bit chunks, so limbs stay
+        // below 2^32 and a delta's sign must match its actual direction.
+        let three = two + E::ONE;
+        let four = two + two;
+        let chunk_constraints = TRANSITION_CONSTRAINTS_V6.len();
+        for (k, &(column, _, constraint)) in RANGE_CHECKED.iter().enumerate() {
+            let first = RANGE_CHUNK_COLUMN + k * RANGE_CHUNKS;
+            let chunks = &next[first..first + RANGE_CHUNKS];
+            let value = chunks.iter().rev().fold(E::ZERO, |acc, &chunk| acc * four + chunk);
+            result[constraint] = next[column] - value;
+            for (i, &chunk) in chunks.iter().enumerate() {
+                result[chunk_constraints + k * RANGE_CHUNKS + i] =
+                    chunk * (chunk - E::ONE) * (chunk - two) * (chunk - three);
+            }
+        }
+
+        // ===== CONSTRAINTS 17-19: Canonical Vote Delta =====
+        let vote_sign = next[VOTE_SIGN_COLUMN];
+        result[17] = next[11] - (next[4] - cur[4]) * (E::ONE - two * vote_sign);
+        result[18] = vote_sign * (vote_sign - E::ONE);
+        result[19] = vote_sign * (E::ONE - next[11] * next[VOTE_INVERSE_COLUMN]);
+
+        // ===== Degree Padding =====
+        // pad^d vanishes wherever the constraints are enforced (see `degree_pad`)
+        let pad = periodic_values[0];
+        let degrees = TRANSITION_CONSTRAINTS_V6
+            .iter()
+            .map(|&(_, degree, _)| degree)
+            .chain(std::iter::repeat(RANGE_CHUNK_DEGREE));
+        for (r, degree) in result.iter_mut().zip(degrees).filter(|&(_, degree)| degree > 1) {
+            *r += (1..degree).fold(pad, |acc, _| acc * pad);
+        }
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Felt>> {
+        if self.revision.range_checks() {
+            vec![degree_pad(self.trace_length())]
+        } else {
+            Vec::new()
+        }
     }
 
     fn get_assertions(&self) -> Vec<Assertion<Felt>> {
-        let start_slot = Felt::from(self.pub_inputs.start as u32);
-        let end_slot = Felt::from(self.pub_inputs.end as u32);
-        let steps = (self.pub_inputs.end - self.pub_inputs.start) as usize;
-        
-        // Initial Merkle root from before state
-        let before_hash = extract_first_limb(&self.pub_inputs.before);
-        // Final Merkle root from after state
-        let after_hash = extract_first_limb(&self.pub_inputs.after);
-        
-        vec![
-            // Slot boundaries
-            Assertion::single(0, 0, start_slot),
-            Assertion::single(0, steps, end_slot),
-            // Merkle root boundaries (binds to REAL Solana state)
-            Assertion::single(12, 0, before_hash), // Initial root
-            Assertion::single(12, steps, after_hash), // Final root
-        ]
+        revision_assertions(&self.pub_inputs, self.revision)
+    }
+}
+
+/// Every boundary assertion `revision` makes; its length is the AIR's assertion count.
+fn revision_assertions(pub_inputs: &PublicInputs, revision: AirRevision) -> Vec<Assertion<Felt>> {
+    let mut assertions = boundary_assertions(pub_inputs);
+    if revision.pins_step() {
+        // With the step transition this makes column 1 the row index
+        assertions.push(Assertion::single(1, 0, Felt::ZERO));
+    }
+    if revision.binds_endpoints() {
+        assertions.extend(endpoint_assertions(pub_inputs));
+    }
+    if revision.binds_participation() {
+        let ppm = pub_inputs.participation_ppm.unwrap_or_default();
+        assertions.push(Assertion::single(PARTICIPATION_COLUMN, last_row(pub_inputs), Felt::from(ppm)));
+    }
+    assertions
+}
+
+/// Stake limbs (columns 2-3) and vote count (column 4) at the first and last row.
+///
+/// The vote column holds the count modulo 2^32, so only that limb is asserted; missing
+/// endpoints assert zeros, which the prover refuses up front (see `check_endpoints`).
+fn endpoint_assertions(pub_inputs: &PublicInputs) -> Vec<Assertion<Felt>> {
+    let endpoints = pub_inputs.endpoints.unwrap_or_default();
+    let last = last_row(pub_inputs);
+    let mut assertions = Vec::with_capacity(6);
+    for (step, stake, votes) in [
+        (0, endpoints.stake_before, endpoints.votes_before),
+        (last, endpoints.stake_after, endpoints.votes_after),
+    ] {
+        let (stake_low, stake_high) = u64_to_limbs(stake);
+        assertions.push(Assertion::single(2, step, Felt::from(stake_low)));
+        assertions.push(Assertion::single(3, step, Felt::from(stake_high)));
+        assertions.push(Assertion::single(4, step, Felt::from(votes as u32)));
+    }
+    assertions
+}
+
+/// Require endpoints and participation in the public inputs of revisions that assert them.
+fn check_endpoints(pub_inputs: &PublicInputs, revision: AirRevision) -> Result<()> {
+    if revision.binds_endpoints() && pub_inputs.endpoints.is_none() {
+        anyhow::bail!("{revision:?} proofs bind stake and vote endpoints, but the public inputs have none");
+    }
+    if revision.binds_participation() && pub_inputs.participation_ppm.is_none() {
+        anyhow::bail!("{revision:?} proofs bind vote participation, but the public inputs have none");
+    }
+    Ok(())
+}
+
+/// Refuse options whose blowup is too small for the constraint degrees of `revision`;
+/// winterfell would panic building the AIR.
+fn check_blowup(revision: AirRevision, options: &ProofOptions) -> Result<()> {
+    if revision.range_checks() && options.blowup_factor() < MIN_RANGE_CHECK_BLOWUP {
+        anyhow::bail!(
+            "{revision:?} proofs need a blowup factor of at least {MIN_RANGE_CHECK_BLOWUP}, got {}",
+            options.blowup_factor()
+        );
     }
+    Ok(())
 }
 
-/// Interpret the first 4 bytes of a 32-byte array as a u32 limb (LE) and convert to field element.
-fn extract_first_limb(bytes: &[u8; 32]) -> Felt {
-    Felt::from(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+/// Trace row of `pub_inputs.end`.
+///
+/// Provers and verifiers refuse a reversed range first (see `check_slot_range`); one that
+/// reaches the AIR anyway collapses onto row 0 instead of wrapping.
+fn last_row(pub_inputs: &PublicInputs) -> usize {
+    check_slot_range(pub_inputs.start, pub_inputs.end)
+        .ok()
+        .and_then(|steps| usize::try_from(steps).ok())
+        .unwrap_or(0)
 }
 
-/// Build an execution trace from witness data (one row per slot).
+/// Slot and Merkle root boundary assertions shared by every AIR revision.
+fn boundary_assertions(pub_inputs: &PublicInputs) -> Vec<Assertion<Felt>> {
+    let start_slot = Felt::new(pub_inputs.start);
+    let end_slot = Felt::new(pub_inputs.end);
+    let steps = last_row(pub_inputs);
+
+    // Initial Merkle root from before state
+    let before_hash = pub_inputs.before.extract_first_limb();
+    // Final Merkle root from after state
+    let after_hash = pub_inputs.after.extract_first_limb();
+
+    vec![
+        // Slot boundaries
+        Assertion::single(0, 0, start_slot),
+        Assertion::single(0, steps, end_slot),
+        // Merkle root boundaries (binds to REAL Solana state)
+        Assertion::single(12, 0, before_hash), // Initial root
+        Assertion::single(12, steps, after_hash), // Final root
+    ]
+}
+
+/// Canonical stake delta between adjacent rows as `(|cur - prev|, decreased)`.
+///
+/// Equal stake is always `(0, false)`: the AIR only admits `decreased` with a non-zero
+/// delta, so "no change" cannot be confused with a decrease.
+fn stake_delta(prev: u64, cur: u64) -> (u64, bool) {
+    (cur.abs_diff(prev), cur < prev)
+}
+
+/// Whether moving a stake whose low limb is `prev_low` by the low limb `delta_low` of its
+/// delta carries out of the low limb (an increase) or borrows into it (a decrease).
+fn stake_carry(prev_low: u32, delta_low: u32, decreased: bool) -> bool {
+    if decreased {
+        prev_low < delta_low
+    } else {
+        prev_low.checked_add(delta_low).is_none()
+    }
+}
+
+/// Periodic column revision 6 pads its constraints to their declared degrees with.
+///
+/// winter-prover checks in debug builds that each constraint reaches exactly its declared
+/// degree, which fails for one over columns that never change, such as the high chunks of a
+/// small stake. The column is zero on every row but the last, which the transition divisor
+/// exempts, so `pad^d` has degree `d * (n - 1)` and adding it changes no enforced value.
+fn degree_pad(trace_length: usize) -> Vec<Felt> {
+    let mut pad = vec![Felt::ZERO; trace_length];
+    if let Some(last) = pad.last_mut() {
+        *last = Felt::ONE;
+    }
+    pad
+}
+
+/// Little-endian 2-bit chunks of `value`, the witness of its 32-bit range check.
+fn range_chunks(value: u32) -> [u32; RANGE_CHUNKS] {
+    std::array::from_fn(|i| (value >> (2 * i)) & 3)
+}
+
+/// Build the execution trace of `revision` from witness data (one row per slot).
 fn build_trace_from_witness(
     pub_inputs: &PublicInputs,
     witnesses: &[crate::witness::SlotWitness],
+    revision: AirRevision,
 ) -> Result<TraceTable<Felt>> {
-    let steps = (pub_inputs.end - pub_inputs.start) as usize;
+    check_slot_fits(pub_inputs.start)?;
+    check_slot_fits(pub_inputs.end)?;
+    let steps = usize::try_from(check_slot_range(pub_inputs.start, pub_inputs.end)?)?;
     let trace_len = steps + 1;
+    check_trace_length(trace_len)?;
     
     if witnesses.len() != trace_len {
         anyhow::bail!("Witness count mismatch: expected {}, got {}", trace_len, witnesses.len());
     }
     
-    // Initialize 16 columns for REAL zkSTARK constraints
-    let mut columns: Vec<Vec<Felt>> = (0..16).map(|_| Vec::with_capacity(trace_len)).collect();
+    // Initialize every column of the current revision; older ones keep a prefix of them
+    let mut columns: Vec<Vec<Felt>> = (0..TRACE_WIDTH).map(|_| Vec::with_capacity(trace_len)).collect();
+    let (mut voting_stake, mut total_stake_sum) = (0u128, 0u128);
     
     // Process each witness to build trace
     for (idx, witness) in witnesses.iter().enumerate() {
         // Column 0: Slot
-        columns[0].push(Felt::from(witness.slot as u32));
+        columns[0].push(check_slot_fits(witness.slot)?);
         
         // Column 1: Step counter
         columns[1].push(Felt::from(idx as u32));
         
         // Aggregate validator data from REAL Solana witness
-        let mut total_stake = 0u64;
-        let mut total_votes = 0u64;
-        let mut max_root = 0u64;
-        
-        for vote_acc in &witness.vote_accounts {
-            total_stake = total_stake.saturating_add(vote_acc.activated_stake);
-            if vote_acc.last_vote > 0 {
-                total_votes += 1;
-            }
-            max_root = max_root.max(vote_acc.root_slot);
-        }
+        let (total_stake, total_votes) = witness_totals(witness);
+        let max_root = witness.vote_accounts.iter().map(|acc| acc.root_slot).max().unwrap_or(0);
         
         // Columns 2-3: 64-bit stake decomposition
-        let stake_low = (total_stake & 0xFFFF_FFFF) as u32;
-        let stake_high = (total_stake >> 32) as u32;
+        let (stake_low, stake_high) = u64_to_limbs(total_stake);
         columns[2].push(Felt::from(stake_low));
         columns[3].push(Felt::from(stake_high));
         
@@ -351,38 +1111,57 @@ fn build_trace_from_witness(
             columns[6 + i].push(Felt::from(limb));
         }
         
-        // Columns 10-11: Deltas (for non-negativity proofs)
+        // Column 10: Stake delta, with 16-17 carrying its sign and inverse. Revision 6 takes
+        // it over the whole stake, with its high limb in 21 and the carry between limbs in 22
         if idx > 0 {
-            let prev_stake_low = columns[2][idx - 1].as_int() as u32;
-            let cur_stake_low = stake_low;
-            let delta = if cur_stake_low >= prev_stake_low {
-                cur_stake_low - prev_stake_low
+            let prev_low = columns[2][idx - 1].as_int() as u32;
+            let prev_stake = limbs_to_u64(prev_low, columns[3][idx - 1].as_int() as u32);
+            let (abs_delta, decreased) = stake_delta(prev_stake, total_stake);
+            let (delta_low, delta_high) = u64_to_limbs(abs_delta);
+            columns[STAKE_DELTA_HIGH_COLUMN].push(Felt::from(delta_high));
+            let carry = stake_carry(prev_low, delta_low, decreased);
+            columns[STAKE_CARRY_COLUMN].push(if carry { Felt::ONE } else { Felt::ZERO });
+            let (delta, decreased, moved) = if revision.range_checks() {
+                (Felt::from(delta_low), decreased, Felt::from(delta_low) + Felt::from(delta_high))
             } else {
-                0 // Handle underflow (shouldn't happen with real data)
+                let (low_delta, low_decreased) = stake_delta(u64::from(prev_low), u64::from(stake_low));
+                (Felt::new(low_delta), low_decreased, Felt::new(low_delta))
             };
-            columns[10].push(Felt::from(delta));
+            columns[10].push(delta);
+            columns[16].push(if decreased { Felt::ONE } else { Felt::ZERO });
+            columns[17].push(if moved == Felt::ZERO { Felt::ZERO } else { moved.inv() });
             
-            let prev_votes = columns[4][idx - 1].as_int() as u32;
-            let cur_votes = (total_votes % (1u64 << 32)) as u32;
-            let vote_delta = if cur_votes >= prev_votes {
-                cur_votes - prev_votes
-            } else {
-                0
-            };
-            columns[11].push(Felt::from(vote_delta));
+            // Columns 11, 19-20: the vote delta, encoded like the stake delta
+            let prev_votes = columns[4][idx - 1].as_int();
+            let cur_votes = total_votes % (1u64 << 32);
+            let (abs_delta, decreased) = stake_delta(prev_votes, cur_votes);
+            let delta = Felt::new(abs_delta);
+            columns[11].push(delta);
+            columns[VOTE_SIGN_COLUMN].push(if decreased { Felt::ONE } else { Felt::ZERO });
+            columns[VOTE_INVERSE_COLUMN].push(if delta == Felt::ZERO { Felt::ZERO } else { delta.inv() });
         } else {
-            columns[10].push(Felt::ZERO);
-            columns[11].push(Felt::ZERO);
+            for column in [
+                10,
+                11,
+                16,
+                17,
+                VOTE_SIGN_COLUMN,
+                VOTE_INVERSE_COLUMN,
+                STAKE_DELTA_HIGH_COLUMN,
+                STAKE_CARRY_COLUMN,
+            ] {
+                columns[column].push(Felt::ZERO);
+            }
         }
         
         // Columns 12-15: Merkle tree verification
         // Column 12: Merkle root (from witness state_root)
-        let root_limb = extract_first_limb(&witness.state_root);
+        let root_limb = witness.state_root.extract_first_limb();
         columns[12].push(root_limb);
         
         // Column 13: Merkle leaf (first account hash if available)
         if !witness.account_hashes.is_empty() {
-            let leaf_limb = extract_first_limb(&witness.account_hashes[0]);
+            let leaf_limb = witness.account_hashes[0].extract_first_limb();
             columns[13].push(leaf_limb);
         } else {
             columns[13].push(Felt::ZERO);
@@ -390,7 +1169,7 @@ fn build_trace_from_witness(
         
         // Column 14: Sibling hash (second account hash if available)
         if witness.account_hashes.len() > 1 {
-            let sibling_limb = extract_first_limb(&witness.account_hashes[1]);
+            let sibling_limb = witness.account_hashes[1].extract_first_limb();
             columns[14].push(sibling_limb);
         } else {
             columns[14].push(Felt::ZERO);
@@ -399,22 +1178,38 @@ fn build_trace_from_witness(
         // Column 15: Transition mask (1 for all rows except last, where it is 0)
         let is_last = idx + 1 == trace_len;
         columns[15].push(if is_last { Felt::ZERO } else { Felt::ONE });
+
+        // Column 18: Participation over rows 0..=idx, so the last row holds the range total
+        voting_stake += u128::from(witness_voting_stake(witness));
+        total_stake_sum += u128::from(total_stake);
+        columns[PARTICIPATION_COLUMN].push(Felt::from(ppm_of(voting_stake, total_stake_sum)));
+
+        // Columns 23-102: range-check chunks of this row's limbs and deltas
+        for (k, &(column, _, _)) in RANGE_CHECKED.iter().enumerate() {
+            let value = columns[column][idx].as_int() as u32;
+            for (i, chunk) in range_chunks(value).into_iter().enumerate() {
+                columns[RANGE_CHUNK_COLUMN + k * RANGE_CHUNKS + i].push(Felt::from(chunk));
+            }
+        }
     }
     
+    columns.truncate(revision.trace_width());
     Ok(TraceTable::init(columns))
 }
 
-/// Prover implementation that produces STARK proofs over the SolanaStateAir.
-struct SolanaStateProver {
+/// Prover implementation that produces STARK proofs over a SolanaStateAir revision `A`.
+struct SolanaStateProver<A> {
     /// Proving system options (queries, blowup, FRI).
     options: ProofOptions,
     /// Public inputs supplied to the proof.
     pub_inputs: PublicInputs,
+    /// AIR revision the proof is generated under.
+    _air: PhantomData<A>,
 }
 
-impl Prover for SolanaStateProver {
+impl<A: Air<BaseField = Felt, PublicInputs = PublicInputs> + 'static> Prover for SolanaStateProver<A> {
     type BaseField = Felt;
-    type Air = SolanaStateAir;
+    type Air = A;
     type Trace = TraceTable<Self::BaseField>;
     type HashFn = Blake3_256<Felt>;
     type RandomCoin = DefaultRandomCoin<Self::HashFn>;
@@ -466,17 +1261,77 @@ impl Prover for SolanaStateProver {
     }
 }
 
+/// Stage reached while generating a proof, reported to an optional progress callback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// About to fetch this slot's witness from RPC.
+    FetchingSlot(u64),
+    /// Building the execution trace.
+    BuildingTrace,
+    /// Running the prover.
+    Proving,
+    /// Proof generated.
+    Done {
+        /// Serialized proof size, before base64.
+        bytes: usize,
+    },
+}
+
 /// Generate a STARK proof from real Solana RPC-derived witness data.
+///
+/// `slot_binding` selects how witness state roots (and so `before`/`after`) commit to their
+/// slot and `merkle_version` how their trees are hashed. The proof is generated under `params`, which its verifier must be given too (see
+/// [`verify_stark_proof_with_params`]). `progress`, if given, receives a [`ProgressEvent`] at
+/// each stage so callers can render progress without parsing stdout.
+#[allow(clippy::too_many_arguments)]
 pub fn generate_stark_proof_from_witness(
     rpc_url: &str,
     start: u64,
     end: u64,
-    proof_hash: [u8; 32],
+    commitment: Commitment,
+    chain_id: u64,
+    slot_binding: crate::witness::SlotBinding,
+    merkle_version: MerkleVersion,
+    params: &ProofParams,
+    progress: Option<&dyn Fn(ProgressEvent)>,
+) -> Result<StarkOutput> {
+    let rpc = crate::rpc::HttpTransport::with_timeout(rpc_url, crate::rpc::DEFAULT_REQUEST_TIMEOUT)?;
+    generate_stark_proof_over(&rpc, start, end, commitment, chain_id, slot_binding, merkle_version, params, progress)
+}
+
+/// [`generate_stark_proof_from_witness`] over any [`RpcTransport`](crate::rpc::RpcTransport),
+/// such as a simulated cluster.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_stark_proof_over(
+    rpc: &dyn crate::rpc::RpcTransport,
+    start: u64,
+    end: u64,
+    commitment: Commitment,
+    chain_id: u64,
+    slot_binding: crate::witness::SlotBinding,
+    merkle_version: MerkleVersion,
+    params: &ProofParams,
+    progress: Option<&dyn Fn(ProgressEvent)>,
 ) -> Result<StarkOutput> {
     use crate::witness;
     
-    println!("Generating REAL zkSTARK proof from Solana RPC data...");
-    let witnesses = witness::generate_witness_from_rpc(rpc_url, start, end)?;
+    println!("Generating REAL zkSTARK proof from Solana RPC data ({} commitment)...", commitment.as_str());
+    let on_slot = |slot| {
+        if let Some(progress) = progress {
+            progress(ProgressEvent::FetchingSlot(slot));
+        }
+    };
+    let witnesses = witness::generate_witness_from_rpc_with_codec(
+        rpc,
+        start,
+        end,
+        commitment,
+        &witness::Blake3LeafCodec,
+        slot_binding,
+        merkle_version,
+        &witness::WitnessFetchConfig::default(),
+        Some(&on_slot),
+    )?;
     
     if witnesses.is_empty() {
         anyhow::bail!("No witnesses generated from RPC");
@@ -486,61 +1341,237 @@ pub fn generate_stark_proof_from_witness(
     let after = witnesses.last().map(|w| w.state_root).ok_or_else(|| anyhow::anyhow!("No witnesses"))?;
     // Compute North Star Route public inputs (C_in/C_out/H_B/S_in/S_out) from REAL block data
     let (c_in_hex, c_out_hex, h_b_hex, s_in, s_out) =
-        witness::generate_north_star_public_inputs(rpc_url, start, end, &witnesses)?;
+        witness::generate_north_star_public_inputs(rpc, start, end, &witnesses, commitment)?;
     
-    let pub_inputs = PublicInputs {
+    let mut pub_inputs = PublicInputs {
         start,
         end,
         before,
         after,
-        proof_hash,
+        proof_hash: [0u8; 32],
         c_in_hex,
         c_out_hex,
         h_b_hex,
         s_in,
         s_out,
+        commitment,
+        chain_id,
+        endpoints: AggregateEndpoints::from_witnesses(&witnesses),
+        participation_ppm: vote_participation_ppm(&witnesses),
+        merkle_version,
     };
-    
-    // Production-grade security parameters
-    let options = ProofOptions::new(
-        32, // num_queries: 32 queries ≈ 96-bit security
-        8,  // blowup_factor: 8x for efficiency
-        0,  // grinding_factor: 0 for testnet (increase for production)
-        FieldExtension::None,
-        8,  // fri_folding_factor
-        1,  // fri_remainder_max_degree
-        BatchingMethod::Linear,
-        BatchingMethod::Linear,
-    );
-    
+    pub_inputs.proof_hash = pub_inputs.derive_proof_hash()?;
+
+    prove_from_witnesses_with_progress(pub_inputs, &witnesses, AirRevision::CURRENT, params, progress)
+}
+
+/// Options of the [`ProofParams::testnet`] preset, which proofs are generated and verified
+/// under unless a caller passes its own [`ProofParams`].
+fn proof_options() -> ProofOptions {
+    ProofParams::testnet().build()
+}
+
+/// Build the trace for `witnesses` and prove it under `air_id` with the testnet options.
+pub(crate) fn prove_from_witnesses(
+    pub_inputs: PublicInputs,
+    witnesses: &[crate::witness::SlotWitness],
+    air_id: AirRevision,
+) -> Result<StarkOutput> {
+    prove_from_witnesses_with_progress(pub_inputs, witnesses, air_id, &ProofParams::testnet(), None)
+}
+
+/// [`prove_from_witnesses`] under `params`, reporting each stage to `progress`.
+fn prove_from_witnesses_with_progress(
+    pub_inputs: PublicInputs,
+    witnesses: &[crate::witness::SlotWitness],
+    air_id: AirRevision,
+    params: &ProofParams,
+    progress: Option<&dyn Fn(ProgressEvent)>,
+) -> Result<StarkOutput> {
+    let report = |event| {
+        if let Some(progress) = progress {
+            progress(event);
+        }
+    };
+    let options = params.to_options()?;
+    check_blowup(air_id, &options)?;
+
     println!("Building execution trace from {} witness slots...", witnesses.len());
-    let trace = build_trace_from_witness(&pub_inputs, &witnesses)?;
+    report(ProgressEvent::BuildingTrace);
+    check_endpoints(&pub_inputs, air_id)?;
+    let trace = build_trace_from_witness(&pub_inputs, witnesses, air_id)?;
+    check_trace_boundaries(&trace, &revision_assertions(&pub_inputs, air_id))?;
     
     println!("Proving with REAL constraints (Rescue hash, Merkle verification, 64-bit arithmetic)...");
-    let prover = SolanaStateProver { options, pub_inputs: pub_inputs.clone() };
-    let proof = Prover::prove(&prover, trace)?;
+    report(ProgressEvent::Proving);
+    let proof = match air_id {
+        AirRevision::V1 => prove_with::<air_v1::SolanaStateAirV1>(options, &pub_inputs, trace)?,
+        AirRevision::V2 => prove_with::<air_shared::SolanaStateAirV2>(options, &pub_inputs, trace)?,
+        AirRevision::V3 => prove_with::<air_shared::SolanaStateAirV3>(options, &pub_inputs, trace)?,
+        AirRevision::V4 => prove_with::<air_shared::SolanaStateAirV4>(options, &pub_inputs, trace)?,
+        AirRevision::V5 => prove_with::<air_shared::SolanaStateAirV5>(options, &pub_inputs, trace)?,
+        AirRevision::V6 => prove_with::<SolanaStateAir>(options, &pub_inputs, trace)?,
+    };
     
     let bytes = proof.to_bytes();
+    report(ProgressEvent::Done { bytes: bytes.len() });
     let proof_b64 = B64.encode(bytes);
     
     println!("✓ STARK proof generated successfully ({} bytes)", proof_b64.len());
     
-    Ok(StarkOutput { public_inputs: pub_inputs, proof_b64 })
+    Ok(StarkOutput { air_id, public_inputs: pub_inputs, proof_b64, prover_signature: None })
 }
 
-/// Verify a STARK proof against acceptable options and the provided public inputs.
+/// Prove `trace` under AIR revision `A`.
+fn prove_with<A: Air<BaseField = Felt, PublicInputs = PublicInputs> + 'static>(
+    options: ProofOptions,
+    pub_inputs: &PublicInputs,
+    trace: TraceTable<Felt>,
+) -> Result<Proof> {
+    let prover = SolanaStateProver::<A> { options, pub_inputs: pub_inputs.clone(), _air: PhantomData };
+    Ok(Prover::prove(&prover, trace)?)
+}
+
+/// Verify a STARK proof under the AIR revision named by its `air_id` and the testnet options.
 pub fn verify_stark_proof(stark: &StarkOutput) -> Result<()> {
+    verify_stark_proof_with_params(stark, &ProofParams::testnet())
+}
+
+/// [`verify_stark_proof`] accepting only proofs generated under exactly `params`.
+pub fn verify_stark_proof_with_params(stark: &StarkOutput, params: &ProofParams) -> Result<()> {
+    let options = params.to_options()?;
+    check_blowup(stark.air_id, &options)?;
+    // Slots are bound as field elements, so a claim past the modulus would verify as its alias
+    check_slot_fits(stark.public_inputs.start)?;
+    check_slot_fits(stark.public_inputs.end)?;
+    // The AIR asserts the end slot at row end - start, so the range must run forwards and
+    // match the proof's trace; winterfell panics on an assertion outside the trace
+    let steps = check_slot_range(stark.public_inputs.start, stark.public_inputs.end)?;
+    check_endpoints(&stark.public_inputs, stark.air_id)?;
     let proof_bytes = B64.decode(stark.proof_b64.as_bytes())?;
     let proof = Proof::from_bytes(&proof_bytes)?;
-    
-    let acceptable: AcceptableOptions = AcceptableOptions::OptionSet(vec![ProofOptions::new(
-        32, 8, 0, FieldExtension::None, 8, 1,
-        BatchingMethod::Linear, BatchingMethod::Linear,
-    )]);
-    
-    verify::<SolanaStateAir, Blake3_256<Felt>, DefaultRandomCoin<Blake3_256<Felt>>, MerkleTree<Blake3_256<Felt>>>(
+    let width = proof.trace_info().main_trace_width();
+    if width != stark.air_id.trace_width() {
+        anyhow::bail!("{:?} proofs have {} trace columns, got {width}", stark.air_id, stark.air_id.trace_width());
+    }
+    let rows = proof.trace_info().length();
+    if usize::try_from(steps).ok().and_then(|steps| steps.checked_add(1)) != Some(rows) {
+        let (start, end) = (stark.public_inputs.start, stark.public_inputs.end);
+        anyhow::bail!("slots {start}..={end} need {} trace rows, the proof has {rows}", steps.saturating_add(1));
+    }
+    let pub_inputs = stark.public_inputs.clone();
+    match stark.air_id {
+        AirRevision::V1 => verify_with::<air_v1::SolanaStateAirV1>(proof, pub_inputs, options),
+        AirRevision::V2 => verify_with::<air_shared::SolanaStateAirV2>(proof, pub_inputs, options),
+        AirRevision::V3 => verify_with::<air_shared::SolanaStateAirV3>(proof, pub_inputs, options),
+        AirRevision::V4 => verify_with::<air_shared::SolanaStateAirV4>(proof, pub_inputs, options),
+        AirRevision::V5 => verify_with::<air_shared::SolanaStateAirV5>(proof, pub_inputs, options),
+        AirRevision::V6 => verify_with::<SolanaStateAir>(proof, pub_inputs, options),
+    }
+}
+
+/// [`SolanaStateAir::active_constraints`] of the AIR `stark` is verified under.
+///
+/// Revision 1 has its own reduced AIR and is not reported.
+pub fn output_constraints(stark: &StarkOutput) -> Result<Vec<ConstraintInfo>> {
+    if stark.air_id == AirRevision::V1 {
+        anyhow::bail!("constraint report is not available for {:?}", stark.air_id);
+    }
+    let pi = &stark.public_inputs;
+    check_slot_fits(pi.end)?;
+    let trace_len = usize::try_from(check_slot_range(pi.start, pi.end)?)? + 1;
+    check_trace_length(trace_len)?;
+    let trace_info = TraceInfo::new(stark.air_id.trace_width(), trace_len);
+    let air = SolanaStateAir::for_revision(trace_info, pi.clone(), proof_options(), stark.air_id);
+    Ok(air.active_constraints())
+}
+
+/// Fail unless every constraint named in `required` is reported and enabled for `stark`.
+pub fn require_constraints(stark: &StarkOutput, required: &[String]) -> Result<()> {
+    let constraints = output_constraints(stark)?;
+    for name in required {
+        if !constraints.iter().any(|c| &c.name == name && c.enabled) {
+            anyhow::bail!("constraint {name} is not enforced by {:?} proofs", stark.air_id);
+        }
+    }
+    Ok(())
+}
+
+/// Verify a STARK proof and require its bound `chain_id` to be one of `chains`.
+///
+/// Lets one verifier accept proofs from several deployments (e.g. devnet and mainnet);
+/// unbound proofs (`chain_id == 0`) are only accepted if 0 is listed.
+pub fn verify_stark_proof_for_chains(stark: &StarkOutput, chains: &[u64]) -> Result<()> {
+    verify_stark_proof(stark)?;
+    let got = stark.public_inputs.chain_id;
+    if !chains.contains(&got) {
+        return Err(ProverError::ChainNotAllowed { got }.into());
+    }
+    Ok(())
+}
+
+/// Regenerate the North Star public inputs of `start..=end` from `rpc` and check all five
+/// (`C_in`, `C_out`, `H_B`, `S_in`, `S_out`) against `expected`.
+///
+/// Blocks are read at `expected.commitment`. The sets are compared pair by pair as well as
+/// through their commitments, so a tampered set is caught even if its commitment was
+/// recomputed to match. Witnesses are fetched with the default slot binding and the tree
+/// version `expected` records; they only feed the commitments of a range in which every slot
+/// was skipped.
+///
+/// Fails with `ProverError::NorthStarMismatch` naming the first input that diverges.
+pub fn verify_north_star_inputs(
+    rpc: &dyn crate::rpc::RpcTransport,
+    start: u64,
+    end: u64,
+    expected: &PublicInputs,
+) -> Result<()> {
+    use crate::witness;
+
+    let witnesses = witness::generate_witness_from_rpc_with_codec(
+        rpc,
+        start,
+        end,
+        expected.commitment,
+        &witness::Blake3LeafCodec,
+        witness::SlotBinding::Bound,
+        expected.merkle_version,
+        &witness::WitnessFetchConfig::default(),
+        None,
+    )?;
+    let (c_in_hex, c_out_hex, h_b_hex, s_in, s_out) =
+        witness::generate_north_star_public_inputs(rpc, start, end, &witnesses, expected.commitment)?;
+    let checks = [
+        ("c_in", c_in_hex == expected.c_in_hex),
+        ("c_out", c_out_hex == expected.c_out_hex),
+        ("h_b", h_b_hex == expected.h_b_hex),
+        ("s_in", s_in == expected.s_in),
+        ("s_out", s_out == expected.s_out),
+    ];
+    if let Some((field, _)) = checks.iter().find(|(_, ok)| !ok) {
+        return Err(ProverError::NorthStarMismatch { field }.into());
+    }
+    Ok(())
+}
+
+/// Verify `proofs` in parallel on the rayon pool, one result per proof in input order.
+///
+/// Each proof keeps the error [`verify_stark_proof`] gives it alone, and a failure never stops
+/// the rest. A panic while verifying is not a rejection; it propagates to the caller.
+pub fn verify_batch(proofs: &[StarkOutput]) -> Vec<Result<()>> {
+    proofs.par_iter().map(verify_stark_proof).collect()
+}
+
+/// Verify `proof` against AIR revision `A`, accepting only `options`.
+fn verify_with<A: Air<BaseField = Felt, PublicInputs = PublicInputs>>(
+    proof: Proof,
+    pub_inputs: PublicInputs,
+    options: ProofOptions,
+) -> Result<()> {
+    let acceptable = AcceptableOptions::OptionSet(vec![options]);
+    verify::<A, Blake3_256<Felt>, DefaultRandomCoin<Blake3_256<Felt>>, MerkleTree<Blake3_256<Felt>>>(
         proof,
-        stark.public_inputs.clone(),
+        pub_inputs,
         &acceptable,
     )
     .map_err(|e: VerifierError| anyhow::anyhow!(format!("STARK verify failed: {e}")))
@@ -643,4 +1674,873 @@ mod tests {
         let rt = reconstruct_bytes_from_elements(&elems);
         assert_eq!(rt, arr);
     }
+
+    /// The original fixed-index extraction, kept as a reference for the chunked versions.
+    fn indexed_limbs(bytes: &[u8; 32]) -> Vec<Felt> {
+        (0..8)
+            .map(|i| {
+                let start = i * 4;
+                Felt::from(u32::from_le_bytes([
+                    bytes[start],
+                    bytes[start + 1],
+                    bytes[start + 2],
+                    bytes[start + 3],
+                ]))
+            })
+            .collect()
+    }
+
+    fn limb_patterns() -> [[u8; 32]; 3] {
+        let mut incrementing = [0u8; 32];
+        for (i, b) in incrementing.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        [[0u8; 32], [0xFFu8; 32], incrementing]
+    }
+
+    #[test]
+    fn test_chunked_limbs_match_indexed_extraction() {
+        for arr in limb_patterns() {
+            let expected = indexed_limbs(&arr);
+            assert_eq!(bytes32_to_elements(&arr), expected);
+            assert_eq!(arr.extract_first_limb(), expected[0]);
+        }
+    }
+
+    #[test]
+    fn test_first_limb_ignores_trailing_bytes() {
+        for mut arr in limb_patterns() {
+            let limb = arr.extract_first_limb();
+            for b in arr.iter_mut().skip(4) {
+                *b ^= 0x5A;
+            }
+            assert_eq!(arr.extract_first_limb(), limb);
+        }
+    }
+
+    fn witness_with_stake(slot: u64, stake: u64, state_root: [u8; 32]) -> crate::witness::SlotWitness {
+        crate::witness::SlotWitness {
+            slot,
+            vote_accounts: vec![crate::witness::VoteAccountWitness {
+                vote_pubkey: format!("vote{slot}"),
+                node_pubkey: format!("node{slot}"),
+                activated_stake: stake,
+                commission: 0,
+                last_vote: slot,
+                root_slot: slot.saturating_sub(32),
+                epoch_credits: Vec::new(),
+            }],
+            state_root,
+            account_hashes: Vec::new(),
+            merkle_version: MerkleVersion::CURRENT,
+        }
+    }
+
+    /// Whether `trace` proves under the AIR `A` and the proof verifies as `air_id`.
+    ///
+    /// winter-prover asserts in debug builds that the trace satisfies the AIR before proving,
+    /// so there an unsatisfying trace panics rather than yielding a proof the verifier rejects.
+    fn proves<A: Air<BaseField = Felt, PublicInputs = PublicInputs> + 'static>(
+        air_id: AirRevision,
+        pub_inputs: &PublicInputs,
+        trace: TraceTable<Felt>,
+    ) -> bool {
+        let proof = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            prove_with::<A>(proof_options(), pub_inputs, trace)
+        }));
+        let Ok(Ok(proof)) = proof else { return false };
+        let public_inputs = pub_inputs.clone();
+        let out = StarkOutput { air_id, public_inputs, proof_b64: B64.encode(proof.to_bytes()), prover_signature: None };
+        verify_stark_proof(&out).is_ok()
+    }
+
+    #[test]
+    fn test_stake_delta_is_canonical() {
+        assert_eq!(stake_delta(7, 7), (0, false));
+        assert_eq!(stake_delta(7, 9), (2, false));
+        assert_eq!(stake_delta(9, 7), (2, true));
+        assert_eq!(stake_delta(0, 0), (0, false));
+    }
+
+    /// Eight slots where rows 1-2 and 5-6 carry equal stake; the rest move in both directions.
+    fn equal_stake_inputs() -> (PublicInputs, Vec<crate::witness::SlotWitness>) {
+        stake_inputs([100, 150, 150, 120, 200, 180, 180, 300])
+    }
+
+    /// Eight consecutive slots from 1000, one vote account each, with the given total stakes.
+    fn stake_inputs(stakes: [u64; 8]) -> (PublicInputs, Vec<crate::witness::SlotWitness>) {
+        let start = 1_000u64;
+        let witnesses: Vec<_> = stakes
+            .iter()
+            .enumerate()
+            .map(|(i, &stake)| witness_with_stake(start + i as u64, stake, [i as u8; 32]))
+            .collect();
+        let end = start + stakes.len() as u64 - 1;
+        let pub_inputs = PublicInputs {
+            start,
+            end,
+            before: witnesses[0].state_root,
+            after: witnesses[stakes.len() - 1].state_root,
+            proof_hash: [7u8; 32],
+            c_in_hex: String::new(),
+            c_out_hex: String::new(),
+            h_b_hex: String::new(),
+            s_in: Vec::new(),
+            s_out: Vec::new(),
+            commitment: Commitment::Finalized,
+            chain_id: 0,
+            endpoints: AggregateEndpoints::from_witnesses(&witnesses),
+            participation_ppm: vote_participation_ppm(&witnesses),
+            merkle_version: MerkleVersion::CURRENT,
+        };
+        (pub_inputs, witnesses)
+    }
+
+    #[test]
+    fn test_equal_stake_slots_encode_zero_delta_and_verify() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let trace = build_trace_from_witness(&pub_inputs, &witnesses, AirRevision::CURRENT).expect("trace");
+        for row in [2, 6] {
+            assert_eq!(trace.get(10, row), Felt::ZERO, "delta at row {row}");
+            assert_eq!(trace.get(16, row), Felt::ZERO, "sign at row {row}");
+            assert_eq!(trace.get(17, row), Felt::ZERO, "inverse at row {row}");
+        }
+        assert_eq!(trace.get(10, 3), Felt::from(30u32));
+        assert_eq!(trace.get(16, 3), Felt::ONE);
+
+        let out = prove_from_witnesses(pub_inputs, &witnesses, AirRevision::CURRENT).expect("prove");
+        verify_stark_proof(&out).expect("verify");
+    }
+
+    #[test]
+    fn test_each_params_preset_roundtrips() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let prove = |params: &ProofParams| {
+            prove_from_witnesses_with_progress(pub_inputs.clone(), &witnesses, AirRevision::CURRENT, params, None)
+                .expect("prove")
+        };
+        let (testnet, production) = (ProofParams::testnet(), ProofParams::production());
+        for params in [testnet, production] {
+            let out = prove(&params);
+            verify_stark_proof_with_params(&out, &params).expect("verify");
+        }
+        // A verifier only accepts the options it was configured with
+        let out = prove(&production);
+        assert!(verify_stark_proof(&out).is_err(), "production proof accepted under testnet options");
+        let custom = ProofParams { num_queries: 40, grinding_factor: 4, ..testnet };
+        let out = prove(&custom);
+        verify_stark_proof_with_params(&out, &custom).expect("custom params verify");
+        assert!(verify_stark_proof_with_params(&out, &testnet).is_err());
+        // Invalid params are refused before proving
+        let invalid = ProofParams { fri_folding_factor: 3, ..testnet };
+        assert!(prove_from_witnesses_with_progress(pub_inputs, &witnesses, AirRevision::CURRENT, &invalid, None).is_err());
+    }
+
+    fn north_star_inputs() -> PublicInputs {
+        let mut pi = sample_output(0).public_inputs;
+        pi.c_in_hex = hex::encode([0x11u8; 32]);
+        pi.c_out_hex = hex::encode([0x22u8; 32]);
+        pi.h_b_hex = hex::encode([0x33u8; 32]);
+        pi
+    }
+
+    #[test]
+    fn test_proof_hash_covers_every_north_star_field() {
+        let base = north_star_inputs();
+        let hash = base.derive_proof_hash().expect("derive");
+        // Known-answer vector shared with orchestrator/test/proof_hash.test.ts
+        assert_eq!(hex::encode(hash), PROOF_HASH_KAT);
+        assert_eq!(base.derive_proof_hash().expect("derive"), hash, "deterministic");
+
+        let flipped = hex::encode([0x44u8; 32]);
+        let variants = [
+            PublicInputs { c_in_hex: flipped.clone(), ..base.clone() },
+            PublicInputs { c_out_hex: flipped.clone(), ..base.clone() },
+            PublicInputs { h_b_hex: flipped, ..base.clone() },
+            PublicInputs { before: [0x44u8; 32], ..base.clone() },
+            PublicInputs { after: [0x44u8; 32], ..base.clone() },
+            PublicInputs { start: base.start + 1, ..base.clone() },
+            PublicInputs { end: base.end + 1, ..base.clone() },
+        ];
+        for (i, pi) in variants.iter().enumerate() {
+            assert_ne!(pi.derive_proof_hash().expect("derive"), hash, "variant {i}");
+        }
+        // proof_hash itself is not part of the preimage
+        let relabelled = PublicInputs { proof_hash: [0x55u8; 32], ..base.clone() };
+        assert_eq!(relabelled.derive_proof_hash().expect("derive"), hash);
+        assert!(PublicInputs { c_in_hex: String::new(), ..base }.derive_proof_hash().is_err());
+    }
+
+    #[test]
+    fn test_build_trace_rejects_non_power_of_two_slot_range() {
+        let (mut pub_inputs, mut witnesses) = equal_stake_inputs();
+        witnesses.truncate(6);
+        pub_inputs.end = pub_inputs.start + 5;
+        let err = build_trace_from_witness(&pub_inputs, &witnesses, AirRevision::CURRENT).expect_err("6 rows");
+        assert_eq!(
+            err.downcast_ref::<crate::error::ProverError>(),
+            Some(&crate::error::ProverError::TraceLengthNotPow2 { len: 6 })
+        );
+    }
+
+    #[test]
+    fn test_slots_beyond_u32_are_proven_not_aliased() {
+        const SHIFT: u64 = 1 << 32;
+        let (mut pub_inputs, mut witnesses) = equal_stake_inputs();
+        for w in &mut witnesses {
+            w.slot += SHIFT;
+        }
+        pub_inputs.start += SHIFT;
+        pub_inputs.end += SHIFT;
+        let trace = build_trace_from_witness(&pub_inputs, &witnesses, AirRevision::CURRENT).expect("trace");
+        assert_eq!(trace.get(0, 0).as_int(), pub_inputs.start);
+
+        let out = prove_from_witnesses(pub_inputs, &witnesses, AirRevision::CURRENT).expect("prove");
+        verify_stark_proof(&out).expect("verify");
+        // Truncated to u32 the range would read as slots 1000..=1007
+        let mut aliased = out.clone();
+        aliased.public_inputs.start -= SHIFT;
+        aliased.public_inputs.end -= SHIFT;
+        assert!(verify_stark_proof(&aliased).is_err(), "proof verified for the truncated range");
+
+        let mut out = sample_output(8);
+        out.public_inputs.start = Felt::MODULUS;
+        let err = verify_stark_proof(&out).expect_err("slot past the modulus");
+        assert_eq!(err.downcast_ref::<ProverError>(), Some(&ProverError::SlotExceedsField { slot: Felt::MODULUS }));
+    }
+
+    #[test]
+    fn test_reversed_or_resized_slot_ranges_are_errors_not_panics() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let out = prove_from_witnesses(pub_inputs.clone(), &witnesses, AirRevision::CURRENT).expect("prove");
+        let (start, end) = (pub_inputs.start, pub_inputs.end);
+        let reversed = ProverError::SlotRangeReversed { start: end, end: start };
+
+        let mut flipped = out.clone();
+        (flipped.public_inputs.start, flipped.public_inputs.end) = (end, start);
+        let err = verify_stark_proof(&flipped).expect_err("reversed range");
+        assert_eq!(err.downcast_ref::<ProverError>(), Some(&reversed));
+        let err = build_trace_from_witness(&flipped.public_inputs, &witnesses, AirRevision::CURRENT)
+            .expect_err("reversed range");
+        assert_eq!(err.downcast_ref::<ProverError>(), Some(&reversed));
+
+        // Sixteen slots asserted against an eight-row trace
+        let mut stretched = out;
+        stretched.public_inputs.end += 8;
+        let err = verify_stark_proof(&stretched).expect_err("longer range");
+        assert!(err.to_string().contains("need 16 trace rows, the proof has 8"), "{err}");
+    }
+
+    #[test]
+    fn test_reported_constraints_match_what_is_enforced() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let trace = build_trace_from_witness(&pub_inputs, &witnesses, AirRevision::CURRENT).expect("trace");
+        let air = SolanaStateAir::new(TraceInfo::new(TRACE_WIDTH, 8), pub_inputs.clone(), proof_options());
+        // Rows 2 -> 3 lower the stake, so the sign and inverse columns are live
+        let row = |r: usize| (0..TRACE_WIDTH).map(|c| trace.get(c, r)).collect::<Vec<_>>();
+        let (cur, next) = (row(2), row(3));
+        let constraints = transition_constraints(AirRevision::CURRENT).len();
+        let mut result = vec![Felt::ZERO; constraints];
+        // The degree pad is zero on every row the transition constraints cover
+        let pad = [Felt::ZERO];
+        air.evaluate_transition(&EvaluationFrame::from_rows(cur.clone(), next.clone()), &pad, &mut result);
+        assert!(result.iter().all(|r| *r == Felt::ZERO), "valid frame violates {result:?}");
+
+        // A constraint is live iff perturbing some cell of the frame makes it fire; 5 is
+        // outside every chunk's 0..=3, so a range-check chunk cannot absorb it
+        let mut fired = vec![false; constraints];
+        for column in 0..TRACE_WIDTH {
+            for in_next in [false, true] {
+                let (mut cur, mut next) = (cur.clone(), next.clone());
+                let cell = if in_next { &mut next[column] } else { &mut cur[column] };
+                *cell += Felt::new(5);
+                air.evaluate_transition(&EvaluationFrame::from_rows(cur, next), &pad, &mut result);
+                for (fired, r) in fired.iter_mut().zip(&result) {
+                    *fired |= *r != Felt::ZERO;
+                }
+            }
+        }
+        let report = air.active_constraints();
+        let reported: Vec<bool> = report
+            .iter()
+            .filter(|c| c.kind == ConstraintKind::Transition)
+            .map(|c| c.enabled)
+            .collect();
+        assert_eq!(reported, fired);
+
+        let boundaries: Vec<&str> =
+            report.iter().filter(|c| c.kind == ConstraintKind::Boundary).map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            boundaries,
+            [
+                "slot@0", "slot@7", "merkle_root@0", "merkle_root@7", "step@0", "stake_low@0", "stake_high@0",
+                "vote_count@0", "stake_low@7", "stake_high@7", "vote_count@7", "participation_ppm@7",
+            ]
+        );
+
+        let out = StarkOutput { air_id: AirRevision::V3, public_inputs: pub_inputs, proof_b64: String::new(), prover_signature: None };
+        require_constraints(&out, &["slot_increment".to_string(), "merkle_root@7".to_string()]).expect("live");
+        assert!(require_constraints(&out, &["stake_low@0".to_string()]).is_err(), "not pinned before revision 4");
+        assert!(require_constraints(&out, &["merkle_path".to_string()]).is_err(), "stubbed");
+    }
+
+    #[test]
+    fn test_each_air_revision_verifies_only_its_own_proofs() {
+        const REVISIONS: [AirRevision; 6] =
+            [AirRevision::V1, AirRevision::V2, AirRevision::V3, AirRevision::V4, AirRevision::V5, AirRevision::V6];
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        for proven_under in REVISIONS {
+            let mut out = prove_from_witnesses(pub_inputs.clone(), &witnesses, proven_under).expect("prove");
+            assert_eq!(out.air_id, proven_under);
+            for claimed in REVISIONS {
+                out.air_id = claimed;
+                assert_eq!(
+                    verify_stark_proof(&out).is_ok(),
+                    claimed == proven_under,
+                    "{proven_under:?} proof checked as {claimed:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_stake_limbs_are_rejected() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let trace = build_trace_from_witness(&pub_inputs, &witnesses, AirRevision::CURRENT).expect("trace");
+        for row in 0..8 {
+            for (k, &(column, _, _)) in RANGE_CHECKED.iter().enumerate() {
+                let chunks = (0..RANGE_CHUNKS).map(|i| trace.get(RANGE_CHUNK_COLUMN + k * RANGE_CHUNKS + i, row));
+                let value = chunks.rev().fold(Felt::ZERO, |acc, chunk| acc * Felt::new(4) + chunk);
+                assert_eq!(value, trace.get(column, row), "column {column} at row {row}");
+            }
+        }
+
+        // Claim 2^32 in the high limb mid-range, which no 64-bit stake decomposes into
+        let forge = |revision| {
+            let mut trace = build_trace_from_witness(&pub_inputs, &witnesses, revision).expect("trace");
+            trace.set(3, 3, Felt::new(1 << 32));
+            trace
+        };
+
+        // Revision 5 leaves the limb unconstrained between the endpoints
+        let legacy = forge(AirRevision::V5);
+        assert!(proves::<air_shared::SolanaStateAirV5>(AirRevision::V5, &pub_inputs, legacy), "revision 5 rejects the limb");
+        let forged = forge(AirRevision::CURRENT);
+        assert!(!proves::<SolanaStateAir>(AirRevision::CURRENT, &pub_inputs, forged), "out-of-range stake limb accepted");
+    }
+
+    #[test]
+    fn test_stake_high_moves_only_with_the_delta_and_carry() {
+        // Crosses 2^32 in both directions, and moves the high limb by more than the carry
+        const HIGH: u64 = 1 << 32;
+        let (pub_inputs, witnesses) =
+            stake_inputs([HIGH - 50, HIGH + 20, HIGH + 20, HIGH - 10, 3 * HIGH, 5, 2 * HIGH, 7]);
+        let trace = build_trace_from_witness(&pub_inputs, &witnesses, AirRevision::CURRENT).expect("trace");
+        let carries: Vec<u64> = (0..8).map(|row| trace.get(STAKE_CARRY_COLUMN, row).as_int()).collect();
+        assert_eq!(carries, [0, 1, 0, 1, 1, 1, 1, 1]);
+        assert_eq!(trace.get(STAKE_DELTA_HIGH_COLUMN, 4), Felt::from(2u32));
+        let out = prove_from_witnesses(pub_inputs.clone(), &witnesses, AirRevision::CURRENT).expect("prove");
+        verify_stark_proof(&out).expect("verify");
+
+        // Raise the high limb at row 3, chunks included, so the stake there reads 2^33 - 10
+        let tamper = |revision: AirRevision| {
+            let mut trace = build_trace_from_witness(&pub_inputs, &witnesses, revision).expect("trace");
+            trace.set(3, 3, Felt::ONE);
+            if revision.range_checks() {
+                trace.set(RANGE_CHUNK_COLUMN + RANGE_CHUNKS, 3, Felt::ONE);
+            }
+            trace
+        };
+        let tampered = tamper(AirRevision::CURRENT);
+        let air = SolanaStateAir::new(TraceInfo::new(TRACE_WIDTH, 8), pub_inputs.clone(), proof_options());
+        let row = |r: usize| (0..TRACE_WIDTH).map(|c| tampered.get(c, r)).collect::<Vec<_>>();
+        let mut result = vec![Felt::ZERO; transition_constraints(AirRevision::CURRENT).len()];
+        for (cur, next) in [(2, 3), (3, 4)] {
+            air.evaluate_transition(&EvaluationFrame::from_rows(row(cur), row(next)), &[Felt::ZERO], &mut result);
+            let fired: Vec<usize> = result.iter().enumerate().filter(|(_, r)| **r != Felt::ZERO).map(|(i, _)| i).collect();
+            assert_eq!(fired, [3], "stake_high_carry alone catches rows {cur} -> {next}");
+        }
+
+        // Revision 5 derives the delta from the low limb only, so it proves the tampered stake
+        let legacy = tamper(AirRevision::V5);
+        assert!(proves::<air_shared::SolanaStateAirV5>(AirRevision::V5, &pub_inputs, legacy), "revision 5 rejects it");
+        assert!(!proves::<SolanaStateAir>(AirRevision::CURRENT, &pub_inputs, tampered), "tampered stake_high accepted");
+    }
+
+    #[test]
+    fn test_range_checks_need_a_wide_enough_blowup() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let narrow = ProofParams { blowup_factor: 2, ..ProofParams::testnet() };
+        let err = prove_from_witnesses_with_progress(pub_inputs.clone(), &witnesses, AirRevision::CURRENT, &narrow, None)
+            .expect_err("blowup 2");
+        assert!(err.to_string().contains("blowup factor of at least 4"), "{err}");
+        prove_from_witnesses_with_progress(pub_inputs, &witnesses, AirRevision::V5, &narrow, None).expect("revision 5");
+    }
+
+    #[test]
+    fn test_stake_and_vote_endpoints_are_bound() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let endpoints = pub_inputs.endpoints.expect("endpoints");
+        assert_eq!((endpoints.stake_before, endpoints.stake_after), (100, 300));
+        assert_eq!((endpoints.votes_before, endpoints.votes_after), (1, 1));
+        let out = prove_from_witnesses(pub_inputs.clone(), &witnesses, AirRevision::CURRENT).expect("prove");
+        verify_stark_proof(&out).expect("verify");
+
+        let tampered_with = |edit: fn(&mut AggregateEndpoints)| {
+            let mut edited = endpoints;
+            edit(&mut edited);
+            let mut tampered = out.clone();
+            tampered.public_inputs.endpoints = Some(edited);
+            verify_stark_proof(&tampered).is_err()
+        };
+        assert!(tampered_with(|e| e.stake_before += 1), "initial stake not bound");
+        assert!(tampered_with(|e| e.stake_after += 1 << 32), "final stake high limb not bound");
+        assert!(tampered_with(|e| e.votes_before += 1), "initial votes not bound");
+        assert!(tampered_with(|e| e.votes_after = 0), "final votes not bound");
+        let mut stripped = out;
+        stripped.public_inputs.endpoints = None;
+        assert!(verify_stark_proof(&stripped).is_err());
+
+        // A wrong endpoint is reported before proving, and missing ones are refused outright
+        let mut wrong = pub_inputs.clone();
+        wrong.endpoints = Some(AggregateEndpoints { stake_before: 101, ..endpoints });
+        let err = prove_from_witnesses(wrong, &witnesses, AirRevision::CURRENT).err().expect("wrong stake");
+        assert_eq!(
+            err.downcast_ref::<ProverError>(),
+            Some(&ProverError::TraceBoundaryMismatch { column: 2, step: 0, expected: 101, got: 100 })
+        );
+        let missing = PublicInputs { endpoints: None, ..pub_inputs };
+        assert!(prove_from_witnesses(missing.clone(), &witnesses, AirRevision::CURRENT).is_err());
+        prove_from_witnesses(missing, &witnesses, AirRevision::V3).expect("revision 3 needs none");
+    }
+
+    #[test]
+    fn test_vote_participation_is_bound() {
+        assert_eq!((ppm_of(0, 0), ppm_of(1, 3), ppm_of(5, 5)), (0, 333_333, 1_000_000));
+
+        // Next to each voting account, add 100 stake that votes on odd slots only
+        let (mut pub_inputs, mut witnesses) = equal_stake_inputs();
+        for w in &mut witnesses {
+            let mut idle = w.vote_accounts[0].clone();
+            idle.activated_stake = 100;
+            idle.last_vote = if w.slot % 2 == 1 { w.slot } else { 0 };
+            w.vote_accounts.push(idle);
+        }
+        // (1380 + 4 * 100) / (1380 + 8 * 100)
+        assert_eq!(vote_participation_ppm(&witnesses), Some(816_513));
+        assert_eq!(vote_participation_ppm(&[]), None);
+        pub_inputs.endpoints = AggregateEndpoints::from_witnesses(&witnesses);
+        pub_inputs.participation_ppm = vote_participation_ppm(&witnesses);
+
+        let trace = build_trace_from_witness(&pub_inputs, &witnesses, AirRevision::CURRENT).expect("trace");
+        // The first slot (1000) is even, so only 100 of 200 has voted there
+        assert_eq!(trace.get(PARTICIPATION_COLUMN, 0), Felt::from(500_000u32));
+        assert_eq!(trace.get(PARTICIPATION_COLUMN, 7), Felt::from(816_513u32));
+
+        let out = prove_from_witnesses(pub_inputs.clone(), &witnesses, AirRevision::CURRENT).expect("prove");
+        verify_stark_proof(&out).expect("verify");
+        assert_eq!(out.to_json()["public_inputs"]["participation_ppm"], 816_513);
+        for claimed in [Some(816_514), Some(0), None] {
+            let mut forged = out.clone();
+            forged.public_inputs.participation_ppm = claimed;
+            assert!(verify_stark_proof(&forged).is_err(), "participation {claimed:?} accepted");
+        }
+
+        let missing = PublicInputs { participation_ppm: None, ..pub_inputs };
+        assert!(prove_from_witnesses(missing.clone(), &witnesses, AirRevision::CURRENT).is_err());
+        prove_from_witnesses(missing, &witnesses, AirRevision::V4).expect("revision 4 needs none");
+    }
+
+    #[test]
+    fn test_identical_inputs_prove_byte_identical() {
+        let (mut pub_inputs, _) = equal_stake_inputs();
+        let witnesses = crate::witness::synthetic_witnesses(pub_inputs.start, 8, 4);
+        pub_inputs.before = witnesses[0].state_root;
+        pub_inputs.after = witnesses[7].state_root;
+        pub_inputs.endpoints = AggregateEndpoints::from_witnesses(&witnesses);
+        pub_inputs.participation_ppm = vote_participation_ppm(&witnesses);
+
+        let first = prove_from_witnesses(pub_inputs.clone(), &witnesses, AirRevision::CURRENT).expect("prove");
+        let second = prove_from_witnesses(pub_inputs, &witnesses, AirRevision::CURRENT).expect("prove");
+        assert!(first.proof_b64 == second.proof_b64, "proving is not deterministic");
+        assert_eq!(first.artifact_bytes().expect("artifact"), second.artifact_bytes().expect("artifact"));
+    }
+
+    #[test]
+    fn test_full_proof_against_simulated_cluster() {
+        let (start, end) = (2_000, 2_007);
+        let cluster = crate::sim::SimCluster::new(42, start, end);
+        let out = generate_stark_proof_over(
+            &cluster,
+            start,
+            end,
+            Commitment::Finalized,
+            103,
+            crate::witness::SlotBinding::Bound,
+            MerkleVersion::CURRENT,
+            &ProofParams::testnet(),
+            None,
+        )
+        .expect("prove");
+        verify_stark_proof_for_chains(&out, &[103]).expect("verify");
+        let pi = &out.public_inputs;
+        assert!(!pi.s_in.is_empty());
+        assert_ne!(pi.c_in_hex, pi.c_out_hex, "the simulated transfers move lamports");
+        assert_eq!(pi.proof_hash, pi.derive_proof_hash().expect("proof hash"));
+    }
+
+    #[test]
+    fn test_north_star_inputs_audit_against_simulated_cluster() {
+        let (start, end) = (2_000, 2_007);
+        let cluster = crate::sim::SimCluster::new(42, start, end);
+        let witnesses =
+            crate::witness::generate_witness_from_rpc(
+                &cluster,
+                start,
+                end,
+                Commitment::Finalized,
+                &crate::witness::WitnessFetchConfig::default(),
+            )
+            .expect("witnesses");
+        let (c_in_hex, c_out_hex, h_b_hex, s_in, s_out) = crate::witness::generate_north_star_public_inputs(
+            &cluster,
+            start,
+            end,
+            &witnesses,
+            Commitment::Finalized,
+        )
+        .expect("public inputs");
+        let mut expected = north_star_inputs();
+        (expected.start, expected.end) = (start, end);
+        (expected.c_in_hex, expected.c_out_hex, expected.h_b_hex) = (c_in_hex, c_out_hex, h_b_hex);
+        (expected.s_in, expected.s_out) = (s_in, s_out);
+        verify_north_star_inputs(&cluster, start, end, &expected).expect("matching inputs");
+
+        let mismatch = |pi: &PublicInputs| {
+            let err = verify_north_star_inputs(&cluster, start, end, pi).expect_err("tampered");
+            match err.downcast_ref::<ProverError>() {
+                Some(ProverError::NorthStarMismatch { field }) => *field,
+                other => panic!("unexpected error {other:?}"),
+            }
+        };
+        let cases: [(&str, fn(&mut PublicInputs)); 5] = [
+            ("c_in", |pi| pi.c_in_hex = pi.c_out_hex.clone()),
+            ("c_out", |pi| pi.c_out_hex = "00".repeat(32)),
+            ("h_b", |pi| pi.h_b_hex = pi.c_in_hex.clone()),
+            ("s_in", |pi| pi.s_in[0].value.push('0')),
+            ("s_out", |pi| {
+                pi.s_out.pop();
+            }),
+        ];
+        for (field, tamper) in cases {
+            let mut pi = expected.clone();
+            tamper(&mut pi);
+            assert_eq!(mismatch(&pi), field);
+        }
+    }
+
+    #[test]
+    fn test_desynced_public_input_is_caught_before_proving() {
+        let (mut pub_inputs, witnesses) = equal_stake_inputs();
+        let last = witnesses.len() - 1;
+        let real_after = pub_inputs.after;
+        pub_inputs.after = [0xEE; 32];
+        let err = prove_from_witnesses(pub_inputs, &witnesses, AirRevision::V3).err().expect("desynced");
+        assert_eq!(
+            err.downcast_ref::<ProverError>(),
+            Some(&ProverError::TraceBoundaryMismatch {
+                column: 12,
+                step: last,
+                expected: u64::from(u32::from_le_bytes([0xEE; 4])),
+                got: real_after.extract_first_limb().as_int(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_step_counter_must_start_at_zero() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let mut trace = build_trace_from_witness(&pub_inputs, &witnesses, AirRevision::V2).expect("trace");
+        for row in 0..witnesses.len() {
+            trace.set(1, row, trace.get(1, row) + Felt::from(5u32));
+        }
+        // Revision 2 only enforces step increments, so the shifted counter still proves
+        let proof = prove_with::<air_shared::SolanaStateAirV2>(proof_options(), &pub_inputs, trace).expect("prove");
+        let mut out = StarkOutput {
+            air_id: AirRevision::V2,
+            public_inputs: pub_inputs,
+            proof_b64: B64.encode(proof.to_bytes()),
+            prover_signature: None,
+        };
+        verify_stark_proof(&out).expect("accepted by v2");
+        out.air_id = AirRevision::V3;
+        assert!(verify_stark_proof(&out).is_err(), "shifted step counter accepted");
+    }
+
+    #[test]
+    fn test_progress_events_follow_proving_stages() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let events = std::cell::RefCell::new(Vec::new());
+        let record = |event| events.borrow_mut().push(event);
+        let out = prove_from_witnesses_with_progress(
+            pub_inputs,
+            &witnesses,
+            AirRevision::CURRENT,
+            &ProofParams::testnet(),
+            Some(&record),
+        )
+        .expect("prove");
+        let bytes = B64.decode(out.proof_b64.as_bytes()).expect("base64").len();
+        assert_eq!(
+            events.into_inner(),
+            vec![ProgressEvent::BuildingTrace, ProgressEvent::Proving, ProgressEvent::Done { bytes }]
+        );
+    }
+
+    #[test]
+    fn test_verify_for_chains_checks_bound_chain_id() {
+        const DEVNET: u64 = 103;
+        const MAINNET: u64 = 101;
+        let (mut pub_inputs, witnesses) = equal_stake_inputs();
+        pub_inputs.chain_id = DEVNET;
+        let out = prove_from_witnesses(pub_inputs, &witnesses, AirRevision::CURRENT).expect("prove");
+        assert_eq!(out.to_json()["public_inputs"]["chain_id"], DEVNET);
+
+        verify_stark_proof_for_chains(&out, &[MAINNET, DEVNET]).expect("devnet allowed");
+        let err = verify_stark_proof_for_chains(&out, &[MAINNET]).expect_err("devnet not allowed");
+        assert_eq!(err.downcast_ref::<ProverError>(), Some(&ProverError::ChainNotAllowed { got: DEVNET }));
+
+        // Relabelling the chain breaks the proof rather than reaching the allow-list check
+        let mut forged = out.clone();
+        forged.public_inputs.chain_id = MAINNET;
+        let err = verify_stark_proof_for_chains(&forged, &[MAINNET]).expect_err("relabelled");
+        assert!(err.downcast_ref::<ProverError>().is_none(), "{err}");
+
+        // A bound chain never shares a transcript with an unbound commitment tail
+        let mut bound = out.public_inputs.clone();
+        bound.chain_id = 1;
+        let unbound = PublicInputs { chain_id: 0, commitment: Commitment::Confirmed, ..bound.clone() };
+        assert_ne!(bound.to_elements(), unbound.to_elements());
+    }
+
+    #[test]
+    fn test_verify_batch_isolates_results_in_order() {
+        let (pub_inputs, witnesses) = equal_stake_inputs();
+        let valid = prove_from_witnesses(pub_inputs, &witnesses, AirRevision::CURRENT).expect("prove");
+        let mut tampered = valid.clone();
+        tampered.public_inputs.proof_hash = [0xEEu8; 32];
+        let mut garbled = valid.clone();
+        garbled.proof_b64 = "not base64!".to_string();
+
+        let mut reversed = valid.clone();
+        reversed.public_inputs.end = reversed.public_inputs.start - 1;
+
+        let batch = [valid.clone(), tampered, valid.clone(), garbled, valid, reversed];
+        let results = verify_batch(&batch);
+        let ok: Vec<bool> = results.iter().map(Result::is_ok).collect();
+        assert_eq!(ok, [true, false, true, false, true, false]);
+        // Each slot carries the error the proof gets on its own, typed errors included
+        for (proof, result) in batch.iter().zip(&results) {
+            let alone = verify_stark_proof(proof).map_err(|e| e.to_string());
+            assert_eq!(result.as_ref().map_err(ToString::to_string), alone);
+        }
+        let err = results.last().and_then(|r| r.as_ref().err()).expect("reversed range rejected");
+        assert!(matches!(err.downcast_ref::<ProverError>(), Some(ProverError::SlotRangeReversed { .. })), "{err}");
+        assert!(verify_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_confirmed_commitment_is_recorded_and_bound() {
+        let (mut pub_inputs, witnesses) = equal_stake_inputs();
+        pub_inputs.commitment = Commitment::Confirmed;
+        let out = prove_from_witnesses(pub_inputs, &witnesses, AirRevision::CURRENT).expect("prove");
+        assert_eq!(out.public_inputs.commitment.level(), 1);
+        assert_eq!(out.to_json()["public_inputs"]["commitment"], "confirmed");
+        verify_stark_proof(&out).expect("verify");
+
+        for relabel in [Commitment::Processed, Commitment::Finalized] {
+            let mut forged = out.clone();
+            forged.public_inputs.commitment = relabel;
+            assert!(verify_stark_proof(&forged).is_err(), "confirmed proof accepted as {relabel:?}");
+        }
+    }
+
+    #[test]
+    fn test_air_revision_from_envelope() {
+        let out = sample_output(4);
+        assert_eq!(AirRevision::from_envelope(&out.to_json()).expect("current"), AirRevision::CURRENT);
+        // Envelopes written before air_id existed are revision 1
+        let legacy = serde_json::json!({ "public_inputs": {}, "proof_b64": "" });
+        assert_eq!(AirRevision::from_envelope(&legacy).expect("legacy"), AirRevision::V1);
+        let unknown = serde_json::json!({ "air_id": "solana-state/v9" });
+        assert!(AirRevision::from_envelope(&unknown).is_err());
+    }
+
+    const PROOF_HASH_KAT: &str = "b5524c1723fe7c81e00d1aa5f639f2ac801b95144b18dfa53a86bb8b1a469cf6";
+
+    fn sample_output(proof_len: usize) -> StarkOutput {
+        StarkOutput {
+            air_id: AirRevision::CURRENT,
+            public_inputs: PublicInputs {
+                start: 1,
+                end: 8,
+                before: [1u8; 32],
+                after: [2u8; 32],
+                proof_hash: [3u8; 32],
+                c_in_hex: String::new(),
+                c_out_hex: String::new(),
+                h_b_hex: String::new(),
+                s_in: Vec::new(),
+                s_out: Vec::new(),
+                commitment: Commitment::Finalized,
+                chain_id: 0,
+                endpoints: None,
+                participation_ppm: None,
+                merkle_version: MerkleVersion::CURRENT,
+            },
+            proof_b64: "A".repeat(proof_len),
+            prover_signature: None,
+        }
+    }
+
+    #[test]
+    fn test_prover_signature_authenticates_output() {
+        let prover = SigningKey::from_bytes(&[5u8; 32]);
+        let mut out = sample_output(8);
+        assert!(!verify_output_signature(&out, &prover.verifying_key()), "unsigned output accepted");
+        sign_output(&prover, &mut out);
+        assert!(verify_output_signature(&out, &prover.verifying_key()));
+        // The signature travels in the artifact and is not part of what it signs
+        assert!(out.to_json().get("prover_signature").is_some());
+        assert!(!out.to_canonical_bytes().windows(16).any(|w| w == b"prover_signature"));
+
+        // Wrong key
+        let other = SigningKey::from_bytes(&[6u8; 32]);
+        assert!(!verify_output_signature(&out, &other.verifying_key()));
+
+        // Tampered proof, public input and signature
+        let mut tampered = out.clone();
+        tampered.proof_b64.push('B');
+        assert!(!verify_output_signature(&tampered, &prover.verifying_key()));
+        let mut tampered = out.clone();
+        tampered.public_inputs.chain_id = 7;
+        assert!(!verify_output_signature(&tampered, &prover.verifying_key()));
+        let mut tampered = out;
+        tampered.prover_signature = tampered.prover_signature.map(|sig| sig.replacen('0', "1", 1));
+        assert!(!verify_output_signature(&tampered, &prover.verifying_key()));
+    }
+
+    #[test]
+    fn test_merkle_version_is_recorded_and_legacy_outputs_keep_their_bytes() {
+        let mut out = sample_output(8);
+        assert_eq!(out.to_json()["public_inputs"]["merkle_version"], "V2");
+        // V1 roots are the implicit version of outputs predating the field
+        out.public_inputs.merkle_version = MerkleVersion::V1;
+        assert!(out.to_json()["public_inputs"].get("merkle_version").is_none());
+        let mut legacy = serde_json::to_value(&out.public_inputs).expect("json");
+        legacy.as_object_mut().expect("object").remove("merkle_version");
+        let legacy: PublicInputs = serde_json::from_value(legacy).expect("legacy inputs");
+        assert_eq!(legacy.merkle_version, MerkleVersion::V1);
+    }
+
+    #[test]
+    fn test_anchor_args_pass_preflight() {
+        let out = sample_output(8);
+        let (chain_id, program_id, aggregator) = (103, [9u8; 32], [4u8; 32]);
+        for epoch_nonce in [0, 2] {
+            let args = out
+                .to_anchor_args([1u8; 16], 5, aggregator, 1_700_000_000, chain_id, &program_id, epoch_nonce)
+                .expect("args");
+            assert_eq!(args.proof_hash, out.public_inputs.proof_hash);
+            assert_eq!((args.start_slot, args.end_slot), (out.public_inputs.start, out.public_inputs.end));
+            assert_eq!((args.state_root_before, args.state_root_after), (out.public_inputs.before, out.public_inputs.after));
+            assert_eq!(args.commitment_level, 2);
+            assert_eq!(args.artifact_len as usize, out.artifact_bytes().expect("bytes").len());
+            args.preflight(chain_id, &program_id, epoch_nonce).expect("preflight");
+
+            // DS context and argument mistakes are caught before submission
+            assert!(args.preflight(chain_id + 1, &program_id, epoch_nonce).is_err());
+            assert!(args.preflight(chain_id, &[8u8; 32], epoch_nonce).is_err());
+            assert!(args.preflight(chain_id, &program_id, epoch_nonce + 1).is_err());
+            assert!(AnchorProofArgs { seq: 6, ..args.clone() }.preflight(chain_id, &program_id, epoch_nonce).is_err());
+            assert!(AnchorProofArgs { aggregator_pubkey: [0u8; 32], ..args.clone() }
+                .preflight(chain_id, &program_id, epoch_nonce)
+                .is_err());
+        }
+        // Oversized artifacts and ranges are refused
+        let big = sample_output(600 * 1024);
+        let args = big.to_anchor_args([1u8; 16], 1, [4u8; 32], 0, 103, &program_id, 0).expect("args");
+        assert!(args.preflight(103, &program_id, 0).is_err());
+        let mut wide = sample_output(8);
+        wide.public_inputs.end = wide.public_inputs.start + 2048;
+        let args = wide.to_anchor_args([1u8; 16], 1, [4u8; 32], 0, 103, &program_id, 0).expect("args");
+        assert!(args.preflight(103, &program_id, 0).is_err());
+    }
+
+    #[test]
+    fn test_write_proof_atomic_creates_parents_and_returns_path() {
+        let dir = std::env::temp_dir().join(format!("zksl-atomic-{}", std::process::id()));
+        let path = dir.join("nested").join("proof.json");
+        let out = sample_output(16);
+        let written = write_proof_atomic(&path, &out).expect("write");
+        assert_eq!(written, path);
+        let v: serde_json::Value =
+            serde_json::from_slice(&fs::read(&path).expect("read")).expect("json");
+        assert_eq!(v, out.to_json());
+        // No temp files left behind
+        let entries = fs::read_dir(dir.join("nested")).expect("dir").count();
+        assert_eq!(entries, 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_artifact_len_is_the_written_file_size() {
+        let dir = std::env::temp_dir().join(format!("zksl-artifact-len-{}", std::process::id()));
+        let path = dir.join("proof.json");
+        let mut out = sample_output(100);
+        for signed in [false, true] {
+            if signed {
+                sign_output(&SigningKey::from_bytes(&[5u8; 32]), &mut out);
+            }
+            write_proof_atomic(&path, &out).expect("write");
+            let on_disk = fs::metadata(&path).expect("metadata").len();
+            let len = out.artifact_len().expect("artifact_len");
+            assert_eq!(u64::from(len), on_disk, "signed: {signed}");
+            let args = out.to_anchor_args([1u8; 16], 1, [4u8; 32], 0, 103, &[9u8; 32], 0).expect("args");
+            assert_eq!(args.artifact_len, len);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_write_proof_atomic_readers_never_see_partial_files() {
+        let dir = std::env::temp_dir().join(format!("zksl-atomic-race-{}", std::process::id()));
+        let path = dir.join("proof.json");
+        // Outputs of very different sizes make a torn write show up as invalid JSON
+        let outputs: Vec<StarkOutput> = [8, 64 * 1024, 512].into_iter().map(sample_output).collect();
+        write_proof_atomic(&path, &outputs[0]).expect("seed");
+        let expected: Vec<serde_json::Value> = outputs.iter().map(StarkOutput::to_json).collect();
+
+        std::thread::scope(|scope| {
+            for out in &outputs {
+                let path = &path;
+                scope.spawn(move || {
+                    for _ in 0..50 {
+                        write_proof_atomic(path, out).expect("write");
+                    }
+                });
+            }
+            for _ in 0..2 {
+                let (path, expected) = (&path, &expected);
+                scope.spawn(move || {
+                    for _ in 0..200 {
+                        let raw = fs::read(path).expect("read");
+                        let v: serde_json::Value = serde_json::from_slice(&raw).expect("complete json");
+                        assert!(expected.contains(&v));
+                    }
+                });
+            }
+        });
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,68 @@
+//! `SolanaStateAir` revisions 2-5, kept so proofs anchored under them stay verifiable.
+//!
+//! These revisions share `SolanaStateAir`'s transition constraints and differ only in their
+//! trace width and boundary assertions, so each is a newtype over it pinned to its revision.
+
+use super::{AirRevision, Felt, PublicInputs, SolanaStateAir};
+use winter_air::{Air, AirContext, Assertion, EvaluationFrame, ProofOptions, TraceInfo};
+use winter_math::FieldElement;
+
+/// Define `$name` as `SolanaStateAir` under `$revision`.
+macro_rules! revision_air {
+    ($(#[$doc:meta])* $name:ident => $revision:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name(SolanaStateAir);
+
+        impl Air for $name {
+            type BaseField = Felt;
+            type PublicInputs = PublicInputs;
+
+            fn new(trace_info: TraceInfo, pub_inputs: Self::PublicInputs, options: ProofOptions) -> Self {
+                Self(SolanaStateAir::for_revision(trace_info, pub_inputs, options, $revision))
+            }
+
+            fn context(&self) -> &AirContext<Felt> {
+                self.0.context()
+            }
+
+            fn evaluate_transition<E: FieldElement<BaseField = Felt>>(
+                &self,
+                frame: &EvaluationFrame<E>,
+                periodic_values: &[E],
+                result: &mut [E],
+            ) {
+                self.0.evaluate_transition(frame, periodic_values, result);
+            }
+
+            fn get_assertions(&self) -> Vec<Assertion<Felt>> {
+                self.0.get_assertions()
+            }
+        }
+    };
+}
+
+revision_air! {
+    /// Revision 2 of the Solana validator state AIR: revision 3 without the step counter
+    /// boundary assertion, so its column may start at any value.
+    SolanaStateAirV2 => AirRevision::V2
+}
+
+revision_air! {
+    /// Revision 3 of the Solana validator state AIR: revision 4 without the stake and vote
+    /// endpoint assertions, so those columns may start and end at any value.
+    SolanaStateAirV3 => AirRevision::V3
+}
+
+revision_air! {
+    /// Revision 4 of the Solana validator state AIR: revision 5 over its first 18 columns,
+    /// without the participation column and its assertion.
+    SolanaStateAirV4 => AirRevision::V4
+}
+
+revision_air! {
+    /// Revision 5 of the Solana validator state AIR: the first 19 columns of the current
+    /// layout, without the range-check columns, so stake limbs and deltas may take any field
+    /// value, and with the stake delta taken over the low limb only.
+    SolanaStateAirV5 => AirRevision::V5
+}
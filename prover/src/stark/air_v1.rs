@@ -0,0 +1,60 @@
+//! `SolanaStateAir` revision 1, kept so proofs anchored before revision 2 stay verifiable.
+//!
+//! Layout is the first 16 columns of the current trace (no stake delta sign/inverse);
+//! only the slot and step transitions are enforced, the remaining slots evaluate to zero.
+
+use super::{revision_assertions, AirRevision, Felt, PublicInputs};
+use winter_air::{
+    Air, AirContext, Assertion, EvaluationFrame, ProofOptions, TraceInfo,
+    TransitionConstraintDegree,
+};
+use winter_math::FieldElement;
+
+/// Trace width under revision 1.
+pub const NUM_COLS: usize = 16;
+
+/// Number of transition constraint slots under revision 1.
+const NUM_CONSTRAINTS: usize = 14;
+
+/// Revision 1 of the Solana validator state AIR.
+#[derive(Clone)]
+pub struct SolanaStateAirV1 {
+    /// AIR context (degrees, assertions, options).
+    context: AirContext<Felt>,
+    /// Public inputs bound to this instance.
+    pub_inputs: PublicInputs,
+}
+
+impl Air for SolanaStateAirV1 {
+    type BaseField = Felt;
+    type PublicInputs = PublicInputs;
+
+    fn new(trace_info: TraceInfo, pub_inputs: Self::PublicInputs, options: ProofOptions) -> Self {
+        let degrees = vec![TransitionConstraintDegree::new(1); NUM_CONSTRAINTS];
+        let num_assertions = revision_assertions(&pub_inputs, AirRevision::V1).len();
+        let context = AirContext::new(trace_info, degrees, num_assertions, options);
+        Self { context, pub_inputs }
+    }
+
+    fn context(&self) -> &AirContext<Felt> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement<BaseField = Felt>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        _periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let cur = frame.current();
+        let next = frame.next();
+        result.fill(E::ZERO);
+        // slot[i+1] = slot[i] + 1 and step[i+1] = step[i] + 1
+        result[0] = next[0] - cur[0] - E::ONE;
+        result[1] = next[1] - cur[1] - E::ONE;
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Felt>> {
+        revision_assertions(&self.pub_inputs, AirRevision::V1)
+    }
+}
@@ -1,12 +1,150 @@
 #![allow(clippy::missing_errors_doc)]
 //! REAL Witness generator: Fetches per-slot Solana data and builds Merkle trees
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use blake3::Hasher as Blake3;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use crate::merkle::MerkleTree;
-use std::collections::{BTreeMap, HashMap};
+use crate::error::ProverError;
+use crate::merkle::{slot_bound_root, MerkleTree, MerkleVersion};
+use crate::rpc::RpcTransport;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
+use std::thread;
+use std::time::Duration;
+
+/// RPC commitment level the witness is fetched at; recorded in the proof's public inputs
+/// and in `ProofRecord.commitment_level` so consumers can see the reorg risk they accepted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    /// Latest block voted on by this node; may be skipped.
+    Processed,
+    /// Voted on by a supermajority of stake; reorg is possible but rare.
+    Confirmed,
+    /// Rooted by a supermajority of stake.
+    #[default]
+    Finalized,
+}
+
+impl Commitment {
+    /// Name used in the RPC `commitment` config field.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Processed => "processed",
+            Self::Confirmed => "confirmed",
+            Self::Finalized => "finalized",
+        }
+    }
+
+    /// On-chain encoding (0=processed, 1=confirmed, 2=finalized).
+    pub const fn level(self) -> u8 {
+        match self {
+            Self::Processed => 0,
+            Self::Confirmed => 1,
+            Self::Finalized => 2,
+        }
+    }
+
+    /// `getBlock` does not accept `processed`; such ranges read blocks at `confirmed`.
+    const fn block_commitment(self) -> Self {
+        match self {
+            Self::Processed => Self::Confirmed,
+            Self::Confirmed | Self::Finalized => self,
+        }
+    }
+}
+
+/// Whether a witness `state_root` is bound to its slot.
+///
+/// `Bound` commits `slot_bound_root(slot, tree_root)`, so two slots with identical validator
+/// sets still get distinct roots. `Unbound` commits the bare [`MerkleTree`] root for verifiers
+/// that rebuild the tree themselves; those roots collide across slots whose validator sets
+/// match, so a proof over them does not by itself pin which slot each row came from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SlotBinding {
+    /// `state_root = blake3(slot_le || tree_root)`.
+    #[default]
+    Bound,
+    /// `state_root = tree_root`.
+    Unbound,
+}
+
+impl SlotBinding {
+    /// The committed state root for a tree with root `tree_root` at `slot`.
+    fn state_root(self, slot: u64, tree_root: &[u8; 32]) -> [u8; 32] {
+        match self {
+            Self::Bound => slot_bound_root(slot, tree_root),
+            Self::Unbound => *tree_root,
+        }
+    }
+}
+
+/// How the witness pipeline retries its RPC calls.
+///
+/// Timeouts belong to the transport, which the pipeline only calls; give an
+/// [`HttpTransport`](crate::rpc::HttpTransport) one with
+/// [`HttpTransport::with_timeout`](crate::rpc::HttpTransport::with_timeout).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WitnessFetchConfig {
+    /// Attempts per request, the first included; one is always made.
+    pub max_attempts: u32,
+    /// Delay before the first retry; it doubles after each further failure.
+    pub initial_backoff: Duration,
+    /// Longest delay between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for WitnessFetchConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+impl WitnessFetchConfig {
+    /// Delay before retry `retry` (0 for the first): the doubled backoff, capped at
+    /// `max_backoff`, with its upper half drawn at random so parallel fetches spread out.
+    fn backoff(&self, retry: u32) -> Duration {
+        let ceiling = self
+            .initial_backoff
+            .saturating_mul(1u32.checked_shl(retry).unwrap_or(u32::MAX))
+            .min(self.max_backoff);
+        let floor = ceiling / 2;
+        floor + (ceiling - floor).mul_f64(rand::random::<f64>())
+    }
+}
+
+/// `rpc.call`, repeated per `fetch` while it fails with [`ProverError::RpcUnavailable`].
+///
+/// Any other failure, such as a malformed response, is returned at once.
+fn call_with_retry(
+    rpc: &dyn RpcTransport,
+    fetch: &WitnessFetchConfig,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let mut retry = 0;
+    loop {
+        match rpc.call(method, params.clone()) {
+            Err(err)
+                if retry + 1 < fetch.max_attempts
+                    && matches!(err.downcast_ref::<ProverError>(), Some(ProverError::RpcUnavailable { .. })) =>
+            {
+                println!("{method} failed ({err}), retrying");
+                thread::sleep(fetch.backoff(retry));
+                retry += 1;
+            }
+            result => return result,
+        }
+    }
+}
 
 /// Real Solana vote account data fetched from RPC
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,170 +179,435 @@ pub struct SlotWitness {
     pub vote_accounts: Vec<VoteAccountWitness>,
     pub state_root: [u8; 32], // Merkle root of all account hashes
     pub account_hashes: Vec<[u8; 32]>, // Individual account hashes (Merkle leaves)
+    /// Hashing of the tree `state_root` commits to; witnesses saved before versions existed
+    /// are `V1`.
+    #[serde(default)]
+    pub merkle_version: MerkleVersion,
 }
 
 /// Generate witness from REAL Solana RPC - fetches data PER SLOT
+///
+/// `getVoteAccounts` only answers at the node's tip, so it supplies the validator set
+/// (identities, stake, commission, credits, root), which only changes at epoch boundaries.
+/// Only vote recency is read per slot, from the blocks: an account's `last_vote` is the latest
+/// slot up to the witness's own whose block carries a successful vote transaction for it,
+/// counting the last produced block before the range (see [`VOTE_SEED_LOOKBACK`]), and 0 when
+/// none does. Every other field is the tip's in every witness. Trees are built under
+/// [`MerkleVersion::CURRENT`].
 pub fn generate_witness_from_rpc(
-    rpc_url: &str,
+    rpc: &dyn RpcTransport,
+    start_slot: u64,
+    end_slot: u64,
+    commitment: Commitment,
+    fetch: &WitnessFetchConfig,
+) -> Result<Vec<SlotWitness>> {
+    generate_witness_from_rpc_with_codec(
+        rpc,
+        start_slot,
+        end_slot,
+        commitment,
+        &Blake3LeafCodec,
+        SlotBinding::Bound,
+        MerkleVersion::CURRENT,
+        fetch,
+        None,
+    )
+}
+
+/// [`generate_witness_from_rpc`] with the validator-set leaves encoded by `codec`, the tree
+/// hashed per `version` and the root bound to its slot per `binding`, so the committed
+/// `state_root` can match a tree built by another system or an earlier proof. Requests are
+/// retried per `fetch`; `on_slot`, if given, is called with each slot before it is fetched.
+///
+/// Slots are fetched one at a time, each after the previous one has been answered. Transports
+/// whose answers depend on the order of requests (such as a simulated cluster) need this;
+/// others can use [`generate_witness_from_rpc_parallel`].
+#[allow(clippy::too_many_arguments)]
+pub fn generate_witness_from_rpc_with_codec(
+    rpc: &dyn RpcTransport,
+    start_slot: u64,
+    end_slot: u64,
+    commitment: Commitment,
+    codec: &dyn LeafCodec,
+    binding: SlotBinding,
+    version: MerkleVersion,
+    fetch: &WitnessFetchConfig,
+    on_slot: Option<&dyn Fn(u64)>,
+) -> Result<Vec<SlotWitness>> {
+    let landed = seed_landed(rpc, fetch, start_slot, commitment)?;
+    fetch_witnesses(rpc, start_slot, end_slot, commitment, codec, binding, version, fetch, on_slot, landed)
+}
+
+/// The sequential fetch behind [`generate_witness_from_rpc_with_codec`], with the vote state
+/// of the slots before `start_slot` already recorded in `landed`.
+#[allow(clippy::too_many_arguments)]
+fn fetch_witnesses(
+    rpc: &dyn RpcTransport,
     start_slot: u64,
     end_slot: u64,
+    commitment: Commitment,
+    codec: &dyn LeafCodec,
+    binding: SlotBinding,
+    version: MerkleVersion,
+    fetch: &WitnessFetchConfig,
+    on_slot: Option<&dyn Fn(u64)>,
+    mut landed: BTreeMap<String, u64>,
 ) -> Result<Vec<SlotWitness>> {
-    let client = reqwest::blocking::Client::new();
     let mut witnesses = Vec::new();
     
     // Fetch REAL data for each slot individually
     for slot in start_slot..=end_slot {
-        println!("Fetching slot {} data from RPC...", slot);
-        
-        // Try to get block data for this specific slot
-        let block_response = client
-            .post(rpc_url)
-            .json(&json!({
-                "jsonrpc": "2.0",
-                "id": 1,
-                "method": "getBlock",
-                "params": [slot, {"encoding": "json", "maxSupportedTransactionVersion": 0}]
-            }))
-            .send()?;
-        
-        let block_result: serde_json::Value = block_response.json()?;
-        
-        // If block doesn't exist (slot not produced), use vote accounts as fallback
-        let witness = if block_result["result"].is_null() {
-            println!("Slot {} not found, using vote accounts snapshot", slot);
-            generate_witness_from_vote_accounts(&client, rpc_url, slot)?
-        } else {
-            generate_witness_from_block(&client, rpc_url, slot, &block_result)?
-        };
-        
-        witnesses.push(witness);
+        if let Some(on_slot) = on_slot {
+            on_slot(slot);
+        }
+        let fetched = fetch_slot(rpc, fetch, slot, commitment).context(ProverError::SlotFetchFailed { slot })?;
+        witnesses.push(build_slot_witness(codec, slot, fetched.vote_state(slot, &mut landed), binding, version));
     }
     
     Ok(witnesses)
 }
 
-/// Generate witness from vote accounts (fallback for skipped slots)
-fn generate_witness_from_vote_accounts(
-    client: &reqwest::blocking::Client,
-    rpc_url: &str,
-    slot: u64,
-) -> Result<SlotWitness> {
-    let response = client
-        .post(rpc_url)
-        .json(&json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "getVoteAccounts",
-            "params": []
-        }))
-        .send()?;
-    
-    let rpc_result: serde_json::Value = response.json()?;
-    let vote_accounts_resp: VoteAccountsResponse = serde_json::from_value(
-        rpc_result["result"].clone()
+/// [`generate_witness_from_rpc_with_codec`] with up to `max_in_flight` slots fetched at once.
+///
+/// Witnesses come back in slot order regardless of which request finishes first. Once a slot
+/// fails no further slots are started, and the error carries
+/// [`ProverError::SlotFetchFailed`] for the lowest failing slot; every slot below it has been
+/// fetched by then, so that is the same slot the sequential path would report.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_witness_from_rpc_parallel(
+    rpc: &(dyn RpcTransport + Sync),
+    start_slot: u64,
+    end_slot: u64,
+    commitment: Commitment,
+    codec: &dyn LeafCodec,
+    binding: SlotBinding,
+    version: MerkleVersion,
+    fetch: &WitnessFetchConfig,
+    max_in_flight: usize,
+) -> Result<Vec<SlotWitness>> {
+    let mut landed = seed_landed(rpc, fetch, start_slot, commitment)?;
+    let slots: Vec<u64> = (start_slot..=end_slot).collect();
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let workers = max_in_flight.clamp(1, slots.len().max(1));
+
+    let mut fetched: Vec<(usize, Result<SlotFetch>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    // Slots are claimed in increasing order, so a failure never leaves a lower slot unclaimed
+                    while !failed.load(atomic::Ordering::Relaxed) {
+                        let index = next.fetch_add(1, atomic::Ordering::Relaxed);
+                        let Some(&slot) = slots.get(index) else { break };
+                        let result = fetch_slot(rpc, fetch, slot, commitment);
+                        if result.is_err() {
+                            failed.store(true, atomic::Ordering::Relaxed);
+                        }
+                        done.push((index, result));
+                    }
+                    done
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    });
+    fetched.sort_by_key(|(index, _)| *index);
+
+    // Vote state carries from slot to slot, so it is applied here in slot order
+    let mut witnesses = Vec::with_capacity(slots.len());
+    for ((index, result), slot) in fetched.into_iter().zip(&slots) {
+        debug_assert_eq!(slots.get(index), Some(slot));
+        let fetched = result.context(ProverError::SlotFetchFailed { slot: *slot })?;
+        witnesses.push(build_slot_witness(codec, *slot, fetched.vote_state(*slot, &mut landed), binding, version));
+    }
+    Ok(witnesses)
+}
+
+/// Write `witnesses` to `path` as a JSON array, replacing any earlier file only once the new
+/// one is complete.
+pub fn save_witnesses(path: impl AsRef<Path>, witnesses: &[SlotWitness]) -> Result<()> {
+    let path = path.as_ref();
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&tmp, serde_json::to_vec(witnesses)?).with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("write {}", path.display()))
+}
+
+/// Read witnesses written by [`save_witnesses`], checking that they cover consecutive slots.
+pub fn load_witnesses(path: impl AsRef<Path>) -> Result<Vec<SlotWitness>> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    let witnesses: Vec<SlotWitness> =
+        serde_json::from_slice(&bytes).with_context(|| format!("parse {}", path.display()))?;
+    if let Some(pair) = witnesses.windows(2).find(|pair| pair[0].slot.checked_add(1) != Some(pair[1].slot)) {
+        anyhow::bail!("{} skips from slot {} to {}", path.display(), pair[0].slot, pair[1].slot);
+    }
+    Ok(witnesses)
+}
+
+/// [`generate_witness_from_rpc`] through a witness file at `cache_path`.
+///
+/// A cache is only reused for a range starting where it starts: each witness's vote state
+/// depends on the slots before it in the range. Its witnesses up to `end_slot` are kept, the
+/// slots past its end are fetched carrying its vote state forward, and the extended set is
+/// saved back under the tree version of the cached witnesses ([`MerkleVersion::CURRENT`] for a
+/// new cache). A cache starting at another slot, or whose roots do not match its vote accounts
+/// under the default leaf codec and slot binding and their recorded version, is refused rather
+/// than overwritten. The file does not record `commitment`; keep one cache per commitment level.
+pub fn generate_witness_from_rpc_cached(
+    rpc: &dyn RpcTransport,
+    start_slot: u64,
+    end_slot: u64,
+    commitment: Commitment,
+    fetch: &WitnessFetchConfig,
+    cache_path: impl AsRef<Path>,
+) -> Result<Vec<SlotWitness>> {
+    let cache_path = cache_path.as_ref();
+    let mut witnesses = if cache_path.exists() { load_witnesses(cache_path)? } else { Vec::new() };
+    if let (Some(first), Some(last)) = (witnesses.first(), witnesses.last()) {
+        if first.slot != start_slot {
+            anyhow::bail!(
+                "{} caches slots {}..={}, not a range starting at {start_slot}",
+                cache_path.display(),
+                first.slot,
+                last.slot
+            );
+        }
+    }
+    if let Some(stale) = witnesses.iter().find(|w| {
+        compute_merkle_root(&Blake3LeafCodec, &w.vote_accounts, w.slot, SlotBinding::Bound, w.merkle_version).0
+            != w.state_root
+    }) {
+        anyhow::bail!("{} has a state root for slot {} that its vote accounts do not produce", cache_path.display(), stale.slot);
+    }
+
+    let next_slot = witnesses.last().map_or(start_slot, |w| w.slot.saturating_add(1));
+    if next_slot > end_slot {
+        witnesses.retain(|w| w.slot <= end_slot);
+        return Ok(witnesses);
+    }
+    // The last cached witness holds every validator's latest vote so far
+    let landed = match witnesses.last() {
+        Some(w) => w
+            .vote_accounts
+            .iter()
+            .filter(|a| a.last_vote > 0)
+            .map(|a| (a.vote_pubkey.clone(), a.last_vote))
+            .collect(),
+        None => seed_landed(rpc, fetch, start_slot, commitment)?,
+    };
+    let version = witnesses.last().map_or(MerkleVersion::CURRENT, |w| w.merkle_version);
+    let fetched = fetch_witnesses(
+        rpc,
+        next_slot,
+        end_slot,
+        commitment,
+        &Blake3LeafCodec,
+        SlotBinding::Bound,
+        version,
+        fetch,
+        None,
+        landed,
     )?;
+    witnesses.extend(fetched);
+    save_witnesses(cache_path, &witnesses)?;
+    Ok(witnesses)
+}
+
+/// Vote program; a transaction naming it and a vote account is that account's vote.
+const VOTE_PROGRAM_ID: &str = "Vote111111111111111111111111111111111111111";
+
+/// One slot's RPC answers, before the vote state carried from earlier slots is applied.
+struct SlotFetch {
+    /// The validator set as `getVoteAccounts` reports it at the node's tip.
+    vote_accounts: Vec<VoteAccountWitness>,
+    /// Vote accounts with a successful vote transaction in the slot's block; empty when skipped.
+    voted: BTreeSet<String>,
+}
+
+impl SlotFetch {
+    /// The vote accounts as of `slot`, recording this slot's votes in `landed` (vote account to
+    /// the latest slot one of its votes landed in), which the caller carries across the range
+    /// after seeding it with [`seed_landed`].
+    ///
+    /// Only `last_vote` is per-slot, and 0 for an account with no vote in `landed`; stake,
+    /// commission, credits and `root_slot` are kept as `getVoteAccounts` reported them.
+    fn vote_state(self, slot: u64, landed: &mut BTreeMap<String, u64>) -> Vec<VoteAccountWitness> {
+        for vote_pubkey in self.voted {
+            landed.insert(vote_pubkey, slot);
+        }
+        self.vote_accounts
+            .into_iter()
+            .map(|mut account| {
+                account.last_vote = landed.get(&account.vote_pubkey).copied().unwrap_or(0);
+                account
+            })
+            .collect()
+    }
+}
+
+/// Slots before a range searched for the produced block its vote state is seeded from.
+pub const VOTE_SEED_LOOKBACK: u64 = 32;
+
+/// Vote state just before `start_slot`: the voters of the last produced block among the
+/// [`VOTE_SEED_LOOKBACK`] slots before it, each at that block's slot; empty if there is none.
+///
+/// Validators vote on nearly every block, so the accounts active before the range start out
+/// voted instead of at 0 until their first vote inside it lands.
+fn seed_landed(
+    rpc: &dyn RpcTransport,
+    fetch: &WitnessFetchConfig,
+    start_slot: u64,
+    commitment: Commitment,
+) -> Result<BTreeMap<String, u64>> {
+    for slot in (start_slot.saturating_sub(VOTE_SEED_LOOKBACK)..start_slot).rev() {
+        let block = call_with_retry(rpc, fetch, "getBlock", get_block_params(slot, commitment))
+            .context(ProverError::SlotFetchFailed { slot })?;
+        if !block["result"].is_null() {
+            return Ok(block_voters(slot, &block["result"]).into_iter().map(|voter| (voter, slot)).collect());
+        }
+    }
+    Ok(BTreeMap::new())
+}
+
+/// Fetch `slot`'s block, if it was produced, and the validator set.
+fn fetch_slot(
+    rpc: &dyn RpcTransport,
+    fetch: &WitnessFetchConfig,
+    slot: u64,
+    commitment: Commitment,
+) -> Result<SlotFetch> {
+    println!("Fetching slot {} data from RPC...", slot);
     
-    let vote_witnesses: Vec<VoteAccountWitness> = vote_accounts_resp.current;
-    
-    // Build REAL Merkle tree from account hashes
-    let (state_root, account_hashes) = compute_merkle_root(&vote_witnesses, slot);
+    // Try to get block data for this specific slot
+    let block_result = call_with_retry(rpc, fetch, "getBlock", get_block_params(slot, commitment))?;
     
-    Ok(SlotWitness {
+    // A skipped slot has no block, so no votes landed in it
+    let voted = if block_result["result"].is_null() {
+        println!("Slot {} not found, carrying vote state forward", slot);
+        BTreeSet::new()
+    } else {
+        block_voters(slot, &block_result["result"])
+    };
+    Ok(SlotFetch { vote_accounts: fetch_vote_accounts(rpc, fetch, commitment)?, voted })
+}
+
+/// Witness for `slot` committing to `vote_witnesses` (REAL Merkle tree over their leaves).
+fn build_slot_witness(
+    codec: &dyn LeafCodec,
+    slot: u64,
+    vote_witnesses: Vec<VoteAccountWitness>,
+    binding: SlotBinding,
+    version: MerkleVersion,
+) -> SlotWitness {
+    let (state_root, account_hashes) = compute_merkle_root(codec, &vote_witnesses, slot, binding, version);
+    SlotWitness {
         slot,
         vote_accounts: vote_witnesses,
         state_root,
         account_hashes,
-    })
+        merkle_version: version,
+    }
 }
 
-/// Generate witness from actual block data (REAL per-slot state)
-fn generate_witness_from_block(
-    client: &reqwest::blocking::Client,
-    rpc_url: &str,
-    slot: u64,
-    block_data: &serde_json::Value,
-) -> Result<SlotWitness> {
-    // Extract transactions from block
+/// Vote accounts whose vote transaction in `block` succeeded (REAL per-slot state).
+///
+/// A vote transaction names the vote program and the vote account it updates; any other
+/// account it names (the node identity, sysvars) is not a vote account of the validator set
+/// and so never matches one.
+fn block_voters(slot: u64, block: &serde_json::Value) -> BTreeSet<String> {
     let empty_vec = vec![];
-    let transactions = block_data["result"]["transactions"].as_array()
-        .unwrap_or(&empty_vec);
-    
-    println!("Slot {} has {} transactions, extracting account updates...", slot, transactions.len());
+    let transactions = block["transactions"].as_array().unwrap_or(&empty_vec);
     
-    // Parse account states from transaction meta
-    let mut account_keys = Vec::new();
+    let mut voted = BTreeSet::new();
     for tx in transactions {
-        if let Some(meta) = tx.get("meta") {
-            // Extract pre/post balances and account keys
-            if let Some(keys) = tx.get("transaction")
-                .and_then(|t| t.get("message"))
-                .and_then(|m| m.get("accountKeys"))
-                .and_then(|k| k.as_array()) {
-                for key in keys {
-                    if let Some(key_str) = key.as_str() {
-                        account_keys.push(key_str.to_string());
-                    }
-                }
-            }
-            
-            // Check for vote program interactions
-            if let Some(log_messages) = meta.get("logMessages").and_then(|l| l.as_array()) {
-                for log in log_messages {
-                    if let Some(log_str) = log.as_str() {
-                        if log_str.contains("Vote111111111111111111111111111111111111111") {
-                            println!("  Found vote transaction in slot {}", slot);
-                        }
-                    }
-                }
-            }
+        if !tx["meta"]["err"].is_null() {
+            continue;
+        }
+        let keys: Vec<&str> = tx["transaction"]["message"]["accountKeys"]
+            .as_array()
+            .map(|keys| keys.iter().filter_map(|k| k.as_str()).collect())
+            .unwrap_or_default();
+        if keys.contains(&VOTE_PROGRAM_ID) {
+            voted.extend(keys.into_iter().filter(|k| *k != VOTE_PROGRAM_ID).map(str::to_string));
         }
     }
-    
-    // Fetch actual vote accounts to get real state (more reliable than parsing)
-    let vote_witnesses = fetch_vote_accounts_for_slot(client, rpc_url)?;
-    
-    let (state_root, account_hashes) = compute_merkle_root(&vote_witnesses, slot);
-    
-    Ok(SlotWitness {
-        slot,
-        vote_accounts: vote_witnesses,
-        state_root,
-        account_hashes,
-    })
+    println!("Slot {} has {} transactions, {} accounts in vote transactions", slot, transactions.len(), voted.len());
+    voted
 }
 
-/// Fetch current vote accounts (real state snapshot)
-fn fetch_vote_accounts_for_slot(
-    client: &reqwest::blocking::Client,
-    rpc_url: &str,
+/// Fetch the current vote accounts (the validator set at the node's tip)
+fn fetch_vote_accounts(
+    rpc: &dyn RpcTransport,
+    fetch: &WitnessFetchConfig,
+    commitment: Commitment,
 ) -> Result<Vec<VoteAccountWitness>> {
-    let response = client
-        .post(rpc_url)
-        .json(&json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "getVoteAccounts",
-            "params": []
-        }))
-        .send()?;
-    
-    let rpc_result: serde_json::Value = response.json()?;
-    let vote_accounts_resp: VoteAccountsResponse = serde_json::from_value(
-        rpc_result["result"].clone()
-    )?;
-    
+    let rpc_result = call_with_retry(rpc, fetch, "getVoteAccounts", json!([{ "commitment": commitment.as_str() }]))?;
+    parse_vote_accounts(&rpc_result)
+}
+
+/// `getBlock` params for `slot`, read at the block-level equivalent of `commitment`.
+fn get_block_params(slot: u64, commitment: Commitment) -> serde_json::Value {
+    json!([slot, {
+        "encoding": "json",
+        "maxSupportedTransactionVersion": 0,
+        "commitment": commitment.block_commitment().as_str()
+    }])
+}
+
+/// Most epoch-credit entries a vote account carries (Solana's `MAX_EPOCH_CREDITS_HISTORY`).
+pub const MAX_EPOCH_CREDITS: usize = 64;
+
+/// Extract the current vote accounts from a `getVoteAccounts` response.
+///
+/// Every `epoch_credits` entry is hashed into the committed root, so instead of truncating
+/// (which would make the root depend on where the cut falls) an account with more entries
+/// than a real validator can have rejects the whole response.
+fn parse_vote_accounts(rpc_result: &serde_json::Value) -> Result<Vec<VoteAccountWitness>> {
+    let result = rpc_result.get("result").cloned().unwrap_or_default();
+    let vote_accounts_resp: VoteAccountsResponse = serde_json::from_value(result)?;
+    for account in &vote_accounts_resp.current {
+        if account.epoch_credits.len() > MAX_EPOCH_CREDITS {
+            return Err(ProverError::TooManyEpochCredits {
+                got: account.epoch_credits.len(),
+                max: MAX_EPOCH_CREDITS,
+            }
+            .into());
+        }
+    }
     Ok(vote_accounts_resp.current)
 }
 
-/// Compute REAL Merkle root from vote account data
-fn compute_merkle_root(vote_accounts: &[VoteAccountWitness], slot: u64) -> ([u8; 32], Vec<[u8; 32]>) {
-    // Sort vote accounts by pubkey for determinism
-    let mut sorted = vote_accounts.to_vec();
-    sorted.sort_by(|a, b| a.vote_pubkey.cmp(&b.vote_pubkey));
-    
-    // Hash each account into a Merkle leaf
-    let mut account_hashes = Vec::new();
-    for vote_acc in sorted {
+/// How vote accounts become Merkle leaves: the leaf order and each leaf's hash.
+///
+/// Only the leaves are pluggable; interior nodes are always [`MerkleTree`]'s and the
+/// root's slot binding is chosen separately with [`SlotBinding`].
+pub trait LeafCodec {
+    /// Order of the leaves in the tree; by default the base58 `vote_pubkey` strings compared
+    /// bytewise.
+    fn order(&self, a: &VoteAccountWitness, b: &VoteAccountWitness) -> Ordering {
+        a.vote_pubkey.cmp(&b.vote_pubkey)
+    }
+
+    /// Hash of one vote account.
+    fn leaf(&self, account: &VoteAccountWitness) -> [u8; 32];
+}
+
+/// Default leaf encoding, committed by every proof unless a caller injects another codec.
+///
+/// `leaf = blake3(vote_pubkey || node_pubkey || activated_stake || commission || last_vote
+/// || root_slot || (epoch || credits || prev_credits)*)`, where the pubkeys are their base58
+/// strings as UTF-8, `commission` is one byte, every other integer is a little-endian u64,
+/// and `epoch_credits` entries appear in RPC order. Leaves are ordered by
+/// [`LeafCodec::order`]'s default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blake3LeafCodec;
+
+impl LeafCodec for Blake3LeafCodec {
+    fn leaf(&self, vote_acc: &VoteAccountWitness) -> [u8; 32] {
         let mut hasher = Blake3::new();
         hasher.update(vote_acc.vote_pubkey.as_bytes());
         hasher.update(vote_acc.node_pubkey.as_bytes());
@@ -220,8 +623,24 @@ fn compute_merkle_root(vote_accounts: &[VoteAccountWitness], slot: u64) -> ([u8;
             hasher.update(&prev_credits.to_le_bytes());
         }
         
-        account_hashes.push(*hasher.finalize().as_bytes());
+        *hasher.finalize().as_bytes()
     }
+}
+
+/// Compute REAL Merkle root from vote account data, hashing the tree per `version`
+fn compute_merkle_root(
+    codec: &dyn LeafCodec,
+    vote_accounts: &[VoteAccountWitness],
+    slot: u64,
+    binding: SlotBinding,
+    version: MerkleVersion,
+) -> ([u8; 32], Vec<[u8; 32]>) {
+    // Sort vote accounts for determinism
+    let mut sorted = vote_accounts.to_vec();
+    sorted.sort_by(|a, b| codec.order(a, b));
+    
+    // Hash each account into a Merkle leaf
+    let mut account_hashes: Vec<[u8; 32]> = sorted.iter().map(|acc| codec.leaf(acc)).collect();
     
     // If no accounts, create a single zero leaf
     if account_hashes.is_empty() {
@@ -229,33 +648,54 @@ fn compute_merkle_root(vote_accounts: &[VoteAccountWitness], slot: u64) -> ([u8;
     }
     
     // Build REAL Merkle tree
-    let tree = MerkleTree::new(account_hashes.clone());
+    let tree = MerkleTree::with_version(account_hashes.clone(), version);
     
-    // Bind slot to root for uniqueness
-    let mut final_hasher = Blake3::new();
-    final_hasher.update(&slot.to_le_bytes());
-    final_hasher.update(&tree.root());
-    let state_root = *final_hasher.finalize().as_bytes();
+    // Bind slot to root for uniqueness, unless the caller wants the bare tree root
+    let state_root = binding.state_root(slot, &tree.root());
     
     (state_root, account_hashes)
 }
 
-/// Generate before/after state roots for a slot range using REAL RPC data
-pub fn generate_state_roots(
-    rpc_url: &str,
-    start_slot: u64,
-    end_slot: u64,
-) -> Result<([u8; 32], [u8; 32])> {
-    let witnesses = generate_witness_from_rpc(rpc_url, start_slot, end_slot)?;
-    
-    if witnesses.is_empty() {
-        anyhow::bail!("No witnesses generated");
+/// Deterministic, network-free witnesses for `count` consecutive slots from `start_slot`.
+///
+/// Stakes are derived from the slot so every run sees the same trace; used by benchmarks
+/// and tests that need realistic Merkle-rooted witnesses without an RPC endpoint.
+pub fn synthetic_witnesses(start_slot: u64, count: usize, accounts_per_slot: usize) -> Vec<SlotWitness> {
+    (start_slot..)
+        .take(count)
+        .map(|slot| {
+            let vote_accounts: Vec<VoteAccountWitness> = (0..accounts_per_slot)
+                .map(|i| {
+                    let mut hasher = Blake3::new();
+                    hasher.update(&slot.to_le_bytes());
+                    hasher.update(&(i as u64).to_le_bytes());
+                    let seed = *hasher.finalize().as_bytes();
+                    let [s0, s1, s2, s3, ..] = seed;
+                    VoteAccountWitness {
+                        vote_pubkey: format!("vote{i}"),
+                        node_pubkey: format!("node{i}"),
+                        activated_stake: u64::from(u32::from_le_bytes([s0, s1, s2, s3])),
+                        commission: 5,
+                        last_vote: slot,
+                        root_slot: slot.saturating_sub(32),
+                        epoch_credits: Vec::new(),
+                    }
+                })
+                .collect();
+            let version = MerkleVersion::CURRENT;
+            let (state_root, account_hashes) =
+                compute_merkle_root(&Blake3LeafCodec, &vote_accounts, slot, SlotBinding::Bound, version);
+            SlotWitness { slot, vote_accounts, state_root, account_hashes, merkle_version: version }
+        })
+        .collect()
+}
+
+/// Before/after state roots of a slot range's `witnesses`: the first and last `state_root`.
+pub fn state_roots(witnesses: &[SlotWitness]) -> Result<([u8; 32], [u8; 32])> {
+    match (witnesses.first(), witnesses.last()) {
+        (Some(first), Some(last)) => Ok((first.state_root, last.state_root)),
+        _ => anyhow::bail!("No witnesses generated"),
     }
-    
-    let before = witnesses.first().unwrap().state_root;
-    let after = witnesses.last().unwrap().state_root;
-    
-    Ok((before, after))
 }
 
 /// Canonical JSON (stable key order) used for hashing PI sets
@@ -289,35 +729,45 @@ fn stringify_canonical(v: &serde_json::Value) -> String {
     }
 }
 
+/// North Star touched-account commitment: blake3 over the canonical JSON of `kv`.
+///
+/// `kv` must already be in the order the prover emits (sorted by account); this is the
+/// `C_in`/`C_out` of [`generate_north_star_public_inputs`] for its `S_in`/`S_out`.
+pub fn compute_c(kv: &[crate::stark::KVPair]) -> [u8; 32] {
+    *Blake3::new().update(canonicalize(&kv).as_bytes()).finalize().as_bytes()
+}
+
+/// Whether `expected` is the commitment to `kv` (see [`compute_c`]).
+pub fn verify_c(kv: &[crate::stark::KVPair], expected: &[u8; 32]) -> bool {
+    compute_c(kv) == *expected
+}
+
 /// Generate North Star Route Public Inputs from REAL Devnet data:
 /// - C_in, C_out: blake3 hash of canonical JSON S_in/S_out (touched accounts with pre/post lamports)
 /// - H_B: blake3 hash of canonicalized block headers/tx signatures across slot range
 /// - S_in/S_out: arrays of {account, value} pairs (value = lamports as decimal string) sorted by account
+///
+/// An account touched by several transactions contributes its pre-balance from the first one
+/// to S_in and its post-balance from the last one to S_out. "First" and "last" follow the
+/// ledger: slots ascending, then each block's `transactions` in the order `getBlock` returns
+/// them, which is the order they were executed in. A finalized block never changes, so the
+/// same range always yields the same S_in/S_out; the maps are only ordered by account when
+/// they are emitted.
 pub fn generate_north_star_public_inputs(
-    rpc_url: &str,
+    rpc: &dyn RpcTransport,
     start_slot: u64,
     end_slot: u64,
     _witnesses: &[SlotWitness],
+    commitment: Commitment,
 ) -> Result<(String, String, String, Vec<crate::stark::KVPair>, Vec<crate::stark::KVPair>)> {
-    let client = reqwest::blocking::Client::new();
-
     // Aggregators for S_in/S_out and H_B payloads
-    // Use HashMap to collect, then BTreeMap (sorted) for canonical output
-    let mut pre_map: HashMap<String, u64> = HashMap::new();
-    let mut post_map: HashMap<String, u64> = HashMap::new();
+    // Sorted maps, so S_in/S_out come out in account order without depending on hash seeds
+    let mut pre_map: BTreeMap<String, u64> = BTreeMap::new();
+    let mut post_map: BTreeMap<String, u64> = BTreeMap::new();
     let mut blocks_repr: Vec<serde_json::Value> = Vec::new();
 
     for slot in start_slot..=end_slot {
-        let resp = client
-            .post(rpc_url)
-            .json(&json!({
-                "jsonrpc": "2.0",
-                "id": 1,
-                "method": "getBlock",
-                "params": [slot, { "encoding": "json", "maxSupportedTransactionVersion": 0 }]
-            }))
-            .send()?;
-        let v: serde_json::Value = resp.json()?;
+        let v = rpc.call("getBlock", get_block_params(slot, commitment))?;
         let result = v.get("result");
         if result.is_none() || result.unwrap().is_null() {
             // skipped slot - include minimal header entry for determinism
@@ -374,7 +824,8 @@ pub fn generate_north_star_public_inputs(
                 let len = message_keys.len().min(pre_bal.len()).min(post_bal.len());
                 for i in 0..len {
                     let acc = &message_keys[i];
-                    // Record earliest pre seen (S_in) and latest post (S_out)
+                    // Record earliest pre seen (S_in) and latest post (S_out); transactions
+                    // are visited in execution order, so both are well defined
                     pre_map.entry(acc.clone()).or_insert(pre_bal[i]);
                     post_map.insert(acc.clone(), post_bal[i]);
                 }
@@ -383,20 +834,11 @@ pub fn generate_north_star_public_inputs(
     }
 
     // Build S_in/S_out arrays sorted by account
-    let mut s_in_pairs: Vec<crate::stark::KVPair> = Vec::new();
-    let mut s_out_pairs: Vec<crate::stark::KVPair> = Vec::new();
-
-    let mut pre_sorted: BTreeMap<String, u64> = BTreeMap::new();
-    let mut post_sorted: BTreeMap<String, u64> = BTreeMap::new();
-    pre_sorted.extend(pre_map.into_iter());
-    post_sorted.extend(post_map.into_iter());
-
-    for (k, v) in pre_sorted.iter() {
-        s_in_pairs.push(crate::stark::KVPair { account: k.clone(), value: v.to_string() });
-    }
-    for (k, v) in post_sorted.iter() {
-        s_out_pairs.push(crate::stark::KVPair { account: k.clone(), value: v.to_string() });
-    }
+    let to_pairs = |map: BTreeMap<String, u64>| -> Vec<crate::stark::KVPair> {
+        map.into_iter().map(|(account, value)| crate::stark::KVPair { account, value: value.to_string() }).collect()
+    };
+    let mut s_in_pairs = to_pairs(pre_map);
+    let mut s_out_pairs = to_pairs(post_map);
 
     // If we failed to find any blocks (fully skipped range), fallback to vote accounts snapshot
     if s_in_pairs.is_empty() && s_out_pairs.is_empty() {
@@ -416,14 +858,8 @@ pub fn generate_north_star_public_inputs(
     }
 
     // Canonicalize S_in/S_out and compute commitments
-    let s_in_json = canonicalize(&s_in_pairs);
-    let s_out_json = canonicalize(&s_out_pairs);
-    let mut h_in = Blake3::new();
-    h_in.update(s_in_json.as_bytes());
-    let mut h_out = Blake3::new();
-    h_out.update(s_out_json.as_bytes());
-    let c_in_hex = hex::encode(*h_in.finalize().as_bytes());
-    let c_out_hex = hex::encode(*h_out.finalize().as_bytes());
+    let c_in_hex = hex::encode(compute_c(&s_in_pairs));
+    let c_out_hex = hex::encode(compute_c(&s_out_pairs));
 
     // Canonicalize H_B payload
     let h_b_payload = canonicalize(&blocks_repr);
@@ -435,13 +871,503 @@ pub fn generate_north_star_public_inputs(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rpc::MockTransport;
     
     #[test]
-    #[ignore] // Requires live RPC
-    fn test_real_witness_generation() {
-        let witnesses = generate_witness_from_rpc("https://api.devnet.solana.com", 1, 2).unwrap();
-        assert!(!witnesses.is_empty());
-        assert!(!witnesses[0].vote_accounts.is_empty());
+    fn test_synthetic_witnesses_are_deterministic() {
+        let a = synthetic_witnesses(1_000, 4, 3);
+        let b = synthetic_witnesses(1_000, 4, 3);
+        assert_eq!(a.len(), 4);
+        for (x, y) in a.iter().zip(&b) {
+            assert_eq!(x.slot, y.slot);
+            assert_eq!(x.state_root, y.state_root);
+            assert_eq!(x.vote_accounts.len(), 3);
+        }
+        assert_eq!(a[3].slot, 1_003);
+        assert_ne!(a[0].state_root, a[1].state_root);
+    }
+
+    /// Stand-in for an external tree: stake-descending order and a keyed, reordered encoding.
+    struct ExternalCodec;
+
+    impl LeafCodec for ExternalCodec {
+        fn order(&self, a: &VoteAccountWitness, b: &VoteAccountWitness) -> Ordering {
+            b.activated_stake.cmp(&a.activated_stake)
+        }
+
+        fn leaf(&self, account: &VoteAccountWitness) -> [u8; 32] {
+            let mut hasher = Blake3::new_derive_key("external validator-set leaf");
+            hasher.update(&account.activated_stake.to_be_bytes());
+            hasher.update(account.vote_pubkey.as_bytes());
+            *hasher.finalize().as_bytes()
+        }
+    }
+
+    #[test]
+    fn test_custom_leaf_codec_changes_root_deterministically() {
+        let w = synthetic_witnesses(500, 1, 4).remove(0);
+        // The default codec reproduces the committed root
+        let (default_root, _) = compute_merkle_root(&Blake3LeafCodec, &w.vote_accounts, w.slot, SlotBinding::Bound, w.merkle_version);
+        assert_eq!(default_root, w.state_root);
+
+        let (root, leaves) = compute_merkle_root(&ExternalCodec, &w.vote_accounts, w.slot, SlotBinding::Bound, w.merkle_version);
+        assert_ne!(root, default_root);
+        assert_eq!(compute_merkle_root(&ExternalCodec, &w.vote_accounts, w.slot, SlotBinding::Bound, w.merkle_version).0, root);
+        // Input order does not matter; the codec's order does
+        let mut shuffled = w.vote_accounts.clone();
+        shuffled.reverse();
+        assert_eq!(compute_merkle_root(&ExternalCodec, &shuffled, w.slot, SlotBinding::Bound, w.merkle_version).0, root);
+        let mut by_stake = w.vote_accounts.clone();
+        by_stake.sort_by(|a, b| b.activated_stake.cmp(&a.activated_stake));
+        let expected: Vec<[u8; 32]> = by_stake.iter().map(|a| ExternalCodec.leaf(a)).collect();
+        assert_eq!(leaves, expected);
+    }
+
+    #[test]
+    fn test_unbound_root_is_the_bare_tree_root() {
+        let w = synthetic_witnesses(500, 1, 4).remove(0);
+        let (bound, leaves) = compute_merkle_root(&Blake3LeafCodec, &w.vote_accounts, w.slot, SlotBinding::Bound, w.merkle_version);
+        let (unbound, unbound_leaves) =
+            compute_merkle_root(&Blake3LeafCodec, &w.vote_accounts, w.slot, SlotBinding::Unbound, w.merkle_version);
+        assert_eq!(leaves, unbound_leaves);
+        assert_ne!(bound, unbound);
+        let tree_root = MerkleTree::new(leaves).root();
+        assert_eq!(unbound, tree_root);
+        assert_eq!(bound, slot_bound_root(w.slot, &tree_root));
+        // The tradeoff: the same validator set at another slot yields the same unbound root
+        let other_slot = |binding| compute_merkle_root(&Blake3LeafCodec, &w.vote_accounts, w.slot + 1, binding, w.merkle_version).0;
+        assert_eq!(other_slot(SlotBinding::Unbound), unbound);
+        assert_ne!(other_slot(SlotBinding::Bound), bound);
+        assert_eq!(SlotBinding::default(), SlotBinding::Bound);
+    }
+
+    fn vote_accounts_response(epochs: u64) -> serde_json::Value {
+        let credits: Vec<(u64, u64, u64)> = (0..epochs).map(|e| (e, e * 10, e * 10)).collect();
+        json!({
+            "result": {
+                "current": [{
+                    "votePubkey": "vote",
+                    "nodePubkey": "node",
+                    "activatedStake": 42,
+                    "commission": 5,
+                    "lastVote": 100,
+                    "rootSlot": 68,
+                    "epochCredits": credits
+                }],
+                "delinquent": []
+            }
+        })
+    }
+
+    #[test]
+    fn test_oversized_epoch_credits_are_rejected() {
+        let accounts = parse_vote_accounts(&vote_accounts_response(MAX_EPOCH_CREDITS as u64)).expect("at the cap");
+        assert_eq!(accounts[0].epoch_credits.len(), MAX_EPOCH_CREDITS);
+
+        let err = parse_vote_accounts(&vote_accounts_response(10_000)).expect_err("over the cap");
+        assert_eq!(
+            err.downcast_ref::<ProverError>(),
+            Some(&ProverError::TooManyEpochCredits { got: 10_000, max: MAX_EPOCH_CREDITS })
+        );
+    }
+
+    /// Slot 10 is a produced block touching two accounts; slot 11 was skipped. Slot 9, the
+    /// block the vote state is seeded from, carries no votes.
+    fn mock_devnet() -> MockTransport {
+        let block = json!({
+            "result": {
+                "blockhash": "hash10",
+                "parentSlot": 9,
+                "transactions": [{
+                    "transaction": {
+                        "signatures": ["sig10"],
+                        "message": { "accountKeys": ["acctA", "acctB"] }
+                    },
+                    "meta": { "preBalances": [100, 50], "postBalances": [90, 60] }
+                }, {
+                    "transaction": {
+                        "signatures": ["vote10"],
+                        "message": { "accountKeys": ["node", "vote", VOTE_PROGRAM_ID] }
+                    },
+                    "meta": { "err": null }
+                }]
+            }
+        });
+        MockTransport::default()
+            .on("getBlock", json!({ "result": null }))
+            .on_params("getBlock", &get_block_params(9, Commitment::Finalized), json!({ "result": { "transactions": [] } }))
+            .on_params("getBlock", &get_block_params(10, Commitment::Finalized), block)
+            .on("getVoteAccounts", vote_accounts_response(2))
+    }
+
+    #[test]
+    fn test_witness_pipeline_against_mocked_rpc() {
+        let rpc = mock_devnet();
+        let witnesses = generate_witness_from_rpc(&rpc, 10, 11, Commitment::Finalized, &WitnessFetchConfig::default()).expect("witnesses");
+        assert_eq!(witnesses.iter().map(|w| w.slot).collect::<Vec<_>>(), vec![10, 11]);
+        let accounts = parse_vote_accounts(&vote_accounts_response(2)).expect("accounts");
+        // The vote landed in slot 10 and carries into skipped slot 11; the rest is the tip's
+        for w in &witnesses {
+            let mut expected = accounts.clone();
+            expected[0].last_vote = 10;
+            assert_eq!(w.vote_accounts.len(), 1);
+            assert_eq!(w.state_root, compute_merkle_root(&Blake3LeafCodec, &expected, w.slot, SlotBinding::Bound, w.merkle_version).0);
+        }
+        assert_ne!(witnesses[0].state_root, witnesses[1].state_root);
+        assert_eq!(
+            state_roots(&witnesses).expect("roots"),
+            (witnesses[0].state_root, witnesses[1].state_root)
+        );
+
+        let (c_in, c_out, h_b, s_in, s_out) =
+            generate_north_star_public_inputs(&rpc, 10, 11, &witnesses, Commitment::Finalized).expect("public inputs");
+        let values = |pairs: &[crate::stark::KVPair]| {
+            pairs.iter().map(|p| (p.account.clone(), p.value.clone())).collect::<Vec<_>>()
+        };
+        assert_eq!(values(&s_in), vec![("acctA".into(), "100".into()), ("acctB".into(), "50".into())]);
+        assert_eq!(values(&s_out), vec![("acctA".into(), "90".into()), ("acctB".into(), "60".into())]);
+        assert_ne!(c_in, c_out);
+        assert_eq!(h_b.len(), 64);
+        let decode = |c: &str| crate::stark::hex32_to_array(c).expect("hex32");
+        assert!(verify_c(&s_in, &decode(&c_in)));
+        assert!(verify_c(&s_out, &decode(&c_out)));
+        assert!(!verify_c(&s_in, &decode(&c_out)));
+        // Altering any value breaks the commitment
+        for i in 0..s_in.len() {
+            let mut altered = s_in.clone();
+            altered[i].value.push('0');
+            assert!(!verify_c(&altered, &decode(&c_in)), "altered value {i} still verifies");
+        }
+        // Deterministic for the same responses
+        let again = generate_north_star_public_inputs(&rpc, 10, 11, &witnesses, Commitment::Finalized).expect("again");
+        assert_eq!((again.0, again.1, again.2), (c_in, c_out, h_b));
+    }
+
+    #[test]
+    fn test_parallel_fetch_keeps_slot_order_and_names_the_failing_slot() {
+        // Every slot from 19 to 31 has its own block; only 19 (the seed) and the listed ones are answered
+        let mock = |answered: &[u64]| {
+            answered.iter().chain(&[19]).fold(MockTransport::default().on("getVoteAccounts", vote_accounts_response(2)), |rpc, &slot| {
+                let block = json!({ "result": { "blockhash": format!("hash{slot}"), "transactions": [] } });
+                rpc.on_params("getBlock", &get_block_params(slot, Commitment::Finalized), block)
+            })
+        };
+        let all: Vec<u64> = (20..=31).collect();
+        let rpc = mock(&all);
+        let sequential = generate_witness_from_rpc(&rpc, 20, 31, Commitment::Finalized, &WitnessFetchConfig::default()).expect("sequential");
+        for max_in_flight in [1, 3, 64] {
+            let parallel = generate_witness_from_rpc_parallel(
+                &rpc,
+                20,
+                31,
+                Commitment::Finalized,
+                &Blake3LeafCodec,
+                SlotBinding::Bound,
+                MerkleVersion::CURRENT,
+                &WitnessFetchConfig::default(),
+                max_in_flight,
+            )
+            .expect("parallel");
+            assert_eq!(parallel.iter().map(|w| w.slot).collect::<Vec<_>>(), all);
+            let roots = |ws: &[SlotWitness]| ws.iter().map(|w| w.state_root).collect::<Vec<_>>();
+            assert_eq!(roots(&parallel), roots(&sequential));
+        }
+
+        // Slots 25 and 29 have no answer; the lowest of them is reported on both paths
+        let gaps: Vec<u64> = all.iter().copied().filter(|s| *s != 25 && *s != 29).collect();
+        let rpc = mock(&gaps);
+        let err = generate_witness_from_rpc_parallel(
+            &rpc,
+            20,
+            31,
+            Commitment::Finalized,
+            &Blake3LeafCodec,
+            SlotBinding::Bound,
+            MerkleVersion::CURRENT,
+            &WitnessFetchConfig::default(),
+            4,
+        )
+        .expect_err("slot 25 fails");
+        assert_eq!(err.downcast_ref::<ProverError>(), Some(&ProverError::SlotFetchFailed { slot: 25 }));
+        assert!(format!("{err:#}").contains("no canned response for getBlock"), "{err:#}");
+        let err = generate_witness_from_rpc(&rpc, 20, 31, Commitment::Finalized, &WitnessFetchConfig::default())
+            .expect_err("slot 25 fails");
+        assert_eq!(err.downcast_ref::<ProverError>(), Some(&ProverError::SlotFetchFailed { slot: 25 }));
+    }
+
+    #[test]
+    fn test_historical_slots_commit_their_own_vote_state() {
+        // The node reports the same tip snapshot whichever slot is asked about
+        let vote_tx = |sig: &str, vote: &str, err: serde_json::Value| {
+            json!({
+                "transaction": { "signatures": [sig], "message": { "accountKeys": ["node", vote, VOTE_PROGRAM_ID] } },
+                "meta": { "err": err }
+            })
+        };
+        let block = |txs: Vec<serde_json::Value>| json!({ "result": { "transactions": txs } });
+        let mut snapshot = vote_accounts_response(2);
+        for (i, vote_pubkey) in ["vote2", "vote3"].into_iter().enumerate() {
+            let mut account = snapshot["result"]["current"][0].clone();
+            account["votePubkey"] = json!(vote_pubkey);
+            account["activatedStake"] = json!(100 + i);
+            snapshot["result"]["current"].as_array_mut().unwrap().push(account);
+        }
+        let tip = parse_vote_accounts(&snapshot).expect("snapshot");
+        // Slot 39 is skipped; 38, the last block before the range, seeds vote2
+        let rpc = MockTransport::default()
+            .on("getVoteAccounts", snapshot)
+            .on("getBlock", json!({ "result": null }))
+            .on_params("getBlock", &get_block_params(38, Commitment::Finalized), block(vec![vote_tx("s", "vote2", json!(null))]))
+            .on_params("getBlock", &get_block_params(40, Commitment::Finalized), block(vec![vote_tx("a", "vote", json!(null))]))
+            .on_params(
+                "getBlock",
+                &get_block_params(41, Commitment::Finalized),
+                block(vec![vote_tx("b", "vote2", json!(null)), vote_tx("c", "vote", json!({ "InstructionError": [0, "Custom"] }))]),
+            );
+        let witnesses = generate_witness_from_rpc_with_codec(
+            &rpc,
+            40,
+            41,
+            Commitment::Finalized,
+            &Blake3LeafCodec,
+            SlotBinding::Unbound,
+            MerkleVersion::CURRENT,
+            &WitnessFetchConfig::default(),
+            None,
+        )
+        .expect("witnesses");
+        fn last_votes(w: &SlotWitness) -> Vec<(&str, u64)> {
+            w.vote_accounts.iter().map(|a| (a.vote_pubkey.as_str(), a.last_vote)).collect()
+        }
+        // vote3 has no vote since the seed block, so it alone reads as not having voted
+        assert_eq!(last_votes(&witnesses[0]), vec![("vote", 40), ("vote2", 38), ("vote3", 0)]);
+        // The failed vote does not land; the earlier one carries forward
+        assert_eq!(last_votes(&witnesses[1]), vec![("vote", 40), ("vote2", 41), ("vote3", 0)]);
+        // Only vote recency is per-slot: everything else is the tip snapshot's in every witness
+        for w in &witnesses {
+            for (account, tip) in w.vote_accounts.iter().zip(&tip) {
+                let VoteAccountWitness { vote_pubkey, node_pubkey, activated_stake, commission, root_slot, epoch_credits, .. } = tip;
+                assert_eq!(
+                    (&account.vote_pubkey, &account.node_pubkey, account.activated_stake),
+                    (vote_pubkey, node_pubkey, *activated_stake)
+                );
+                assert_eq!((account.commission, account.root_slot, &account.epoch_credits), (*commission, *root_slot, epoch_credits));
+            }
+        }
+        // Without the slot binding the roots still differ: the trees themselves do
+        assert_ne!(witnesses[0].state_root, witnesses[1].state_root);
+    }
+
+    /// Fails its first `failures` calls with `error`, then answers from `inner`.
+    struct Flaky {
+        inner: MockTransport,
+        failures: std::cell::Cell<u32>,
+        calls: std::cell::Cell<u32>,
+        error: fn() -> anyhow::Error,
+    }
+
+    impl RpcTransport for Flaky {
+        fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+            self.calls.set(self.calls.get() + 1);
+            if self.failures.get() > 0 {
+                self.failures.set(self.failures.get() - 1);
+                return Err((self.error)());
+            }
+            self.inner.call(method, params)
+        }
+    }
+
+    #[test]
+    fn test_transient_rpc_failures_are_retried_and_permanent_ones_are_not() {
+        let fetch = WitnessFetchConfig {
+            max_attempts: 3,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            ..WitnessFetchConfig::default()
+        };
+        let flaky = |failures, error| Flaky {
+            inner: mock_devnet(),
+            failures: std::cell::Cell::new(failures),
+            calls: std::cell::Cell::new(0),
+            error,
+        };
+        let rate_limited: fn() -> anyhow::Error = || ProverError::RpcUnavailable { status: Some(429) }.into();
+
+        // Two rate limits fit in three attempts: 2 failures, then the seed block, getBlock and
+        // getVoteAccounts
+        let rpc = flaky(2, rate_limited);
+        let witnesses = generate_witness_from_rpc(&rpc, 10, 10, Commitment::Finalized, &fetch).expect("retried");
+        assert_eq!(witnesses.len(), 1);
+        assert_eq!(rpc.calls.get(), 5);
+
+        // A third exhausts them on the seed block; its slot and the last RPC failure are both reported
+        let rpc = flaky(3, rate_limited);
+        let err = generate_witness_from_rpc(&rpc, 10, 10, Commitment::Finalized, &fetch).expect_err("gave up");
+        assert_eq!(rpc.calls.get(), 3);
+        assert_eq!(err.downcast_ref::<ProverError>(), Some(&ProverError::SlotFetchFailed { slot: 9 }));
+        assert!(err
+            .chain()
+            .any(|cause| cause.downcast_ref::<ProverError>() == Some(&ProverError::RpcUnavailable { status: Some(429) })));
+
+        // A malformed response is not retried
+        let rpc = flaky(1, || anyhow::anyhow!("expected value at line 1 column 1"));
+        generate_witness_from_rpc(&rpc, 10, 10, Commitment::Finalized, &fetch).expect_err("malformed");
+        assert_eq!(rpc.calls.get(), 1);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap_with_jitter() {
+        let fetch = WitnessFetchConfig {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            ..WitnessFetchConfig::default()
+        };
+        for (retry, ceiling_ms) in [(0, 100), (1, 200), (3, 800), (4, 1_000), (40, 1_000)] {
+            let ceiling = Duration::from_millis(ceiling_ms);
+            for _ in 0..20 {
+                let delay = fetch.backoff(retry);
+                assert!(delay >= ceiling / 2 && delay <= ceiling, "retry {retry}: {delay:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_cached_witnesses_are_reused_and_extended() {
+        let dir = std::env::temp_dir().join(format!("zksl-witness-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("witnesses.json");
+        let _ = fs::remove_file(&path);
+        // "vote" votes in slots 20 and 24 only, so slots 21-23 carry its vote from 20
+        let mut inner = MockTransport::default().on("getVoteAccounts", vote_accounts_response(2));
+        for slot in 19..=25u64 {
+            let txs = if slot % 4 == 0 {
+                json!([{ "transaction": { "signatures": ["v"], "message": { "accountKeys": ["node", "vote", VOTE_PROGRAM_ID] } }, "meta": { "err": null } }])
+            } else {
+                json!([])
+            };
+            inner = inner.on_params("getBlock", &get_block_params(slot, Commitment::Finalized), json!({ "result": { "transactions": txs } }));
+        }
+        let rpc = Flaky { inner, failures: std::cell::Cell::new(0), calls: std::cell::Cell::new(0), error: || anyhow::anyhow!("unused") };
+        let fetch = WitnessFetchConfig::default();
+        let cached = |start, end| generate_witness_from_rpc_cached(&rpc, start, end, Commitment::Finalized, &fetch, &path);
+        let roots = |ws: &[SlotWitness]| ws.iter().map(|w| (w.slot, w.state_root)).collect::<Vec<_>>();
+        let uncached = generate_witness_from_rpc(&rpc, 20, 25, Commitment::Finalized, &fetch).expect("uncached");
+        rpc.calls.set(0);
+
+        // The seed block, then two calls per fetched slot: getBlock and getVoteAccounts
+        assert_eq!(roots(&cached(20, 22).expect("first run")), roots(&uncached[..3]));
+        assert_eq!(rpc.calls.get(), 7);
+        assert_eq!(roots(&load_witnesses(&path).expect("saved")), roots(&uncached[..3]));
+        // Only the slots past the cache are fetched, continuing its vote state
+        assert_eq!(roots(&cached(20, 25).expect("extended")), roots(&uncached));
+        assert_eq!(rpc.calls.get(), 13);
+        assert_eq!(roots(&cached(20, 23).expect("prefix")), roots(&uncached[..4]));
+        assert_eq!(rpc.calls.get(), 13);
+
+        // Another start, a tampered root or a gap is refused
+        let err = cached(21, 25).expect_err("other start");
+        assert!(err.to_string().contains("not a range starting at 21"), "{err}");
+        let mut tampered = uncached.clone();
+        tampered[2].state_root[0] ^= 1;
+        save_witnesses(&path, &tampered).unwrap();
+        let err = cached(20, 25).expect_err("tampered");
+        assert!(err.to_string().contains("slot 22"), "{err}");
+        let mut gap = uncached.clone();
+        gap.remove(3);
+        save_witnesses(&path, &gap).unwrap();
+        let err = load_witnesses(&path).expect_err("gap");
+        assert!(err.to_string().contains("skips from slot 22 to 24"), "{err}");
+
+        // A cache saved before trees were versioned holds V1 roots; it is checked and extended
+        // under V1 rather than refused as stale
+        let legacy = |end| {
+            let (codec, binding) = (&Blake3LeafCodec, SlotBinding::Bound);
+            generate_witness_from_rpc_with_codec(&rpc, 20, end, Commitment::Finalized, codec, binding, MerkleVersion::V1, &fetch, None)
+                .expect("legacy")
+        };
+        let mut saved = serde_json::to_value(legacy(22)).unwrap();
+        for w in saved.as_array_mut().unwrap() {
+            w.as_object_mut().unwrap().remove("merkle_version");
+        }
+        fs::write(&path, serde_json::to_vec(&saved).unwrap()).unwrap();
+        let extended = cached(20, 25).expect("legacy cache");
+        assert!(extended.iter().all(|w| w.merkle_version == MerkleVersion::V1));
+        assert_eq!(roots(&extended), roots(&legacy(25)));
+        assert_ne!(roots(&extended), roots(&uncached));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn transfer_tx(sig: &str, keys: &[&str], pre: &[u64], post: &[u64]) -> serde_json::Value {
+        json!({
+            "transaction": { "signatures": [sig], "message": { "accountKeys": keys } },
+            "meta": { "preBalances": pre, "postBalances": post }
+        })
+    }
+
+    #[test]
+    fn test_repeated_account_takes_first_pre_and_last_post() {
+        // acctA moves 100 -> 90 -> 70 within slot 10, then 70 -> 75 in slot 11
+        let block = |hash: &str, parent: u64, txs: Vec<serde_json::Value>| {
+            json!({ "result": { "blockhash": hash, "parentSlot": parent, "transactions": txs } })
+        };
+        let rpc = MockTransport::default()
+            .on_params(
+                "getBlock",
+                &get_block_params(10, Commitment::Finalized),
+                block("hash10", 9, vec![
+                    transfer_tx("sig1", &["acctA", "acctB"], &[100, 50], &[90, 60]),
+                    transfer_tx("sig2", &["acctC", "acctA"], &[5, 90], &[25, 70]),
+                ]),
+            )
+            .on_params(
+                "getBlock",
+                &get_block_params(11, Commitment::Finalized),
+                block("hash11", 10, vec![transfer_tx("sig3", &["acctA", "acctC"], &[70, 25], &[75, 20])]),
+            );
+        fn values(pairs: &[crate::stark::KVPair]) -> Vec<(&str, &str)> {
+            pairs.iter().map(|p| (p.account.as_str(), p.value.as_str())).collect()
+        }
+        let run = || generate_north_star_public_inputs(&rpc, 10, 11, &[], Commitment::Finalized).expect("public inputs");
+        let (c_in, c_out, h_b, s_in, s_out) = run();
+        assert_eq!(values(&s_in), vec![("acctA", "100"), ("acctB", "50"), ("acctC", "5")]);
+        assert_eq!(values(&s_out), vec![("acctA", "75"), ("acctB", "60"), ("acctC", "20")]);
+        for _ in 0..3 {
+            let again = run();
+            assert_eq!((again.0, again.1, again.2), (c_in.clone(), c_out.clone(), h_b.clone()));
+        }
+    }
+
+    #[test]
+    fn test_witness_generation_against_simulated_cluster() {
+        let generate = |seed| {
+            let sim = crate::sim::SimCluster::new(seed, 200, 215);
+            generate_witness_from_rpc(&sim, 200, 215, Commitment::Finalized, &WitnessFetchConfig::default()).unwrap()
+        };
+        let witnesses = generate(7);
+        assert_eq!(witnesses.len(), 16);
+        assert!(witnesses.iter().all(|w| w.vote_accounts.len() == 6));
+        // Produced slots record the votes they carry; skipped slots keep the previous vote state
+        let sim = crate::sim::SimCluster::new(7, 200, 215);
+        let mut previous: Option<&SlotWitness> = None;
+        for (w, slot) in witnesses.iter().zip(200..) {
+            assert_eq!(w.slot, slot);
+            assert!(w.vote_accounts.iter().all(|a| a.last_vote <= slot && a.root_slot <= slot));
+            let produced = sim.call("getBlock", get_block_params(slot, Commitment::Finalized)).unwrap()["result"].is_object();
+            if produced {
+                assert!(w.vote_accounts.iter().any(|a| a.last_vote == slot), "no vote landed in {slot}");
+            } else {
+                assert!(w.vote_accounts.iter().all(|a| a.last_vote < slot));
+                if let Some(prev) = previous {
+                    let last_votes = |w: &SlotWitness| w.vote_accounts.iter().map(|a| a.last_vote).collect::<Vec<_>>();
+                    assert_eq!(last_votes(w), last_votes(prev));
+                }
+            }
+            previous = Some(w);
+        }
+        assert_ne!(witnesses[0].account_hashes, witnesses[1].account_hashes);
+        let roots = |ws: &[SlotWitness]| ws.iter().map(|w| w.state_root).collect::<Vec<_>>();
+        assert_eq!(roots(&generate(7)), roots(&witnesses));
+        assert_ne!(roots(&generate(8)), roots(&witnesses));
     }
 }
 
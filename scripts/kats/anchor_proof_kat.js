@@ -30,6 +30,7 @@ function encodeAnchorProofArgsFixed(vec) {
     enc64(vec.timestamp), // writes as u64 here for length parity, actual i64 has same size
     enc64(vec.seq),
     Buffer.from(vec.dsHash32),
+    Buffer.from([vec.commitmentLevel]),
   ]);
   return Buffer.concat([disc, payload]);
 }
@@ -49,8 +50,9 @@ function encodeAnchorProofArgsFixed(vec) {
     timestamp: 1n,
     seq: 1n,
     dsHash32: zero32,
+    commitmentLevel: 2,
   });
-  const expectedLen = 8 /*disc*/ + 212; /*payload*/
+  const expectedLen = 8 /*disc*/ + 213; /*payload*/
   if (data.length !== expectedLen)
     throw new Error(
       `anchor_proof data length expected ${expectedLen}, got ${data.length}`